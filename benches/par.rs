@@ -0,0 +1,38 @@
+// par_find against a plain find over the dense trie from the demo in main, on thread pools of
+// growing size. Nothing matches, so every search touches every entry.
+//
+// Run with `cargo bench --features rayon --bench par`.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use trie::Trie;
+
+const N: u32 = 16_u32.pow(5);
+const THREADS: [usize; 4] = [1, 2, 4, 8];
+
+fn bench_find(c: &mut Criterion) {
+    let mut trie = Trie::new();
+    for a in 0..N {
+        trie.insert_int(a, a);
+    }
+    let mut group = c.benchmark_group("find_everything");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function("iter().find", |b| {
+        b.iter(|| black_box(trie.iter().find(|(_, value)| **value == N)))
+    });
+    for threads in THREADS {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_function(BenchmarkId::new("par_find", threads), |b| {
+            b.iter(|| pool.install(|| black_box(trie.par_find(|_, value| *value == N))))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_find);
+criterion_main!(benches);
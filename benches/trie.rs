@@ -0,0 +1,333 @@
+// Trie against HashMap and BTreeMap on the same keys, at three sizes.
+//
+// Run with `cargo bench --features bench_support`. Every benchmark times one pass over all n
+// keys, so throughput is reported per key. Building and dropping the maps an iteration needs
+// happens outside the timed part.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hint::black_box;
+
+use criterion::measurement::WallTime;
+use criterion::{
+    BatchSize, BenchmarkGroup, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main,
+};
+use trie::bench_support::{
+    clustered_keys, missing_keys, random_keys, sequential_keys, shared_prefix_keys,
+};
+use trie::{ArenaTrie, Nibbles, Trie};
+
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+const VALUE: &str = "leaf";
+
+type Group<'a> = BenchmarkGroup<'a, WallTime>;
+type KeyGen = fn(usize) -> Vec<String>;
+
+// The operations being compared, so each benchmark is written once for all three maps.
+trait Map: Default + Clone {
+    const NAME: &'static str;
+    fn put(&mut self, key: &str, value: String);
+    fn find(&self, key: &str) -> Option<&String>;
+    fn remove(&mut self, key: &str);
+    fn count_prefix(&self, prefix: &str) -> usize;
+}
+
+impl Map for Trie<String> {
+    const NAME: &'static str = "Trie";
+
+    fn put(&mut self, key: &str, value: String) {
+        self.insert_lossy(key, value);
+    }
+
+    fn find(&self, key: &str) -> Option<&String> {
+        self.get_lossy(key)
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.delete_lossy(key);
+    }
+
+    fn count_prefix(&self, prefix: &str) -> usize {
+        self.iter_prefix(prefix).count()
+    }
+}
+
+impl Map for HashMap<String, String> {
+    const NAME: &'static str = "HashMap";
+
+    fn put(&mut self, key: &str, value: String) {
+        self.insert(key.to_string(), value);
+    }
+
+    fn find(&self, key: &str) -> Option<&String> {
+        self.get(key)
+    }
+
+    fn remove(&mut self, key: &str) {
+        HashMap::remove(self, key);
+    }
+
+    // No order to lean on, so every key is checked.
+    fn count_prefix(&self, prefix: &str) -> usize {
+        self.keys().filter(|key| key.starts_with(prefix)).count()
+    }
+}
+
+impl Map for BTreeMap<String, String> {
+    const NAME: &'static str = "BTreeMap";
+
+    fn put(&mut self, key: &str, value: String) {
+        self.insert(key.to_string(), value);
+    }
+
+    fn find(&self, key: &str) -> Option<&String> {
+        self.get(key)
+    }
+
+    fn remove(&mut self, key: &str) {
+        BTreeMap::remove(self, key);
+    }
+
+    fn count_prefix(&self, prefix: &str) -> usize {
+        self.range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .count()
+    }
+}
+
+fn build<M: Map>(keys: &[String]) -> M {
+    let mut map = M::default();
+    for key in keys {
+        map.put(key, VALUE.to_string());
+    }
+    map
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let kinds: [(&str, KeyGen); 3] = [
+        ("sequential", sequential_keys),
+        ("random", |n| random_keys(n, SEED)),
+        ("shared_prefix", |n| shared_prefix_keys(n, 48)),
+    ];
+    for (kind, keys_for) in kinds {
+        let mut group = c.benchmark_group(format!("insert/{}", kind));
+        group.sample_size(10);
+        for n in SIZES {
+            let keys = keys_for(n);
+            group.throughput(Throughput::Elements(n as u64));
+            insert_one::<Trie<String>>(&mut group, &keys);
+            insert_one::<HashMap<String, String>>(&mut group, &keys);
+            insert_one::<BTreeMap<String, String>>(&mut group, &keys);
+        }
+        group.finish();
+    }
+}
+
+fn insert_one<M: Map>(group: &mut Group<'_>, keys: &[String]) {
+    group.bench_with_input(BenchmarkId::new(M::NAME, keys.len()), keys, |b, keys| {
+        b.iter_batched(|| (), |()| build::<M>(keys), BatchSize::PerIteration)
+    });
+}
+
+fn bench_get(c: &mut Criterion) {
+    for (name, hit) in [("get_hit", true), ("get_miss", false)] {
+        let mut group = c.benchmark_group(name);
+        group.sample_size(10);
+        for n in SIZES {
+            let keys = random_keys(n, SEED);
+            let probes = if hit {
+                keys.clone()
+            } else {
+                missing_keys(n, SEED)
+            };
+            group.throughput(Throughput::Elements(n as u64));
+            get_one(&mut group, &build::<Trie<String>>(&keys), &probes);
+            get_one(
+                &mut group,
+                &build::<HashMap<String, String>>(&keys),
+                &probes,
+            );
+            get_one(
+                &mut group,
+                &build::<BTreeMap<String, String>>(&keys),
+                &probes,
+            );
+        }
+        group.finish();
+    }
+}
+
+fn get_one<M: Map>(group: &mut Group<'_>, map: &M, probes: &[String]) {
+    group.bench_with_input(
+        BenchmarkId::new(M::NAME, probes.len()),
+        probes,
+        |b, probes| b.iter(|| probes.iter().filter(|key| map.find(key).is_some()).count()),
+    );
+}
+
+fn bench_delete(c: &mut Criterion) {
+    let mut group = c.benchmark_group("delete");
+    group.sample_size(10);
+    for n in SIZES {
+        let keys = random_keys(n, SEED);
+        group.throughput(Throughput::Elements(n as u64));
+        delete_one(&mut group, &build::<Trie<String>>(&keys), &keys);
+        delete_one(&mut group, &build::<HashMap<String, String>>(&keys), &keys);
+        delete_one(&mut group, &build::<BTreeMap<String, String>>(&keys), &keys);
+    }
+    group.finish();
+}
+
+fn delete_one<M: Map>(group: &mut Group<'_>, map: &M, keys: &[String]) {
+    group.bench_with_input(BenchmarkId::new(M::NAME, keys.len()), keys, |b, keys| {
+        b.iter_batched(
+            || map.clone(),
+            |mut map| {
+                for key in keys {
+                    map.remove(key);
+                }
+                map
+            },
+            BatchSize::PerIteration,
+        )
+    });
+}
+
+// Counting the keys under a two-digit prefix, about 1/256 of them.
+fn bench_prefix(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prefix_iter");
+    group.sample_size(10);
+    for n in SIZES {
+        let keys = random_keys(n, SEED);
+        group.throughput(Throughput::Elements(n as u64));
+        prefix_one(&mut group, &build::<Trie<String>>(&keys), n);
+        prefix_one(&mut group, &build::<HashMap<String, String>>(&keys), n);
+        prefix_one(&mut group, &build::<BTreeMap<String, String>>(&keys), n);
+    }
+    group.finish();
+}
+
+fn prefix_one<M: Map>(group: &mut Group<'_>, map: &M, n: usize) {
+    group.bench_function(BenchmarkId::new(M::NAME, n), |b| {
+        b.iter(|| map.count_prefix(black_box("a5")))
+    });
+}
+
+// A few hundred lookups at once in a trie whose keys all share a 10-digit prefix, as one
+// get_many call against a loop of get.
+fn bench_get_many(c: &mut Criterion) {
+    const BATCH: usize = 500;
+    let mut group = c.benchmark_group("get_many");
+    for n in SIZES {
+        let keys = shared_prefix_keys(n, 10);
+        let trie = build::<Trie<String>>(&keys);
+        let probes: Vec<&str> = keys.iter().step_by(n / BATCH).map(String::as_str).collect();
+        group.throughput(Throughput::Elements(probes.len() as u64));
+        group.bench_function(BenchmarkId::new("get_many", n), |b| {
+            b.iter(|| trie.get_many(black_box(&probes)))
+        });
+        group.bench_function(BenchmarkId::new("get_loop", n), |b| {
+            b.iter(|| {
+                black_box(&probes)
+                    .iter()
+                    .map(|key| trie.get_lossy(key))
+                    .collect::<Vec<_>>()
+            })
+        });
+    }
+    group.finish();
+}
+
+// Inserting a stream of nearby keys through a cursor, against a loop of insert.
+fn bench_cursor(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cursor_insert");
+    group.sample_size(10);
+    for n in SIZES {
+        let keys = clustered_keys(n, SEED);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::new("insert", n), |b| {
+            b.iter_batched(
+                || (),
+                |()| build::<Trie<String>>(&keys),
+                BatchSize::PerIteration,
+            )
+        });
+        group.bench_function(BenchmarkId::new("CursorMut::insert", n), |b| {
+            b.iter_batched(
+                || (),
+                |()| {
+                    let mut trie = Trie::new();
+                    let mut cursor = trie.cursor_mut();
+                    for key in &keys {
+                        cursor.insert(key, String::from(VALUE));
+                    }
+                    trie
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+// The dense build from the demo in main: every 32-bit key below 16^pow, through insert_int and
+// through from_sorted_iter.
+fn bench_dense_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dense_build");
+    group.sample_size(10);
+    for pow in [4, 5, 6] {
+        let n = 16_u32.pow(pow);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::new("Trie", n), |b| {
+            b.iter_batched(
+                || (),
+                |()| {
+                    let mut trie = Trie::new();
+                    for a in 0..n {
+                        trie.insert_int(a, String::from(VALUE));
+                    }
+                    trie
+                },
+                BatchSize::PerIteration,
+            )
+        });
+        group.bench_function(BenchmarkId::new("Trie::from_sorted_iter", n), |b| {
+            b.iter_batched(
+                || (),
+                |()| {
+                    let entries = (0..n)
+                        .map(|a| (Nibbles::from_bytes(&a.to_be_bytes()), String::from(VALUE)));
+                    Trie::from_sorted_iter(entries).unwrap()
+                },
+                BatchSize::PerIteration,
+            )
+        });
+        group.bench_function(BenchmarkId::new("ArenaTrie", n), |b| {
+            b.iter_batched(
+                || (),
+                |()| {
+                    let mut trie = ArenaTrie::new();
+                    trie.reserve((16_usize.pow(pow + 1) - 1) / 15);
+                    for a in 0..n {
+                        trie.insert_int(a, String::from(VALUE));
+                    }
+                    trie
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_insert,
+    bench_get,
+    bench_delete,
+    bench_prefix,
+    bench_get_many,
+    bench_cursor,
+    bench_dense_build
+);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+#![no_main]
+
+use std::collections::HashMap;
+
+use libfuzzer_sys::fuzz_target;
+use trie::Trie;
+use trie::testing::{apply_and_check, ops_from_bytes};
+
+// Runs whatever operations the input decodes to against a trie and a HashMap, panicking on
+// the first disagreement.
+fuzz_target!(|data: &[u8]| {
+    let mut trie = Trie::new();
+    let mut oracle = HashMap::new();
+    for op in ops_from_bytes(data) {
+        apply_and_check(&mut trie, &mut oracle, op);
+    }
+});
@@ -0,0 +1,299 @@
+// A hex trie whose nodes all live in one vector.
+//
+// Children are indices into `nodes` rather than boxes, so adding a node is a push onto the
+// vector and a walk down the trie reads from one contiguous block instead of one allocation per
+// node. The root is always at index 0 and is nobody's child, which leaves 0 free to mean "no
+// child" through `NonZeroU32`'s niche: a child slot is four bytes, the same as a bare index.
+//
+// Deleting a key frees the nodes it pruned by putting their indices on `free`, and inserts take
+// from there before growing the vector. Freed nodes are left with no value and no children, so
+// nothing they held outlives the delete. Once more than half of a large vector is free it is
+// compacted: the live nodes are moved out in key order and the free list is dropped.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::num::NonZeroU32;
+
+use crate::int_key::IntKey;
+use crate::key::{key_symbols, nibbles_to_hex};
+
+// Below this many slots a mostly free vector isn't worth copying.
+const COMPACT_MIN: usize = 1024;
+
+struct ArenaNode<V> {
+    children: [Option<NonZeroU32>; 16],
+    value: Option<V>,
+}
+
+impl<V> ArenaNode<V> {
+    fn new() -> Self {
+        Self {
+            children: [None; 16],
+            value: None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.value.is_none() && self.children.iter().all(Option::is_none)
+    }
+}
+
+/// A hex-keyed trie that stores its nodes in a single vector and links them
+/// by index.
+///
+/// It holds the same keys as [`Trie`](crate::Trie), but building it costs
+/// one vector push per new node instead of one allocation, and lookups walk
+/// contiguous memory. Nodes freed by [`ArenaTrie::delete`] are reused by
+/// later inserts, and a large vector is compacted once more than half of it
+/// is free.
+///
+/// ```
+/// let mut trie = trie::ArenaTrie::new();
+/// for round in 0..100 {
+///     trie.insert("a1f0", round);
+///     trie.delete("a1f0");
+/// }
+/// // The four nodes under the root are reused each round.
+/// assert_eq!(trie.arena_len(), 5);
+/// ```
+pub struct ArenaTrie<V> {
+    nodes: Vec<ArenaNode<V>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<V> Default for ArenaTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> ArenaTrie<V> {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![ArenaNode::new()],
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Stores `value` under `hex_key`, returning the value that was replaced.
+    pub fn insert(&mut self, hex_key: &str, value: V) -> Option<V> {
+        self.insert_nibbles(key_symbols::<16>(hex_key), value)
+    }
+
+    /// Stores `value` under the integer `key`, as
+    /// [`Trie::insert_int`](crate::Trie::insert_int) does.
+    pub fn insert_int<K: IntKey>(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_nibbles(key.nibbles(), value)
+    }
+
+    fn insert_nibbles(&mut self, nibbles: impl Iterator<Item = usize>, value: V) -> Option<V> {
+        let mut cur = 0;
+        for nibble in nibbles {
+            cur = match self.nodes[cur].children[nibble] {
+                Some(child) => child.get() as usize,
+                None => {
+                    let child = self.alloc();
+                    self.nodes[cur].children[nibble] = Some(child);
+                    child.get() as usize
+                }
+            };
+        }
+        let old = self.nodes[cur].value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    // A fresh empty node, from the free list if it has one.
+    fn alloc(&mut self) -> NonZeroU32 {
+        if let Some(index) = self.free.pop() {
+            return NonZeroU32::new(index).unwrap();
+        }
+        let index = u32::try_from(self.nodes.len()).expect("ArenaTrie is limited to u32 nodes");
+        self.nodes.push(ArenaNode::new());
+        NonZeroU32::new(index).unwrap()
+    }
+
+    // The index of the node at the end of `nibbles`, if the path exists.
+    fn find(&self, nibbles: impl Iterator<Item = usize>) -> Option<usize> {
+        let mut cur = 0;
+        for nibble in nibbles {
+            cur = self.nodes[cur].children[nibble]?.get() as usize;
+        }
+        Some(cur)
+    }
+
+    /// Returns the value stored under `hex_key`, if any.
+    pub fn get(&self, hex_key: &str) -> Option<&V> {
+        let node = self.find(key_symbols::<16>(hex_key))?;
+        self.nodes[node].value.as_ref()
+    }
+
+    /// Returns the value stored under the integer `key`, if any.
+    pub fn get_int<K: IntKey>(&self, key: K) -> Option<&V> {
+        let node = self.find(key.nibbles())?;
+        self.nodes[node].value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value stored under `hex_key`.
+    pub fn get_mut(&mut self, hex_key: &str) -> Option<&mut V> {
+        let node = self.find(key_symbols::<16>(hex_key))?;
+        self.nodes[node].value.as_mut()
+    }
+
+    /// Returns `true` if a value is stored under `hex_key`.
+    pub fn contains_key(&self, hex_key: &str) -> bool {
+        self.get(hex_key).is_some()
+    }
+
+    /// Removes the value stored under `hex_key` and returns it, freeing the
+    /// nodes left without values or children.
+    pub fn delete(&mut self, hex_key: &str) -> Option<V> {
+        let mut path = vec![];
+        let mut cur = 0;
+        for nibble in key_symbols::<16>(hex_key) {
+            path.push((cur, nibble));
+            cur = self.nodes[cur].children[nibble]?.get() as usize;
+        }
+        let removed = self.nodes[cur].value.take()?;
+        self.len -= 1;
+
+        // Unhook nodes bottom-up for as long as they're empty; the root never goes.
+        while let Some((parent, nibble)) = path.pop() {
+            if !self.nodes[cur].is_empty() {
+                break;
+            }
+            self.nodes[parent].children[nibble] = None;
+            self.free.push(cur as u32);
+            cur = parent;
+        }
+        if self.nodes.len() >= COMPACT_MIN && self.free.len() > self.nodes.len() / 2 {
+            self.compact();
+        }
+        Some(removed)
+    }
+
+    /// Rebuilds the node vector with only the live nodes, in key order, and
+    /// releases the rest of its memory, returning how many bytes that gave
+    /// back.
+    ///
+    /// [`ArenaTrie::delete`] does this on its own once more than half of a
+    /// vector of 1024 slots or more is free, so calling it is only needed to
+    /// trim the vector sooner, or to drop capacity left by
+    /// [`ArenaTrie::reserve`].
+    pub fn compact(&mut self) -> usize {
+        let before = self.nodes.capacity() * size_of::<ArenaNode<V>>()
+            + self.free.capacity() * size_of::<u32>();
+        let mut nodes = Vec::with_capacity(self.node_count());
+        let mut old: Vec<Option<ArenaNode<V>>> = core::mem::take(&mut self.nodes)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.free = Vec::new();
+
+        // Each node is moved over when it's reached, and its children are pushed in reverse so
+        // they come off the stack, and into `nodes`, in key order. Their index in the new
+        // vector is patched into the parent once they've been moved.
+        let mut stack = vec![(0, None)];
+        while let Some((index, parent)) = stack.pop() {
+            let node: ArenaNode<V> = old[index].take().unwrap();
+            let new_index = nodes.len();
+            if let Some((parent, nibble)) = parent {
+                let parent: &mut ArenaNode<V> = &mut nodes[parent];
+                parent.children[nibble] = NonZeroU32::new(new_index as u32);
+            }
+            for (nibble, child) in node.children.iter().enumerate().rev() {
+                if let Some(child) = child {
+                    stack.push((child.get() as usize, Some((new_index, nibble))));
+                }
+            }
+            nodes.push(node);
+        }
+        self.nodes = nodes;
+        before - self.nodes.capacity() * size_of::<ArenaNode<V>>()
+    }
+
+    /// Makes room for at least `additional` more nodes, so a bulk load grows
+    /// the vector once instead of copying it each time it fills.
+    ///
+    /// A key of `n` digits needs at most `n` new nodes.
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes
+            .reserve(additional.saturating_sub(self.free.len()));
+    }
+
+    /// Returns the number of stored values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the trie holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of live nodes, including the root.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    /// Returns the number of node slots in the vector, live or free.
+    pub fn arena_len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns an iterator over all entries in key order.
+    pub fn iter(&self) -> ArenaIter<'_, V> {
+        ArenaIter {
+            trie: self,
+            stack: vec![(0, 0)],
+            path: Vec::new(),
+        }
+    }
+}
+
+/// An iterator over the entries of an [`ArenaTrie`] in key order.
+///
+/// Returned by [`ArenaTrie::iter`].
+pub struct ArenaIter<'a, V> {
+    trie: &'a ArenaTrie<V>,
+    // A node being visited and the next child slot to look at; slot 0 means its own value
+    // hasn't been yielded yet.
+    stack: Vec<(usize, usize)>,
+    path: Vec<u8>,
+}
+
+impl<'a, V> Iterator for ArenaIter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let nodes = &self.trie.nodes;
+        while let Some((index, slot)) = self.stack.last_mut() {
+            let node = &nodes[*index];
+            if *slot == 0 {
+                *slot = 1;
+                if let Some(value) = &node.value {
+                    return Some((nibbles_to_hex(&self.path), value));
+                }
+            }
+            match (*slot - 1..16).find(|&i| node.children[i].is_some()) {
+                Some(nibble) => {
+                    *slot = nibble + 2;
+                    self.path.push(nibble as u8);
+                    let child = node.children[nibble].unwrap().get() as usize;
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,121 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::key::{KeyError, check_key};
+use crate::trie::Trie;
+
+/// One write of a batch passed to [`Trie::apply_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<V> {
+    /// Stores `value` under `key`.
+    Insert { key: String, value: V },
+    /// Removes whatever `key` holds.
+    Delete { key: String },
+}
+
+impl<V> Op<V> {
+    /// The hex key the op writes to.
+    pub fn key(&self) -> &str {
+        match self {
+            Op::Insert { key, .. } | Op::Delete { key } => key,
+        }
+    }
+}
+
+/// What each op of a batch did, in the order of the ops.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReceipt {
+    pub outcomes: Vec<OpOutcome>,
+}
+
+/// What a single op of a batch did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpOutcome {
+    /// An insert stored a new key.
+    Inserted,
+    /// An insert replaced the value of a key.
+    Overwritten,
+    /// A delete removed a value.
+    Deleted,
+    /// A delete found nothing to remove.
+    NotFound,
+}
+
+/// Why [`Trie::apply_batch`] refused a batch. Nothing was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchError {
+    /// Position of the first rejected op in the batch.
+    pub index: usize,
+    /// The key it was rejected for.
+    pub key: String,
+    pub reason: KeyError,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "op {} has bad key {:?}: {}",
+            self.index, self.key, self.reason
+        )
+    }
+}
+
+impl Error for BatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.reason)
+    }
+}
+
+impl<V: Clone, const N: usize> Trie<V, N> {
+    /// Applies every op of `ops` in order, or none of them.
+    ///
+    /// Every key is checked as strictly as [`Trie::try_insert`] does before
+    /// anything is written, and that check is the only way a batch can
+    /// fail, so a refused batch leaves the trie exactly as it was. Later ops
+    /// see the writes of earlier ones, so deleting a key the batch inserted
+    /// reports [`OpOutcome::Deleted`].
+    ///
+    /// ```
+    /// use trie::{Op, OpOutcome, Trie};
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert_lossy("a1", 1);
+    /// let ops = [
+    ///     Op::Insert { key: "a1".to_string(), value: 2 },
+    ///     Op::Delete { key: "b0".to_string() },
+    ///     Op::Insert { key: "zz".to_string(), value: 3 },
+    /// ];
+    /// let err = trie.apply_batch(&ops).unwrap_err();
+    /// assert_eq!(err.index, 2);
+    /// assert_eq!(trie.get_lossy("a1"), Some(&1));
+    ///
+    /// let receipt = trie.apply_batch(&ops[..2]).unwrap();
+    /// assert_eq!(receipt.outcomes, [OpOutcome::Overwritten, OpOutcome::NotFound]);
+    /// ```
+    pub fn apply_batch(&mut self, ops: &[Op<V>]) -> Result<BatchReceipt, BatchError> {
+        for (index, op) in ops.iter().enumerate() {
+            check_key::<N>(op.key()).map_err(|reason| BatchError {
+                index,
+                key: op.key().to_string(),
+                reason,
+            })?;
+        }
+        let outcomes = ops
+            .iter()
+            .map(|op| match op {
+                Op::Insert { key, value } => match self.insert_lossy(key, value.clone()) {
+                    Some(_) => OpOutcome::Overwritten,
+                    None => OpOutcome::Inserted,
+                },
+                Op::Delete { key } => match self.delete_lossy(key) {
+                    Some(_) => OpOutcome::Deleted,
+                    None => OpOutcome::NotFound,
+                },
+            })
+            .collect();
+        Ok(BatchReceipt { outcomes })
+    }
+}
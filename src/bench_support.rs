@@ -0,0 +1,59 @@
+//! Key generators for the benchmarks in `benches/`, public so other
+//! measurements can use the same inputs.
+//!
+//! Every generator is deterministic, so two runs (or two machines) time the
+//! same keys.
+
+/// `n` keys counting up from zero as fixed-width hex, so neighbours share
+/// all but their last digits: `"00000000"`, `"00000001"`, ...
+pub fn sequential_keys(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("{:08x}", i)).collect()
+}
+
+/// `n` pseudo-random 16-digit keys from an xorshift generator started at
+/// `seed`, which must not be zero.
+pub fn random_keys(n: usize, seed: u64) -> Vec<String> {
+    let mut state = seed;
+    (0..n)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            format!("{:016x}", state)
+        })
+        .collect()
+}
+
+/// `n` keys that all start with the same `prefix_len` digits and end in a
+/// distinct 8-digit counter, for long shared paths.
+pub fn shared_prefix_keys(n: usize, prefix_len: usize) -> Vec<String> {
+    let prefix = "a5".repeat(prefix_len.div_ceil(2));
+    let prefix = &prefix[..prefix_len];
+    (0..n).map(|i| format!("{}{:08x}", prefix, i)).collect()
+}
+
+/// `n` 16-digit keys in runs of 64 that share a random 12-digit prefix and
+/// end in random digits, so consecutive keys are close without being
+/// sorted.
+pub fn clustered_keys(n: usize, seed: u64) -> Vec<String> {
+    let mut prefix = String::new();
+    random_keys(n, seed)
+        .into_iter()
+        .enumerate()
+        .map(|(i, key)| {
+            if i % 64 == 0 {
+                prefix = key[..12].to_string();
+            }
+            format!("{}{}", prefix, &key[12..])
+        })
+        .collect()
+}
+
+/// `n` 17-digit keys, which no key from [`sequential_keys`] or
+/// [`random_keys`] can equal, for lookups that miss.
+pub fn missing_keys(n: usize, seed: u64) -> Vec<String> {
+    random_keys(n, seed)
+        .into_iter()
+        .map(|key| key + "f")
+        .collect()
+}
@@ -0,0 +1,235 @@
+// The commands shared by the command line and the REPL: parsing them from words, and running
+// them against a trie.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use trie::{DisplayOptions, KeyError, Trie};
+
+// Separates key and value in the files written by dump and read by load.
+const SEP: char = '=';
+
+pub(crate) enum Command {
+    Insert {
+        key: String,
+        value: String,
+    },
+    Get {
+        key: String,
+    },
+    Delete {
+        key: String,
+    },
+    Print {
+        prefix: String,
+        max_depth: Option<usize>,
+    },
+    Dump {
+        path: PathBuf,
+    },
+    Load {
+        path: PathBuf,
+    },
+}
+
+pub(crate) enum Failure {
+    NotFound(String),
+    Usage(String),
+    // Lines of a loaded file that didn't parse; the command itself was fine.
+    Input(String),
+    File(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::NotFound(key) => write!(f, "key {:?} not found", key),
+            Failure::Usage(msg) | Failure::Input(msg) | Failure::File(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl Command {
+    // Parses a command from its words, such as `["insert", "a1f", "leaf"]`.
+    pub(crate) fn parse(words: &[&str]) -> Result<Command, Failure> {
+        Ok(match words {
+            [] => return Err(usage("no command given")),
+            ["insert", key, value] => Command::Insert {
+                key: key.to_string(),
+                value: value.to_string(),
+            },
+            ["get", key] => Command::Get {
+                key: key.to_string(),
+            },
+            ["delete", key] => Command::Delete {
+                key: key.to_string(),
+            },
+            ["print", options @ ..] => parse_print(options)?,
+            ["dump", path] => Command::Dump { path: path.into() },
+            ["load", path] => Command::Load { path: path.into() },
+            [name @ ("insert" | "get" | "delete" | "dump" | "load"), ..] => {
+                return Err(usage(&format!("usage: {}", synopsis(name))));
+            }
+            [name, ..] => return Err(usage(&format!("unknown command {:?}", name))),
+        })
+    }
+
+    // Whether running the command can change the trie.
+    pub(crate) fn mutates(&self) -> bool {
+        matches!(
+            self,
+            Command::Insert { .. } | Command::Delete { .. } | Command::Load { .. }
+        )
+    }
+
+    // Runs the command, writing what it prints to `out`. A load that skipped lines still
+    // keeps the lines that parsed, then fails with `Failure::Input`.
+    pub(crate) fn run(self, trie: &mut Trie<String>, out: &mut impl Write) -> Result<(), Failure> {
+        match self {
+            Command::Insert { key, value } => {
+                trie.try_insert(&key, value)
+                    .map_err(|err| bad_key(&key, err))?;
+            }
+            Command::Get { key } => {
+                let value = trie.try_get(&key).map_err(|err| bad_key(&key, err))?;
+                writeln!(out, "{}", value.ok_or(Failure::NotFound(key))?).map_err(output_error)?;
+            }
+            Command::Delete { key } => {
+                let removed = trie.try_delete(&key).map_err(|err| bad_key(&key, err))?;
+                removed.ok_or(Failure::NotFound(key))?;
+            }
+            Command::Print { prefix, max_depth } => print(trie, &prefix, max_depth, out)?,
+            Command::Dump { path } => trie
+                .dump_lines(create(&path)?, SEP)
+                .map_err(|err| file_error("write", &path, err))?,
+            Command::Load { path } => load(trie, &path, out)?,
+        }
+        Ok(())
+    }
+}
+
+// The usage line of a command, shown when its arguments don't parse.
+pub(crate) fn synopsis(name: &str) -> &'static str {
+    match name {
+        "insert" => "insert <key> <value>",
+        "get" => "get <key>",
+        "delete" => "delete <key>",
+        "print" => "print [--prefix P] [--max-depth N]",
+        "dump" => "dump <file>",
+        "load" => "load <file>",
+        _ => "",
+    }
+}
+
+fn parse_print(options: &[&str]) -> Result<Command, Failure> {
+    let mut prefix = String::new();
+    let mut max_depth = None;
+    let mut options = options.iter();
+    while let Some(&option) = options.next() {
+        let value = value_of(option, options.next().copied())?;
+        match option {
+            "--prefix" => prefix = value.to_string(),
+            "--max-depth" => {
+                let depth = value
+                    .parse()
+                    .map_err(|_| usage(&format!("--max-depth wants a number, not {:?}", value)))?;
+                max_depth = Some(depth);
+            }
+            _ => {
+                return Err(usage(&format!(
+                    "unknown print option {:?}; usage: {}",
+                    option,
+                    synopsis("print")
+                )));
+            }
+        }
+    }
+    Ok(Command::Print { prefix, max_depth })
+}
+
+pub(crate) fn value_of<S: AsRef<str>>(option: &str, value: Option<S>) -> Result<S, Failure> {
+    value.ok_or_else(|| usage(&format!("{} needs a value", option)))
+}
+
+pub(crate) fn usage(msg: &str) -> Failure {
+    Failure::Usage(msg.to_string())
+}
+
+fn bad_key(key: &str, err: KeyError) -> Failure {
+    Failure::Usage(format!("invalid key {:?}: {}", key, err))
+}
+
+fn print(
+    trie: &Trie<String>,
+    prefix: &str,
+    max_depth: Option<usize>,
+    out: &mut impl Write,
+) -> Result<(), Failure> {
+    trie.try_get(prefix).map_err(|err| bad_key(prefix, err))?;
+    let opts = DisplayOptions {
+        max_depth,
+        prefix: prefix.to_string(),
+        ..DisplayOptions::default()
+    };
+    write!(out, "{}", drawing(trie, opts)).map_err(output_error)
+}
+
+// How print and the repl draw the trie: in color when built with the `color` feature and
+// stdout is a terminal.
+#[cfg(feature = "color")]
+pub(crate) fn drawing(trie: &Trie<String>, opts: DisplayOptions) -> impl fmt::Display {
+    trie.display_colored(opts, trie::ColorChoice::Auto)
+}
+
+#[cfg(not(feature = "color"))]
+pub(crate) fn drawing(trie: &Trie<String>, opts: DisplayOptions) -> impl fmt::Display {
+    trie.display_with(opts)
+}
+
+fn load(trie: &mut Trie<String>, path: &Path, out: &mut impl Write) -> Result<(), Failure> {
+    let input = File::open(path).map_err(|err| file_error("open", path, err))?;
+    let report = trie
+        .load_lines(BufReader::new(input), SEP)
+        .map_err(|err| Failure::File(format!("{}: {}", path.display(), err)))?;
+    for skipped in &report.skipped {
+        writeln!(
+            out,
+            "{}:{}: {}",
+            path.display(),
+            skipped.line,
+            skipped.reason
+        )
+        .map_err(output_error)?;
+    }
+    writeln!(
+        out,
+        "{} inserted, {} overwritten, {} skipped",
+        report.inserted,
+        report.overwritten,
+        report.skipped.len()
+    )
+    .map_err(output_error)?;
+    if report.skipped.is_empty() {
+        Ok(())
+    } else {
+        Err(Failure::Input(format!(
+            "{} lines of {} didn't parse",
+            report.skipped.len(),
+            path.display()
+        )))
+    }
+}
+
+pub(crate) fn create(path: &Path) -> Result<File, Failure> {
+    File::create(path).map_err(|err| file_error("create", path, err))
+}
+
+pub(crate) fn file_error(action: &str, path: &Path, err: io::Error) -> Failure {
+    Failure::File(format!("cannot {} {}: {}", action, path.display(), err))
+}
+
+fn output_error(err: io::Error) -> Failure {
+    Failure::File(format!("cannot write output: {}", err))
+}
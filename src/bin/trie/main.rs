@@ -0,0 +1,108 @@
+// Command-line access to a trie of string values kept in a snapshot file, for poking at data
+// files from the shell. Every invocation loads the file named by --file, runs one command and
+// writes the file back if the command changed anything. A file that doesn't exist yet is an
+// empty trie. `trie repl` runs commands read from stdin instead, on a trie kept in memory.
+
+mod command;
+mod repl;
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use trie::Trie;
+
+use crate::command::{Command, Failure, create, file_error, usage, value_of};
+
+const USAGE: &str = "\
+usage: trie --file <path> <command>
+       trie [--file <path>] repl
+
+commands:
+  insert <key> <value>    store value under the hex key
+  get <key>               print the value under key
+  delete <key>            remove key
+  print [--prefix P] [--max-depth N]
+                          draw the trie, or only the part under P, counting
+                          instead of drawing what's more than N levels down
+  dump <file>             write every entry to file as key=value lines
+  load <file>             insert the key=value lines of file
+  repl                    read commands from stdin, starting from the trie in
+                          --file if one is given; changes stay in memory
+
+exit status: 0 on success, 1 if the key isn't in the trie, 2 if the command
+line or a line of a loaded file doesn't parse, 3 if a file can't be read or
+written.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("trie: {}", failure);
+            if let Failure::Usage(_) = failure {
+                eprintln!("run `trie --help` for usage");
+            }
+            ExitCode::from(match failure {
+                Failure::NotFound(_) => 1,
+                Failure::Usage(_) | Failure::Input(_) => 2,
+                Failure::File(_) => 3,
+            })
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), Failure> {
+    let mut file = None;
+    let mut words = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--file" => file = Some(PathBuf::from(value_of(arg, args.next())?)),
+            _ => words.push(arg.as_str()),
+        }
+    }
+
+    if words == ["repl"] {
+        let trie = match &file {
+            Some(file) => open(file)?,
+            None => Trie::new(),
+        };
+        return repl::run(trie).map_err(|err| Failure::File(format!("repl failed: {}", err)));
+    }
+    let command = Command::parse(&words)?;
+    let file = file.ok_or_else(|| usage("no trie file given; pass --file <path>"))?;
+    let mut trie = open(&file)?;
+    let mutates = command.mutates();
+    let result = command.run(&mut trie, &mut io::stdout().lock());
+    // A load that skipped lines still inserted the rest, so those are kept too.
+    if mutates && matches!(result, Ok(()) | Err(Failure::Input(_))) {
+        save(&trie, &file)?;
+    }
+    result
+}
+
+fn open(file: &Path) -> Result<Trie<String>, Failure> {
+    match File::open(file) {
+        Ok(input) => Trie::load_from(input)
+            .map_err(|err| Failure::File(format!("{}: {}", file.display(), err))),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Trie::new()),
+        Err(err) => Err(file_error("open", file, err)),
+    }
+}
+
+// Writes the snapshot next to the file and renames it over the file, so a failed write can't
+// leave half a snapshot behind.
+fn save(trie: &Trie<String>, file: &Path) -> Result<(), Failure> {
+    let mut tmp = file.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    trie.save_to(create(&tmp)?)
+        .and_then(|()| fs::rename(&tmp, file))
+        .map_err(|err| file_error("write", file, err))
+}
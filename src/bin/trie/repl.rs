@@ -0,0 +1,341 @@
+// `trie repl`: commands read a line at a time and run against a trie held in memory, with the
+// trie drawn again after every change, like the demo in main.
+//
+// Only `run` touches the terminal. `parse` turns one line into a `Line` and `session` runs
+// lines from any iterator and writes to any writer, so a session can be driven from a string.
+
+use std::io::{self, IsTerminal, Write};
+
+use trie::{DisplayOptions, Trie};
+
+use crate::command::{Command, Failure, drawing, synopsis, usage};
+
+const HELP: &str = "\
+commands:
+  insert <key> <value>    store value under the hex key; the value is the rest
+                          of the line
+  get <key>               print the value under key
+  delete <key>            remove key
+  print [--prefix P] [--max-depth N]
+                          draw the trie, or only the part under P, counting
+                          instead of drawing what's more than N levels down
+  load <file>             insert the key=value lines of file
+  dump <file>             write every entry to file as key=value lines
+  stats                   show the size and shape of the trie
+  help                    show this list
+  quit                    leave, as does the end of input";
+
+const GREETING: &str = "type `help` for the commands, `quit` to leave";
+
+pub(crate) enum Line {
+    Command(Command),
+    Stats,
+    Help,
+    Quit,
+    Blank,
+}
+
+// Parses one line of input. Blank lines and lines starting with `#` do nothing.
+pub(crate) fn parse(line: &str) -> Result<Line, Failure> {
+    let words = split(line);
+    Ok(match words.as_slice() {
+        [] => Line::Blank,
+        [first, ..] if first.starts_with('#') => Line::Blank,
+        ["stats"] => Line::Stats,
+        ["help"] => Line::Help,
+        ["quit" | "exit"] => Line::Quit,
+        [name @ ("stats" | "help" | "quit" | "exit"), ..] => {
+            return Err(usage(&format!("{} takes no arguments", name)));
+        }
+        [name, ..] if synopsis(name).is_empty() => {
+            return Err(usage(&format!(
+                "unknown command {:?}; type `help` for the commands",
+                name
+            )));
+        }
+        _ => Line::Command(Command::parse(&words)?),
+    })
+}
+
+// Splits a line at whitespace, except that everything after the key of an insert is its
+// value, spaces and all.
+fn split(line: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        if let ["insert", _] = words.as_slice() {
+            words.push(rest);
+            break;
+        }
+        let (word, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        words.push(word);
+        rest = tail.trim_start();
+    }
+    words
+}
+
+// Runs one parsed line, writing its output and any error to `out`. Returns false once the
+// session should end.
+pub(crate) fn execute(
+    trie: &mut Trie<String>,
+    line: Line,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    match line {
+        Line::Command(command) => {
+            let mutates = command.mutates();
+            let result = command.run(trie, out);
+            if let Err(failure) = &result {
+                writeln!(out, "error: {}", failure)?;
+            }
+            // A load that skipped lines still inserted the rest.
+            if mutates && matches!(result, Ok(()) | Err(Failure::Input(_))) {
+                write!(out, "{}", drawing(trie, DisplayOptions::default()))?;
+            }
+        }
+        Line::Stats => {
+            writeln!(out, "{} entries\n{}", trie.len(), trie.stats())?;
+            #[cfg(feature = "metrics")]
+            writeln!(out, "{}", trie.metrics())?;
+        }
+        Line::Help => writeln!(out, "{}", HELP)?,
+        Line::Quit => return Ok(false),
+        Line::Blank => {}
+    }
+    Ok(true)
+}
+
+// Runs lines until `quit` or the end of the input. Lines that don't parse are reported and
+// the session carries on.
+pub(crate) fn session(
+    mut trie: Trie<String>,
+    lines: impl IntoIterator<Item = io::Result<String>>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for line in lines {
+        let go_on = match parse(&line?) {
+            Ok(line) => execute(&mut trie, line, out)?,
+            Err(failure) => {
+                writeln!(out, "error: {}", failure)?;
+                true
+            }
+        };
+        if !go_on {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Reads stdin a line at a time, with a prompt when it's a terminal.
+#[cfg(not(feature = "readline"))]
+pub(crate) fn run(trie: Trie<String>) -> io::Result<()> {
+    let interactive = io::stdin().is_terminal();
+    if interactive {
+        println!("{}", GREETING);
+    }
+    let mut stdin = io::stdin().lock();
+    let lines = std::iter::from_fn(|| {
+        if interactive && let Err(err) = prompt() {
+            return Some(Err(err));
+        }
+        let mut line = String::new();
+        match io::BufRead::read_line(&mut stdin, &mut line) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(line)),
+            Err(err) => Some(Err(err)),
+        }
+    });
+    session(trie, lines, &mut io::stdout())
+}
+
+#[cfg(not(feature = "readline"))]
+fn prompt() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(b"> ")?;
+    stdout.flush()
+}
+
+// Reads lines with rustyline, for line editing and a history kept in ~/.trie_history.
+#[cfg(feature = "readline")]
+pub(crate) fn run(trie: Trie<String>) -> io::Result<()> {
+    use rustyline::error::ReadlineError;
+
+    let mut editor = rustyline::DefaultEditor::new().map_err(io::Error::other)?;
+    let history =
+        std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".trie_history"));
+    if let Some(path) = &history {
+        // It doesn't exist before the first session.
+        let _ = editor.load_history(path);
+    }
+    if io::stdin().is_terminal() {
+        println!("{}", GREETING);
+    }
+    let lines = std::iter::from_fn(|| match editor.readline("> ") {
+        Ok(line) => Some(
+            editor
+                .add_history_entry(&line)
+                .map(|_| line)
+                .map_err(io::Error::other),
+        ),
+        // Ctrl-C drops the line being typed, not the session.
+        Err(ReadlineError::Interrupted) => Some(Ok(String::new())),
+        Err(ReadlineError::Eof) => None,
+        Err(err) => Some(Err(io::Error::other(err))),
+    });
+    session(trie, lines, &mut io::stdout())?;
+    match &history {
+        Some(path) => editor.save_history(path).map_err(io::Error::other),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs `input` as a session on an empty trie and returns what it wrote.
+    fn transcript(input: &str) -> String {
+        let mut out = Vec::new();
+        let lines = input.lines().map(|line| Ok(line.to_string()));
+        session(Trie::new(), lines, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn insert_value(line: &str) -> String {
+        match parse(line) {
+            Ok(Line::Command(Command::Insert { value, .. })) => value,
+            _ => panic!("{:?} isn't an insert", line),
+        }
+    }
+
+    fn error(line: &str) -> String {
+        match parse(line) {
+            Err(failure) => failure.to_string(),
+            Ok(_) => panic!("{:?} parsed", line),
+        }
+    }
+
+    #[test]
+    fn parses_each_command() {
+        assert!(matches!(
+            parse("insert a1f hello"),
+            Ok(Line::Command(Command::Insert { key, value })) if key == "a1f" && value == "hello"
+        ));
+        assert!(matches!(
+            parse("get a1f"),
+            Ok(Line::Command(Command::Get { key })) if key == "a1f"
+        ));
+        assert!(matches!(
+            parse("delete a1f"),
+            Ok(Line::Command(Command::Delete { key })) if key == "a1f"
+        ));
+        assert!(matches!(
+            parse("print --prefix a1 --max-depth 2"),
+            Ok(Line::Command(Command::Print { prefix, max_depth: Some(2) })) if prefix == "a1"
+        ));
+        assert!(matches!(
+            parse("load data.txt"),
+            Ok(Line::Command(Command::Load { path })) if path.to_str() == Some("data.txt")
+        ));
+        assert!(matches!(
+            parse("dump data.txt"),
+            Ok(Line::Command(Command::Dump { path })) if path.to_str() == Some("data.txt")
+        ));
+        assert!(matches!(parse("stats"), Ok(Line::Stats)));
+        assert!(matches!(parse("help"), Ok(Line::Help)));
+        assert!(matches!(parse("quit"), Ok(Line::Quit)));
+        assert!(matches!(parse("exit"), Ok(Line::Quit)));
+    }
+
+    #[test]
+    fn blank_lines_and_comments_do_nothing() {
+        for line in ["", "   ", "\t", "# insert a1 x", "#"] {
+            assert!(matches!(parse(line), Ok(Line::Blank)), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn an_insert_value_is_the_rest_of_the_line() {
+        assert_eq!(insert_value("insert a1 hello world"), "hello world");
+        assert_eq!(insert_value("  insert   a1   two  spaces  "), "two  spaces");
+        assert_eq!(insert_value("insert a1 tab\there"), "tab\there");
+    }
+
+    #[test]
+    fn quotes_in_a_value_are_kept_as_typed() {
+        assert_eq!(insert_value("insert a1 \"quoted\""), "\"quoted\"");
+        assert_eq!(insert_value("insert a1 it's"), "it's");
+        assert_eq!(insert_value("insert a1 # not a comment"), "# not a comment");
+    }
+
+    #[test]
+    fn malformed_lines_explain_themselves() {
+        assert_eq!(
+            error("frobnicate"),
+            "unknown command \"frobnicate\"; type `help` for the commands"
+        );
+        assert_eq!(error("insert a1"), "usage: insert <key> <value>");
+        assert_eq!(error("get"), "usage: get <key>");
+        assert_eq!(error("delete a1 a2"), "usage: delete <key>");
+        assert_eq!(error("stats now"), "stats takes no arguments");
+        assert_eq!(error("quit please"), "quit takes no arguments");
+        assert_eq!(error("print --max-depth"), "--max-depth needs a value");
+        assert_eq!(
+            error("print --max-depth deep"),
+            "--max-depth wants a number, not \"deep\""
+        );
+        assert!(error("print --colour red").starts_with("unknown print option"));
+    }
+
+    #[test]
+    fn a_session_draws_the_trie_after_each_change() {
+        let out = transcript("insert a1 one\nget a1\ndelete a1\nget a1\n");
+        assert_eq!(
+            out,
+            "(root)\n└── a1 = one\n\
+             one\n\
+             (root)\n\
+             error: key \"a1\" not found\n"
+        );
+    }
+
+    #[test]
+    fn a_session_carries_on_after_errors() {
+        let out = transcript("bogus\ninsert zz x\ninsert a y\nget a\n");
+        assert!(
+            out.starts_with("error: unknown command \"bogus\""),
+            "{}",
+            out
+        );
+        assert!(out.contains("error: invalid key \"zz\""), "{}", out);
+        assert!(out.ends_with("y\n"), "{}", out);
+    }
+
+    #[test]
+    fn a_session_stops_at_quit() {
+        let out = transcript("insert a x\nquit\ninsert b y\n");
+        assert_eq!(out, "(root)\n└── a = x\n");
+    }
+
+    #[test]
+    fn help_and_stats_print_without_changing_anything() {
+        assert_eq!(transcript("help"), format!("{}\n", HELP));
+        let out = transcript("insert a1 x\nstats\n");
+        assert!(out.contains("1 entries\n"), "{}", out);
+    }
+
+    #[test]
+    fn load_inserts_a_dataset() {
+        let path = std::env::temp_dir().join(format!("trie-repl-load-{}.txt", std::process::id()));
+        std::fs::write(&path, "a1=one\nff=two\n").unwrap();
+        let out = transcript(&format!("load {}\nget ff\n", path.display()));
+        std::fs::remove_file(&path).unwrap();
+        assert!(
+            out.starts_with("2 inserted, 0 overwritten, 0 skipped\n"),
+            "{}",
+            out
+        );
+        assert!(out.ends_with("two\n"), "{}", out);
+    }
+}
@@ -0,0 +1,103 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::nibbles::Nibbles;
+use crate::node::{Node, Pool};
+use crate::observe::Observer;
+use crate::rollback::Snapshots;
+use crate::trie::Trie;
+
+/// Why [`Trie::from_sorted_iter`] gave up: a key didn't come after the one
+/// before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkLoadError {
+    /// Position of the key in the input.
+    pub index: usize,
+    /// The key, which is smaller than or equal to the key before it.
+    pub key: Nibbles,
+}
+
+impl fmt::Display for BulkLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "key {} at index {} is out of order",
+            self.key, self.index
+        )
+    }
+}
+
+impl Error for BulkLoadError {}
+
+impl<V> Trie<V> {
+    /// Builds a trie from entries sorted by key, as [`Trie::iter_nibbles`]
+    /// yields them, without walking down from the root for every key.
+    ///
+    /// The nodes along the previous key's path are kept on a stack. The
+    /// next key pops the ones its path doesn't share, hooking each onto its
+    /// parent, and pushes new nodes for the rest, so every node is made
+    /// once and never looked up. Keys must strictly increase; a key that
+    /// repeats or goes back is an error.
+    ///
+    /// ```
+    /// use trie::{Nibbles, Trie};
+    ///
+    /// let entries = ["0f", "a1", "a1e", "b"].map(|key| (key.parse::<Nibbles>().unwrap(), key));
+    /// let trie = Trie::from_sorted_iter(entries.clone()).unwrap();
+    /// assert_eq!(trie.get_lossy("a1e"), Some(&"a1e"));
+    ///
+    /// let err = Trie::from_sorted_iter(entries.into_iter().rev()).unwrap_err();
+    /// assert_eq!(err.index, 1);
+    /// ```
+    pub fn from_sorted_iter<I>(iter: I) -> Result<Trie<V>, BulkLoadError>
+    where
+        I: IntoIterator<Item = (Nibbles, V)>,
+    {
+        // `path[d]` is the node at depth `d` of the previous key, with its symbol. None of them
+        // is in its parent yet.
+        let mut path: Vec<(usize, Box<Node<V>>)> = vec![(0, Box::new(Node::new()))];
+        let mut previous: Option<Nibbles> = None;
+        let mut len = 0;
+        for (index, (key, value)) in iter.into_iter().enumerate() {
+            let shared = match &previous {
+                Some(previous) if key <= *previous => {
+                    return Err(BulkLoadError { index, key });
+                }
+                Some(previous) => previous
+                    .iter()
+                    .zip(key.iter())
+                    .take_while(|(a, b)| a == b)
+                    .count(),
+                None => 0,
+            };
+            pop_to(&mut path, shared + 1);
+            for &nibble in &key[shared..] {
+                path.push((nibble as usize, Box::new(Node::new())));
+            }
+            path.last_mut().unwrap().1.value = Some(value);
+            len += 1;
+            previous = Some(key);
+        }
+        pop_to(&mut path, 1);
+        let (_, root) = path.pop().unwrap();
+        Ok(Trie {
+            root: *root,
+            len,
+            pool: Pool::new(),
+            snapshots: Snapshots::default(),
+            observer: Observer::default(),
+        })
+    }
+}
+
+// Hooks the deepest nodes of `path` onto their parents until `depth` are left. Keys only grow,
+// so each one becomes its parent's last child.
+fn pop_to<V>(path: &mut Vec<(usize, Box<Node<V>>)>, depth: usize) {
+    while path.len() > depth {
+        let (symbol, node) = path.pop().unwrap();
+        path.last_mut().unwrap().1.set_child(symbol, node);
+    }
+}
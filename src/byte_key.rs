@@ -0,0 +1,90 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::iter::Walk;
+use crate::key::nibbles_to_hex;
+use crate::trie::Trie;
+
+/// A key with an odd number of nibbles, which can't be written as bytes.
+///
+/// Yielded by [`Trie::iter_bytes`], holding the key as hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OddLengthKey(pub String);
+
+impl fmt::Display for OddLengthKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key {:?} has an odd number of nibbles", self.0)
+    }
+}
+
+impl Error for OddLengthKey {}
+
+// Each byte is two nibbles, high one first, so `[0xa1, 0xf0]` is the path of "a1f0".
+fn byte_nibbles(key: &[u8]) -> impl Iterator<Item = usize> + Clone + '_ {
+    key.iter().flat_map(|&b| [b >> 4, b & 0xf]).map(usize::from)
+}
+
+impl<V> Trie<V> {
+    /// Stores `value` under the byte key, returning the replaced value.
+    ///
+    /// The key is read as its hex encoding without building it, so
+    /// `insert_bytes(&[0xa1, 0xf0], v)` is the same entry as
+    /// `insert("a1f0", v)`.
+    pub fn insert_bytes(&mut self, key: &[u8], value: V) -> Option<V> {
+        self.insert_nibbles(byte_nibbles(key), value)
+    }
+
+    /// Returns the value stored under the byte key, if any.
+    pub fn get_bytes(&self, key: &[u8]) -> Option<&V> {
+        self.get_path(byte_nibbles(key))
+    }
+
+    /// Removes the value stored under the byte key and returns it.
+    pub fn delete_bytes(&mut self, key: &[u8]) -> Option<V> {
+        self.delete_path(byte_nibbles(key))
+    }
+
+    /// Returns an iterator over all entries in key order, with keys decoded
+    /// to bytes.
+    ///
+    /// A key with an odd number of nibbles (`"a1f"`) has no byte form and
+    /// comes out as an [`OddLengthKey`] error in its place; the iterator
+    /// carries on after it. Use `.filter_map(Result::ok)` to skip them.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_bytes(&[0xa1, 0xf0], 1);
+    /// trie.insert_lossy("a1f", 2);
+    /// let entries: Vec<_> = trie.iter_bytes().collect();
+    /// assert_eq!(entries[0], Err(trie::OddLengthKey("a1f".to_string())));
+    /// assert_eq!(entries[1], Ok((vec![0xa1, 0xf0], &1)));
+    /// ```
+    pub fn iter_bytes(&self) -> ByteKeyIter<'_, V> {
+        ByteKeyIter {
+            walk: Walk::new(&self.root, Vec::new()),
+        }
+    }
+}
+
+/// An iterator over the entries of a [`Trie`] in key order with byte keys.
+///
+/// Returned by [`Trie::iter_bytes`].
+pub struct ByteKeyIter<'a, V> {
+    walk: Walk<'a, V>,
+}
+
+impl<'a, V> Iterator for ByteKeyIter<'a, V> {
+    type Item = Result<(Vec<u8>, &'a V), OddLengthKey>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.walk.next_value()?;
+        let path = &self.walk.path;
+        if path.len() % 2 == 1 {
+            return Some(Err(OddLengthKey(nibbles_to_hex(path))));
+        }
+        let key = path.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect();
+        Some(Ok((key, value)))
+    }
+}
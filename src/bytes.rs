@@ -0,0 +1,296 @@
+// A 256-way trie over raw byte keys.
+//
+// A full 256-slot child table would be 2 KiB of pointers per node, and most nodes only have a
+// handful of children, so each node instead keeps its children in a vector sorted by byte.
+// Lookups binary-search it; iteration walks it front to back, which is byte order. Deleting,
+// dropping and drawing all use explicit stacks, so a key can be as long as memory allows.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+struct ByteNode<V> {
+    children: Vec<(u8, Box<ByteNode<V>>)>,
+    value: Option<V>,
+}
+
+impl<V> ByteNode<V> {
+    fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            value: None,
+        }
+    }
+
+    fn child(&self, byte: u8) -> Option<&ByteNode<V>> {
+        let i = self.children.binary_search_by_key(&byte, |c| c.0).ok()?;
+        Some(&self.children[i].1)
+    }
+
+    fn child_mut(&mut self, byte: u8) -> Option<&mut ByteNode<V>> {
+        let i = self.children.binary_search_by_key(&byte, |c| c.0).ok()?;
+        Some(&mut self.children[i].1)
+    }
+
+    fn child_or_insert(&mut self, byte: u8) -> &mut ByteNode<V> {
+        let i = match self.children.binary_search_by_key(&byte, |c| c.0) {
+            Ok(i) => i,
+            Err(i) => {
+                self.children.insert(i, (byte, Box::new(ByteNode::new())));
+                i
+            }
+        };
+        &mut self.children[i].1
+    }
+
+    fn find(&self, key: &[u8]) -> Option<&ByteNode<V>> {
+        key.iter().try_fold(self, |node, &byte| node.child(byte))
+    }
+}
+
+// Moves the children onto a worklist so dropping a deep key doesn't recurse once per byte.
+impl<V> Drop for ByteNode<V> {
+    fn drop(&mut self) {
+        if self.children.is_empty() {
+            return;
+        }
+        let mut work = core::mem::take(&mut self.children);
+        while let Some((_, mut node)) = work.pop() {
+            work.append(&mut node.children);
+        }
+    }
+}
+
+/// A trie keyed by byte strings, one byte per level.
+///
+/// Unlike [`Trie`](crate::Trie), keys don't have to be hex encoded first,
+/// which halves their length. Each node stores only the children it has.
+pub struct ByteTrie<V> {
+    root: ByteNode<V>,
+    len: usize,
+}
+
+impl<V> Default for ByteTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> ByteTrie<V> {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self {
+            root: ByteNode::new(),
+            len: 0,
+        }
+    }
+
+    /// Stores `value` under `key`, returning the value that was replaced.
+    pub fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        let node = key
+            .iter()
+            .fold(&mut self.root, |node, &byte| node.child_or_insert(byte));
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Returns the value stored under `key`, if any.
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        self.root.find(key)?.value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value stored under `key`, if any.
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        let mut cur = &mut self.root;
+        for &byte in key {
+            cur = cur.child_mut(byte)?;
+        }
+        cur.value.as_mut()
+    }
+
+    /// Returns `true` if a value is stored under `key`.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes the value stored under `key` and returns it, pruning nodes
+    /// left without values or children.
+    pub fn delete(&mut self, key: &[u8]) -> Option<V> {
+        // Walk down once to find the deepest node on the path that stays (the root, or one
+        // with a value or another child); everything below it goes with the value.
+        let mut cut = 0;
+        let mut cur = &self.root;
+        for (depth, &byte) in key.iter().enumerate() {
+            if cur.value.is_some() || cur.children.len() > 1 {
+                cut = depth;
+            }
+            cur = cur.child(byte)?;
+        }
+        cur.value.as_ref()?;
+        self.len -= 1;
+        if key.is_empty() || !cur.children.is_empty() {
+            let mut cur = &mut self.root;
+            for &byte in key {
+                cur = cur.child_mut(byte).unwrap();
+            }
+            return cur.value.take();
+        }
+
+        let mut cur = &mut self.root;
+        for &byte in &key[..cut] {
+            cur = cur.child_mut(byte).unwrap();
+        }
+        let i = cur
+            .children
+            .binary_search_by_key(&key[cut], |c| c.0)
+            .unwrap();
+        let (_, mut chain) = cur.children.remove(i);
+        // every node in the chain but the last has exactly one child
+        while let Some((_, next)) = chain.children.pop() {
+            chain = next;
+        }
+        chain.value.take()
+    }
+
+    /// Returns the number of stored values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the trie holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of nodes, including the root.
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.children.iter().map(|c| &*c.1));
+        }
+        count
+    }
+
+    /// Returns an iterator over all entries in byte order.
+    pub fn iter(&self) -> ByteIter<'_, V> {
+        ByteIter {
+            stack: vec![(&self.root, 0)],
+            path: Vec::new(),
+        }
+    }
+
+    /// Returns an iterator over the entries whose key starts with `prefix`,
+    /// in byte order.
+    pub fn iter_prefix(&self, prefix: &[u8]) -> ByteIter<'_, V> {
+        ByteIter {
+            stack: self.root.find(prefix).map(|n| (n, 0)).into_iter().collect(),
+            path: prefix.to_vec(),
+        }
+    }
+}
+
+/// An iterator over the entries of a [`ByteTrie`] in byte order.
+///
+/// Returned by [`ByteTrie::iter`] and [`ByteTrie::iter_prefix`].
+pub struct ByteIter<'a, V> {
+    // (node, cursor): cursor 0 means the node's value is still pending, i + 1 means its i-th
+    // child is next
+    stack: Vec<(&'a ByteNode<V>, usize)>,
+    path: Vec<u8>,
+}
+
+impl<'a, V> Iterator for ByteIter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, cursor)) = self.stack.last_mut() {
+            let node: &'a ByteNode<V> = node;
+            if *cursor == 0 {
+                *cursor = 1;
+                if let Some(value) = &node.value {
+                    return Some((self.path.clone(), value));
+                }
+                continue;
+            }
+            match node.children.get(*cursor - 1) {
+                Some((byte, child)) => {
+                    *cursor += 1;
+                    self.path.push(*byte);
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    if !self.stack.is_empty() {
+                        self.path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a, V> IntoIterator for &'a ByteTrie<V> {
+    type Item = (Vec<u8>, &'a V);
+    type IntoIter = ByteIter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Same drawing as Trie's, with each byte written as two hex digits and runs of value-less
+// single-child nodes collapsed onto one line. The indent is one shared string cut back to the
+// depth of each line.
+impl<V: fmt::Display> fmt::Display for ByteTrie<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // (byte, node, depth, whether it is its parent's last child); children go on in
+        // reverse so they come off in byte order
+        fn push_children<'a, V>(
+            stack: &mut Vec<(u8, &'a ByteNode<V>, usize, bool)>,
+            node: &'a ByteNode<V>,
+            depth: usize,
+        ) {
+            let last = node.children.len().wrapping_sub(1);
+            for (i, (byte, child)) in node.children.iter().enumerate().rev() {
+                stack.push((*byte, child, depth, i == last));
+            }
+        }
+
+        let value_str = |node: &ByteNode<V>| {
+            node.value
+                .as_ref()
+                .map(|v| format!(" = {}", v))
+                .unwrap_or_default()
+        };
+        writeln!(f, "(root){}", value_str(&self.root))?;
+        let mut stack = Vec::new();
+        push_children(&mut stack, &self.root, 0);
+        let mut indent = String::new();
+        // widths[d] is the length of `indent` for a line at depth d
+        let mut widths = vec![0];
+        while let Some((byte, mut child, depth, is_last)) = stack.pop() {
+            widths.truncate(depth + 1);
+            indent.truncate(widths[depth]);
+            let bullet = if is_last { "└── " } else { "├── " };
+            let mut label = format!("{:02x}", byte);
+            while let ([(byte, only)], None) = (&child.children[..], &child.value) {
+                label.push_str(&format!("{:02x}", byte));
+                child = only;
+            }
+            writeln!(f, "{}{}{}{}", indent, bullet, label, value_str(child))?;
+            indent.push_str(if is_last { "    " } else { "│   " });
+            widths.push(indent.len());
+            push_children(&mut stack, child, depth + 1);
+        }
+        Ok(())
+    }
+}
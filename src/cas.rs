@@ -0,0 +1,82 @@
+use core::error::Error;
+use core::fmt;
+
+use crate::key::key_symbols;
+use crate::trie::Trie;
+
+/// Why [`Trie::compare_and_swap`] left a key alone: its value wasn't the
+/// expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CasError<V> {
+    /// The value the key holds, or `None` if it holds none, to retry with.
+    pub actual: Option<V>,
+}
+
+impl<V: fmt::Debug> fmt::Display for CasError<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.actual {
+            Some(value) => write!(f, "key holds {:?} instead of the expected value", value),
+            None => write!(f, "key holds no value instead of the expected one"),
+        }
+    }
+}
+
+impl<V: fmt::Debug> Error for CasError<V> {}
+
+impl<V: PartialEq + Clone, const N: usize> Trie<V, N> {
+    /// Writes `new` under `hex_key` only if the key holds `expected` right
+    /// now, where `None` on either side means no value: `expected: None`
+    /// inserts only into a missing key, and `new: None` deletes, pruning
+    /// like [`Trie::delete_lossy`].
+    ///
+    /// The comparison and the write happen in one walk down the key. If the
+    /// values differ nothing changes and the error carries the current
+    /// value.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.compare_and_swap("a1", None, Some(1)).unwrap();
+    /// let err = trie.compare_and_swap("a1", None, Some(2)).unwrap_err();
+    /// assert_eq!(err.actual, Some(1));
+    /// trie.compare_and_swap("a1", err.actual.as_ref(), Some(2)).unwrap();
+    /// assert_eq!(trie.get_lossy("a1"), Some(&2));
+    /// trie.compare_and_swap("a1", Some(&2), None).unwrap();
+    /// assert!(trie.is_empty());
+    /// ```
+    pub fn compare_and_swap(
+        &mut self,
+        hex_key: &str,
+        expected: Option<&V>,
+        new: Option<V>,
+    ) -> Result<(), CasError<V>> {
+        self.save_key(hex_key);
+        let mut actual = None;
+        // the value swapped out, kept only for an observer to hear about
+        let mut old = None;
+        let watched = self.observer.get().is_some();
+        let symbols = key_symbols::<N>(hex_key);
+        let counts = self.root.update_nibbles(
+            symbols,
+            |current| {
+                if current.as_ref() == expected {
+                    if watched {
+                        old = current;
+                    }
+                    new
+                } else {
+                    actual = Some(current.clone());
+                    current
+                }
+            },
+            &mut self.pool,
+        );
+        self.count_update(counts);
+        if actual.is_none() {
+            self.report_update(hex_key, counts, old.as_ref());
+        }
+        match actual {
+            Some(actual) => Err(CasError { actual }),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+use crate::iter::Walk;
+use crate::trie::Trie;
+
+// Two tries are equal when they hold the same keys with equal values. This is checked by
+// walking both in key order side by side, exactly what `iter()` would yield, rather than by
+// comparing node layout: mutations always prune empty nodes so the layouts would agree anyway,
+// but the contents are what callers mean by equality.
+impl<V: PartialEq> PartialEq for Trie<V> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+        let mut a = Walk::new(&self.root, Vec::new());
+        let mut b = Walk::new(&other.root, Vec::new());
+        loop {
+            match (a.next_value(), b.next_value()) {
+                (None, None) => return true,
+                (Some(x), Some(y)) if a.path == b.path && x == y => {}
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl<V: Eq> Eq for Trie<V> {}
+
+// Hashes the same thing equality compares: every (key, value) pair in key order, so equal
+// tries always hash alike whatever their history.
+impl<V: Hash> Hash for Trie<V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        let mut walk = Walk::new(&self.root, Vec::new());
+        while let Some(value) = walk.next_value() {
+            walk.path.hash(state);
+            value.hash(state);
+        }
+    }
+}
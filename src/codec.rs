@@ -0,0 +1,54 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Conversion between values and raw bytes, used wherever a trie has to
+/// store or hash its values outside of memory.
+pub trait ValueCodec: Sized {
+    /// Returns the byte encoding of the value.
+    fn encode(&self) -> Cow<'_, [u8]>;
+
+    /// Rebuilds a value from its encoding, or `None` if the bytes are not a
+    /// valid encoding.
+    fn decode(bytes: Vec<u8>) -> Option<Self>;
+}
+
+impl ValueCodec for String {
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+
+    fn decode(bytes: Vec<u8>) -> Option<Self> {
+        String::from_utf8(bytes).ok()
+    }
+}
+
+impl ValueCodec for Vec<u8> {
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+
+    fn decode(bytes: Vec<u8>) -> Option<Self> {
+        Some(bytes)
+    }
+}
+
+impl ValueCodec for u64 {
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.to_be_bytes().to_vec())
+    }
+
+    fn decode(bytes: Vec<u8>) -> Option<Self> {
+        Some(u64::from_be_bytes(bytes.try_into().ok()?))
+    }
+}
+
+impl ValueCodec for () {
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&[])
+    }
+
+    fn decode(bytes: Vec<u8>) -> Option<Self> {
+        bytes.is_empty().then_some(())
+    }
+}
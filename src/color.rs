@@ -0,0 +1,72 @@
+use std::fmt;
+use std::io::IsTerminal;
+
+use crate::display::{DisplayOptions, Drawing, PLAIN, Palette};
+use crate::trie::Trie;
+
+// Bold blue root, dim branches, bold green labels for nodes holding values and dim markers
+// for what was left out. Structural labels and the values themselves keep the terminal's
+// default color.
+const ANSI: Palette = Palette {
+    root: "\x1b[1;34m",
+    branch: "\x1b[2m",
+    value_label: "\x1b[1;32m",
+    marker: "\x1b[2m",
+};
+
+/// Whether [`Trie::display_colored`] colors its output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color only when stdout is a terminal and `NO_COLOR` isn't set to
+    /// anything but an empty string.
+    #[default]
+    Auto,
+    /// Always color, even when writing to a file or a pipe.
+    Always,
+    /// Never color, which draws exactly what `Display` does.
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns whether this choice colors output right now, checking stdout
+    /// and `NO_COLOR` for [`ColorChoice::Auto`].
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl<V: fmt::Display, const N: usize> Trie<V, N> {
+    /// Returns the drawing [`Trie::display_with`] makes, colored with ANSI
+    /// escapes if `color` says so: the root, the branches, the labels of
+    /// nodes holding values and the markers for left-out entries each get
+    /// their own color. Without the color the text is exactly the same.
+    ///
+    /// `color` is looked at once, when this is called.
+    ///
+    /// ```
+    /// use trie::{ColorChoice, DisplayOptions, Trie};
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert_lossy("a1f", "leaf-A1F");
+    /// let colored = trie
+    ///     .display_colored(DisplayOptions::default(), ColorChoice::Always)
+    ///     .to_string();
+    /// assert!(colored.contains("\x1b[1;32ma1f\x1b[0m = leaf-A1F"));
+    /// let plain = trie.display_colored(DisplayOptions::default(), ColorChoice::Never);
+    /// assert_eq!(plain.to_string(), trie.to_string());
+    /// ```
+    pub fn display_colored(&self, opts: DisplayOptions, color: ColorChoice) -> impl fmt::Display {
+        Drawing {
+            root: &self.root,
+            opts,
+            palette: if color.enabled() { &ANSI } else { &PLAIN },
+        }
+    }
+}
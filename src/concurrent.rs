@@ -0,0 +1,155 @@
+// A trie shared between threads, split by the first nibble of the key into sixteen shards
+// that each sit behind their own lock, so threads working on keys with different first
+// nibbles never wait for each other. The empty key has no first nibble and gets a slot of its
+// own. A shard only ever holds keys starting with its nibble, stored whole, so reading the
+// shards in order reads the keys in order.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::key::hex_to_nibbles;
+use crate::trie::Trie;
+
+/// A trie that many threads can read and write at once through `&self`,
+/// sharded by the first hex digit of the key.
+///
+/// Keys are read leniently, like [`Trie::insert_lossy`] reads them. Reads of one
+/// shard run side by side; a write locks only the shard it goes to. Values
+/// never escape a lock by reference: [`ConcurrentTrie::get`] clones the
+/// value and [`ConcurrentTrie::get_with`] runs a closure on it.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let trie = Arc::new(trie::ConcurrentTrie::new());
+/// let writers: Vec<_> = (0..4)
+///     .map(|t| {
+///         let trie = Arc::clone(&trie);
+///         thread::spawn(move || {
+///             for i in 0..100 {
+///                 trie.insert(&format!("{:x}{:02x}", t, i), i);
+///             }
+///         })
+///     })
+///     .collect();
+/// for writer in writers {
+///     writer.join().unwrap();
+/// }
+/// assert_eq!(trie.len(), 400);
+/// assert_eq!(trie.get("30a"), Some(10));
+/// ```
+pub struct ConcurrentTrie<V> {
+    shards: [RwLock<Trie<V>>; 16],
+    // the value under the empty key
+    root: RwLock<Option<V>>,
+    len: AtomicUsize,
+}
+
+impl<V> Default for ConcurrentTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> ConcurrentTrie<V> {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| RwLock::new(Trie::new())),
+            root: RwLock::new(None),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Stores `value` under `hex_key` and returns the value it replaced.
+    pub fn insert(&self, hex_key: &str, value: V) -> Option<V> {
+        let old = match shard_of(hex_key) {
+            Some(shard) => write(&self.shards[shard]).insert_lossy(hex_key, value),
+            None => write(&self.root).replace(value),
+        };
+        if old.is_none() {
+            self.len.fetch_add(1, Ordering::Relaxed);
+        }
+        old
+    }
+
+    /// Returns a clone of the value stored under `hex_key`, if any.
+    pub fn get(&self, hex_key: &str) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.get_with(hex_key, V::clone)
+    }
+
+    /// Runs `f` on the value stored under `hex_key`, if any, while holding
+    /// the read lock on its shard, and returns what `f` returns.
+    ///
+    /// `f` must not write to the same trie, or it waits for itself.
+    pub fn get_with<R>(&self, hex_key: &str, f: impl FnOnce(&V) -> R) -> Option<R> {
+        match shard_of(hex_key) {
+            Some(shard) => read(&self.shards[shard]).get_lossy(hex_key).map(f),
+            None => read(&self.root).as_ref().map(f),
+        }
+    }
+
+    /// Removes the value stored under `hex_key` and returns it.
+    pub fn delete(&self, hex_key: &str) -> Option<V> {
+        let old = match shard_of(hex_key) {
+            Some(shard) => write(&self.shards[shard]).delete_lossy(hex_key),
+            None => write(&self.root).take(),
+        };
+        if old.is_some() {
+            self.len.fetch_sub(1, Ordering::Relaxed);
+        }
+        old
+    }
+
+    /// Returns the number of stored values. With writes going on at the
+    /// same time it's only a moment's count.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if no values are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over a snapshot of all entries in key order.
+    ///
+    /// Every shard is read-locked at once while the entries are copied out,
+    /// so the snapshot is the trie as it was at one moment. The locks are
+    /// let go before the iterator is returned.
+    pub fn iter(&self) -> std::vec::IntoIter<(String, V)>
+    where
+        V: Clone,
+    {
+        // Always taken root first, then the shards in order. Writers hold one lock at a time,
+        // so this can't wait in a cycle with them.
+        let root = read(&self.root);
+        let shards: Vec<_> = self.shards.iter().map(read).collect();
+        let mut entries =
+            Vec::with_capacity(shards.iter().map(|shard| shard.len()).sum::<usize>() + 1);
+        entries.extend(root.iter().map(|value| (String::new(), value.clone())));
+        for shard in &shards {
+            entries.extend(shard.iter().map(|(key, value)| (key, value.clone())));
+        }
+        entries.into_iter()
+    }
+}
+
+// The shard for `hex_key`, or `None` for the empty key.
+fn shard_of(hex_key: &str) -> Option<usize> {
+    hex_to_nibbles(hex_key).next()
+}
+
+// The trie's own methods leave a shard whole even when a panic cuts them short, so a poisoned
+// lock is used like any other.
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,167 @@
+// Conversions between a trie and the std maps keyed by hex strings.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::hash::BuildHasher;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::key::{KeyError, check_key};
+use crate::nibbles::Nibbles;
+use crate::trie::Trie;
+
+/// Why a map couldn't be turned into a [`Trie`]: some of its keys aren't
+/// hex, or name the same entry as another key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapKeyError {
+    /// Every key that isn't valid hex, sorted, with what's wrong with it.
+    pub invalid: Vec<(String, KeyError)>,
+    /// Keys spelled by more than one key of the map, since hex digits
+    /// aren't case-sensitive: `"A1"` and `"a1"` are one entry. Sorted and in
+    /// lowercase. Only checked once every key is known to be valid.
+    pub colliding: Vec<String>,
+}
+
+impl fmt::Display for MapKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = match (self.invalid.first(), self.colliding.first()) {
+            (Some((key, err)), _) => {
+                write!(f, "invalid key {:?}: {}", key, err)?;
+                self.invalid.len()
+            }
+            (None, Some(key)) => {
+                write!(f, "key {:?} is spelled more than once", key)?;
+                self.colliding.len()
+            }
+            (None, None) => return write!(f, "no rejected keys"),
+        };
+        if count > 1 {
+            write!(f, " (and {} more)", count - 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for MapKeyError {}
+
+/// Builds a trie from a map after checking every key, failing with all the
+/// keys that are rejected rather than just the first.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use trie::Trie;
+///
+/// let map = HashMap::from([("a1".to_string(), 1), ("b0".to_string(), 2)]);
+/// let trie = Trie::try_from(map).unwrap();
+/// assert_eq!(trie.get_lossy("b0"), Some(&2));
+///
+/// let map: HashMap<_, _> = [("zz", 1), ("a1", 2), ("q", 3)]
+///     .map(|(key, value)| (key.to_string(), value))
+///     .into();
+/// let err = Trie::try_from(map).unwrap_err();
+/// let keys: Vec<_> = err.invalid.iter().map(|(key, _)| key.as_str()).collect();
+/// assert_eq!(keys, ["q", "zz"]);
+/// ```
+#[cfg(feature = "std")]
+impl<V, S> TryFrom<HashMap<String, V, S>> for Trie<V> {
+    type Error = MapKeyError;
+
+    fn try_from(map: HashMap<String, V, S>) -> Result<Self, Self::Error> {
+        let invalid = invalid_keys(map.keys());
+        build(map, invalid)
+    }
+}
+
+/// Builds a trie from a map after checking every key, failing with all the
+/// keys that are rejected rather than just the first.
+///
+/// Lowercase hex keys sort the same way as strings and as nibble paths, so
+/// when every key is lowercase the trie is built in one pass by
+/// [`Trie::from_sorted_iter`].
+impl<V> TryFrom<BTreeMap<String, V>> for Trie<V> {
+    type Error = MapKeyError;
+
+    fn try_from(map: BTreeMap<String, V>) -> Result<Self, Self::Error> {
+        let invalid = invalid_keys(map.keys());
+        if !invalid.is_empty()
+            || map
+                .keys()
+                .any(|key| key.bytes().any(|b| b.is_ascii_uppercase()))
+        {
+            return build(map, invalid);
+        }
+        let entries = map
+            .into_iter()
+            .map(|(key, value)| (Nibbles::from_hex(&key).unwrap(), value));
+        Ok(Trie::from_sorted_iter(entries).expect("lowercase keys of a BTreeMap are sorted"))
+    }
+}
+
+/// Moves every entry into a `HashMap`, keyed by lowercase hex.
+#[cfg(feature = "std")]
+impl<V, S: BuildHasher + Default> From<Trie<V>> for HashMap<String, V, S> {
+    fn from(trie: Trie<V>) -> Self {
+        let mut map = HashMap::with_capacity_and_hasher(trie.len(), S::default());
+        map.extend(trie);
+        map
+    }
+}
+
+/// Moves every entry into a `BTreeMap`, keyed by lowercase hex.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// let mut trie = trie::Trie::new();
+/// trie.insert_lossy("A1", 1);
+/// trie.insert_lossy("0f", 2);
+/// let map = BTreeMap::from(trie);
+/// let keys: Vec<_> = map.keys().map(String::as_str).collect();
+/// assert_eq!(keys, ["0f", "a1"]);
+/// ```
+impl<V> From<Trie<V>> for BTreeMap<String, V> {
+    fn from(trie: Trie<V>) -> Self {
+        // The trie yields keys in order, which the map's bulk build from an iterator handles
+        // without searching for each key.
+        trie.into_iter().collect()
+    }
+}
+
+fn invalid_keys<'a>(keys: impl Iterator<Item = &'a String>) -> Vec<(String, KeyError)> {
+    let mut invalid: Vec<_> = keys
+        .filter_map(|key| Some((key.clone(), check_key::<16>(key).err()?)))
+        .collect();
+    invalid.sort_by(|(a, _), (b, _)| a.cmp(b));
+    invalid
+}
+
+// Inserts every entry once the keys are known to be valid. A key the trie already holds was
+// spelled twice, the second time in a different case.
+fn build<V>(
+    entries: impl IntoIterator<Item = (String, V)>,
+    invalid: Vec<(String, KeyError)>,
+) -> Result<Trie<V>, MapKeyError> {
+    if !invalid.is_empty() {
+        return Err(MapKeyError {
+            invalid,
+            colliding: Vec::new(),
+        });
+    }
+    let mut trie = Trie::new();
+    let mut colliding = Vec::new();
+    for (key, value) in entries {
+        if trie.insert_lossy(&key, value).is_some() {
+            colliding.push(key.to_ascii_lowercase());
+        }
+    }
+    if colliding.is_empty() {
+        return Ok(trie);
+    }
+    colliding.sort();
+    colliding.dedup();
+    Err(MapKeyError { invalid, colliding })
+}
@@ -0,0 +1,339 @@
+// Cursors that keep the path to the node they're at, so the next operation starts from where
+// its key parts from the last one instead of from the root.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+use crate::key::{hex_to_nibbles, key_symbols, symbols_to_hex};
+use crate::nibbles::Nibbles;
+use crate::node::{Node, Pool};
+use crate::observe::Observer;
+use crate::rollback::Snapshots;
+use crate::trie::Trie;
+
+/// A cursor for runs of writes and lookups whose keys are close to each
+/// other, returned by [`Trie::cursor_mut`].
+///
+/// The cursor remembers the nodes along the key of the last call. The next
+/// call backs up only to where its key parts from that one and walks down
+/// from there, so keys that share most of their digits skip most of the
+/// walk. Keys in any order give the same results as the trie's own methods;
+/// they're just not faster.
+///
+/// The cursor holds the trie's mutable borrow for as long as it lives.
+///
+/// ```
+/// let mut trie = trie::Trie::new();
+/// let mut cursor = trie.cursor_mut();
+/// for i in 0..256 {
+///     cursor.insert(&format!("a1b2c3{:02x}", i), i);
+/// }
+/// assert_eq!(cursor.delete("a1b2c3ff"), Some(255));
+/// assert_eq!(cursor.get("a1b2c310"), Some(&16));
+/// assert_eq!(trie.len(), 255);
+/// ```
+pub struct CursorMut<'a, V, const N: usize = 16> {
+    // `nodes[0]` is the root and `nodes[d + 1]` the child of `nodes[d]` under `path[d]`, down
+    // the last key for as far as it exists. These point into the trie borrowed for 'a, which
+    // only the cursor can reach while it lives.
+    nodes: Vec<NonNull<Node<V, N>>>,
+    path: Vec<u8>,
+    // How many of the top of `nodes` have already lost their cached hash. The cursor never
+    // computes one, so they stay that way.
+    invalidated: usize,
+    // The symbols of the key being looked at, kept to reuse the buffer.
+    key: Vec<u8>,
+    len: &'a mut usize,
+    pool: &'a mut Pool<V, N>,
+    snapshots: &'a mut Snapshots<V, N>,
+    observer: &'a mut Observer<V>,
+    _trie: PhantomData<&'a mut Node<V, N>>,
+}
+
+impl<V, const N: usize> Trie<V, N> {
+    /// Returns a cursor for runs of nearby keys, starting at the root.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, V, N> {
+        let Trie {
+            root,
+            len,
+            pool,
+            snapshots,
+            observer,
+        } = self;
+        CursorMut {
+            nodes: vec![NonNull::from(root)],
+            path: Vec::new(),
+            invalidated: 0,
+            key: Vec::new(),
+            len,
+            pool,
+            snapshots,
+            observer,
+            _trie: PhantomData,
+        }
+    }
+}
+
+impl<V, const N: usize> CursorMut<'_, V, N> {
+    /// Stores `value` under `hex_key` and returns the value it replaced, as
+    /// [`Trie::insert_lossy`] does.
+    pub fn insert(&mut self, hex_key: &str, value: V) -> Option<V> {
+        self.seek(hex_key);
+        self.invalidate();
+        while self.path.len() < self.key.len() {
+            let symbol = self.key[self.path.len()];
+            let mut top = *self.nodes.last().unwrap();
+            // SAFETY: see `top`.
+            let child = unsafe { top.as_mut() }.child_or_insert(symbol as usize, self.pool);
+            child.invalidate();
+            self.nodes.push(NonNull::from(child));
+            self.path.push(symbol);
+        }
+        self.invalidated = self.nodes.len();
+        // SAFETY: see `top`.
+        let old = unsafe { self.top() }.value.replace(value);
+        self.snapshots.save_value(&self.key, old.as_ref());
+        if old.is_none() {
+            *self.len += 1;
+        }
+        #[cfg(feature = "metrics")]
+        self.pool
+            .metrics
+            .record_insert(old.is_some(), self.key.len());
+        let top = *self.nodes.last().unwrap();
+        if let Some(observer) = self.observer.get() {
+            // SAFETY: see `top`.
+            let new = unsafe { top.as_ref() }.value.as_ref().unwrap();
+            observer.on_insert(&symbols_to_hex::<N>(&self.key), old.as_ref(), new);
+        }
+        old
+    }
+
+    /// Returns the value stored under `hex_key`, if any.
+    pub fn get(&mut self, hex_key: &str) -> Option<&V> {
+        let found = self.seek(hex_key);
+        #[cfg(feature = "metrics")]
+        {
+            // SAFETY: see `top`.
+            let hit = found && unsafe { self.top() }.value.is_some();
+            self.pool.metrics.record_lookup(hit);
+        }
+        if !found {
+            return None;
+        }
+        // SAFETY: see `top`; the reference keeps the cursor borrowed, so nothing can change
+        // the node while it's alive.
+        unsafe { self.top() }.value.as_ref()
+    }
+
+    /// Removes the value stored under `hex_key` and returns it, pruning the
+    /// nodes it leaves empty as [`Trie::delete_lossy`] does.
+    pub fn delete(&mut self, hex_key: &str) -> Option<V> {
+        // SAFETY (here and below): see `top`.
+        if !self.seek(hex_key) || unsafe { self.top() }.value.is_none() {
+            return None;
+        }
+        self.invalidate();
+        *self.len -= 1;
+        #[cfg(feature = "metrics")]
+        self.pool.metrics.record_delete();
+        let depth = self.path.len();
+        let value = unsafe { self.top() }.value.take();
+        self.snapshots.save_value(&self.key, value.as_ref());
+        if let (Some(observer), Some(value)) = (self.observer.get(), &value) {
+            observer.on_delete(&symbols_to_hex::<N>(&self.key), value);
+        }
+        if depth == 0 || unsafe { self.top() }.has_children() {
+            return value;
+        }
+        // Like delete_nibbles: the chain below the deepest node above that keeps a value or
+        // another child exists only for this key. The cursor lets go of it before it's cut off.
+        let cut = (0..depth)
+            .rev()
+            .find(|&d| {
+                let node = unsafe { self.nodes[d].as_ref() };
+                d == 0 || node.value.is_some() || node.child_count() > 1
+            })
+            .unwrap();
+        let symbol = self.path[cut];
+        self.nodes.truncate(cut + 1);
+        self.path.truncate(cut);
+        self.invalidated = self.nodes.len();
+        let chain = unsafe { self.top() }.take_child(symbol as usize).unwrap();
+        self.pool.put(chain);
+        value
+    }
+
+    // Moves the cursor to `hex_key`: back up to the deepest node the last key shares with it,
+    // then down through the nodes that exist. Returns whether the whole key was found.
+    fn seek(&mut self, hex_key: &str) -> bool {
+        self.key.clear();
+        self.key
+            .extend(key_symbols::<N>(hex_key).map(|symbol| symbol as u8));
+        let shared = self
+            .path
+            .iter()
+            .zip(&self.key)
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.nodes.truncate(shared + 1);
+        self.path.truncate(shared);
+        self.invalidated = self.invalidated.min(self.nodes.len());
+        while let Some(&symbol) = self.key.get(self.path.len()) {
+            let mut top = *self.nodes.last().unwrap();
+            // SAFETY: see `top`.
+            let Some(child) = unsafe { top.as_mut() }.child_mut(symbol as usize) else {
+                return false;
+            };
+            self.nodes.push(NonNull::from(child));
+            self.path.push(symbol);
+        }
+        true
+    }
+
+    // Drops the cached hash of every node on the path that may still have one, before the
+    // node at the end changes.
+    fn invalidate(&mut self) {
+        for node in &mut self.nodes[self.invalidated..] {
+            // SAFETY: see `top`.
+            unsafe { node.as_mut() }.invalidate();
+        }
+        self.invalidated = self.nodes.len();
+    }
+
+    // The node at the end of the path.
+    //
+    // SAFETY: every pointer in `nodes` was made from a mutable reference into the trie, which
+    // stays borrowed for as long as the cursor lives, and each is the child of the one before
+    // it. The root is a field of the trie and every other node is boxed, so none of them move
+    // when their parent's children change, and the only changes that remove a node from the
+    // trie, in `delete`, take it off `nodes` first. Only one of these references is alive at a
+    // time, and it borrows the cursor.
+    unsafe fn top(&mut self) -> &mut Node<V, N> {
+        unsafe { self.nodes.last_mut().unwrap().as_mut() }
+    }
+}
+
+/// A cursor for walking a [`Trie`] by hand, one node at a time, returned by
+/// [`Trie::cursor`].
+///
+/// It starts at the root and sits on a node, which may or may not hold a
+/// value, and keeps the nodes above it so that moving costs one step. Moves
+/// that can't be made return `false` and leave the cursor where it was.
+/// Cursors only borrow the trie, so any number of them can walk it at once.
+///
+/// ```
+/// let mut trie = trie::Trie::new();
+/// for key in ["a1", "a1f", "b0"] {
+///     trie.insert_lossy(key, key);
+/// }
+/// let mut cursor = trie.cursor();
+/// assert!(cursor.first_child());
+/// assert_eq!(cursor.path().to_hex(), "a");
+/// assert!(cursor.descend(1));
+/// assert_eq!(cursor.value(), Some(&"a1"));
+/// assert!(!cursor.next_sibling());
+/// assert!(cursor.ascend());
+/// assert!(cursor.next_sibling());
+/// assert_eq!(cursor.path().to_hex(), "b");
+/// assert!(cursor.seek("a1f"));
+/// assert_eq!(cursor.value(), Some(&"a1f"));
+/// ```
+#[derive(Clone)]
+pub struct Cursor<'a, V> {
+    // `nodes[0]` is the root and `nodes[d + 1]` the child of `nodes[d]` under `path[d]`.
+    nodes: Vec<&'a Node<V>>,
+    path: Nibbles,
+}
+
+impl<V> Trie<V> {
+    /// Returns a cursor at the root.
+    pub fn cursor(&self) -> Cursor<'_, V> {
+        Cursor {
+            nodes: vec![&self.root],
+            path: Nibbles::default(),
+        }
+    }
+}
+
+impl<'a, V> Cursor<'a, V> {
+    /// The value of the node the cursor is at, if it holds one.
+    pub fn value(&self) -> Option<&'a V> {
+        self.node().value.as_ref()
+    }
+
+    /// The key of the node the cursor is at: the nibbles from the root down
+    /// to it.
+    pub fn path(&self) -> &Nibbles {
+        &self.path
+    }
+
+    /// Moves to the child under `nibble`, if there is one.
+    pub fn descend(&mut self, nibble: u8) -> bool {
+        if nibble >= 16 {
+            return false;
+        }
+        let Some(child) = self.node().child(nibble as usize) else {
+            return false;
+        };
+        self.nodes.push(child);
+        self.path.0.push(nibble);
+        true
+    }
+
+    /// Moves to the parent, unless the cursor is at the root.
+    pub fn ascend(&mut self) -> bool {
+        if self.path.is_empty() {
+            return false;
+        }
+        self.nodes.pop();
+        self.path.0.pop();
+        true
+    }
+
+    /// Moves to the child with the lowest nibble, if there are any.
+    pub fn first_child(&mut self) -> bool {
+        match self.node().next_child(0) {
+            Some((nibble, _)) => self.descend(nibble as u8),
+            None => false,
+        }
+    }
+
+    /// Moves to the next child of the parent after this node, if there is
+    /// one. The root has no siblings.
+    pub fn next_sibling(&mut self) -> bool {
+        let Some(&nibble) = self.path.last() else {
+            return false;
+        };
+        let parent = self.nodes[self.nodes.len() - 2];
+        let Some((next, sibling)) = parent.next_child(nibble as usize + 1) else {
+            return false;
+        };
+        *self.nodes.last_mut().unwrap() = sibling;
+        *self.path.0.last_mut().unwrap() = next as u8;
+        true
+    }
+
+    /// Moves to the node for `hex_key`, read leniently like any other key,
+    /// going back up only as far as where it parts from the current path.
+    ///
+    /// Returns whether the node exists, holding a value or not. If it
+    /// doesn't, the cursor stops at the deepest node along the key that
+    /// does.
+    pub fn seek(&mut self, hex_key: &str) -> bool {
+        let mut nibbles = hex_to_nibbles(hex_key).map(|n| n as u8).peekable();
+        let mut shared = 0;
+        while shared < self.path.len() && nibbles.next_if_eq(&self.path[shared]).is_some() {
+            shared += 1;
+        }
+        self.nodes.truncate(shared + 1);
+        self.path.0.truncate(shared);
+        nibbles.all(|nibble| self.descend(nibble))
+    }
+
+    fn node(&self) -> &'a Node<V> {
+        self.nodes[self.nodes.len() - 1]
+    }
+}
@@ -0,0 +1,115 @@
+use alloc::string::String;
+use alloc::vec;
+use core::fmt;
+
+use crate::display::push_symbol;
+use crate::node::Node;
+use crate::trie::Trie;
+
+// How many entries, or nodes in the `{:#?}` form, are printed before the rest is elided.
+const MAX_DEBUG_ITEMS: usize = 64;
+
+/// `{:?}` prints the length and the entries in key order, and `{:#?}` lists
+/// the nodes instead, in the same order, each with the symbols of its
+/// children and its value. Either form stops after 64 items and ends with
+/// `..`, so a failing assertion on a large trie stays readable. Only
+/// `V: Debug` is needed.
+///
+/// ```
+/// let mut trie = trie::Trie::new();
+/// trie.insert_lossy("a1f", 1);
+/// trie.insert_lossy("a1e", 2);
+/// assert_eq!(
+///     format!("{:?}", trie),
+///     r#"Trie { len: 2, entries: {"a1e": 2, "a1f": 1} }"#
+/// );
+/// assert_eq!(
+///     format!("{:#?}", trie),
+///     r#"Trie {
+///     len: 2,
+///     nodes: [
+///         "" children=[a] value=None,
+///         "a" children=[1] value=None,
+///         "a1" children=[e, f] value=None,
+///         "a1e" children=[] value=Some(2),
+///         "a1f" children=[] value=Some(1),
+///     ],
+/// }"#
+/// );
+/// ```
+impl<V: fmt::Debug, const N: usize> fmt::Debug for Trie<V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes = f.alternate();
+        let mut out = f.debug_struct("Trie");
+        out.field("len", &self.len);
+        if nodes {
+            out.field("nodes", &Nodes(&self.root));
+        } else {
+            out.field("entries", &Entries(self));
+        }
+        out.finish()
+    }
+}
+
+struct Entries<'a, V, const N: usize>(&'a Trie<V, N>);
+
+impl<V: fmt::Debug, const N: usize> fmt::Debug for Entries<'_, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for (key, value) in self.0.iter().take(MAX_DEBUG_ITEMS) {
+            map.entry(&key, value);
+        }
+        if self.0.len > MAX_DEBUG_ITEMS {
+            map.finish_non_exhaustive()
+        } else {
+            map.finish()
+        }
+    }
+}
+
+struct Nodes<'a, V, const N: usize>(&'a Node<V, N>);
+
+impl<V: fmt::Debug, const N: usize> fmt::Debug for Nodes<'_, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut stack = vec![(self.0, String::new())];
+        let mut printed = 0;
+        while let Some((node, path)) = stack.pop() {
+            if printed == MAX_DEBUG_ITEMS {
+                return list.finish_non_exhaustive();
+            }
+            printed += 1;
+            list.entry(&NodeLine { node, path: &path });
+            // pushed in reverse so the lowest symbol comes off the stack first
+            for (symbol, child) in node.children().rev() {
+                let mut child_path = path.clone();
+                push_symbol::<N>(&mut child_path, symbol);
+                stack.push((child, child_path));
+            }
+        }
+        list.finish()
+    }
+}
+
+// One node of the `{:#?}` form, on one line: its path, the symbols of its children and its
+// value.
+struct NodeLine<'a, V, const N: usize> {
+    node: &'a Node<V, N>,
+    path: &'a str,
+}
+
+impl<V: fmt::Debug, const N: usize> fmt::Debug for NodeLine<'_, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} children=[", self.path)?;
+        let mut symbol = String::new();
+        for (i, (s, _)) in self.node.children().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            symbol.clear();
+            push_symbol::<N>(&mut symbol, s);
+            f.write_str(&symbol)?;
+        }
+        write!(f, "] value={:?}", self.node.value)
+    }
+}
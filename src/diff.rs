@@ -0,0 +1,95 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ptr;
+
+use crate::key::nibbles_to_hex;
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// One difference between two tries, as yielded by [`Trie::diff`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffEntry<'a, V> {
+    /// The key is only in the other trie.
+    Added(String, &'a V),
+    /// The key is only in this trie.
+    Removed(String, &'a V),
+    /// The key is in both with different values, given as `(old, new)`.
+    Changed(String, &'a V, &'a V),
+}
+
+/// An iterator over the differences between two tries in key order.
+///
+/// Returned by [`Trie::diff`].
+pub struct Diff<'a, V> {
+    // Same frame layout as the plain iterator, except each frame pairs up the node on either
+    // side at the same path; a missing side means that whole subtree exists on one side only.
+    stack: Vec<Frame<'a, V>>,
+    path: Vec<u8>,
+}
+
+type Frame<'a, V> = (Option<&'a Node<V>>, Option<&'a Node<V>>, usize);
+
+impl<V: PartialEq> Trie<V> {
+    /// Returns the changes that turn `self` into `other`, in key order.
+    ///
+    /// Both tries are walked together. Subtrees that are the very same nodes
+    /// on both sides (such as diffing a trie against itself) are skipped
+    /// without being visited.
+    pub fn diff<'a>(&'a self, other: &'a Trie<V>) -> Diff<'a, V> {
+        let stack = if ptr::eq(&self.root, &other.root) {
+            Vec::new()
+        } else {
+            vec![(Some(&self.root), Some(&other.root), 0)]
+        };
+        Diff {
+            stack,
+            path: Vec::new(),
+        }
+    }
+}
+
+impl<'a, V: PartialEq> Iterator for Diff<'a, V> {
+    type Item = DiffEntry<'a, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((old, new, cursor)) = self.stack.last_mut() {
+            let (old, new) = (*old, *new);
+            if *cursor == 0 {
+                *cursor = 1;
+                let entry = match (
+                    old.and_then(|n| n.value.as_ref()),
+                    new.and_then(|n| n.value.as_ref()),
+                ) {
+                    (Some(a), Some(b)) if a != b => {
+                        DiffEntry::Changed(nibbles_to_hex(&self.path), a, b)
+                    }
+                    (Some(a), None) => DiffEntry::Removed(nibbles_to_hex(&self.path), a),
+                    (None, Some(b)) => DiffEntry::Added(nibbles_to_hex(&self.path), b),
+                    _ => continue,
+                };
+                return Some(entry);
+            }
+            let child = |side: Option<&'a Node<V>>, i: usize| side.and_then(|n| n.child(i));
+            let next = (*cursor - 1..16).find_map(|i| match (child(old, i), child(new, i)) {
+                (None, None) => None,
+                (Some(a), Some(b)) if ptr::eq(a, b) => None,
+                (a, b) => Some((i, a, b)),
+            });
+            match next {
+                Some((i, a, b)) => {
+                    *cursor = i + 2;
+                    self.path.push(i as u8);
+                    self.stack.push((a, b, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    if !self.stack.is_empty() {
+                        self.path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,326 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use core::fmt;
+
+use crate::key::{NIBBLE_TO_HEX, key_symbols};
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// Settings for [`Trie::display_with`]. The default draws the whole trie,
+/// just like its `Display` output.
+#[derive(Debug, Clone, Default)]
+pub struct DisplayOptions {
+    /// Leave out every node more than this many symbols below the root. A
+    /// node at the limit that has children ends its line with
+    /// ` … (+N entries)`, counting the values under it.
+    pub max_depth: Option<usize>,
+    /// Stop after this many lines below the root, then finish with a
+    /// `… (+N more entries)` line counting the values not drawn.
+    pub max_entries: Option<usize>,
+    /// Cut values longer than this many characters down to that many,
+    /// followed by `…`.
+    pub max_value_len: Option<usize>,
+    /// Label each line with every symbol from the root down, so at the
+    /// default fanout a value's line shows its whole key: `├── a1f = leaf`.
+    pub full_path_labels: bool,
+    /// Leave out the tree: print one `key = value` line per value, in key
+    /// order, with the full path as the key. The root's own value stays
+    /// `(root) = value`. Lines summarizing a depth cut are kept.
+    pub paths_only: bool,
+    /// Draw only the subtree under this hex key, read leniently like any
+    /// other key. Its top line is labeled `(prefix)` instead of `(root)`,
+    /// and depths count from there. The labels below it are relative as
+    /// usual, while [`full_path_labels`](Self::full_path_labels) and
+    /// [`paths_only`](Self::paths_only) show whole keys, prefix included.
+    /// If no key starts with the prefix the drawing is the single line
+    /// `<prefix not present>`, or nothing in paths-only mode.
+    pub prefix: String,
+}
+
+impl<V: fmt::Display, const N: usize> Trie<V, N> {
+    /// Returns a drawing of the trie like its `Display` output, cut down by
+    /// `opts` so that large tries stay readable.
+    ///
+    /// Everything left out is counted: the markers report how many values
+    /// each cut hides, so the walk still visits the nodes below a cut.
+    ///
+    /// ```
+    /// use trie::{DisplayOptions, Trie};
+    ///
+    /// let mut trie = Trie::new();
+    /// for (key, value) in [("a1f", "leaf-A1F"), ("a1e", "leaf-A1E"), ("b0", "leaf-B0")] {
+    ///     trie.insert_lossy(key, value);
+    /// }
+    /// let opts = DisplayOptions {
+    ///     max_depth: Some(1),
+    ///     max_value_len: Some(4),
+    ///     ..DisplayOptions::default()
+    /// };
+    /// assert_eq!(
+    ///     trie.display_with(opts).to_string(),
+    ///     "(root)\n├── a … (+2 entries)\n└── b … (+1 entry)\n"
+    /// );
+    /// let opts = DisplayOptions {
+    ///     max_entries: Some(2),
+    ///     max_value_len: Some(4),
+    ///     ..DisplayOptions::default()
+    /// };
+    /// assert_eq!(
+    ///     trie.display_with(opts).to_string(),
+    ///     "(root)\n├── a1\n│   ├── e = leaf…\n… (+2 more entries)\n"
+    /// );
+    /// let opts = DisplayOptions {
+    ///     paths_only: true,
+    ///     ..DisplayOptions::default()
+    /// };
+    /// assert_eq!(
+    ///     trie.display_with(opts).to_string(),
+    ///     "a1e = leaf-A1E\na1f = leaf-A1F\nb0 = leaf-B0\n"
+    /// );
+    /// ```
+    pub fn display_with(&self, opts: DisplayOptions) -> impl fmt::Display {
+        Drawing {
+            root: &self.root,
+            opts,
+            palette: &PLAIN,
+        }
+    }
+
+    /// Returns a drawing of just the subtree under `prefix`, which is
+    /// [`Trie::display_with`] with only [`DisplayOptions::prefix`] set.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for key in ["a1", "a1f", "a1e", "b0"] {
+    ///     trie.insert_lossy(key, key.to_uppercase());
+    /// }
+    /// assert_eq!(
+    ///     trie.display_prefix("a1").to_string(),
+    ///     "(a1) = A1\n├── e = A1E\n└── f = A1F\n"
+    /// );
+    /// assert_eq!(trie.display_prefix("c").to_string(), "<prefix not present>\n");
+    /// ```
+    pub fn display_prefix<'a>(&'a self, prefix: &str) -> impl fmt::Display + use<'a, V, N> {
+        self.display_with(DisplayOptions {
+            prefix: prefix.to_string(),
+            ..DisplayOptions::default()
+        })
+    }
+}
+
+// The escape sequences that color each part of a drawing, or none at all.
+pub(crate) struct Palette {
+    pub(crate) root: &'static str,
+    pub(crate) branch: &'static str,
+    pub(crate) value_label: &'static str,
+    pub(crate) marker: &'static str,
+}
+
+pub(crate) const PLAIN: Palette = Palette {
+    root: "",
+    branch: "",
+    value_label: "",
+    marker: "",
+};
+
+pub(crate) struct Drawing<'a, V, const N: usize> {
+    pub(crate) root: &'a Node<V, N>,
+    pub(crate) opts: DisplayOptions,
+    pub(crate) palette: &'static Palette,
+}
+
+impl<V: fmt::Display, const N: usize> fmt::Display for Drawing<'_, V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        draw(f, self.root, &self.opts, self.palette)
+    }
+}
+
+impl<V: fmt::Display, const N: usize> fmt::Display for Node<V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        draw(f, self, &DisplayOptions::default(), &PLAIN)
+    }
+}
+
+// Pretty printer to visualize the trie.
+//
+// It walks the tree with an explicit stack so a very deep trie can't overflow the call stack.
+// Each frame is a node whose children are being printed, the next child to print, and how
+// much of `indent` and of `path` belongs to that node. Both are one shared string: stepping
+// into a child appends its segment, and going back to a sibling just truncates it. `path`
+// holds the symbols from the trie's root, so a line's label is either all of it or the part
+// added since the parent's line. Depths are counted from the node the drawing starts at, the
+// end of `opts.prefix`.
+fn draw<V: fmt::Display, const N: usize>(
+    f: &mut fmt::Formatter<'_>,
+    root: &Node<V, N>,
+    opts: &DisplayOptions,
+    palette: &Palette,
+) -> fmt::Result {
+    let mut path = String::new();
+    for symbol in key_symbols::<N>(&opts.prefix) {
+        push_symbol::<N>(&mut path, symbol);
+    }
+    let Some(root) = root.find(key_symbols::<N>(&opts.prefix)) else {
+        if opts.paths_only {
+            return Ok(());
+        }
+        return writeln!(f, "<prefix not present>");
+    };
+    let base = depth::<N>(&path);
+
+    let cut = is_cut(root, 0, opts);
+    if !opts.paths_only || root.value.is_some() || cut {
+        let label = match (path.is_empty(), opts.paths_only) {
+            (true, _) => "(root)".to_string(),
+            (false, true) => path.clone(),
+            (false, false) => format!("({})", path),
+        };
+        paint(f, palette.root, &label)?;
+        end_line(f, root, cut, opts, palette)?;
+    }
+    if cut {
+        return Ok(());
+    }
+
+    let mut indent = String::new();
+    let mut lines = 0;
+    let mut stack = vec![(root, root.next_child(0), 0, path.len())];
+    while let Some(&mut (node, ref mut next, indent_len, path_len)) = stack.last_mut() {
+        let Some((nib, mut child)) = *next else {
+            stack.pop();
+            continue;
+        };
+        if opts.max_entries == Some(lines) {
+            // Every frame's next child is the first it hasn't drawn, so what's hidden is that
+            // child and its later siblings, all the way down the stack.
+            let hidden: usize = stack
+                .iter()
+                .filter_map(|&(node, next, ..)| Some((node, next?.0)))
+                .flat_map(|(node, from)| node.children().filter(move |&(i, _)| i >= from))
+                .map(|(_, child)| child.count_values())
+                .sum();
+            let marker = format!("… (+{} more {})", hidden, entries(hidden));
+            paint(f, palette.marker, &marker)?;
+            return writeln!(f);
+        }
+        *next = node.next_child(nib + 1);
+        let is_last = next.is_none();
+
+        // A run of nodes with no value and a single child prints as one line, so the node
+        // whose line this is and whose children come next is the end of the run.
+        path.truncate(path_len);
+        push_symbol::<N>(&mut path, nib);
+        while child.value.is_none() && !is_cut(child, depth::<N>(&path) - base, opts) {
+            let mut present = child.children();
+            match (present.next(), present.next()) {
+                (Some((nib, only)), None) => {
+                    push_symbol::<N>(&mut path, nib);
+                    child = only;
+                }
+                _ => break,
+            }
+        }
+        let cut = is_cut(child, depth::<N>(&path) - base, opts);
+
+        let label_color = match child.value {
+            Some(_) => palette.value_label,
+            None => "",
+        };
+        indent.truncate(indent_len);
+        if !opts.paths_only {
+            lines += 1;
+            paint(f, palette.branch, &indent)?;
+            paint(f, palette.branch, if is_last { "└── " } else { "├── " })?;
+            let label = if opts.full_path_labels {
+                &path
+            } else {
+                &path[path_len..]
+            };
+            paint(f, label_color, label)?;
+            end_line(f, child, cut, opts, palette)?;
+        } else if child.value.is_some() || cut {
+            lines += 1;
+            paint(f, label_color, &path)?;
+            end_line(f, child, cut, opts, palette)?;
+        }
+
+        // if this child isn't last, its subtree hangs off a vertical '│'; else just spaces
+        indent.push_str(if is_last { "    " } else { "│   " });
+        if !cut {
+            stack.push((child, child.next_child(0), indent.len(), path.len()));
+        }
+    }
+    Ok(())
+}
+
+// Whether the depth limit keeps the children of a node at `depth` from being drawn.
+fn is_cut<V, const N: usize>(node: &Node<V, N>, depth: usize, opts: &DisplayOptions) -> bool {
+    opts.max_depth.is_some_and(|max| depth >= max) && node.has_children()
+}
+
+// Appends one symbol to a label: a hex digit, or two for a 256-way trie.
+pub(crate) fn push_symbol<const N: usize>(label: &mut String, symbol: usize) {
+    if N == 256 {
+        label.push(NIBBLE_TO_HEX[symbol >> 4] as char);
+    }
+    label.push(NIBBLE_TO_HEX[symbol & 0xf] as char);
+}
+
+// The depth of the node a label of symbols leads to.
+fn depth<const N: usize>(label: &str) -> usize {
+    if N == 256 {
+        label.len() / 2
+    } else {
+        label.len()
+    }
+}
+
+// Ends a line, with ` = value` first if the node holds one, and then the count of values
+// below it if its children are cut off.
+fn end_line<V: fmt::Display, const N: usize>(
+    f: &mut fmt::Formatter<'_>,
+    node: &Node<V, N>,
+    cut: bool,
+    opts: &DisplayOptions,
+    palette: &Palette,
+) -> fmt::Result {
+    if let Some(value) = &node.value {
+        match opts.max_value_len {
+            None => write!(f, " = {}", value)?,
+            Some(max) => {
+                let value = value.to_string();
+                match value.char_indices().nth(max) {
+                    Some((end, _)) => write!(f, " = {}…", &value[..end])?,
+                    None => write!(f, " = {}", value)?,
+                }
+            }
+        }
+    }
+    if cut {
+        let below = node.count_values() - node.value.is_some() as usize;
+        let marker = format!("… (+{} {})", below, entries(below));
+        f.write_str(" ")?;
+        paint(f, palette.marker, &marker)?;
+    }
+    writeln!(f)
+}
+
+// Writes `text` in `color`, or just `text` if there's no color or nothing to color.
+fn paint(f: &mut fmt::Formatter<'_>, color: &str, text: &str) -> fmt::Result {
+    if color.is_empty() || text.is_empty() {
+        f.write_str(text)
+    } else {
+        write!(f, "{}{}\x1b[0m", color, text)
+    }
+}
+
+fn entries(n: usize) -> &'static str {
+    if n == 1 { "entry" } else { "entries" }
+}
+
+impl<V: fmt::Display, const N: usize> fmt::Display for Trie<V, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.fmt(f)
+    }
+}
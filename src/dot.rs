@@ -0,0 +1,110 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::key::nibbles_to_hex;
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// Settings for [`Trie::to_dot`].
+#[derive(Debug, Clone)]
+pub struct DotOptions {
+    /// Label nodes with their whole hex path instead of just the last
+    /// nibble.
+    pub full_path_labels: bool,
+    /// Leave out every node deeper than this many nibbles.
+    pub max_depth: Option<usize>,
+    /// Draw nodes that hold no value. When off, each value node is linked
+    /// straight to its nearest drawn ancestor by an edge labeled with all the
+    /// nibbles in between.
+    pub show_empty_interior: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            full_path_labels: false,
+            max_depth: None,
+            show_empty_interior: true,
+        }
+    }
+}
+
+impl<V: fmt::Display> Trie<V> {
+    /// Renders the trie as a Graphviz digraph.
+    ///
+    /// Node IDs are derived from the hex path (`n` for the root, `na1` for
+    /// `"a1"`), edges are labeled with their nibbles, and value nodes are
+    /// drawn as filled double circles showing the value.
+    pub fn to_dot(&self, opts: DotOptions) -> String {
+        let mut out = String::new();
+        out.push_str("digraph trie {\n");
+        out.push_str("    node [shape=circle];\n");
+
+        // each entry carries the node's path and the length of the path of the nearest
+        // ancestor actually drawn, which is where its edge starts
+        let mut stack: Vec<(&Node<V>, Vec<u8>, usize)> = vec![(&self.root, Vec::new(), 0)];
+        while let Some((node, path, anchor)) = stack.pop() {
+            let drawn = path.is_empty() || opts.show_empty_interior || node.value.is_some();
+            if drawn {
+                let label = if path.is_empty() {
+                    "(root)".to_string()
+                } else if opts.full_path_labels {
+                    nibbles_to_hex(&path)
+                } else {
+                    nibbles_to_hex(&path[path.len() - 1..])
+                };
+                let id = nibbles_to_hex(&path);
+                match &node.value {
+                    Some(value) => writeln!(
+                        out,
+                        "    n{} [label=\"{}\\n{}\", shape=doublecircle, style=filled, fillcolor=\"#cde8ff\"];",
+                        id,
+                        label,
+                        escape(&value.to_string())
+                    ),
+                    None => writeln!(out, "    n{} [label=\"{}\"];", id, label),
+                }
+                .unwrap();
+                if !path.is_empty() {
+                    writeln!(
+                        out,
+                        "    n{} -> n{} [label=\"{}\"];",
+                        nibbles_to_hex(&path[..anchor]),
+                        id,
+                        nibbles_to_hex(&path[anchor..])
+                    )
+                    .unwrap();
+                }
+            }
+            if opts.max_depth.is_some_and(|depth| path.len() >= depth) {
+                continue;
+            }
+            let anchor = if drawn { path.len() } else { anchor };
+            for (nibble, child) in node.children().rev() {
+                let mut child_path = path.clone();
+                child_path.push(nibble as u8);
+                stack.push((child, child_path, anchor));
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+// Escapes text for use inside a double-quoted DOT string.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,209 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::key::{KeyError, check_key, hex_to_nibbles};
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::node::{Node, Pool};
+use crate::observe::{TrieObserver, canonical_key};
+use crate::trie::Trie;
+
+// The trie's observer, if it has one, with the entry's key spelled out for it.
+type Watcher<'a, V> = Option<(&'a mut (dyn TrieObserver<V> + 'static), String)>;
+
+/// A view into a single key of a [`Trie`], which is either occupied or
+/// vacant.
+///
+/// Returned by [`Trie::entry`].
+pub enum Entry<'a, V> {
+    Occupied(OccupiedEntry<'a, V>),
+    Vacant(VacantEntry<'a, V>),
+}
+
+/// An entry whose key already holds a value.
+pub struct OccupiedEntry<'a, V> {
+    node: &'a mut Node<V>,
+    watcher: Watcher<'a, V>,
+    // the trie's metrics, and the length of the key for them
+    #[cfg(feature = "metrics")]
+    metrics: (&'a Metrics, usize),
+}
+
+/// An entry whose key holds no value yet.
+///
+/// Only the nodes that already exist have been walked; the rest of the path
+/// is created by [`VacantEntry::insert`], so dropping a vacant entry leaves
+/// the trie untouched.
+pub struct VacantEntry<'a, V> {
+    // deepest node along the key that already exists
+    node: &'a mut Node<V>,
+    // nibbles still to be created below `node`
+    remaining: Vec<usize>,
+    len: &'a mut usize,
+    pool: &'a mut Pool<V, 16>,
+    watcher: Watcher<'a, V>,
+    // the length of the key, for the metrics
+    #[cfg(feature = "metrics")]
+    depth: usize,
+}
+
+impl<V> Trie<V> {
+    /// Gets the entry for `hex_key` for in-place manipulation.
+    ///
+    /// ```
+    /// let mut counts = trie::Trie::new();
+    /// for key in ["a1", "b2", "a1"] {
+    ///     *counts.entry(key).or_insert(0) += 1;
+    /// }
+    /// assert_eq!(counts.get_lossy("a1"), Some(&2));
+    /// ```
+    pub fn entry(&mut self, hex_key: &str) -> Entry<'_, V> {
+        self.save_key(hex_key);
+        let Trie {
+            root,
+            len,
+            pool,
+            observer,
+            ..
+        } = self;
+        let watcher = observer
+            .get()
+            .map(|observer| (observer, canonical_key::<16>(hex_key)));
+        #[cfg(feature = "metrics")]
+        let depth = hex_to_nibbles(hex_key).count();
+        let mut cur = root;
+        let mut nibbles = hex_to_nibbles(hex_key);
+        // either kind of entry can change the value, so the cached hashes along the way go
+        cur.invalidate();
+        while let Some(nibble) = nibbles.next() {
+            if cur.child(nibble).is_none() {
+                #[cfg(feature = "metrics")]
+                pool.metrics.record_lookup(false);
+                return Entry::Vacant(VacantEntry {
+                    node: cur,
+                    remaining: core::iter::once(nibble).chain(nibbles).collect(),
+                    len,
+                    pool,
+                    watcher,
+                    #[cfg(feature = "metrics")]
+                    depth,
+                });
+            }
+            cur = cur.child_mut(nibble).unwrap();
+            cur.invalidate();
+        }
+        #[cfg(feature = "metrics")]
+        pool.metrics.record_lookup(cur.value.is_some());
+        if cur.value.is_some() {
+            Entry::Occupied(OccupiedEntry {
+                node: cur,
+                watcher,
+                #[cfg(feature = "metrics")]
+                metrics: (&pool.metrics, depth),
+            })
+        } else {
+            Entry::Vacant(VacantEntry {
+                node: cur,
+                remaining: Vec::new(),
+                len,
+                pool,
+                watcher,
+                #[cfg(feature = "metrics")]
+                depth,
+            })
+        }
+    }
+
+    /// Like [`Trie::entry`], but fails instead of skipping characters that
+    /// aren't hex digits.
+    pub fn try_entry(&mut self, hex_key: &str) -> Result<Entry<'_, V>, KeyError> {
+        check_key::<16>(hex_key)?;
+        Ok(self.entry(hex_key))
+    }
+}
+
+impl<'a, V> Entry<'a, V> {
+    /// Inserts `default` if the entry is vacant and returns a mutable
+    /// reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of `default` if the entry is vacant and returns a
+    /// mutable reference to the value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Runs `f` on the value if the entry is occupied.
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, V: Default> Entry<'a, V> {
+    /// Inserts `V::default()` if the entry is vacant and returns a mutable
+    /// reference to the value.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, V> OccupiedEntry<'a, V> {
+    /// Returns a reference to the stored value.
+    pub fn get(&self) -> &V {
+        self.node.value.as_ref().unwrap()
+    }
+
+    /// Returns a mutable reference to the stored value.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.node.value.as_mut().unwrap()
+    }
+
+    /// Converts the entry into a mutable reference tied to the trie borrow.
+    pub fn into_mut(self) -> &'a mut V {
+        self.node.value.as_mut().unwrap()
+    }
+
+    /// Replaces the stored value and returns the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        let old = core::mem::replace(self.get_mut(), value);
+        #[cfg(feature = "metrics")]
+        self.metrics.0.record_insert(true, self.metrics.1);
+        if let Some((observer, key)) = &mut self.watcher {
+            observer.on_insert(key, Some(&old), self.node.value.as_ref().unwrap());
+        }
+        old
+    }
+}
+
+impl<'a, V> VacantEntry<'a, V> {
+    /// Creates the rest of the path, stores `value` and returns a mutable
+    /// reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.len += 1;
+        let mut cur = self.node;
+        for nibble in self.remaining {
+            cur = cur.child_or_insert(nibble, self.pool);
+        }
+        #[cfg(feature = "metrics")]
+        self.pool.metrics.record_insert(false, self.depth);
+        let value = cur.value.insert(value);
+        if let Some((observer, key)) = self.watcher {
+            observer.on_insert(&key, None, value);
+        }
+        value
+    }
+}
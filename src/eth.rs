@@ -0,0 +1,134 @@
+// Ethereum-style Merkle Patricia roots.
+//
+// The trie is kept as one node per nibble, so the Patricia shape is worked out while hashing:
+// a run of valueless single-child nodes becomes the path of a leaf or an extension, and
+// anything that forks (or holds a value and has children) becomes a 17-item branch. Nodes
+// are RLP encoded, paths use hex-prefix encoding, and a child node whose encoding is under 32
+// bytes is embedded in its parent instead of being referenced by its Keccak-256 hash. This
+// recurses once per node on the Patricia path, which for Ethereum's 64-nibble keys is
+// shallow.
+
+use crate::codec::ValueCodec;
+use crate::merkle::{Keccak256Hasher, TrieHasher};
+use crate::node::Node;
+use crate::trie::Trie;
+
+impl<V: ValueCodec> Trie<V> {
+    /// Returns the root Ethereum would compute for the same entries, with
+    /// keys taken as nibble paths (hex-encode byte keys first) and values as
+    /// their [`ValueCodec`] bytes.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for (key, value) in [("do", "verb"), ("dog", "puppy"), ("doge", "coin"), ("horse", "stallion")] {
+    ///     let key: String = key.bytes().map(|b| format!("{:02x}", b)).collect();
+    ///     trie.insert_lossy(&key, value.to_string());
+    /// }
+    /// let root: String = trie.eth_root().iter().map(|b| format!("{:02x}", b)).collect();
+    /// assert_eq!(root, "5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84");
+    /// ```
+    pub fn eth_root(&self) -> [u8; 32] {
+        Keccak256Hasher::hash(&encode_node(&self.root))
+    }
+}
+
+// RLP encoding of the Patricia node for the subtree at `node`.
+fn encode_node<V: ValueCodec>(mut node: &Node<V>) -> Vec<u8> {
+    let mut path = Vec::new();
+    while node.value.is_none() {
+        let mut children = node.children();
+        match (children.next(), children.next()) {
+            (Some((nibble, child)), None) => {
+                path.push(nibble as u8);
+                node = child;
+            }
+            _ => break,
+        }
+    }
+
+    if !node.has_children() {
+        // a leaf, or the empty root (which has no value either and encodes as the empty string)
+        return match &node.value {
+            Some(value) => rlp_list(&[
+                rlp_bytes(&hex_prefix(&path, true)),
+                rlp_bytes(&value.encode()),
+            ]),
+            None => rlp_bytes(&[]),
+        };
+    }
+
+    let mut items: Vec<Vec<u8>> = (0..16)
+        .map(|i| match node.child(i) {
+            Some(child) => node_ref(encode_node(child)),
+            None => rlp_bytes(&[]),
+        })
+        .collect();
+    items.push(rlp_bytes(
+        node.value
+            .as_ref()
+            .map(|v| v.encode())
+            .as_deref()
+            .unwrap_or(&[]),
+    ));
+    let branch = rlp_list(&items);
+    if path.is_empty() {
+        branch
+    } else {
+        rlp_list(&[rlp_bytes(&hex_prefix(&path, false)), node_ref(branch)])
+    }
+}
+
+// How a parent refers to a child: short encodings are inlined, the rest are hashed.
+fn node_ref(encoded: Vec<u8>) -> Vec<u8> {
+    if encoded.len() < 32 {
+        encoded
+    } else {
+        rlp_bytes(&Keccak256Hasher::hash(&encoded))
+    }
+}
+
+// Packs nibbles two to a byte behind a flag nibble: bit 1 marks a leaf, bit 0 an odd length
+// (in which case the first nibble shares the flag byte).
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = (leaf as u8) << 1 | (nibbles.len() % 2) as u8;
+    let (first, rest) = if nibbles.len() % 2 == 1 {
+        (flag << 4 | nibbles[0], &nibbles[1..])
+    } else {
+        (flag << 4, nibbles)
+    };
+    let mut out = Vec::with_capacity(1 + rest.len() / 2);
+    out.push(first);
+    out.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    out
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if let [b] = bytes
+        && *b < 0x80
+    {
+        return vec![*b];
+    }
+    let mut out = rlp_header(0x80, bytes.len());
+    out.extend_from_slice(bytes);
+    out
+}
+
+// Concatenates already-encoded items into a list.
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = rlp_header(0xc0, items.iter().map(Vec::len).sum());
+    for item in items {
+        out.extend_from_slice(item);
+    }
+    out
+}
+
+fn rlp_header(offset: u8, len: usize) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+    let len_bytes = len.to_be_bytes();
+    let skip = len_bytes.iter().take_while(|&&b| b == 0).count();
+    let mut out = vec![offset + 55 + (len_bytes.len() - skip) as u8];
+    out.extend_from_slice(&len_bytes[skip..]);
+    out
+}
@@ -0,0 +1,178 @@
+use std::time::{Duration, Instant};
+
+use crate::trie::Trie;
+
+/// Where an [`ExpiringTrie`] gets the time from.
+///
+/// [`SystemClock`] reads the system's monotonic clock. Tests can use a
+/// clock they move by hand instead of sleeping, reaching it through
+/// [`ExpiringTrie::clock`].
+pub trait Clock {
+    /// The current time.
+    fn now(&self) -> Instant;
+}
+
+/// The [`Clock`] that reads [`Instant::now`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A trie whose entries can expire, for caches.
+///
+/// Each value is stored with the time it expires, if it does. From that
+/// time on, lookups and iteration treat the entry as absent, but it stays
+/// in the trie until something removes it: [`ExpiringTrie::purge_expired`]
+/// sweeps the whole trie, and [`ExpiringTrie::get_or_evict`] removes an
+/// expired entry it comes across.
+///
+/// Because of that, [`ExpiringTrie::len`] counts the entries stored,
+/// expired or not, and stays O(1). Right after a purge it's the number of
+/// live entries; [`ExpiringTrie::iter`] counts them at any time.
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::time::{Duration, Instant};
+/// use trie::{Clock, ExpiringTrie};
+///
+/// struct Manual(Cell<Instant>);
+///
+/// impl Clock for Manual {
+///     fn now(&self) -> Instant {
+///         self.0.get()
+///     }
+/// }
+///
+/// let start = Instant::now();
+/// let mut cache = ExpiringTrie::with_clock(Manual(Cell::new(start)));
+/// cache.insert_with_ttl("a1", "short", Duration::from_secs(1));
+/// cache.insert_with_ttl("a2", "long", Duration::from_secs(60));
+/// cache.insert("b", "forever");
+///
+/// cache.clock().0.set(start + Duration::from_secs(5));
+/// assert_eq!(cache.get("a1"), None);
+/// assert_eq!(cache.get("a2"), Some(&"long"));
+/// assert_eq!(cache.len(), 3);
+/// assert_eq!(cache.purge_expired(), 1);
+/// assert_eq!(cache.len(), 2);
+/// ```
+pub struct ExpiringTrie<V, C = SystemClock> {
+    inner: Trie<(V, Option<Instant>)>,
+    clock: C,
+}
+
+impl<V> Default for ExpiringTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> ExpiringTrie<V> {
+    /// Creates an empty trie that reads the system clock.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<V, C: Clock> ExpiringTrie<V, C> {
+    /// Creates an empty trie that takes the time from `clock`.
+    pub fn with_clock(clock: C) -> Self {
+        Self {
+            inner: Trie::new(),
+            clock,
+        }
+    }
+
+    /// The clock the trie reads.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns the number of entries stored, counting expired ones that
+    /// haven't been removed yet.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if no entries are stored, expired or not.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Stores `value` under `hex_key` for good, and returns the live value
+    /// it replaced, if any.
+    pub fn insert(&mut self, hex_key: &str, value: V) -> Option<V> {
+        self.store(hex_key, value, None)
+    }
+
+    /// Stores `value` under `hex_key` until `ttl` from now, and returns the
+    /// live value it replaced, if any.
+    ///
+    /// A `ttl` too long for [`Instant`] to represent never expires.
+    pub fn insert_with_ttl(&mut self, hex_key: &str, value: V, ttl: Duration) -> Option<V> {
+        let expires = self.clock.now().checked_add(ttl);
+        self.store(hex_key, value, expires)
+    }
+
+    fn store(&mut self, hex_key: &str, value: V, expires: Option<Instant>) -> Option<V> {
+        let now = self.clock.now();
+        let (old, old_expires) = self.inner.insert_lossy(hex_key, (value, expires))?;
+        live(old_expires, now).then_some(old)
+    }
+
+    /// Returns the value stored under `hex_key`, unless it has expired.
+    pub fn get(&self, hex_key: &str) -> Option<&V> {
+        let (value, expires) = self.inner.get_lossy(hex_key)?;
+        live(*expires, self.clock.now()).then_some(value)
+    }
+
+    /// Like [`ExpiringTrie::get`], but removes the entry if it has expired.
+    pub fn get_or_evict(&mut self, hex_key: &str) -> Option<&V> {
+        let (_, expires) = self.inner.get_lossy(hex_key)?;
+        if !live(*expires, self.clock.now()) {
+            self.inner.delete_lossy(hex_key);
+            return None;
+        }
+        self.inner.get_lossy(hex_key).map(|(value, _)| value)
+    }
+
+    /// Returns when the entry under `hex_key` expires, or `None` if it
+    /// never does or there is no live entry.
+    pub fn expires_at(&self, hex_key: &str) -> Option<Instant> {
+        let (_, expires) = self.inner.get_lossy(hex_key)?;
+        expires.filter(|&at| at > self.clock.now())
+    }
+
+    /// Removes the entry under `hex_key` and returns its value, unless it
+    /// had expired, in which case it is removed all the same.
+    pub fn delete(&mut self, hex_key: &str) -> Option<V> {
+        let (value, expires) = self.inner.delete_lossy(hex_key)?;
+        live(expires, self.clock.now()).then_some(value)
+    }
+
+    /// Removes every expired entry, pruning the nodes they leave empty, and
+    /// returns how many there were.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let before = self.inner.len();
+        self.inner.retain(|_, (_, expires)| live(*expires, now));
+        before - self.inner.len()
+    }
+
+    /// Returns an iterator over the live entries in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (String, &V)> {
+        let now = self.clock.now();
+        self.inner
+            .iter()
+            .filter_map(move |(key, (value, expires))| live(*expires, now).then_some((key, value)))
+    }
+}
+
+// An entry is live until the moment it expires.
+fn live(expires: Option<Instant>, now: Instant) -> bool {
+    expires.is_none_or(|at| at > now)
+}
@@ -0,0 +1,206 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::key::nibbles_to_hex;
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// A key pattern for [`Trie::iter_glob`]: hex digits match themselves, `?`
+/// matches any one nibble and `*` any run of nibbles, including none.
+///
+/// Patterns match whole keys, so `"a*"` matches the keys starting with
+/// `a` and `"a"` only the key `"a"`. Digits may be in either case.
+///
+/// ```
+/// use trie::GlobPattern;
+///
+/// assert!(GlobPattern::parse("a?f*").is_ok());
+/// let err = GlobPattern::parse("a1-*").unwrap_err();
+/// assert_eq!((err.index, err.found), (2, '-'));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobPattern {
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Nibble(u8),
+    Any,
+    Star,
+}
+
+/// Why [`GlobPattern::parse`] rejected a pattern: it holds a character
+/// that is neither a hex digit, `?` nor `*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobError {
+    /// Byte offset of the character in the pattern.
+    pub index: usize,
+    /// The character.
+    pub found: char,
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid character {:?} at index {} in glob pattern",
+            self.found, self.index
+        )
+    }
+}
+
+impl Error for GlobError {}
+
+impl GlobPattern {
+    /// Reads a pattern, rejecting every character but hex digits, `?` and
+    /// `*`.
+    pub fn parse(pattern: &str) -> Result<GlobPattern, GlobError> {
+        let mut tokens = Vec::new();
+        for (index, found) in pattern.char_indices() {
+            let token = match found {
+                '?' => Token::Any,
+                '*' => Token::Star,
+                _ => match found.to_digit(16) {
+                    Some(nibble) => Token::Nibble(nibble as u8),
+                    None => return Err(GlobError { index, found }),
+                },
+            };
+            // runs of stars match what one does
+            if !(token == Token::Star && tokens.last() == Some(&Token::Star)) {
+                tokens.push(token);
+            }
+        }
+        Ok(GlobPattern { tokens })
+    }
+
+    // Adds `at` to `states`, along with every position after the stars that follow it, since
+    // a star can match nothing.
+    fn enter(&self, states: &mut Vec<usize>, mut at: usize) {
+        loop {
+            if !states.contains(&at) {
+                states.push(at);
+            }
+            match self.tokens.get(at) {
+                Some(Token::Star) => at += 1,
+                _ => return,
+            }
+        }
+    }
+}
+
+/// An iterator over the entries whose keys match a [`GlobPattern`], in key
+/// order. Returned by [`Trie::iter_glob`].
+pub struct Glob<'a, V> {
+    pattern: GlobPattern,
+    // Nodes still to visit, the highest child last so the lowest comes out first: the node,
+    // the length of its path, its nibble, and the pattern positions it's at.
+    stack: Vec<(&'a Node<V>, usize, u8, Vec<usize>)>,
+    path: Vec<u8>,
+}
+
+impl<V> Trie<V> {
+    /// Returns an iterator over the entries whose keys match `pattern`, in
+    /// key order.
+    ///
+    /// The walk follows the pattern down the trie: a digit goes straight to
+    /// that child, and only `?` and `*` look at every child, so a pattern
+    /// that starts with digits never leaves their subtree. Each node is
+    /// visited once, however many ways the stars could match it.
+    ///
+    /// ```
+    /// use trie::{GlobPattern, Trie};
+    ///
+    /// let mut trie = Trie::new();
+    /// for key in ["a0f", "a1f", "a1f9", "a2e", "b1f"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// let pattern = GlobPattern::parse("a?f*").unwrap();
+    /// let keys: Vec<String> = trie.iter_glob(&pattern).map(|(k, _)| k).collect();
+    /// assert_eq!(keys, ["a0f", "a1f", "a1f9"]);
+    /// ```
+    pub fn iter_glob(&self, pattern: &GlobPattern) -> Glob<'_, V> {
+        let mut states = Vec::new();
+        pattern.enter(&mut states, 0);
+        Glob {
+            pattern: pattern.clone(),
+            stack: vec![(&self.root, 0, 0, states)],
+            path: Vec::new(),
+        }
+    }
+}
+
+impl<'a, V> Iterator for Glob<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth, nibble, states)) = self.stack.pop() {
+            self.path.truncate(depth.saturating_sub(1));
+            if depth > 0 {
+                self.path.push(nibble);
+            }
+            self.push_children(node, &states);
+            let matched = states.contains(&self.pattern.tokens.len());
+            if let Some(value) = node.value.as_ref().filter(|_| matched) {
+                return Some((nibbles_to_hex(&self.path), value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, V> Glob<'a, V> {
+    // Queues the children of `node` that some state can step into, with the states they
+    // step into.
+    fn push_children(&mut self, node: &'a Node<V>, states: &[usize]) {
+        let tokens = &self.pattern.tokens;
+        let branches = states
+            .iter()
+            .any(|&at| matches!(tokens.get(at), Some(Token::Any | Token::Star)));
+        let depth = self.path.len() + 1;
+        if !branches {
+            // Only digits: each one names at most one child. They can differ when several
+            // stars led here, so collect them in order.
+            let mut next: Vec<(u8, Vec<usize>)> = Vec::new();
+            for &at in states {
+                let Some(&Token::Nibble(nibble)) = tokens.get(at) else {
+                    continue;
+                };
+                match next.iter_mut().find(|(n, _)| *n == nibble) {
+                    Some((_, into)) => self.pattern.enter(into, at + 1),
+                    None => {
+                        let mut into = Vec::new();
+                        self.pattern.enter(&mut into, at + 1);
+                        next.push((nibble, into));
+                    }
+                }
+            }
+            next.sort_by_key(|&(nibble, _)| core::cmp::Reverse(nibble));
+            for (nibble, into) in next {
+                if let Some(child) = node.child(nibble as usize) {
+                    self.stack.push((child, depth, nibble, into));
+                }
+            }
+            return;
+        }
+        for (nibble, child) in node.children().rev() {
+            let mut into = Vec::new();
+            for &at in states {
+                match tokens.get(at) {
+                    Some(Token::Nibble(n)) if *n as usize == nibble => {
+                        self.pattern.enter(&mut into, at + 1)
+                    }
+                    Some(Token::Any) => self.pattern.enter(&mut into, at + 1),
+                    Some(Token::Star) => self.pattern.enter(&mut into, at),
+                    _ => {}
+                }
+            }
+            if !into.is_empty() {
+                self.stack.push((child, depth, nibble as u8, into));
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::key::{hex_to_nibbles, nibbles_to_hex};
+use crate::trie::Trie;
+
+impl<V> Trie<V> {
+    /// Returns the entries whose keys differ from `hex_key` in at most `k`
+    /// nibbles, closest first and in key order among equally close ones.
+    ///
+    /// Only keys of the same length count: a key one nibble longer or
+    /// shorter isn't a near match, however alike the rest is. The search
+    /// walks down the trie spending one of the `k` differences on every
+    /// child that isn't the key's own nibble, and once they're spent it only
+    /// follows the key, so `k = 0` walks the same path as [`Trie::get_lossy`].
+    /// `hex_key` is read leniently like any other key.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for key in ["a1f0", "a1ff", "a2f0", "b2e0", "a1f"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// let near: Vec<String> = trie
+    ///     .find_within_hamming("a1f0", 1)
+    ///     .into_iter()
+    ///     .map(|(key, _)| key)
+    ///     .collect();
+    /// assert_eq!(near, ["a1f0", "a1ff", "a2f0"]);
+    /// ```
+    pub fn find_within_hamming(&self, hex_key: &str, k: usize) -> Vec<(String, &V)> {
+        let key: Vec<u8> = hex_to_nibbles(hex_key).map(|n| n as u8).collect();
+        let mut found = Vec::new();
+        let mut path = Vec::new();
+        // Nodes still to visit, with the length of their path, their nibble and the
+        // differences spent getting there.
+        let mut stack = vec![(&self.root, 0_usize, 0_u8, 0_usize)];
+        while let Some((node, depth, nibble, spent)) = stack.pop() {
+            path.truncate(depth.saturating_sub(1));
+            if depth > 0 {
+                path.push(nibble);
+            }
+            let Some(&want) = key.get(depth) else {
+                if let Some(value) = &node.value {
+                    found.push((spent, nibbles_to_hex(&path), value));
+                }
+                continue;
+            };
+            if spent == k {
+                if let Some(child) = node.child(want as usize) {
+                    stack.push((child, depth + 1, want, spent));
+                }
+                continue;
+            }
+            for (nibble, child) in node.children() {
+                let cost = usize::from(nibble as u8 != want);
+                stack.push((child, depth + 1, nibble as u8, spent + cost));
+            }
+        }
+        found.sort_unstable_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        found
+            .into_iter()
+            .map(|(_, key, value)| (key, value))
+            .collect()
+    }
+}
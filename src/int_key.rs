@@ -0,0 +1,116 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::iter::Walk;
+use crate::trie::Trie;
+
+/// An unsigned integer that can be used as a trie key.
+///
+/// Integers are stored as fixed-width big-endian nibble paths, so `3u64` is
+/// sixteen nibbles long (`"0000000000000003"`) and the trie's key order is
+/// numeric order. Keys of different widths don't mix: `3u8` is `"03"`, a
+/// different key from `3u64`.
+pub trait IntKey: Copy {
+    /// The number of nibbles in every key of this type.
+    const NIBBLES: usize;
+
+    /// Returns the key's nibbles, most significant first.
+    fn nibbles(self) -> impl Iterator<Item = usize> + Clone;
+
+    /// Rebuilds a key from its [`IntKey::NIBBLES`] nibbles.
+    fn from_nibbles(nibbles: &[u8]) -> Self;
+}
+
+macro_rules! int_key {
+    ($($t:ty),*) => {$(
+        impl IntKey for $t {
+            const NIBBLES: usize = <$t>::BITS as usize / 4;
+
+            fn nibbles(self) -> impl Iterator<Item = usize> + Clone {
+                (0..Self::NIBBLES)
+                    .rev()
+                    .map(move |i| (self >> (i * 4)) as usize & 0xf)
+            }
+
+            fn from_nibbles(nibbles: &[u8]) -> Self {
+                nibbles.iter().fold(0, |acc, &n| acc << 4 | n as $t)
+            }
+        }
+    )*};
+}
+
+int_key!(u8, u16, u32, u64, u128);
+
+impl<V> Trie<V> {
+    /// Stores `value` under the integer `key`, returning the replaced value.
+    pub fn insert_int<K: IntKey>(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_nibbles(key.nibbles(), value)
+    }
+
+    /// Returns the value stored under the integer `key`, if any.
+    pub fn get_int<K: IntKey>(&self, key: K) -> Option<&V> {
+        self.get_path(key.nibbles())
+    }
+
+    /// Removes the value stored under the integer `key` and returns it.
+    pub fn delete_int<K: IntKey>(&mut self, key: K) -> Option<V> {
+        self.delete_path(key.nibbles())
+    }
+
+    /// Stores `value` under the `u64` key, as [`Trie::insert_int`] does.
+    pub fn insert_u64(&mut self, key: u64, value: V) -> Option<V> {
+        self.insert_int(key, value)
+    }
+
+    /// Returns the value stored under the `u64` key, if any.
+    pub fn get_u64(&self, key: u64) -> Option<&V> {
+        self.get_int(key)
+    }
+
+    /// Removes the value stored under the `u64` key and returns it.
+    pub fn delete_u64(&mut self, key: u64) -> Option<V> {
+        self.delete_int(key)
+    }
+
+    /// Returns an iterator over the entries whose keys are exactly `K` wide,
+    /// decoded back into integers, in numeric order.
+    ///
+    /// Keys of any other length are skipped.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for n in [300u32, 7, 42] {
+    ///     trie.insert_int(n, ());
+    /// }
+    /// let keys: Vec<u32> = trie.iter_int().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, [7, 42, 300]);
+    /// ```
+    pub fn iter_int<K: IntKey>(&self) -> IntIter<'_, V, K> {
+        IntIter {
+            walk: Walk::new(&self.root, Vec::new()),
+            key: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the fixed-width integer keys of a [`Trie`] in numeric
+/// order.
+///
+/// Returned by [`Trie::iter_int`].
+pub struct IntIter<'a, V, K> {
+    walk: Walk<'a, V>,
+    key: PhantomData<K>,
+}
+
+impl<'a, V, K: IntKey> Iterator for IntIter<'a, V, K> {
+    type Item = (K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let value = self.walk.next_value()?;
+            if self.walk.path.len() == K::NIBBLES {
+                return Some((K::from_nibbles(&self.walk.path), value));
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::trie::Trie;
+
+/// A routing table mapping IPv4 and IPv6 prefixes to values, with
+/// longest-prefix-match lookups.
+///
+/// Each address family is a binary [`Trie`] over the address bits, most
+/// significant first, with a route stored at the depth of its prefix length.
+/// A `/0` route sits at the root and matches every address of its family.
+pub struct IpTrie<V> {
+    v4: Trie<V, 2>,
+    v6: Trie<V, 2>,
+}
+
+impl<V> Default for IpTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> IpTrie<V> {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self {
+            v4: Trie::with_fanout(),
+            v6: Trie::with_fanout(),
+        }
+    }
+
+    /// Stores `value` for `addr/len`, returning the value previously stored
+    /// for that exact prefix. Address bits past `len` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than 32.
+    pub fn insert_v4(&mut self, addr: Ipv4Addr, len: u8, value: V) -> Option<V> {
+        assert!(len <= 32, "IPv4 prefix length {} is over 32", len);
+        insert(&mut self.v4, bits(addr.to_bits().into(), 32, len), value)
+    }
+
+    /// Stores `value` for `addr/len`, returning the value previously stored
+    /// for that exact prefix. Address bits past `len` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than 128.
+    pub fn insert_v6(&mut self, addr: Ipv6Addr, len: u8, value: V) -> Option<V> {
+        assert!(len <= 128, "IPv6 prefix length {} is over 128", len);
+        insert(&mut self.v6, bits(addr.to_bits(), 128, len), value)
+    }
+
+    /// Removes the route for exactly `addr/len` and returns its value.
+    pub fn remove_v4(&mut self, addr: Ipv4Addr, len: u8) -> Option<V> {
+        remove(&mut self.v4, bits(addr.to_bits().into(), 32, len.min(32)))
+    }
+
+    /// Removes the route for exactly `addr/len` and returns its value.
+    pub fn remove_v6(&mut self, addr: Ipv6Addr, len: u8) -> Option<V> {
+        remove(&mut self.v6, bits(addr.to_bits(), 128, len.min(128)))
+    }
+
+    /// Returns the value of the most specific route covering `addr`.
+    pub fn lookup(&self, addr: IpAddr) -> Option<&V> {
+        self.longest_match(addr).map(|(_, value)| value)
+    }
+
+    /// Returns the prefix length and value of the most specific route
+    /// covering `addr`.
+    pub fn longest_match(&self, addr: IpAddr) -> Option<(u8, &V)> {
+        let (trie, addr_bits) = match addr {
+            IpAddr::V4(addr) => (&self.v4, bits(addr.to_bits().into(), 32, 32)),
+            IpAddr::V6(addr) => (&self.v6, bits(addr.to_bits(), 128, 128)),
+        };
+        let mut cur = &trie.root;
+        let mut best = cur.value.as_ref().map(|v| (0, v));
+        for (depth, bit) in addr_bits.enumerate() {
+            match cur.child(bit) {
+                Some(child) => cur = child,
+                None => break,
+            }
+            if let Some(value) = &cur.value {
+                best = Some((depth as u8 + 1, value));
+            }
+        }
+        best
+    }
+
+    /// Returns the number of routes across both families.
+    pub fn len(&self) -> usize {
+        self.v4.len() + self.v6.len()
+    }
+
+    /// Returns `true` if the table holds no routes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// The first `len` bits of a `width`-bit address, most significant first.
+fn bits(addr: u128, width: u32, len: u8) -> impl Iterator<Item = usize> + Clone {
+    (0..len as u32).map(move |i| (addr >> (width - 1 - i)) as usize & 1)
+}
+
+fn insert<V>(trie: &mut Trie<V, 2>, path: impl Iterator<Item = usize>, value: V) -> Option<V> {
+    let old = trie.root.insert_nibbles(path, value, &mut trie.pool);
+    if old.is_none() {
+        trie.len += 1;
+    }
+    old
+}
+
+fn remove<V>(trie: &mut Trie<V, 2>, path: impl Iterator<Item = usize> + Clone) -> Option<V> {
+    let removed = trie.root.delete_nibbles(path, &mut trie.pool)?;
+    trie.len -= 1;
+    Some(removed)
+}
@@ -0,0 +1,723 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Bound, RangeBounds};
+
+use crate::key::{
+    KeyError, check_key, hex_to_nibbles, key_symbols, nibbles_to_hex, symbols_to_hex,
+};
+use crate::node::{ChildrenMut, Node, Pool};
+use crate::trie::Trie;
+
+// Depth-first walk over the stored values in nibble order, from either end.
+//
+// Each stack frame is a node plus a cursor: 0 means the node's own value hasn't been looked at
+// yet, `i + 1` means child `i` is the next one to try. A node's value comes out before any of
+// its children, which is exactly lexicographic order since "a1" sorts before "a1f".
+// `path` always holds the nibbles leading to the node on top of the stack, so callers that
+// want keys can read it after each step and callers that don't pay nothing for it.
+//
+// `end` is an optional upper bound. Every key below a node is at least the node's own path, so
+// the first time the walk steps onto a path beyond the bound nothing left can be in range and
+// the walk simply stops.
+//
+// The back end is a second stack over the same nodes, only set up on the first `next_back`.
+// Its order is the exact reverse: children from high to low, then the node's value. A back
+// cursor means every child below it is still to try; `DONE` means the value has been looked at
+// too. Both ends stop where the other last stopped, so they never yield the same value, and
+// once one end runs out there is nothing left for the other.
+pub(crate) struct Walk<'a, V, const N: usize = 16> {
+    stack: Vec<(&'a Node<V, N>, usize)>,
+    pub(crate) path: Vec<u8>,
+    end: Bound<Vec<u8>>,
+    // The node the walk covers and the length of its path, which `path` starts with.
+    top: Option<(&'a Node<V, N>, usize)>,
+    start: Bound<Vec<u8>>,
+    back: Vec<(&'a Node<V, N>, usize)>,
+    pub(crate) back_path: Vec<u8>,
+    back_started: bool,
+    front_yielded: bool,
+    back_yielded: bool,
+}
+
+const DONE: usize = usize::MAX;
+
+impl<'a, V, const N: usize> Walk<'a, V, N> {
+    // Starts a walk at `node`, whose own key is `path`.
+    pub(crate) fn new(node: &'a Node<V, N>, path: Vec<u8>) -> Self {
+        Self {
+            stack: vec![(node, 0)],
+            top: Some((node, path.len())),
+            path,
+            end: Bound::Unbounded,
+            start: Bound::Unbounded,
+            back: Vec::new(),
+            back_path: Vec::new(),
+            back_started: false,
+            front_yielded: false,
+            back_yielded: false,
+        }
+    }
+
+    pub(crate) fn empty() -> Self {
+        Self {
+            stack: Vec::new(),
+            path: Vec::new(),
+            end: Bound::Unbounded,
+            top: None,
+            start: Bound::Unbounded,
+            back: Vec::new(),
+            back_path: Vec::new(),
+            back_started: false,
+            front_yielded: false,
+            back_yielded: false,
+        }
+    }
+
+    // Starts a walk at `root` positioned so the first value it yields is the smallest key
+    // `>= start` (or `> start` when `inclusive` is false). The stack is seeded by descending
+    // the start path once: every ancestor on it gets its cursor set just past the nibble taken,
+    // so its own value (a strict prefix of `start`, hence smaller) and all smaller children are
+    // never visited. The back end checks what it yields against `start` instead.
+    pub(crate) fn seek(root: &'a Node<V, N>, start: Vec<u8>, inclusive: bool) -> Self {
+        let mut walk = Self::new(root, Vec::new());
+        walk.descend_start(&start, inclusive);
+        walk.start = match inclusive {
+            true => Bound::Included(start),
+            false => Bound::Excluded(start),
+        };
+        walk
+    }
+
+    fn descend_start(&mut self, start: &[u8], inclusive: bool) {
+        for &nibble in start {
+            let (node, cursor) = self.stack.last_mut().unwrap();
+            match node.child(nibble as usize) {
+                Some(child) => {
+                    *cursor = nibble as usize + 2;
+                    self.path.push(nibble);
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    // no key starts with this much of `start`; carry on with the next
+                    // larger sibling
+                    *cursor = nibble as usize + 1;
+                    return;
+                }
+            }
+        }
+        if !inclusive {
+            self.stack.last_mut().unwrap().1 = 1;
+        }
+    }
+
+    // Limits the walk to keys up to `end`. Only walks from the root take one.
+    pub(crate) fn with_end(mut self, end: Bound<Vec<u8>>) -> Self {
+        self.end = end;
+        self
+    }
+
+    fn past_end(&self) -> bool {
+        let past = match &self.end {
+            Bound::Unbounded => false,
+            Bound::Included(end) => self.path > *end,
+            Bound::Excluded(end) => self.path >= *end,
+        };
+        past || (self.back_yielded && self.path >= self.back_path)
+    }
+
+    fn before_start(&self) -> bool {
+        let before = match &self.start {
+            Bound::Unbounded => false,
+            Bound::Included(start) => self.back_path < *start,
+            Bound::Excluded(start) => self.back_path <= *start,
+        };
+        before || (self.front_yielded && self.back_path <= self.path)
+    }
+
+    pub(crate) fn next_value(&mut self) -> Option<&'a V> {
+        if self.back_started && self.back.is_empty() {
+            self.stack.clear();
+        }
+        while let Some((node, cursor)) = self.stack.last_mut() {
+            let node: &'a Node<V, N> = node;
+            if *cursor == 0 {
+                *cursor = 1;
+                if self.past_end() {
+                    self.stack.clear();
+                    return None;
+                }
+                if let Some(value) = &node.value {
+                    self.front_yielded = true;
+                    return Some(value);
+                }
+                continue;
+            }
+            match node.next_child(*cursor - 1) {
+                Some((i, child)) => {
+                    *cursor = i + 2;
+                    self.path.push(i as u8);
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    if !self.stack.is_empty() {
+                        self.path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub(crate) fn next_back_value(&mut self) -> Option<&'a V> {
+        if !self.back_started {
+            self.back_started = true;
+            self.seed_back();
+        }
+        if self.stack.is_empty() {
+            self.back.clear();
+        }
+        while let Some((node, cursor)) = self.back.last_mut() {
+            let node: &'a Node<V, N> = node;
+            if *cursor == DONE {
+                self.back.pop();
+                if !self.back.is_empty() {
+                    self.back_path.pop();
+                }
+                continue;
+            }
+            match node.prev_child(*cursor) {
+                Some((i, child)) => {
+                    *cursor = i;
+                    self.back_path.push(i as u8);
+                    self.back.push((child, N));
+                }
+                None => {
+                    *cursor = DONE;
+                    if let Some(value) = &node.value {
+                        if self.before_start() {
+                            self.back.clear();
+                            return None;
+                        }
+                        self.back_yielded = true;
+                        return Some(value);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Puts the back end on the largest key the walk may yield, descending the end bound the
+    // way `seek` descends the start: every node on it only has its children below the nibble
+    // taken left, and then its own value.
+    fn seed_back(&mut self) {
+        let Some((node, depth)) = self.top else {
+            return;
+        };
+        self.back_path.extend_from_slice(&self.path[..depth]);
+        self.back.push((node, N));
+        let (end, inclusive) = match &self.end {
+            Bound::Unbounded => return,
+            Bound::Included(end) => (end, true),
+            Bound::Excluded(end) => (end, false),
+        };
+        for &nibble in end {
+            let (node, cursor) = self.back.last_mut().unwrap();
+            *cursor = nibble as usize;
+            match node.child(nibble as usize) {
+                Some(child) => {
+                    self.back_path.push(nibble);
+                    self.back.push((child, N));
+                }
+                None => return,
+            }
+        }
+        // everything below the end's own node is past it, and so is its value if excluded
+        self.back.last_mut().unwrap().1 = if inclusive { 0 } else { DONE };
+    }
+}
+
+/// An iterator over the entries of a [`Trie`] in key order.
+///
+/// Keys are rebuilt as lowercase hex strings. It can be walked from both
+/// ends, and the two ends never yield the same entry. It knows how many
+/// entries are left, since it covers the whole trie. Returned by
+/// [`Trie::iter`].
+pub struct Iter<'a, V, const N: usize = 16> {
+    walk: Walk<'a, V, N>,
+    remaining: usize,
+}
+
+impl<'a, V, const N: usize> Iterator for Iter<'a, V, N> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.walk.next_value()?;
+        self.remaining -= 1;
+        Some((symbols_to_hex::<N>(&self.walk.path), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<V, const N: usize> DoubleEndedIterator for Iter<'_, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.walk.next_back_value()?;
+        self.remaining -= 1;
+        Some((symbols_to_hex::<N>(&self.walk.back_path), value))
+    }
+}
+
+impl<V, const N: usize> ExactSizeIterator for Iter<'_, V, N> {}
+
+/// An iterator over the entries of part of a [`Trie`] in key order.
+///
+/// Like [`Iter`], but how many entries the part holds isn't known without
+/// walking it, so the size hint is only an upper bound. Returned by
+/// [`Trie::iter_prefix`], [`Trie::range`] and [`Trie::iter_from`].
+pub struct Range<'a, V, const N: usize = 16> {
+    pub(crate) walk: Walk<'a, V, N>,
+    // at most this many entries are left, if known
+    pub(crate) upper: Option<usize>,
+}
+
+impl<'a, V, const N: usize> Iterator for Range<'a, V, N> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.walk.next_value()?;
+        self.upper = self.upper.map(|upper| upper - 1);
+        Some((symbols_to_hex::<N>(&self.walk.path), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.upper)
+    }
+}
+
+impl<V, const N: usize> DoubleEndedIterator for Range<'_, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.walk.next_back_value()?;
+        self.upper = self.upper.map(|upper| upper - 1);
+        Some((symbols_to_hex::<N>(&self.walk.back_path), value))
+    }
+}
+
+/// An iterator over the keys of a [`Trie`] in key order.
+///
+/// Returned by [`Trie::keys`].
+pub struct Keys<'a, V, const N: usize = 16> {
+    walk: Walk<'a, V, N>,
+    remaining: usize,
+}
+
+impl<V, const N: usize> Iterator for Keys<'_, V, N> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.walk.next_value()?;
+        self.remaining -= 1;
+        Some(symbols_to_hex::<N>(&self.walk.path))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<V, const N: usize> DoubleEndedIterator for Keys<'_, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.walk.next_back_value()?;
+        self.remaining -= 1;
+        Some(symbols_to_hex::<N>(&self.walk.back_path))
+    }
+}
+
+impl<V, const N: usize> ExactSizeIterator for Keys<'_, V, N> {}
+
+/// An iterator over the values of a [`Trie`] in key order.
+///
+/// Returned by [`Trie::values`]. No key strings are built.
+pub struct Values<'a, V, const N: usize = 16> {
+    walk: Walk<'a, V, N>,
+    remaining: usize,
+}
+
+impl<'a, V, const N: usize> Iterator for Values<'a, V, N> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.walk.next_value()?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<V, const N: usize> DoubleEndedIterator for Values<'_, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.walk.next_back_value()?;
+        self.remaining -= 1;
+        Some(value)
+    }
+}
+
+impl<V, const N: usize> ExactSizeIterator for Values<'_, V, N> {}
+
+/// A mutable iterator over the values of a [`Trie`] in key order.
+///
+/// Returned by [`Trie::values_mut`].
+pub struct ValuesMut<'a, V> {
+    // the root value is handed out before any child is visited
+    root_value: Option<&'a mut V>,
+    // one pending child iterator per level; a node's value is yielded as soon as its children
+    // are pushed, which keeps the order pre-order without recursing
+    stack: Vec<ChildrenMut<'a, V, 16>>,
+}
+
+impl<'a, V> Iterator for ValuesMut<'a, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root_value.take() {
+            return Some(value);
+        }
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some((_, child)) => {
+                    child.invalidate();
+                    let (value, children) = child.value_and_children_mut();
+                    self.stack.push(children);
+                    if let Some(value) = value {
+                        return Some(value);
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<V, const N: usize> Trie<V, N> {
+    /// Returns an iterator over all entries in key order.
+    ///
+    /// Keys are compared nibble by nibble, so a key sorts before every key it
+    /// is a prefix of. A value stored under the empty key comes first, and
+    /// last when walking back with [`Iterator::rev`].
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for key in ["", "a1", "a1f", "b0"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// let keys: Vec<String> = trie.iter().rev().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, ["b0", "a1f", "a1", ""]);
+    ///
+    /// let mut iter = trie.iter();
+    /// iter.next();
+    /// assert_eq!(iter.len(), 3);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, V, N> {
+        Iter {
+            walk: Walk::new(&self.root, Vec::new()),
+            remaining: self.len,
+        }
+    }
+
+    /// Returns an iterator over the entries whose key starts with `prefix`, in
+    /// key order.
+    ///
+    /// Only the subtree below the prefix is visited. A value stored exactly at
+    /// `prefix` comes first, and a prefix that isn't in the trie yields
+    /// nothing.
+    ///
+    /// At a fanout of 256, where keys drop a trailing odd digit, a prefix
+    /// keeps it: `"a"` matches the keys starting with a byte from `a0` to
+    /// `af`, not every key.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::<u8, 256>::with_fanout();
+    /// for key in ["00", "a0", "af01", "b0"] {
+    ///     trie.insert_lossy(key, 0);
+    /// }
+    /// let keys: Vec<String> = trie.iter_prefix("a").map(|(k, _)| k).collect();
+    /// assert_eq!(keys, ["a0", "af01"]);
+    /// ```
+    pub fn iter_prefix(&self, prefix: &str) -> Range<'_, V, N> {
+        let path: Vec<u8> = key_symbols::<N>(prefix).map(|n| n as u8).collect();
+        if N == 256 && hex_to_nibbles(prefix).count() % 2 == 1 {
+            let high = hex_to_nibbles(prefix).last().unwrap() as u8;
+            return self.iter_half_prefix(path, high);
+        }
+        let walk = match self.root.find(path.iter().map(|&n| n as usize)) {
+            Some(node) => Walk::new(node, path),
+            None => Walk::empty(),
+        };
+        Range {
+            walk,
+            upper: Some(self.len),
+        }
+    }
+
+    /// Like [`Trie::iter_prefix`], but fails instead of skipping characters
+    /// that aren't hex digits. An odd prefix is fine at a fanout of 256.
+    pub fn try_iter_prefix(&self, prefix: &str) -> Result<Range<'_, V, N>, KeyError> {
+        check_key::<16>(prefix)?;
+        Ok(self.iter_prefix(prefix))
+    }
+
+    // The keys under `path` whose next byte starts with the digit `high`: everything from
+    // `path` and that digit followed by 0, up to where keys stop starting with it.
+    fn iter_half_prefix(&self, mut path: Vec<u8>, high: u8) -> Range<'_, V, N> {
+        path.push(high << 4);
+        let start = path.clone();
+        *path.last_mut().unwrap() |= 0xf;
+        // the smallest path after every one that starts with `path`
+        while path.last() == Some(&0xff) {
+            path.pop();
+        }
+        let end = match path.last_mut() {
+            Some(last) => {
+                *last += 1;
+                Bound::Excluded(path)
+            }
+            None => Bound::Unbounded,
+        };
+        Range {
+            walk: Walk::seek(&self.root, start, true).with_end(end),
+            upper: Some(self.len),
+        }
+    }
+
+    /// Returns an iterator over the entries whose keys fall inside `range`, in
+    /// key order.
+    ///
+    /// Bounds are hex keys compared nibble by nibble, the same order
+    /// [`Trie::iter`] uses, so `"a1"` sorts before `"a1f"`. Only subtrees that
+    /// can still hold keys inside the range are visited. A range whose start
+    /// lies after its end yields nothing.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for key in ["a0", "a1", "a1f", "a2", "b0"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// let keys: Vec<String> = trie.range("a1".."a2").map(|(k, _)| k).collect();
+    /// assert_eq!(keys, ["a1", "a1f"]);
+    /// ```
+    pub fn range<'k, R: RangeBounds<&'k str>>(&self, range: R) -> Range<'_, V, N> {
+        let to_path = |key: &str| key_symbols::<N>(key).map(|n| n as u8).collect::<Vec<_>>();
+        let walk = match range.start_bound() {
+            Bound::Included(start) => Walk::seek(&self.root, to_path(start), true),
+            Bound::Excluded(start) => Walk::seek(&self.root, to_path(start), false),
+            Bound::Unbounded => Walk::new(&self.root, Vec::new()),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => Bound::Included(to_path(end)),
+            Bound::Excluded(end) => Bound::Excluded(to_path(end)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        Range {
+            walk: walk.with_end(end),
+            upper: Some(self.len),
+        }
+    }
+
+    /// Returns an iterator over the entries with keys `>= start` in key
+    /// order.
+    ///
+    /// `start` doesn't have to be in the trie. The walk starts by descending
+    /// its path once, so resuming from the last key of a page costs the
+    /// length of the key rather than a scan of everything before it.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for key in ["a0", "a1", "a1f", "b0"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// let keys: Vec<String> = trie.iter_from("a10").map(|(k, _)| k).collect();
+    /// assert_eq!(keys, ["a1f", "b0"]);
+    /// ```
+    pub fn iter_from(&self, start: &str) -> Range<'_, V, N> {
+        let start: Vec<u8> = key_symbols::<N>(start).map(|n| n as u8).collect();
+        Range {
+            walk: Walk::seek(&self.root, start, true),
+            upper: Some(self.len),
+        }
+    }
+
+    /// Returns an iterator over all keys in key order.
+    pub fn keys(&self) -> Keys<'_, V, N> {
+        Keys {
+            walk: Walk::new(&self.root, Vec::new()),
+            remaining: self.len,
+        }
+    }
+
+    /// Returns an iterator over all values in key order.
+    pub fn values(&self) -> Values<'_, V, N> {
+        Values {
+            walk: Walk::new(&self.root, Vec::new()),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<V> Trie<V> {
+    /// Returns an iterator over mutable references to all values in key
+    /// order.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, V> {
+        self.snapshots.save_root(&self.root);
+        self.root.invalidate();
+        let (root_value, children) = self.root.value_and_children_mut();
+        ValuesMut {
+            root_value,
+            stack: vec![children],
+        }
+    }
+}
+
+impl<'a, V, const N: usize> IntoIterator for &'a Trie<V, N> {
+    type Item = (String, &'a V);
+    type IntoIter = Iter<'a, V, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An owning iterator over the entries of a [`Trie`] in key order.
+///
+/// Nodes are freed as soon as their subtree has been yielded, and dropping
+/// the iterator early frees the rest without recursion. Returned by
+/// `Trie::into_iter`.
+pub struct IntoIter<V> {
+    // same frame layout as `Walk`, but the frames own their nodes and children are moved out
+    // of their slot when descended into
+    stack: Vec<(Box<Node<V>>, usize)>,
+    path: Vec<u8>,
+    remaining: usize,
+}
+
+impl<V> IntoIter<V> {
+    // The next entry, handing each node to `free` once everything under it has been yielded.
+    fn next_freeing(&mut self, mut free: impl FnMut(Box<Node<V>>)) -> Option<(String, V)> {
+        while let Some((node, cursor)) = self.stack.last_mut() {
+            if *cursor == 0 {
+                *cursor = 1;
+                if let Some(value) = node.value.take() {
+                    self.remaining -= 1;
+                    return Some((nibbles_to_hex(&self.path), value));
+                }
+                continue;
+            }
+            match node.next_child(*cursor - 1) {
+                Some((i, _)) => {
+                    *cursor = i + 2;
+                    let child = node.take_child(i).unwrap();
+                    self.path.push(i as u8);
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    free(self.stack.pop().unwrap().0);
+                    if !self.stack.is_empty() {
+                        self.path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<V> Iterator for IntoIter<V> {
+    type Item = (String, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_freeing(drop)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<V> ExactSizeIterator for IntoIter<V> {}
+
+impl<V> IntoIterator for Trie<V> {
+    type Item = (String, V);
+    type IntoIter = IntoIter<V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            stack: vec![(Box::new(self.root), 0)],
+            path: Vec::new(),
+            remaining: self.len,
+        }
+    }
+}
+
+/// A draining iterator over the entries of a [`Trie`] in key order.
+///
+/// The whole structure is detached from the trie when the drain is created,
+/// so the trie is empty afterwards even if the drain is dropped before it is
+/// exhausted. The trie keeps its observer, node pool and metrics, and the
+/// nodes go back to the pool as they're emptied. Returned by
+/// [`Trie::drain`].
+pub struct Drain<'a, V> {
+    inner: IntoIter<V>,
+    pool: &'a mut Pool<V, 16>,
+}
+
+impl<V> Iterator for Drain<'_, V> {
+    type Item = (String, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_freeing(|node| self.pool.put(node))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<V> ExactSizeIterator for Drain<'_, V> {}
+
+// What a drain dropped early didn't yield goes to the pool with the rest.
+impl<V> Drop for Drain<'_, V> {
+    fn drop(&mut self) {
+        for (node, _) in self.inner.stack.drain(..) {
+            self.pool.put(node);
+        }
+    }
+}
+
+impl<V> Trie<V> {
+    /// Removes all entries, returning them as an iterator in key order.
+    pub fn drain(&mut self) -> Drain<'_, V> {
+        self.snapshots.save_root(&self.root);
+        // The root is swapped for a node from the pool rather than boxed, so the pool counts
+        // the node it'll get back.
+        let mut root = self.pool.take();
+        core::mem::swap(&mut *root, &mut self.root);
+        let inner = IntoIter {
+            stack: vec![(root, 0)],
+            path: Vec::new(),
+            remaining: core::mem::take(&mut self.len),
+        };
+        Drain {
+            inner,
+            pool: &mut self.pool,
+        }
+    }
+}
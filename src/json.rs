@@ -0,0 +1,141 @@
+// Hierarchical JSON export and import, enabled by the `json` feature.
+//
+// Each node becomes an object with an optional "value" and an optional "children" object
+// keyed by single hex digits, so `{"children": {"a": {"value": 1}}}` stores 1 under "a".
+// Both fields are left out when empty to keep sparse tries compact.
+
+use std::error::Error;
+use std::fmt;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::key::{NIBBLE_TO_HEX, nibbles_to_hex};
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// Why [`Trie::from_json`] rejected its input. Paths are the hex key of the
+/// offending node.
+#[derive(Debug)]
+pub enum JsonError {
+    /// A node was not a JSON object.
+    NotAnObject(String),
+    /// A node had a field other than `value` and `children`.
+    UnknownField(String, String),
+    /// A `children` key was not a single hex digit.
+    InvalidChildKey(String, String),
+    /// A value could not be deserialized.
+    InvalidValue(String, serde_json::Error),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::NotAnObject(path) => write!(f, "node {:?} is not an object", path),
+            JsonError::UnknownField(path, field) => {
+                write!(f, "node {:?} has unknown field {:?}", path, field)
+            }
+            JsonError::InvalidChildKey(path, key) => {
+                write!(f, "node {:?} has invalid child key {:?}", path, key)
+            }
+            JsonError::InvalidValue(path, err) => {
+                write!(f, "node {:?} has an invalid value: {}", path, err)
+            }
+        }
+    }
+}
+
+impl Error for JsonError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonError::InvalidValue(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<V: Serialize> Trie<V> {
+    /// Exports the trie as nested JSON objects, one per node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value fails to serialize, which only happens for types
+    /// with custom `Serialize` impls that return errors.
+    pub fn to_json(&self) -> Value {
+        fn to_json_rec<V: Serialize>(node: &Node<V>) -> Value {
+            let mut object = Map::new();
+            if let Some(value) = &node.value {
+                let value = serde_json::to_value(value).expect("value failed to serialize");
+                object.insert("value".to_string(), value);
+            }
+            let mut children = Map::new();
+            for (nibble, child) in node.children() {
+                let key = (NIBBLE_TO_HEX[nibble] as char).to_string();
+                children.insert(key, to_json_rec(child));
+            }
+            if !children.is_empty() {
+                object.insert("children".to_string(), Value::Object(children));
+            }
+            Value::Object(object)
+        }
+        to_json_rec(&self.root)
+    }
+}
+
+impl<V: DeserializeOwned> Trie<V> {
+    /// Rebuilds a trie from the JSON produced by [`Trie::to_json`].
+    ///
+    /// A `null` or missing `value` means the node holds none. Nodes that end
+    /// up with neither a value nor children are dropped.
+    pub fn from_json(json: &Value) -> Result<Trie<V>, JsonError> {
+        fn from_json_rec<V: DeserializeOwned>(
+            json: &Value,
+            path: &mut Vec<u8>,
+        ) -> Result<Node<V>, JsonError> {
+            let object = json
+                .as_object()
+                .ok_or_else(|| JsonError::NotAnObject(nibbles_to_hex(path)))?;
+            let mut node = Node::new();
+            for (field, value) in object {
+                match field.as_str() {
+                    "value" if value.is_null() => {}
+                    "value" => {
+                        let value = serde_json::from_value(value.clone())
+                            .map_err(|err| JsonError::InvalidValue(nibbles_to_hex(path), err))?;
+                        node.value = Some(value);
+                    }
+                    "children" => {
+                        let children = value
+                            .as_object()
+                            .ok_or_else(|| JsonError::NotAnObject(nibbles_to_hex(path)))?;
+                        for (key, child) in children {
+                            let mut chars = key.chars();
+                            let nibble =
+                                match (chars.next().and_then(|c| c.to_digit(16)), chars.next()) {
+                                    (Some(nibble), None) => nibble as usize,
+                                    _ => {
+                                        return Err(JsonError::InvalidChildKey(
+                                            nibbles_to_hex(path),
+                                            key.clone(),
+                                        ));
+                                    }
+                                };
+                            path.push(nibble as u8);
+                            let child = from_json_rec(child, path)?;
+                            path.pop();
+                            if !child.is_empty() {
+                                node.set_child(nibble, Box::new(child));
+                            }
+                        }
+                    }
+                    _ => {
+                        return Err(JsonError::UnknownField(nibbles_to_hex(path), field.clone()));
+                    }
+                }
+            }
+            Ok(node)
+        }
+        Ok(Trie::from_root(from_json_rec(json, &mut Vec::new())?))
+    }
+}
@@ -0,0 +1,203 @@
+// Helpers for turning hex keys into nibble paths and back.
+
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+
+pub(crate) const NIBBLE_TO_HEX: &[u8; 16] = b"0123456789abcdef";
+
+//This function returns an interator of nibbles from a hex
+pub(crate) fn hex_to_nibbles(s: &str) -> impl Iterator<Item = usize> + Clone + '_ {
+    s.chars().filter_map(|c| {
+        let d = c.to_digit(16)?;
+        Some(d as usize)
+    })
+}
+
+/// Why a key was rejected by the strict `try_*` methods of
+/// [`Trie`](crate::Trie) or by [`Nibbles`](crate::Nibbles).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyError {
+    /// The key contains a character that isn't a hex digit, at byte offset
+    /// `index`.
+    InvalidChar { index: usize, found: char },
+    /// A nibble path holds a value of 16 or more at position `index`.
+    InvalidNibble { index: usize, found: u8 },
+    /// The key has an odd number of digits, but the trie reads two per level
+    /// (a fanout of 256).
+    OddLength,
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::InvalidChar { index, found } => {
+                write!(f, "invalid hex character {:?} at index {}", found, index)
+            }
+            KeyError::InvalidNibble { index, found } => {
+                write!(f, "nibble {} at index {} is out of range", found, index)
+            }
+            KeyError::OddLength => write!(f, "key has an odd number of hex digits"),
+        }
+    }
+}
+
+impl Error for KeyError {}
+
+// Checks that every character of `s` is a hex digit and that none would be dropped by
+// key_symbols. The empty key passes: it's the root's key, not a mistake.
+pub(crate) fn check_key<const N: usize>(s: &str) -> Result<(), KeyError> {
+    if let Some((index, found)) = s.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(KeyError::InvalidChar { index, found });
+    }
+    if N == 256 && s.len() % 2 == 1 {
+        return Err(KeyError::OddLength);
+    }
+    Ok(())
+}
+
+// Rebuilds the canonical lowercase hex key for a nibble path.
+pub(crate) fn nibbles_to_hex(nibbles: &[u8]) -> String {
+    nibbles
+        .iter()
+        .map(|&n| NIBBLE_TO_HEX[n as usize] as char)
+        .collect()
+}
+
+// Compile-time description of a branching factor. Every fanout reads keys as hex and splits
+// them into symbols of BITS bits, so only powers of two whose symbols line up with hex digits
+// (or pairs of them) are allowed; anything else fails to build when the constant is used.
+pub(crate) struct Fanout<const N: usize>;
+
+impl<const N: usize> Fanout<N> {
+    pub(crate) const BITS: u32 = match N {
+        2 => 1,
+        4 => 2,
+        16 => 4,
+        256 => 8,
+        _ => panic!("unsupported trie fanout: use 2, 4, 16 or 256"),
+    };
+}
+
+// Splits a hex key into symbols below N, most significant bits first. A 256-way trie takes
+// two hex digits per symbol, so a trailing odd digit is dropped.
+pub(crate) fn key_symbols<const N: usize>(s: &str) -> impl Iterator<Item = usize> + Clone + '_ {
+    let bits = Fanout::<N>::BITS;
+    let mut nibbles = hex_to_nibbles(s);
+    let (mut acc, mut acc_bits) = (0usize, 0);
+    core::iter::from_fn(move || {
+        if N == 16 {
+            return nibbles.next();
+        }
+        while acc_bits < bits {
+            acc = (acc << 4 | nibbles.next()?) & 0xff;
+            acc_bits += 4;
+        }
+        acc_bits -= bits;
+        Some((acc >> acc_bits) & (N - 1))
+    })
+}
+
+// Rebuilds the hex key for a symbol path. A path that stops inside a hex digit (only
+// possible with fanouts below 16) has that digit padded with zero bits.
+pub(crate) fn symbols_to_hex<const N: usize>(symbols: &[u8]) -> String {
+    if N == 16 {
+        return nibbles_to_hex(symbols);
+    }
+    let bits = Fanout::<N>::BITS;
+    let mut out = String::with_capacity(symbols.len() * bits as usize / 4 + 1);
+    let (mut acc, mut acc_bits) = (0usize, 0);
+    for &symbol in symbols {
+        acc = (acc << bits | symbol as usize) & 0xff;
+        acc_bits += bits;
+        while acc_bits >= 4 {
+            acc_bits -= 4;
+            out.push(NIBBLE_TO_HEX[(acc >> acc_bits) & 0xf] as char);
+        }
+    }
+    if acc_bits > 0 {
+        out.push(NIBBLE_TO_HEX[(acc << (4 - acc_bits)) & 0xf] as char);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn hex_to_nibbles_reads_either_case_and_skips_the_rest() {
+        let nibbles: Vec<usize> = hex_to_nibbles("a1F-0x9").collect();
+        assert_eq!(nibbles, [10, 1, 15, 0, 9]);
+        assert_eq!(hex_to_nibbles("").count(), 0);
+        assert_eq!(hex_to_nibbles("ghz é").count(), 0);
+    }
+
+    #[test]
+    fn nibbles_to_hex_is_lowercase() {
+        assert_eq!(nibbles_to_hex(&[10, 1, 15, 0]), "a1f0");
+        assert_eq!(nibbles_to_hex(&[]), "");
+        let all: Vec<u8> = (0..16).collect();
+        assert_eq!(nibbles_to_hex(&all).as_bytes(), NIBBLE_TO_HEX);
+    }
+
+    #[test]
+    fn nibbles_round_trip_through_hex() {
+        let key = "0123456789abcdef";
+        let nibbles: Vec<u8> = hex_to_nibbles(key).map(|n| n as u8).collect();
+        assert_eq!(nibbles_to_hex(&nibbles), key);
+    }
+
+    #[test]
+    fn check_key_points_at_the_first_bad_character() {
+        assert_eq!(check_key::<16>("a1F0"), Ok(()));
+        assert_eq!(check_key::<16>(""), Ok(()));
+        assert_eq!(
+            check_key::<16>("a1-f"),
+            Err(KeyError::InvalidChar {
+                index: 2,
+                found: '-'
+            })
+        );
+        // the index is a byte offset
+        assert_eq!(
+            check_key::<16>("éa"),
+            Err(KeyError::InvalidChar {
+                index: 0,
+                found: 'é'
+            })
+        );
+        assert_eq!(check_key::<256>("a1f"), Err(KeyError::OddLength));
+        assert_eq!(check_key::<2>("a1f"), Ok(()));
+    }
+
+    #[test]
+    fn key_symbols_split_digits_for_every_fanout() {
+        let symbols = |key| key_symbols::<2>(key).collect::<Vec<_>>();
+        assert_eq!(symbols("a1"), [1, 0, 1, 0, 0, 0, 0, 1]);
+        let symbols = |key| key_symbols::<4>(key).collect::<Vec<_>>();
+        assert_eq!(symbols("a1"), [2, 2, 0, 1]);
+        let symbols = |key| key_symbols::<16>(key).collect::<Vec<_>>();
+        assert_eq!(symbols("a1"), [10, 1]);
+        let symbols = |key| key_symbols::<256>(key).collect::<Vec<_>>();
+        assert_eq!(symbols("a1f"), [0xa1]);
+    }
+
+    #[test]
+    fn symbols_round_trip_through_hex() {
+        fn round_trip<const N: usize>(key: &str) -> String {
+            let symbols: Vec<u8> = key_symbols::<N>(key).map(|s| s as u8).collect();
+            symbols_to_hex::<N>(&symbols)
+        }
+        for key in ["", "a1f0", "0123456789abcdef"] {
+            assert_eq!(round_trip::<2>(key), key);
+            assert_eq!(round_trip::<4>(key), key);
+            assert_eq!(round_trip::<16>(key), key);
+            assert_eq!(round_trip::<256>(key), key);
+        }
+        // a path stopping inside a digit pads it with zero bits
+        assert_eq!(symbols_to_hex::<2>(&[1, 1]), "c");
+    }
+}
@@ -0,0 +1,199 @@
+//! A simple radix trie keyed by hex strings.
+//!
+//! Every hex character of a key is one nibble, and every nibble selects one of
+//! sixteen children, so `"a1f"` is stored three levels below the root.
+//!
+//! Without the default `std` feature the crate is `no_std` and only needs
+//! `alloc`. The trie itself, its iterators and `Display` are all there;
+//! what reads files, streams or the clock, such as snapshots, the
+//! write-ahead log and `ExpiringTrie`, needs `std`.
+//!
+//! ```
+//! let mut trie = trie::Trie::new();
+//! trie.insert_lossy("a1f", 1);
+//! assert_eq!(trie.get_lossy("a1f"), Some(&1));
+//! assert_eq!(trie.delete_lossy("a1f"), Some(1));
+//! assert!(trie.is_empty());
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+mod arena;
+mod batch;
+#[cfg(feature = "bench_support")]
+pub mod bench_support;
+mod bulk;
+mod byte_key;
+mod bytes;
+mod cas;
+mod cmp;
+mod codec;
+#[cfg(feature = "color")]
+mod color;
+#[cfg(feature = "concurrent")]
+mod concurrent;
+mod convert;
+mod cursor;
+mod debug;
+mod diff;
+mod display;
+mod dot;
+mod entry;
+#[cfg(feature = "eth")]
+mod eth;
+#[cfg(feature = "std")]
+mod expiring;
+mod glob;
+mod hamming;
+mod int_key;
+mod ip;
+mod iter;
+#[cfg(feature = "json")]
+mod json;
+mod key;
+#[cfg(feature = "std")]
+mod lines;
+mod many;
+mod map;
+#[cfg(feature = "std")]
+mod mapped;
+mod memory;
+mod merge;
+mod merkle;
+mod mermaid;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod nibbles;
+mod node;
+mod observe;
+#[cfg(feature = "rayon")]
+mod par;
+mod patricia;
+mod persistent;
+mod proof;
+mod rollback;
+mod secure;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod set_ops;
+#[cfg(feature = "std")]
+mod snapshot;
+mod stats;
+mod subtrie;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod trie;
+mod validate;
+mod versioned;
+mod visit;
+#[cfg(feature = "std")]
+mod wal;
+
+pub use arena::{ArenaIter, ArenaTrie};
+pub use batch::{BatchError, BatchReceipt, Op, OpOutcome};
+pub use bulk::BulkLoadError;
+pub use byte_key::{ByteKeyIter, OddLengthKey};
+pub use bytes::{ByteIter, ByteTrie};
+pub use cas::CasError;
+pub use codec::ValueCodec;
+#[cfg(feature = "color")]
+pub use color::ColorChoice;
+#[cfg(feature = "concurrent")]
+pub use concurrent::ConcurrentTrie;
+pub use convert::MapKeyError;
+pub use cursor::{Cursor, CursorMut};
+pub use diff::{Diff, DiffEntry};
+pub use display::DisplayOptions;
+pub use dot::DotOptions;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+#[cfg(feature = "std")]
+pub use expiring::{Clock, ExpiringTrie, SystemClock};
+pub use glob::{Glob, GlobError, GlobPattern};
+pub use int_key::{IntIter, IntKey};
+pub use ip::IpTrie;
+pub use iter::{Drain, IntoIter, Iter, Keys, Range, Values, ValuesMut};
+#[cfg(feature = "json")]
+pub use json::JsonError;
+pub use key::KeyError;
+#[cfg(feature = "std")]
+pub use lines::{ImportError, ImportReport, LineError, SkippedLine};
+#[cfg(feature = "std")]
+pub use mapped::{MappedIter, MappedTrie};
+pub use memory::{HeapSize, MemoryUsage};
+pub use merge::{MergeFn, MergePolicy};
+#[cfg(feature = "keccak")]
+pub use merkle::Keccak256Hasher;
+#[cfg(feature = "sha2")]
+pub use merkle::Sha256Hasher;
+pub use merkle::{EMPTY_SLOT, TrieHasher};
+pub use mermaid::DEFAULT_MERMAID_NODES;
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+pub use nibbles::{Nibbles, NibblesIter};
+pub use observe::TrieObserver;
+#[cfg(feature = "rayon")]
+pub use par::ParIter;
+pub use patricia::{PatriciaIter, PatriciaTrie};
+pub use persistent::{PersistentIter, PersistentTrie};
+pub use proof::{AbsenceProof, ChildHashes, Proof, ProofStep, verify, verify_absent};
+pub use rollback::SnapshotId;
+pub use secure::{SecureIter, SecureTrie};
+#[cfg(feature = "std")]
+pub use snapshot::SnapshotError;
+pub use stats::TrieStats;
+pub use subtrie::SubTrie;
+pub use trie::Trie;
+pub use validate::{InvariantViolation, ViolationKind};
+pub use versioned::VersionedTrie;
+pub use visit::WalkControl;
+#[cfg(feature = "std")]
+pub use wal::LoggedTrie;
+
+/// A trie with the default one-hex-digit-per-level fanout.
+pub type HexTrie<V = String> = Trie<V, 16>;
+
+// Run by `cargo test --no-default-features`, to check the basic operations work in the build
+// without std as well as compile there.
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use crate::Trie;
+
+    #[test]
+    fn insert_get_delete() {
+        let mut trie = Trie::new();
+        assert_eq!(trie.try_insert("a1f", 1), Ok(None));
+        assert_eq!(trie.try_insert("a1", 2), Ok(None));
+        assert_eq!(trie.try_insert("a1f", 3), Ok(Some(1)));
+        assert_eq!(trie.try_get("a1f"), Ok(Some(&3)));
+        assert_eq!(trie.try_get("a1"), Ok(Some(&2)));
+        assert_eq!(trie.try_get("a"), Ok(None));
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.try_delete("a1f"), Ok(Some(3)));
+        assert_eq!(trie.try_delete("a1f"), Ok(None));
+        assert_eq!(trie.node_count(), 3);
+        assert_eq!(trie.try_delete("a1"), Ok(Some(2)));
+        assert!(trie.is_empty());
+        assert_eq!(trie.node_count(), 1);
+    }
+
+    #[test]
+    fn iterates_and_displays() {
+        let mut trie = Trie::new();
+        for (n, key) in ["b", "a1", "a"].into_iter().enumerate() {
+            trie.insert_lossy(key, n);
+        }
+        let keys: Vec<_> = trie.keys().collect();
+        assert_eq!(keys, ["a", "a1", "b"]);
+        assert_eq!(
+            trie.to_string(),
+            "(root)\n├── a = 2\n│   └── 1 = 1\n└── b = 0\n"
+        );
+    }
+}
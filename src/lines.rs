@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::str::FromStr;
+
+use crate::trie::Trie;
+
+/// What [`Trie::load_lines`] did with its input.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Lines that stored a new key.
+    pub inserted: usize,
+    /// Lines that replaced the value of an existing key.
+    pub overwritten: usize,
+    /// Lines that were skipped because they couldn't be parsed, in input
+    /// order.
+    pub skipped: Vec<SkippedLine>,
+}
+
+/// A line [`Trie::load_lines`] could not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedLine {
+    /// 1-based line number.
+    pub line: usize,
+    pub reason: LineError,
+}
+
+/// Why a line was skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineError {
+    /// The line is not valid UTF-8.
+    InvalidUtf8,
+    /// The separator doesn't appear on the line.
+    MissingSeparator,
+    /// The key contains a character that is not a hex digit.
+    InvalidKey,
+    /// The value could not be parsed into the trie's value type.
+    InvalidValue,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LineError::InvalidUtf8 => "line is not valid UTF-8",
+            LineError::MissingSeparator => "missing separator",
+            LineError::InvalidKey => "key is not hex",
+            LineError::InvalidValue => "value could not be parsed",
+        })
+    }
+}
+
+/// A failure that stopped [`Trie::load_lines`] altogether. Malformed lines
+/// don't cause this; they end up in [`ImportReport::skipped`].
+#[derive(Debug)]
+pub enum ImportError {
+    /// Reading from the source failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(err) => write!(f, "failed to read input: {}", err),
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImportError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(err: io::Error) -> Self {
+        ImportError::Io(err)
+    }
+}
+
+impl<V: FromStr> Trie<V> {
+    /// Inserts one `hexkey<sep>value` entry per line of `reader`.
+    ///
+    /// Blank lines and lines starting with `#` are ignored, whitespace around
+    /// the key is trimmed, and the value is everything after the first `sep`.
+    /// A line with nothing before `sep` stores the root's value, the empty
+    /// key, so what [`Trie::dump_lines`] writes always reads back whole.
+    /// Lines that don't parse are reported and skipped instead of aborting.
+    /// The input is read one line at a time.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::<u32>::new();
+    /// let input = "# counts\n=1\na1=2\n\nzz=3\na1=4\n";
+    /// let report = trie.load_lines(input.as_bytes(), '=').unwrap();
+    /// assert_eq!((report.inserted, report.overwritten), (2, 1));
+    /// assert_eq!(report.skipped[0].line, 5);
+    /// assert_eq!(trie.root_value(), Some(&1));
+    /// ```
+    pub fn load_lines<R: BufRead>(
+        &mut self,
+        mut reader: R,
+        sep: char,
+    ) -> Result<ImportReport, ImportError> {
+        let mut report = ImportReport::default();
+        let mut buf = Vec::new();
+        let mut line_no = 0;
+        loop {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                return Ok(report);
+            }
+            line_no += 1;
+            match self.load_line(&buf, sep) {
+                Ok(Some(true)) => report.overwritten += 1,
+                Ok(Some(false)) => report.inserted += 1,
+                Ok(None) => {}
+                Err(reason) => report.skipped.push(SkippedLine {
+                    line: line_no,
+                    reason,
+                }),
+            }
+        }
+    }
+
+    // Returns whether the line overwrote a value, or None if it was blank or a comment.
+    fn load_line(&mut self, raw: &[u8], sep: char) -> Result<Option<bool>, LineError> {
+        let line = std::str::from_utf8(raw).map_err(|_| LineError::InvalidUtf8)?;
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return Ok(None);
+        }
+        let (key, value) = line.split_once(sep).ok_or(LineError::MissingSeparator)?;
+        let key = key.trim();
+        if !key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(LineError::InvalidKey);
+        }
+        let value = value.parse().map_err(|_| LineError::InvalidValue)?;
+        Ok(Some(self.insert_lossy(key, value).is_some()))
+    }
+}
+
+impl<V: fmt::Display> Trie<V> {
+    /// Writes every entry as a `hexkey<sep>value` line in key order, the
+    /// format read by [`Trie::load_lines`].
+    ///
+    /// The root's value, under the empty key, is written as a line starting
+    /// with `sep`. Values containing line breaks won't read back as one
+    /// entry.
+    pub fn dump_lines<W: Write>(&self, w: W, sep: char) -> io::Result<()> {
+        let mut w = BufWriter::new(w);
+        for (key, value) in self {
+            writeln!(w, "{}{}{}", key, sep, value)?;
+        }
+        w.flush()
+    }
+}
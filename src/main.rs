@@ -1,166 +1,30 @@
-use std::fmt;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
 
-#[derive(Default, Clone)]
-struct Node {
-    children: Vec<Option<Box<Node>>>,
-    value: Option<String>,
-}
+use trie::{ArenaTrie, ByteTrie, DisplayOptions, IntKey, Nibbles, PatriciaTrie, Trie};
 
-impl Node {
-    fn new() -> Self {
-        Self {
-            children: vec![None; 16],
-            value: None,
-        }
-    }
+// Counts heap allocations so the benchmarks can report them next to their timings.
+struct CountingAlloc;
 
-    // This function takes the reference to a node and a key and value
-    // then it sets the current node to the passed in node and loops over the hex_key which is a
-    // series of nibbles (for example 0x7abf would be 7, 10, 11, 15)
-    // for each nibble we grow the trie by either getting the child node at the index of the nibble or inserting a new node
-    // once we have the last nibble we set the value of the node to the value passed in
-    fn insert(&mut self, hex_key: &str, value: String) {
-        let mut cur = self;
-        for nibble in hex_to_nibbles(hex_key) {
-            cur = cur.children[nibble]
-                .get_or_insert_with(|| Box::new(Node::new()))
-                .as_mut();
-        }
-        cur.value = Some(value);
-    }
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
 
-    fn get(&self, hex_key: &str) -> Option<&String> {
-        let mut cur = self;
-        for nibble in hex_to_nibbles(hex_key) {
-            match cur.children[nibble].as_deref() {
-                Some(child) => cur = child,
-                None => return None,
-            }
-        }
-        cur.value.as_ref()
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
     }
 
-    fn delete(&mut self, hex_key: &str) -> bool {
-        fn delete_rec(node: &mut Node, nibbles: &[usize]) -> bool {
-            if (nibbles.is_empty()) {
-                node.value = None;
-            } else {
-                let idx = nibbles[0];
-                if let Some(child) = node.children[idx].as_deref_mut() {
-                    let should_prune = delete_rec(child, &nibbles[1..]);
-                    if should_prune {
-                        node.children[idx] = None;
-                    }
-                }
-            }
-            node.value.is_none() && node.children.iter().all(|c| c.is_none())
-        }
-        delete_rec(self, &hex_to_nibbles(hex_key).collect::<Vec<_>>())
-    }
-
-    fn insert_nibbles<I: IntoIterator<Item = usize>>(&mut self, nibbles: I, value: String) {
-        let mut cur = self;
-        for nib in nibbles {
-            cur = cur.children[nib]
-                .get_or_insert_with(|| Box::new(Node::new()))
-                .as_mut();
-        }
-        cur.value = Some(value);
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
     }
 }
 
-// Pretty printer to visualize the trie.
-impl fmt::Display for Node {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fn print_rec(
-            f: &mut fmt::Formatter<'_>,
-            node: &Node,
-            prefix_path: &mut Vec<usize>,
-            indent: &str,
-            is_last: bool,
-            is_root: bool,
-        ) -> fmt::Result {
-            let bullet = if is_root {
-                "" // no bullet for the root line
-            } else if is_last {
-                "└── "
-            } else {
-                "├── "
-            };
-
-            // line text
-            if is_root {
-                // root label
-                let value_str = node
-                    .value
-                    .as_ref()
-                    .map(|v| format!(" = {}", v))
-                    .unwrap_or_default();
-                writeln!(f, "(root){}", value_str)?;
-            } else {
-                // path like "a1f"
-                let path_hex: String = prefix_path
-                    .iter()
-                    .map(|&n| NIBBLE_TO_HEX[n] as char)
-                    .collect();
-
-                //take the last value in path_hex
-                let last_hex = path_hex.chars().last().unwrap();
-
-                let value_str = node
-                    .value
-                    .as_ref()
-                    .map(|v| format!(" = {}", v))
-                    .unwrap_or_default();
-                writeln!(f, "{}{}{}{}", indent, bullet, last_hex, value_str)?;
-            }
-
-            // collect existing children in nibble order
-            let mut present: Vec<(usize, &Node)> = node
-                .children
-                .iter()
-                .enumerate()
-                .filter_map(|(i, ch)| ch.as_deref().map(|c| (i, c)))
-                .collect();
-            present.sort_by_key(|(i, _)| *i);
-
-            // recurse
-            for (i, (nib, child)) in present.iter().enumerate() {
-                let child_is_last = i + 1 == present.len();
-
-                // extend indent: if this node isn't last, draw a vertical '│'; else just spaces
-                let mut next_indent = String::from(indent);
-                if !is_root {
-                    next_indent.push_str(if is_last { "    " } else { "│   " });
-                }
-
-                // push nibble for path, recurse, then pop
-                prefix_path.push(*nib);
-                print_rec(f, child, prefix_path, &next_indent, child_is_last, false)?;
-                prefix_path.pop();
-            }
-
-            Ok(())
-        }
-
-        print_rec(f, self, &mut Vec::new(), "", true, true)
-    }
-}
-// Helpers
-
-const NIBBLE_TO_HEX: &[u8; 16] = b"0123456789abcdef";
-
-//This function returns an interator of nibbles from a hex
-fn hex_to_nibbles(s: &str) -> impl Iterator<Item = usize> + '_ {
-    s.chars().filter_map(|c| {
-        let d = c.to_digit(16)?;
-        Some(d as usize)
-    })
-}
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
 
 fn main() {
-    let mut trie = Node::new();
+    let mut trie = Trie::new();
 
     // Insert a few keys and show the trie after each step.
     let steps = [
@@ -173,41 +37,244 @@ fn main() {
 
     for (k, v) in steps {
         println!("=== Insert key {:>3} -> {:<12} ===", k, v);
-        trie.insert(k, v.to_string());
+        trie.insert_lossy(k, v.to_string());
         println!("{}", trie);
     }
 
     // Demonstrate get
-    println!("Get a1e -> {:?}", trie.get("a1e"));
-    println!("Get a1d -> {:?}", trie.get("a1d"));
+    println!("Get a1e -> {:?}", trie.get_lossy("a1e"));
+    println!("Get a1d -> {:?}", trie.get_lossy("a1d"));
 
     // Demonstrate delete + pruning
     println!("\n=== Delete a1f (prune if empty) ===");
-    trie.delete("a1f");
+    trie.delete_lossy("a1f");
     println!("{}", trie);
 
     println!("=== Delete a1e (prune if empty) ===");
-    trie.delete("a1e");
+    trie.delete_lossy("a1e");
     println!("{}", trie);
 
-    let mut big_trie = Node::new();
+    // The dense keys below, first in an ArenaTrie, whose nodes share one vector instead of
+    // each being an allocation of its own. It runs first because growing that vector needs one
+    // large block, which memory freed by the boxed tries below can't provide. Reserving the
+    // whole 16^0 + ... + 16^6 nodes up front means it's never copied while it grows.
+    let mut arena = ArenaTrie::new();
+    arena.reserve((16_usize.pow(7) - 1) / 15);
+    counted("ArenaTrie dense build", || {
+        for a in 0..16_u32.pow(6) {
+            arena.insert_int(a, String::from("leaf"));
+        }
+    });
+    let start = Instant::now();
+    let found = (0..16_u32.pow(6))
+        .filter(|&a| arena.get_int(a).is_some())
+        .count();
+    println!(
+        "ArenaTrie: {} nodes, {} lookups {:?}",
+        arena.node_count(),
+        found,
+        start.elapsed()
+    );
+    drop(arena);
+
+    // The keys come in order, so the trie is built in one pass instead of an insert per key.
+    let start = Instant::now();
+    let dense =
+        (0..16_u32.pow(6)).map(|a| (Nibbles::from_bytes(&a.to_be_bytes()), String::from("leaf")));
+    let big_trie = Trie::from_sorted_iter(dense).unwrap();
+    let duration = start.elapsed();
+
+    // Far too big to draw whole, so its shape and the top of it.
+    println!("{}", big_trie.stats());
+    let top = DisplayOptions {
+        max_depth: Some(3),
+        max_entries: Some(8),
+        ..DisplayOptions::default()
+    };
+    print!("{}", big_trie.display_with(top));
+    println!("Time taken: {:?}", duration);
+
+    // A prefix query only walks the subtree under the prefix.
+    let start = Instant::now();
+    let all = big_trie.iter().count();
+    println!("Full iteration: {} entries in {:?}", all, start.elapsed());
 
     let start = Instant::now();
-    for a in 0..16_i32.pow(6) {
-        // convert number -> nibbles directly, no strings
-        let mut nibbles = Vec::new();
-        let mut x = a;
-        // if x =
-        while x > 0 {
-            nibbles.push((x % 16) as usize);
-            x /= 16;
+    let under_a1 = big_trie.iter_prefix("00a1").count();
+    println!("Prefix 00a1: {} entries in {:?}", under_a1, start.elapsed());
+
+    // Path compression: the dense keys again, then a million random 16-nibble keys, where the
+    // plain trie spends a node on nearly every nibble.
+    let dense_nodes = big_trie.node_count();
+    drop(big_trie);
+    let start = Instant::now();
+    let mut patricia = PatriciaTrie::new();
+    for a in 0..16_u32.pow(6) {
+        patricia.insert_nibbles(a.nibbles(), String::from("leaf"));
+    }
+    println!(
+        "Dense: Trie {} nodes in {:?}, PatriciaTrie {} nodes in {:?}",
+        dense_nodes,
+        duration,
+        patricia.node_count(),
+        start.elapsed()
+    );
+    drop(patricia);
+
+    let sparse: Vec<String> = sparse_keys(1_000_000);
+    let start = Instant::now();
+    let mut patricia = PatriciaTrie::new();
+    for key in &sparse {
+        patricia.insert(key, String::from("leaf"));
+    }
+    let patricia_time = start.elapsed();
+    let patricia_nodes = patricia.node_count();
+    drop(patricia);
+    let start = Instant::now();
+    let mut plain = Trie::new();
+    for key in &sparse {
+        plain.insert_lossy(key, String::from("leaf"));
+    }
+    println!(
+        "Sparse: Trie {} nodes in {:?}, PatriciaTrie {} nodes in {:?}",
+        plain.node_count(),
+        start.elapsed(),
+        patricia_nodes,
+        patricia_time
+    );
+    let start = Instant::now();
+    let deleted = sparse
+        .iter()
+        .filter(|key| plain.delete_lossy(key).is_some())
+        .count();
+    println!(
+        "Sparse delete: {} keys in {:?}, {} nodes left",
+        deleted,
+        start.elapsed(),
+        plain.node_count()
+    );
+    drop(plain);
+
+    // Fanout: the same 65,536 four-digit keys with one hex digit per level and one byte per
+    // level. Nodes only store the children they have, so the wider fanout trades half the
+    // levels for a binary search at each one.
+    let keys: Vec<String> = (0..16_u32.pow(4)).map(|a| format!("{:04x}", a)).collect();
+    fanout_bench(Trie::<String, 16>::with_fanout(), &keys);
+    fanout_bench(Trie::<String, 256>::with_fanout(), &keys);
+
+    // Byte keys: 200,000 of the random keys as raw 8-byte strings in a ByteTrie, against the
+    // same keys hex-encoded in the nibble trie.
+    let hex_keys = sparse_keys(200_000);
+    let byte_keys: Vec<Vec<u8>> = hex_keys
+        .iter()
+        .map(|k| u64::from_str_radix(k, 16).unwrap().to_be_bytes().to_vec())
+        .collect();
+    let start = Instant::now();
+    let mut bytes = ByteTrie::new();
+    for key in &byte_keys {
+        bytes.insert(key, String::from("leaf"));
+    }
+    let bytes_insert = start.elapsed();
+    let start = Instant::now();
+    let found = byte_keys.iter().filter(|k| bytes.get(k).is_some()).count();
+    println!(
+        "ByteTrie: {} nodes, insert {:?}, {} lookups {:?}",
+        bytes.node_count(),
+        bytes_insert,
+        found,
+        start.elapsed()
+    );
+    fanout_bench(Trie::<String, 16>::with_fanout(), &hex_keys);
+    drop(bytes);
+
+    // A million byte keys loaded into the nibble trie, then loaded again over the same entries
+    // by hex-encoding each key first and by handing the bytes over directly. The reloads
+    // allocate no nodes, so what's left is the cost of the key strings.
+    let million: Vec<[u8; 8]> = sparse_keys(1_000_000)
+        .iter()
+        .map(|k| u64::from_str_radix(k, 16).unwrap().to_be_bytes())
+        .collect();
+    let mut trie = Trie::new();
+    counted("insert_bytes load", || {
+        for key in &million {
+            trie.insert_bytes(key, ());
         }
-        nibbles.reverse();
+    });
+    counted("Hex-encoded reload", || {
+        for key in &million {
+            let hex: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+            trie.insert_lossy(&hex, ());
+        }
+    });
+    counted("insert_bytes reload", || {
+        for key in &million {
+            trie.insert_bytes(key, ());
+        }
+    });
+    drop(trie);
 
-        big_trie.insert_nibbles(nibbles, String::from("leaf")); // or some simple value
+    // Churn: 100,000 keys inserted and then deleted, ten times over, with the node pool off
+    // and with it big enough to hold every node the keys need.
+    let churn = sparse_keys(100_000);
+    for capacity in [0, 2_000_000] {
+        let mut trie = Trie::new();
+        trie.set_pool_capacity(capacity);
+        counted(&format!("Churn with pool capacity {}", capacity), || {
+            for _ in 0..10 {
+                for key in &churn {
+                    trie.insert_lossy(key, ());
+                }
+                for key in &churn {
+                    trie.delete_lossy(key);
+                }
+            }
+        });
+        println!("Compacting the emptied trie frees {} bytes", trie.compact());
     }
-    let duration = start.elapsed();
+}
 
-    println!("{}", big_trie);
-    println!("Time taken: {:?}", duration);
+fn counted(label: &str, f: impl FnOnce()) {
+    let allocs = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    f();
+    println!(
+        "{}: {:?}, {} allocations",
+        label,
+        start.elapsed(),
+        ALLOCATIONS.load(Ordering::Relaxed) - allocs
+    );
+}
+
+fn fanout_bench<const N: usize>(mut trie: Trie<String, N>, keys: &[String]) {
+    let start = Instant::now();
+    for key in keys {
+        trie.insert_lossy(key, String::from("leaf"));
+    }
+    let insert_time = start.elapsed();
+    let start = Instant::now();
+    let found = keys
+        .iter()
+        .filter(|key| trie.get_lossy(key).is_some())
+        .count();
+    println!(
+        "Fanout {}: {} nodes, insert {:?}, {} lookups {:?}",
+        N,
+        trie.node_count(),
+        insert_time,
+        found,
+        start.elapsed()
+    );
+}
+
+// Pseudo-random (xorshift) 16-nibble keys, the same on every run.
+fn sparse_keys(n: usize) -> Vec<String> {
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    (0..n)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            format!("{:016x}", seed)
+        })
+        .collect()
 }
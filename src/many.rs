@@ -0,0 +1,157 @@
+// Lookups of many keys in one walk. The keys are visited in symbol order, so consecutive keys
+// share their longest prefixes, and the nodes along the previous key's path are kept around
+// to start the next key from where the two part.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Index;
+
+use crate::key::key_symbols;
+use crate::node::Node;
+use crate::trie::Trie;
+
+impl<V, const N: usize> Trie<V, N> {
+    /// Returns the value under each of `keys`, in the order of `keys`.
+    ///
+    /// The keys are sorted first, and each one is looked up starting from
+    /// the node where its path leaves the path of the key before it, so a
+    /// prefix the keys share is only walked once.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1f", 1);
+    /// trie.insert_lossy("a1e", 2);
+    /// assert_eq!(trie.get_many(&["a1f", "b0", "a1e"]), [Some(&1), None, Some(&2)]);
+    /// ```
+    pub fn get_many<'a>(&'a self, keys: &[&str]) -> Vec<Option<&'a V>> {
+        let paths = SymbolPaths::new::<N>(keys);
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_unstable_by(|&a, &b| paths[a].cmp(&paths[b]));
+
+        let mut found = vec![None; keys.len()];
+        // `nodes[d]` is the node `d` symbols down the previous key's path, as far as it exists.
+        let mut nodes = vec![&self.root];
+        let mut previous: &[u8] = &[];
+        for i in order {
+            let path = &paths[i];
+            let shared = previous
+                .iter()
+                .zip(path)
+                .take_while(|(a, b)| a == b)
+                .count();
+            nodes.truncate(shared + 1);
+            while let Some(&symbol) = path.get(nodes.len() - 1) {
+                match nodes[nodes.len() - 1].child(symbol as usize) {
+                    Some(child) => nodes.push(child),
+                    None => break,
+                }
+            }
+            if nodes.len() == path.len() + 1 {
+                found[i] = nodes[path.len()].value.as_ref();
+            }
+            previous = path;
+        }
+        found
+    }
+
+    /// Returns mutable references to the values under each of `keys` at
+    /// once, in the order of `keys`, with `None` for a key holding nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two of the keys are the same key, counting keys that only
+    /// differ in case or in characters that aren't hex digits.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1", 1);
+    /// trie.insert_lossy("b0", 2);
+    /// let [a, b, c] = trie.get_disjoint_mut(["a1", "b0", "c"]);
+    /// core::mem::swap(a.unwrap(), b.unwrap());
+    /// assert!(c.is_none());
+    /// assert_eq!(trie.get_lossy("a1"), Some(&2));
+    /// ```
+    pub fn get_disjoint_mut<const M: usize>(&mut self, keys: [&str; M]) -> [Option<&mut V>; M] {
+        let paths = SymbolPaths::new::<N>(&keys);
+        let mut order: Vec<usize> = (0..M).collect();
+        order.sort_unstable_by(|&a, &b| paths[a].cmp(&paths[b]));
+        for pair in order.windows(2) {
+            assert!(
+                paths[pair[0]] != paths[pair[1]],
+                "get_disjoint_mut: keys {:?} and {:?} are the same key",
+                keys[pair[0]],
+                keys[pair[1]]
+            );
+        }
+
+        for key in keys {
+            self.save_key(key);
+        }
+
+        // Each frame is a node, how deep it is and the run of `order` whose paths go through
+        // it. A node's value and its children are borrowed apart, so every reference handed
+        // out points into a different node.
+        let mut found = core::array::from_fn(|_| None);
+        let mut stack: Vec<(&mut Node<V, N>, usize, &[usize])> = vec![(&mut self.root, 0, &order)];
+        while let Some((node, depth, mut wanted)) = stack.pop() {
+            // the caller may change any of the values, so the hashes above them go
+            node.invalidate();
+            let (value, children) = node.value_and_children_mut();
+            if let Some((&first, rest)) = wanted.split_first()
+                && paths[first].len() == depth
+            {
+                found[first] = value;
+                wanted = rest;
+            }
+            for (symbol, child) in children {
+                // keys going through a child that isn't there are skipped over
+                let missing = wanted
+                    .iter()
+                    .take_while(|&&i| (paths[i][depth] as usize) < symbol)
+                    .count();
+                wanted = &wanted[missing..];
+                let through = wanted
+                    .iter()
+                    .take_while(|&&i| paths[i][depth] as usize == symbol)
+                    .count();
+                if through > 0 {
+                    stack.push((child, depth + 1, &wanted[..through]));
+                }
+                wanted = &wanted[through..];
+                if wanted.is_empty() {
+                    break;
+                }
+            }
+        }
+        found
+    }
+}
+
+// The symbol paths of a batch of keys, all in one buffer.
+struct SymbolPaths {
+    symbols: Vec<u8>,
+    ends: Vec<usize>,
+}
+
+impl SymbolPaths {
+    fn new<const N: usize>(keys: &[&str]) -> Self {
+        let mut symbols = Vec::new();
+        let ends = keys
+            .iter()
+            .map(|key| {
+                symbols.extend(key_symbols::<N>(key).map(|symbol| symbol as u8));
+                symbols.len()
+            })
+            .collect();
+        SymbolPaths { symbols, ends }
+    }
+}
+
+impl Index<usize> for SymbolPaths {
+    type Output = [u8];
+
+    fn index(&self, i: usize) -> &[u8] {
+        let start = if i == 0 { 0 } else { self.ends[i - 1] };
+        &self.symbols[start..self.ends[i]]
+    }
+}
@@ -0,0 +1,61 @@
+use crate::key::symbols_to_hex;
+use crate::node::Pool;
+use crate::observe::Observer;
+use crate::rollback::Snapshots;
+use crate::trie::Trie;
+
+impl<V, const N: usize> Trie<V, N> {
+    /// Turns the trie into one with the same keys, each holding `f` of the
+    /// key and its old value.
+    ///
+    /// The new trie is built node by node in the shape of this one, so no
+    /// key is looked up or inserted. `f` is called in key order.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1", "42".to_string());
+    /// trie.insert_lossy("b0", "7".to_string());
+    /// let parsed = trie.map_values(|_, value| value.parse::<u32>().unwrap());
+    /// assert_eq!(parsed.get_lossy("a1"), Some(&42));
+    /// ```
+    pub fn map_values<U, F>(self, mut f: F) -> Trie<U, N>
+    where
+        F: FnMut(&str, V) -> U,
+    {
+        let len = self.len;
+        Trie {
+            root: self
+                .root
+                .map_values(|path, value| f(&symbols_to_hex::<N>(path), value)),
+            len,
+            pool: Pool::new(),
+            snapshots: Snapshots::default(),
+            observer: Observer::default(),
+        }
+    }
+
+    /// Like [`Trie::map_values`], but leaves this trie as it is and passes
+    /// `f` each value by reference.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1", "abc".to_string());
+    /// let lengths = trie.map_values_ref(|key, value| key.len() + value.len());
+    /// assert_eq!(lengths.get_lossy("a1"), Some(&5));
+    /// assert_eq!(trie.get_lossy("a1").map(String::as_str), Some("abc"));
+    /// ```
+    pub fn map_values_ref<U, F>(&self, mut f: F) -> Trie<U, N>
+    where
+        F: FnMut(&str, &V) -> U,
+    {
+        Trie {
+            root: self
+                .root
+                .map_values_ref(|path, value| f(&symbols_to_hex::<N>(path), value)),
+            len: self.len,
+            pool: Pool::new(),
+            snapshots: Snapshots::default(),
+            observer: Observer::default(),
+        }
+    }
+}
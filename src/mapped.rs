@@ -0,0 +1,317 @@
+// Read-only tries answered straight from their serialized bytes.
+//
+// Layout, all integers little-endian:
+//
+//   header   `RTRM`, version byte, 3 reserved bytes, node count (u32), 4 reserved bytes
+//   nodes    one 76-byte record per node in breadth-first order, root first:
+//            16 child indices (u32, 0 meaning no child), value offset into the blob (u64),
+//            value length (u32, u32::MAX meaning no value)
+//   blob     the encoded values back to back
+//
+// Breadth-first numbering means every child index is larger than its parent's, which the
+// reader enforces; that rules out cycles, so a corrupted file can make a lookup fail but never
+// loop. Every offset is bounds-checked before it is read.
+
+use std::collections::VecDeque;
+use std::io::{self, BufWriter, Write};
+
+use crate::codec::ValueCodec;
+use crate::key::{hex_to_nibbles, nibbles_to_hex};
+use crate::snapshot::SnapshotError;
+use crate::trie::Trie;
+
+const MAGIC: &[u8; 4] = b"RTRM";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 16;
+const RECORD_LEN: usize = 16 * 4 + 8 + 4;
+const NO_VALUE: u32 = u32::MAX;
+
+impl<V: ValueCodec> Trie<V> {
+    /// Writes the trie in the layout read by [`MappedTrie`].
+    pub fn write_mapped<W: Write>(&self, w: W) -> io::Result<()> {
+        let mut w = BufWriter::new(w);
+        let too_big = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "trie too large for the mapped format",
+            )
+        };
+
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([&self.root]);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            queue.extend(node.children().map(|(_, child)| child));
+        }
+        let node_count = u32::try_from(order.len()).map_err(|_| too_big())?;
+
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION, 0, 0, 0])?;
+        w.write_all(&node_count.to_le_bytes())?;
+        w.write_all(&[0; 4])?;
+
+        // children are numbered in the same order they were queued above
+        let mut next_index = 1u32;
+        let mut value_offset = 0u64;
+        for node in &order {
+            for nibble in 0..16 {
+                let index = if node.child(nibble).is_some() {
+                    next_index += 1;
+                    next_index - 1
+                } else {
+                    0
+                };
+                w.write_all(&index.to_le_bytes())?;
+            }
+            let value_len = match &node.value {
+                Some(value) => {
+                    let len = value.encode().len();
+                    u32::try_from(len)
+                        .ok()
+                        .filter(|&l| l != NO_VALUE)
+                        .ok_or_else(too_big)?
+                }
+                None => NO_VALUE,
+            };
+            w.write_all(&value_offset.to_le_bytes())?;
+            w.write_all(&value_len.to_le_bytes())?;
+            if value_len != NO_VALUE {
+                value_offset += value_len as u64;
+            }
+        }
+        for value in order.iter().filter_map(|node| node.value.as_ref()) {
+            w.write_all(&value.encode())?;
+        }
+        w.flush()
+    }
+}
+
+/// A read-only trie answered directly from bytes written by
+/// [`Trie::write_mapped`], without building any nodes.
+///
+/// `B` is any byte container; with the `memmap2` feature,
+/// [`MappedTrie::open`] maps a file so lookups read straight from the page
+/// cache. Values come back as their raw [`ValueCodec`] encoding.
+///
+/// ```
+/// use trie::{MappedTrie, Trie};
+///
+/// let mut trie = Trie::new();
+/// trie.insert_lossy("a1", "one".to_string());
+/// trie.insert_lossy("a1f", "two".to_string());
+/// let mut bytes = Vec::new();
+/// trie.write_mapped(&mut bytes).unwrap();
+///
+/// let mapped = MappedTrie::from_bytes(bytes).unwrap();
+/// assert_eq!(mapped.get("a1f").unwrap(), Some(&b"two"[..]));
+/// assert_eq!(mapped.get("a").unwrap(), None);
+/// let keys: Vec<String> = mapped.iter_prefix("a").map(|e| e.unwrap().0).collect();
+/// assert_eq!(keys, ["a1", "a1f"]);
+/// ```
+pub struct MappedTrie<B> {
+    data: B,
+    node_count: u32,
+}
+
+#[cfg(feature = "memmap2")]
+impl MappedTrie<memmap2::Mmap> {
+    /// Maps the file at `path` and checks its header.
+    ///
+    /// The file must not be modified while it is mapped.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self, SnapshotError> {
+        let file = std::fs::File::open(path)?;
+        // Safety: the mapping is only ever read, and every read is bounds-checked against
+        // the mapped length. Like any mmap this relies on the file not being truncated or
+        // rewritten underneath us.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Self::from_bytes(mmap)
+    }
+}
+
+impl<B: AsRef<[u8]>> MappedTrie<B> {
+    /// Wraps bytes in the mapped layout, checking the header and that every
+    /// node record is present.
+    pub fn from_bytes(data: B) -> Result<Self, SnapshotError> {
+        let bytes = data.as_ref();
+        if bytes.len() < HEADER_LEN {
+            return Err(if bytes.len() >= 4 && &bytes[..4] != MAGIC {
+                SnapshotError::BadMagic
+            } else {
+                SnapshotError::Truncated
+            });
+        }
+        if &bytes[..4] != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        if bytes[4] != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(bytes[4]));
+        }
+        let node_count = read_u32(bytes, 8)?;
+        if node_count == 0 {
+            return Err(SnapshotError::Corrupt("no root node"));
+        }
+        let records_end = (node_count as u64)
+            .checked_mul(RECORD_LEN as u64)
+            .and_then(|n| n.checked_add(HEADER_LEN as u64))
+            .ok_or(SnapshotError::Corrupt("node count overflows"))?;
+        if records_end > bytes.len() as u64 {
+            return Err(SnapshotError::Truncated);
+        }
+        Ok(Self { data, node_count })
+    }
+
+    /// Returns the encoded value stored under `hex_key`, if any.
+    pub fn get(&self, hex_key: &str) -> Result<Option<&[u8]>, SnapshotError> {
+        let mut index = 0;
+        for nibble in hex_to_nibbles(hex_key) {
+            match self.child(index, nibble)? {
+                Some(child) => index = child,
+                None => return Ok(None),
+            }
+        }
+        self.value(index)
+    }
+
+    /// Returns an iterator over the entries whose key starts with `prefix`,
+    /// in key order, with values in their encoded form.
+    ///
+    /// If the file turns out to be corrupt the iterator yields one error and
+    /// stops.
+    pub fn iter_prefix(&self, prefix: &str) -> MappedIter<'_, B> {
+        let mut path = Vec::new();
+        let mut index = Some(0);
+        let mut error = None;
+        for nibble in hex_to_nibbles(prefix) {
+            path.push(nibble as u8);
+            match index.map(|i| self.child(i, nibble)) {
+                Some(Ok(child)) => index = child,
+                Some(Err(err)) => {
+                    error = Some(err);
+                    break;
+                }
+                None => break,
+            }
+        }
+        let stack = match (&error, index) {
+            (None, Some(index)) => vec![(index, 0)],
+            _ => Vec::new(),
+        };
+        MappedIter {
+            trie: self,
+            stack,
+            path,
+            error,
+        }
+    }
+
+    fn record(&self, index: u32) -> Result<usize, SnapshotError> {
+        if index >= self.node_count {
+            return Err(SnapshotError::Corrupt("node index out of range"));
+        }
+        Ok(HEADER_LEN + index as usize * RECORD_LEN)
+    }
+
+    fn child(&self, index: u32, nibble: usize) -> Result<Option<u32>, SnapshotError> {
+        let child = read_u32(self.data.as_ref(), self.record(index)? + nibble * 4)?;
+        match child {
+            0 => Ok(None),
+            child if child <= index => Err(SnapshotError::Corrupt("child index does not increase")),
+            child => self.record(child).map(|_| Some(child)),
+        }
+    }
+
+    fn value(&self, index: u32) -> Result<Option<&[u8]>, SnapshotError> {
+        let bytes = self.data.as_ref();
+        let record = self.record(index)?;
+        let len = read_u32(bytes, record + 16 * 4 + 8)?;
+        if len == NO_VALUE {
+            return Ok(None);
+        }
+        let offset = u64::from_le_bytes(
+            bytes[record + 16 * 4..record + 16 * 4 + 8]
+                .try_into()
+                .unwrap(),
+        );
+        let blob = HEADER_LEN as u64 + self.node_count as u64 * RECORD_LEN as u64;
+        let start = blob.checked_add(offset);
+        let end = start.and_then(|s| s.checked_add(len as u64));
+        match (start, end) {
+            (Some(start), Some(end)) if end <= bytes.len() as u64 => {
+                Ok(Some(&bytes[start as usize..end as usize]))
+            }
+            _ => Err(SnapshotError::Corrupt("value out of range")),
+        }
+    }
+}
+
+/// An iterator over entries of a [`MappedTrie`] in key order.
+///
+/// Returned by [`MappedTrie::iter_prefix`].
+pub struct MappedIter<'a, B> {
+    trie: &'a MappedTrie<B>,
+    // node index plus the same cursor encoding as the in-memory iterator
+    stack: Vec<(u32, usize)>,
+    path: Vec<u8>,
+    error: Option<SnapshotError>,
+}
+
+impl<'a, B: AsRef<[u8]>> Iterator for MappedIter<'a, B> {
+    type Item = Result<(String, &'a [u8]), SnapshotError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+        match self.step() {
+            Ok(entry) => entry.map(Ok),
+            Err(err) => {
+                self.stack.clear();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<'a, B: AsRef<[u8]>> MappedIter<'a, B> {
+    fn step(&mut self) -> Result<Option<(String, &'a [u8])>, SnapshotError> {
+        let trie = self.trie;
+        while let Some((index, cursor)) = self.stack.last_mut() {
+            let index = *index;
+            if *cursor == 0 {
+                *cursor = 1;
+                if let Some(value) = trie.value(index)? {
+                    return Ok(Some((nibbles_to_hex(&self.path), value)));
+                }
+                continue;
+            }
+            let mut next = None;
+            for nibble in *cursor - 1..16 {
+                if let Some(child) = trie.child(index, nibble)? {
+                    next = Some((nibble, child));
+                    break;
+                }
+            }
+            match next {
+                Some((nibble, child)) => {
+                    *cursor = nibble + 2;
+                    self.path.push(nibble as u8);
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    if !self.stack.is_empty() {
+                        self.path.pop();
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, SnapshotError> {
+    let raw = bytes
+        .get(offset..offset + 4)
+        .ok_or(SnapshotError::Truncated)?;
+    Ok(u32::from_le_bytes(raw.try_into().unwrap()))
+}
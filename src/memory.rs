@@ -0,0 +1,183 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// Values that can report the heap memory they own, for
+/// [`Trie::memory_usage`].
+///
+/// Only memory behind the value counts: the value itself is stored inline in
+/// a node and is counted with it. Capacity counts rather than length, so a
+/// `String` with room to spare reports the room too.
+pub trait HeapSize {
+    /// Returns the number of heap bytes owned by the value.
+    fn heap_bytes(&self) -> usize;
+}
+
+macro_rules! no_heap {
+    ($($t:ty),*) => {$(
+        impl HeapSize for $t {
+            fn heap_bytes(&self) -> usize {
+                0
+            }
+        }
+    )*};
+}
+
+no_heap!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    &str
+);
+
+impl HeapSize for String {
+    fn heap_bytes(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(T::heap_bytes).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_bytes(&self) -> usize {
+        size_of::<T>() + (**self).heap_bytes()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_bytes(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_bytes)
+    }
+}
+
+/// An estimate of the memory held by a [`Trie`], from
+/// [`Trie::memory_usage`].
+///
+/// Allocator overhead isn't known and isn't counted, so the process will
+/// use somewhat more than `total_bytes`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Every byte counted below, plus the `Trie` struct itself.
+    pub total_bytes: usize,
+    /// Nodes in the trie, including the root.
+    pub node_count: usize,
+    /// The boxed node structs, which hold the values inline. The root is
+    /// part of the `Trie` struct instead.
+    pub node_bytes: usize,
+    /// Child vectors, counting their spare capacity.
+    pub children_bytes: usize,
+    /// The part of `children_bytes` that is spare capacity, which
+    /// [`Trie::compact`] gives back.
+    pub spare_children_bytes: usize,
+    /// Heap memory owned by the values, as reported by [`HeapSize`].
+    pub value_bytes: usize,
+    /// Cached Merkle hashes.
+    pub hash_bytes: usize,
+    /// Spare nodes kept in the node pool.
+    pub pool_bytes: usize,
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} in {} nodes: nodes {}, children {} ({} spare), values {}, hashes {}, pool {}",
+            Bytes(self.total_bytes),
+            self.node_count,
+            Bytes(self.node_bytes),
+            Bytes(self.children_bytes),
+            Bytes(self.spare_children_bytes),
+            Bytes(self.value_bytes),
+            Bytes(self.hash_bytes),
+            Bytes(self.pool_bytes),
+        )
+    }
+}
+
+// A byte count in the largest binary unit that keeps it at 1 or more.
+struct Bytes(usize);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+        let mut size = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        write!(f, "{:.1} {}", size, UNITS[unit])
+    }
+}
+
+impl<V: HeapSize, const N: usize> Trie<V, N> {
+    /// Estimates the memory held by the trie, walking every node.
+    ///
+    /// Vectors and strings count their capacity, not their length, so room
+    /// left behind by deletes shows up until [`Trie::compact`] trims it.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for n in 0..4096u32 {
+    ///     trie.insert_int(n, format!("value {}", n));
+    /// }
+    /// let full = trie.memory_usage();
+    /// trie.remove_prefix("000008");
+    /// trie.compact();
+    /// let after = trie.memory_usage();
+    /// assert!(after.total_bytes < full.total_bytes);
+    /// assert_eq!(after.spare_children_bytes, 0);
+    /// println!("{}", after);
+    /// ```
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            pool_bytes: self.pool.bytes(),
+            ..MemoryUsage::default()
+        };
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            usage.node_count += 1;
+            usage.children_bytes += node.children_capacity_bytes();
+            usage.spare_children_bytes +=
+                node.children_capacity_bytes() - node.child_count() * size_of::<Box<Node<V, N>>>();
+            usage.value_bytes += node.value.heap_bytes();
+            usage.hash_bytes += node.hash.get().map_or(0, |hash| size_of_val(&**hash));
+            stack.extend(node.children().map(|(_, child)| child));
+        }
+        // Every node but the root is boxed; the root is inside the Trie struct.
+        usage.node_bytes = (usage.node_count - 1) * size_of::<Node<V, N>>();
+        usage.total_bytes = size_of::<Self>()
+            + usage.node_bytes
+            + usage.children_bytes
+            + usage.value_bytes
+            + usage.hash_bytes
+            + usage.pool_bytes;
+        usage
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::key::{NIBBLE_TO_HEX, hex_to_nibbles};
+use crate::observe::{TrieObserver, notify_inserted};
+use crate::trie::Trie;
+
+/// A conflict resolver for [`MergePolicy::WithFn`], called as
+/// `f(key, self_value, other_value)`.
+pub type MergeFn<V> = Box<dyn FnMut(&str, V, V) -> V>;
+
+/// How [`Trie::merge`] resolves a key present in both tries.
+pub enum MergePolicy<V> {
+    /// Keep the value already in `self`.
+    KeepSelf,
+    /// Replace it with the value from `other`.
+    KeepOther,
+    /// Combine both with a function.
+    WithFn(MergeFn<V>),
+}
+
+impl<V> MergePolicy<V> {
+    fn resolve(&mut self, key: &str, ours: V, theirs: V) -> V {
+        match self {
+            MergePolicy::KeepSelf => ours,
+            MergePolicy::KeepOther => theirs,
+            MergePolicy::WithFn(f) => f(key, ours, theirs),
+        }
+    }
+
+    // `resolve`, telling `observer` what became of the key. A function's result is reported
+    // as a new value, since the function took the old one.
+    fn resolve_observed(
+        &mut self,
+        observer: &mut dyn TrieObserver<V>,
+        key: &str,
+        ours: V,
+        theirs: V,
+    ) -> V {
+        match self {
+            MergePolicy::KeepSelf => ours,
+            MergePolicy::KeepOther => {
+                observer.on_insert(key, Some(&ours), &theirs);
+                theirs
+            }
+            MergePolicy::WithFn(f) => {
+                observer.on_delete(key, &ours);
+                let value = f(key, ours, theirs);
+                observer.on_insert(key, None, &value);
+                value
+            }
+        }
+    }
+}
+
+impl<V> Trie<V> {
+    /// Moves every entry of `other` into `self`, using `policy` for keys
+    /// present in both.
+    ///
+    /// Wherever `self` has no child for a branch of `other`, the whole
+    /// subtree is grafted over as-is; the walk only goes deeper where both
+    /// tries have nodes.
+    pub fn merge(&mut self, other: Trie<V>, mut policy: MergePolicy<V>) {
+        let Trie {
+            root,
+            pool,
+            snapshots,
+            observer,
+            ..
+        } = self;
+        let mut conflicts = 0;
+        let mut key = String::new();
+        // Pairs of nodes at the same key still to merge, with the length of the key and its
+        // last digit, the lowest key last so the resolver sees keys in order.
+        let mut stack = vec![(root, other.root, 0_usize, '0')];
+        while let Some((ours, mut theirs, depth, digit)) = stack.pop() {
+            key.truncate(depth.saturating_sub(1));
+            if depth > 0 {
+                key.push(digit);
+            }
+            ours.invalidate();
+            // the path is only built when a snapshot or the observer needs it
+            let mut path = Vec::new();
+            if snapshots.recording() || observer.get().is_some() {
+                path.extend(hex_to_nibbles(&key).map(|n| n as u8));
+            }
+            if theirs.value.is_some() {
+                snapshots.save_value(&path, ours.value.as_ref());
+            }
+            ours.value = match (ours.value.take(), theirs.value.take(), observer.get()) {
+                (Some(a), Some(b), observer) => {
+                    conflicts += 1;
+                    Some(match observer {
+                        Some(observer) => policy.resolve_observed(observer, &key, a, b),
+                        None => policy.resolve(&key, a, b),
+                    })
+                }
+                (None, Some(b), Some(observer)) => {
+                    observer.on_insert(&key, None, &b);
+                    Some(b)
+                }
+                (a, b, _) => a.or(b),
+            };
+            let mut shared = Vec::new();
+            for nibble in 0..16 {
+                let Some(child) = theirs.take_child(nibble) else {
+                    continue;
+                };
+                match ours.child(nibble) {
+                    Some(_) => shared.push((nibble, child)),
+                    None => {
+                        path.push(nibble as u8);
+                        snapshots.save_subtree(&path, None);
+                        if let Some(observer) = observer.get() {
+                            notify_inserted(observer, &child, path.clone());
+                        }
+                        path.pop();
+                        pool.moved_in(&child);
+                        ours.set_child(nibble, child);
+                    }
+                }
+            }
+            let mut shared = shared.into_iter().peekable();
+            let from = stack.len();
+            for (nibble, existing) in ours.children_mut() {
+                if let Some((_, child)) = shared.next_if(|&(i, _)| i == nibble) {
+                    let digit = NIBBLE_TO_HEX[nibble] as char;
+                    stack.push((existing, *child, depth + 1, digit));
+                }
+            }
+            stack[from..].reverse();
+        }
+        self.len = self.len + other.len - conflicts;
+    }
+
+    /// Moves every entry of `other` into `self`, leaving `other` empty.
+    ///
+    /// Like `BTreeMap::append`, values from `other` win on conflicts.
+    pub fn append(&mut self, other: &mut Trie<V>) {
+        self.merge(core::mem::take(other), MergePolicy::KeepOther);
+    }
+}
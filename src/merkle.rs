@@ -0,0 +1,152 @@
+// Merkle hashing.
+//
+// Every node hashes to H(value part || 16 child slots). The value part is a single 0 byte for
+// a node without a value, or a 1 byte, the u32 LE length of the encoded value and the value
+// bytes. Each child slot is the child's 32-byte hash, or EMPTY_SLOT when there is no child, so
+// a node commits to exactly which slots are filled. Since the trie's shape only depends on its
+// keys, the root hash only depends on the contents and not on the order they went in.
+//
+// Every node caches its subtree hash once it has been computed. Mutations clear the cache on
+// each node they walk through, which is exactly the path from the root to the change, so the
+// next root_hash only rehashes that spine and picks up cached hashes for everything beside
+// it. The cache remembers which hasher filled it; asking for a root under a different hasher
+// computes it without touching the cached values.
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::TypeId;
+#[cfg(not(feature = "std"))]
+use core::cell::OnceCell;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+use crate::codec::ValueCodec;
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// The hash standing in for a child slot with no child.
+pub const EMPTY_SLOT: [u8; 32] = [0; 32];
+
+// Boxed so nodes that never get hashed only pay for a pointer. Without std there is no lock to
+// fill it through `&self` from several threads, so a trie there is `Send` but not `Sync`.
+#[cfg(feature = "std")]
+pub(crate) type HashCache = OnceLock<Box<(TypeId, [u8; 32])>>;
+#[cfg(not(feature = "std"))]
+pub(crate) type HashCache = OnceCell<Box<(TypeId, [u8; 32])>>;
+
+/// A 256-bit hash function used to build Merkle commitments over a trie.
+pub trait TrieHasher: 'static {
+    /// Hashes `data` in one go.
+    fn hash(data: &[u8]) -> [u8; 32];
+}
+
+/// SHA-256 from the `sha2` crate.
+#[cfg(feature = "sha2")]
+pub struct Sha256Hasher;
+
+#[cfg(feature = "sha2")]
+impl TrieHasher for Sha256Hasher {
+    fn hash(data: &[u8]) -> [u8; 32] {
+        use sha2::Digest;
+        sha2::Sha256::digest(data).into()
+    }
+}
+
+/// Keccak-256 (the pre-standard SHA-3 variant Ethereum uses) from the `sha3`
+/// crate.
+#[cfg(feature = "keccak")]
+pub struct Keccak256Hasher;
+
+#[cfg(feature = "keccak")]
+impl TrieHasher for Keccak256Hasher {
+    fn hash(data: &[u8]) -> [u8; 32] {
+        use sha3::Digest;
+        sha3::Keccak256::digest(data).into()
+    }
+}
+
+pub(crate) fn node_hash<H: TrieHasher>(value: Option<&[u8]>, slots: &[[u8; 32]; 16]) -> [u8; 32] {
+    let value_len = value.map_or(0, |v| 4 + v.len());
+    let mut buf = Vec::with_capacity(1 + value_len + 16 * 32);
+    match value {
+        Some(bytes) => {
+            buf.push(1);
+            buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            buf.extend_from_slice(bytes);
+        }
+        None => buf.push(0),
+    }
+    for slot in slots {
+        buf.extend_from_slice(slot);
+    }
+    H::hash(&buf)
+}
+
+fn cached<H: TrieHasher, V>(node: &Node<V>) -> Option<[u8; 32]> {
+    match node.hash.get().map(|cache| **cache) {
+        Some((hasher, hash)) if hasher == TypeId::of::<H>() => Some(hash),
+        _ => None,
+    }
+}
+
+// Hashes a whole subtree with an explicit post-order stack. Each frame collects its
+// children's hashes as they finish and is hashed itself once its last child is done.
+// Children with a cached hash fill their slot straight away instead of being walked.
+pub(crate) fn subtree_hash<H: TrieHasher, V: ValueCodec>(node: &Node<V>) -> [u8; 32] {
+    if let Some(hash) = cached::<H, V>(node) {
+        return hash;
+    }
+    struct Frame<'a, V> {
+        node: &'a Node<V>,
+        // slot this node fills in its parent
+        slot: usize,
+        next: usize,
+        slots: [[u8; 32]; 16],
+    }
+    let frame = |node, slot| Frame {
+        node,
+        slot,
+        next: 0,
+        slots: [EMPTY_SLOT; 16],
+    };
+
+    let mut stack = vec![frame(node, 0)];
+    loop {
+        let top = stack.last_mut().unwrap();
+        let child = top.node.next_child(top.next);
+        if let Some((i, child)) = child {
+            top.next = i + 1;
+            match cached::<H, V>(child) {
+                Some(hash) => top.slots[i] = hash,
+                None => stack.push(frame(child, i)),
+            }
+            continue;
+        }
+        let done = stack.pop().unwrap();
+        let hash = node_hash::<H>(
+            done.node.value.as_ref().map(|v| v.encode()).as_deref(),
+            &done.slots,
+        );
+        // a cache already filled by another hasher stays as it is
+        let _ = done.node.hash.set(Box::new((TypeId::of::<H>(), hash)));
+        match stack.last_mut() {
+            Some(parent) => parent.slots[done.slot] = hash,
+            None => return hash,
+        }
+    }
+}
+
+impl<V: ValueCodec> Trie<V> {
+    /// Returns the Merkle root of the trie under the hasher `H`.
+    ///
+    /// Each node's hash commits to its value bytes (via [`ValueCodec`]) and
+    /// to the hashes of all 16 child slots, with [`EMPTY_SLOT`] for missing
+    /// children. Tries with the same contents have the same root.
+    ///
+    /// Node hashes are cached between calls, so after a few changes only the
+    /// nodes on the paths to those changes are hashed again.
+    pub fn root_hash<H: TrieHasher>(&self) -> [u8; 32] {
+        subtree_hash::<H, V>(&self.root)
+    }
+}
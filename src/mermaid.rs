@@ -0,0 +1,86 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::key::nibbles_to_hex;
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// Node budget used by [`Trie::to_mermaid`]; GitHub refuses to render much
+/// larger diagrams.
+pub const DEFAULT_MERMAID_NODES: usize = 200;
+
+impl<V: fmt::Display> Trie<V> {
+    /// Renders the trie as a Mermaid `graph TD` flowchart, truncated after
+    /// [`DEFAULT_MERMAID_NODES`] nodes.
+    pub fn to_mermaid(&self) -> String {
+        self.to_mermaid_with_budget(DEFAULT_MERMAID_NODES)
+    }
+
+    /// Renders the trie as a Mermaid `graph TD` flowchart with at most
+    /// `max_nodes` trie nodes.
+    ///
+    /// Node IDs come from the hex path (`n` for the root, `na1` for `"a1"`),
+    /// so they stay stable as the trie changes. Edges are labeled with their
+    /// nibble and value nodes show `nibble = value`, just like the `Display`
+    /// output. Once the budget runs out a single `...` node marks where the
+    /// diagram was cut.
+    pub fn to_mermaid_with_budget(&self, max_nodes: usize) -> String {
+        let mut out = String::from("graph TD\n");
+        let mut drawn = 0;
+        let mut stack: Vec<(&Node<V>, Vec<u8>)> = vec![(&self.root, Vec::new())];
+        while let Some((node, path)) = stack.pop() {
+            let id = nibbles_to_hex(&path);
+            if drawn == max_nodes {
+                if !path.is_empty() {
+                    let parent = nibbles_to_hex(&path[..path.len() - 1]);
+                    writeln!(out, "    n{} --> truncated[\"...\"]", parent).unwrap();
+                } else {
+                    out.push_str("    truncated[\"...\"]\n");
+                }
+                break;
+            }
+            drawn += 1;
+
+            let mut label = match path.last() {
+                Some(_) => nibbles_to_hex(&path[path.len() - 1..]),
+                None => "(root)".to_string(),
+            };
+            if let Some(value) = &node.value {
+                write!(label, " = {}", value).unwrap();
+            }
+            writeln!(out, "    n{}[\"{}\"]", id, sanitize(&label)).unwrap();
+            if let Some(&nibble) = path.last() {
+                let parent = nibbles_to_hex(&path[..path.len() - 1]);
+                let edge = nibbles_to_hex(&[nibble]);
+                writeln!(out, "    n{} -->|{}| n{}", parent, edge, id).unwrap();
+            }
+
+            for (nibble, child) in node.children().rev() {
+                let mut child_path = path.clone();
+                child_path.push(nibble as u8);
+                stack.push((child, child_path));
+            }
+        }
+        out
+    }
+}
+
+// Mermaid labels can't contain raw quotes or brackets, so those (and angle brackets, which it
+// treats as HTML) become entity codes, and line breaks become spaces.
+fn sanitize(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("#quot;"),
+            '[' => out.push_str("#91;"),
+            ']' => out.push_str("#93;"),
+            '<' => out.push_str("#lt;"),
+            '>' => out.push_str("#gt;"),
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,204 @@
+// Counters a trie keeps about its own use. They're atomics so that `get` can count through
+// `&self` while the trie stays shareable between threads, as `ConcurrentTrie` needs; each
+// update is a relaxed add on an uncontended counter.
+
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::trie::Trie;
+
+/// Counts of what a [`Trie`] has done, from [`Trie::metrics`].
+///
+/// Every way of reading, writing or removing one key counts: lookups by
+/// [`Trie::get_lossy`] and the lookups for integer, byte and
+/// [`Nibbles`](crate::Nibbles) keys; inserts by [`Trie::insert_lossy`],
+/// [`Trie::insert_nibbles`], [`Trie::update`] and
+/// [`Trie::compare_and_swap`] when they store a value; deletes by
+/// [`Trie::delete_lossy`], [`Trie::pop_first`] and the like, and by
+/// `update` or `compare_and_swap` when they empty a key. So does everything
+/// built on them, such as the `try_*` methods and [`Trie::apply_batch`].
+/// [`Trie::get_or_insert_with`] and [`Trie::entry`] count as a lookup, and
+/// as an insert too when they store a value; a [`CursorMut`] counts like
+/// the methods it mirrors. Methods that work on many keys at once, such as
+/// [`Trie::retain`], [`Trie::merge`] or [`Trie::get_many`], don't count
+/// per key.
+///
+/// The node counts cover every method, those included: a node is created
+/// when the trie gains it and pruned when it's cut off, by a delete,
+/// [`Trie::clear`], [`Trie::split_off`] or the like. So as long as the
+/// counters started with the trie empty, the nodes besides the root number
+/// `nodes_created() - nodes_pruned()`.
+///
+/// The counters start at zero with the trie, and a clone starts its own.
+///
+/// [`CursorMut`]: crate::CursorMut
+///
+/// ```
+/// let mut trie = trie::Trie::new();
+/// trie.insert_lossy("a1", 1);
+/// trie.insert_lossy("a1", 2);
+/// trie.get_lossy("a1");
+/// trie.get_lossy("b");
+/// trie.delete_lossy("a1");
+/// let metrics = trie.metrics();
+/// assert_eq!((metrics.inserts(), metrics.overwrites()), (2, 1));
+/// assert_eq!((metrics.lookups(), metrics.hits(), metrics.misses()), (2, 1, 1));
+/// assert_eq!((metrics.nodes_created(), metrics.nodes_pruned()), (2, 2));
+/// assert_eq!(metrics.max_depth(), 2);
+/// ```
+#[derive(Default)]
+pub struct Metrics {
+    lookups: AtomicU64,
+    hits: AtomicU64,
+    inserts: AtomicU64,
+    overwrites: AtomicU64,
+    deletes: AtomicU64,
+    nodes_created: AtomicU64,
+    nodes_pruned: AtomicU64,
+    max_depth: AtomicU64,
+}
+
+impl Metrics {
+    /// Lookups of one key.
+    pub fn lookups(&self) -> u64 {
+        self.lookups.load(Ordering::Relaxed)
+    }
+
+    /// Lookups that found a value.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Lookups that didn't find a value.
+    pub fn misses(&self) -> u64 {
+        self.lookups() - self.hits()
+    }
+
+    /// Values stored under one key, whether the key was new or not.
+    pub fn inserts(&self) -> u64 {
+        self.inserts.load(Ordering::Relaxed)
+    }
+
+    /// Inserts that replaced a value.
+    pub fn overwrites(&self) -> u64 {
+        self.overwrites.load(Ordering::Relaxed)
+    }
+
+    /// Values removed from one key.
+    pub fn deletes(&self) -> u64 {
+        self.deletes.load(Ordering::Relaxed)
+    }
+
+    /// Nodes added to the trie.
+    pub fn nodes_created(&self) -> u64 {
+        self.nodes_created.load(Ordering::Relaxed)
+    }
+
+    /// Nodes cut off from the trie.
+    pub fn nodes_pruned(&self) -> u64 {
+        self.nodes_pruned.load(Ordering::Relaxed)
+    }
+
+    /// The length in symbols of the longest key inserted, which is the
+    /// depth of the deepest node an insert has reached.
+    pub fn max_depth(&self) -> u64 {
+        self.max_depth.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_lookup(&self, hit: bool) {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_insert(&self, overwrite: bool, depth: usize) {
+        self.inserts.fetch_add(1, Ordering::Relaxed);
+        if overwrite {
+            self.overwrites.fetch_add(1, Ordering::Relaxed);
+        }
+        self.max_depth.fetch_max(depth as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_delete(&self) {
+        self.deletes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_nodes_created(&self, count: usize) {
+        self.nodes_created
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_nodes_pruned(&self, count: usize) {
+        self.nodes_pruned.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        for counter in [
+            &self.lookups,
+            &self.hits,
+            &self.inserts,
+            &self.overwrites,
+            &self.deletes,
+            &self.nodes_created,
+            &self.nodes_pruned,
+            &self.max_depth,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metrics")
+            .field("lookups", &self.lookups())
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .field("inserts", &self.inserts())
+            .field("overwrites", &self.overwrites())
+            .field("deletes", &self.deletes())
+            .field("nodes_created", &self.nodes_created())
+            .field("nodes_pruned", &self.nodes_pruned())
+            .field("max_depth", &self.max_depth())
+            .finish()
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "lookups: {} ({} hits, {} misses)",
+            self.lookups(),
+            self.hits(),
+            self.misses()
+        )?;
+        writeln!(
+            f,
+            "inserts: {} ({} overwrites), deletes: {}",
+            self.inserts(),
+            self.overwrites(),
+            self.deletes()
+        )?;
+        write!(
+            f,
+            "nodes created: {}, pruned: {}, max depth: {}",
+            self.nodes_created(),
+            self.nodes_pruned(),
+            self.max_depth()
+        )
+    }
+}
+
+impl<V, const N: usize> Trie<V, N> {
+    /// The trie's counters of lookups, writes and nodes.
+    pub fn metrics(&self) -> &Metrics {
+        &self.pool.metrics
+    }
+
+    /// Sets every counter back to zero.
+    pub fn reset_metrics(&mut self) {
+        self.pool.metrics.reset();
+    }
+}
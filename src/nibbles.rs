@@ -0,0 +1,151 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Bound, Deref, RangeBounds};
+use core::str::FromStr;
+
+use crate::iter::Walk;
+use crate::key::{KeyError, check_key, hex_to_nibbles, nibbles_to_hex};
+use crate::trie::Trie;
+
+/// A key as a checked sequence of nibbles, each below 16.
+///
+/// This is the path a key takes through a [`Trie`], so methods taking a
+/// `&Nibbles` skip hex parsing and can't be handed an out-of-range nibble.
+/// It derefs to `[u8]` for reading, indexing and iterating.
+///
+/// ```
+/// let key = trie::Nibbles::from_hex("A1f").unwrap();
+/// assert_eq!(&key[..], [0xa, 0x1, 0xf]);
+/// assert_eq!(key.to_hex(), "a1f");
+/// assert_eq!(key.slice(..2), trie::Nibbles::from_bytes(&[0xa1]));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Nibbles(pub(crate) Vec<u8>);
+
+impl Nibbles {
+    /// Parses a hex key, failing on any character that isn't a hex digit.
+    pub fn from_hex(hex_key: &str) -> Result<Self, KeyError> {
+        check_key::<16>(hex_key)?;
+        Ok(Self(hex_to_nibbles(hex_key).map(|n| n as u8).collect()))
+    }
+
+    /// Splits each byte into its high and low nibble.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self(bytes.iter().flat_map(|&b| [b >> 4, b & 0xf]).collect())
+    }
+
+    /// Wraps nibbles that are already split out, failing on any value of 16
+    /// or more.
+    pub fn from_slice_checked(nibbles: &[u8]) -> Result<Self, KeyError> {
+        match nibbles.iter().position(|&n| n >= 16) {
+            Some(index) => Err(KeyError::InvalidNibble {
+                index,
+                found: nibbles[index],
+            }),
+            None => Ok(Self(nibbles.to_vec())),
+        }
+    }
+
+    /// Returns the key as lowercase hex.
+    pub fn to_hex(&self) -> String {
+        nibbles_to_hex(&self.0)
+    }
+
+    /// Returns the nibbles in `range` as a key of their own.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Nibbles {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        Self(self.0[bounds].to_vec())
+    }
+
+    /// Returns this key with `nibble` appended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nibble` is 16 or larger.
+    pub fn join(mut self, nibble: u8) -> Nibbles {
+        assert!(nibble < 16, "nibble {} is out of range", nibble);
+        self.0.push(nibble);
+        self
+    }
+
+    /// Returns the nibbles as a plain vector.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for Nibbles {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromStr for Nibbles {
+    type Err = KeyError;
+
+    fn from_str(s: &str) -> Result<Self, KeyError> {
+        Self::from_hex(s)
+    }
+}
+
+impl fmt::Display for Nibbles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+// Lets a checked key go anywhere a raw nibble path is taken, like Trie::insert_nibbles.
+impl<'a> IntoIterator for &'a Nibbles {
+    type Item = usize;
+    type IntoIter = core::iter::Map<core::slice::Iter<'a, u8>, fn(&u8) -> usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().map(|&n| n as usize)
+    }
+}
+
+impl<V> Trie<V> {
+    /// Returns the value stored under `key`, if any.
+    pub fn get_nibbles(&self, key: &Nibbles) -> Option<&V> {
+        self.get_path(key)
+    }
+
+    /// Returns `true` if a value is stored under `key`.
+    pub fn contains_nibbles(&self, key: &Nibbles) -> bool {
+        self.get_nibbles(key).is_some()
+    }
+
+    /// Removes the value stored under `key` and returns it.
+    pub fn delete_nibbles(&mut self, key: &Nibbles) -> Option<V> {
+        self.delete_path(key)
+    }
+
+    /// Returns an iterator over all entries in key order, with keys as
+    /// [`Nibbles`] rather than hex strings.
+    pub fn iter_nibbles(&self) -> NibblesIter<'_, V> {
+        NibblesIter {
+            walk: Walk::new(&self.root, Vec::new()),
+        }
+    }
+}
+
+/// An iterator over the entries of a [`Trie`] in key order, with
+/// [`Nibbles`] keys.
+///
+/// Returned by [`Trie::iter_nibbles`].
+pub struct NibblesIter<'a, V> {
+    walk: Walk<'a, V>,
+}
+
+impl<'a, V> Iterator for NibblesIter<'a, V> {
+    type Item = (Nibbles, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.walk.next_value()?;
+        Some((Nibbles(self.walk.path.clone()), value))
+    }
+}
@@ -0,0 +1,910 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::key::{Fanout, NIBBLE_TO_HEX, key_symbols};
+use crate::merkle::HashCache;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::validate::ViolationKind;
+
+// Children are stored densely: `children` holds only the ones present, ordered by symbol, and
+// for fanouts up to 16 bit `i` of `bitmap` says whether symbol `i` has one. A child's place in
+// `children` is then the number of bits set below its own, so most nodes (leaves and the chains
+// of a sparse key set) pay for the children they have rather than for all N slots. 256-way nodes
+// don't fit the bitmap and binary search on `symbol`, which every child also keeps so the
+// iterators can report it. Only the accessors below touch these fields, which keeps the bitmap,
+// the vector and the symbols in step.
+pub(crate) struct Node<V, const N: usize = 16> {
+    children: Vec<Box<Node<V, N>>>,
+    bitmap: u16,
+    // This node's symbol in its parent.
+    symbol: u8,
+    pub(crate) value: Option<V>,
+    // Merkle hash of this subtree, filled in by root_hash and dropped by anything that changes
+    // the subtree. Everything that mutates a node through `&mut` has to call invalidate on it.
+    pub(crate) hash: HashCache,
+}
+
+impl<V, const N: usize> Default for Node<V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, const N: usize> Node<V, N> {
+    pub(crate) fn new() -> Self {
+        const { Fanout::<N>::BITS };
+        Self {
+            children: Vec::new(),
+            bitmap: 0,
+            symbol: 0,
+            value: None,
+            hash: HashCache::new(),
+        }
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        self.hash.take();
+    }
+
+    // Where symbol `i` sits in `children`: `Ok` if it's there, else `Err` with where it would go.
+    fn position(&self, i: usize) -> Result<usize, usize> {
+        assert!(i < N, "symbol {} is out of range for fanout {}", i, N);
+        if N <= 16 {
+            let below = (self.bitmap as u32 & ((1 << i) - 1)).count_ones() as usize;
+            if self.bitmap as u32 & (1 << i) != 0 {
+                Ok(below)
+            } else {
+                Err(below)
+            }
+        } else {
+            self.children
+                .binary_search_by_key(&i, |child| child.symbol as usize)
+        }
+    }
+
+    pub(crate) fn child(&self, i: usize) -> Option<&Node<V, N>> {
+        let at = self.position(i).ok()?;
+        Some(&self.children[at])
+    }
+
+    pub(crate) fn child_mut(&mut self, i: usize) -> Option<&mut Node<V, N>> {
+        let at = self.position(i).ok()?;
+        Some(&mut self.children[at])
+    }
+
+    // A missing child is a new node from `pool`.
+    pub(crate) fn child_or_insert(&mut self, i: usize, pool: &mut Pool<V, N>) -> &mut Node<V, N> {
+        let at = match self.position(i) {
+            Ok(at) => at,
+            Err(at) => {
+                self.insert_at(at, i, pool.take());
+                at
+            }
+        };
+        &mut self.children[at]
+    }
+
+    // Puts `child` under symbol `i`, replacing whatever was there.
+    pub(crate) fn set_child(&mut self, i: usize, mut child: Box<Node<V, N>>) {
+        match self.position(i) {
+            Ok(at) => {
+                child.symbol = i as u8;
+                self.children[at] = child;
+            }
+            Err(at) => self.insert_at(at, i, child),
+        }
+    }
+
+    fn insert_at(&mut self, at: usize, i: usize, mut child: Box<Node<V, N>>) {
+        child.symbol = i as u8;
+        self.children.insert(at, child);
+        if N <= 16 {
+            self.bitmap |= 1 << i;
+        }
+    }
+
+    // Removes the child under symbol `i`; the vector's buffer goes with the last one.
+    pub(crate) fn take_child(&mut self, i: usize) -> Option<Box<Node<V, N>>> {
+        let at = self.position(i).ok()?;
+        let child = self.children.remove(at);
+        if N <= 16 {
+            self.bitmap &= !(1 << i);
+        }
+        if self.children.is_empty() {
+            self.children = Vec::new();
+        }
+        Some(child)
+    }
+
+    // Cuts off every child at once and hands them back in symbol order.
+    fn take_children(&mut self) -> Vec<Box<Node<V, N>>> {
+        self.bitmap = 0;
+        core::mem::take(&mut self.children)
+    }
+
+    // Puts `child` back after every child there is, which its symbol must sort after.
+    fn push_child(&mut self, child: Box<Node<V, N>>) {
+        let i = child.symbol as usize;
+        self.insert_at(self.children.len(), i, child);
+    }
+
+    // The first child under symbol `from` or later.
+    pub(crate) fn next_child(&self, from: usize) -> Option<(usize, &Node<V, N>)> {
+        if from >= N {
+            return None;
+        }
+        let (Ok(at) | Err(at)) = self.position(from);
+        let child = self.children.get(at)?;
+        Some((child.symbol as usize, child))
+    }
+
+    // The last child under a symbol below `before`.
+    pub(crate) fn prev_child(&self, before: usize) -> Option<(usize, &Node<V, N>)> {
+        let at = if before >= N {
+            self.children.len()
+        } else {
+            let (Ok(at) | Err(at)) = self.position(before);
+            at
+        };
+        let child = self.children.get(at.checked_sub(1)?)?;
+        Some((child.symbol as usize, child))
+    }
+
+    pub(crate) fn children(&self) -> Children<'_, V, N> {
+        Children {
+            children: self.children.iter(),
+        }
+    }
+
+    pub(crate) fn children_mut(&mut self) -> ChildrenMut<'_, V, N> {
+        self.value_and_children_mut().1
+    }
+
+    // The value and the children borrowed apart, for walks that hand out the value while
+    // carrying on into the children.
+    pub(crate) fn value_and_children_mut(&mut self) -> (Option<&mut V>, ChildrenMut<'_, V, N>) {
+        let children = ChildrenMut {
+            children: self.children.iter_mut(),
+        };
+        (self.value.as_mut(), children)
+    }
+
+    // The child at position `at` of the ones present, with its symbol.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn child_at(&self, at: usize) -> (usize, &Node<V, N>) {
+        let child = &self.children[at];
+        (child.symbol as usize, child)
+    }
+
+    pub(crate) fn has_children(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    pub(crate) fn child_count(&self) -> usize {
+        self.children.len()
+    }
+
+    // What's wrong with this node's own child storage, for validate.
+    pub(crate) fn layout_violations(&self) -> Vec<ViolationKind> {
+        let mut found = Vec::new();
+        if N <= 16 && self.bitmap.count_ones() as usize != self.children.len() {
+            found.push(ViolationKind::ChildCount {
+                marked: self.bitmap.count_ones() as usize,
+                stored: self.children.len(),
+            });
+        }
+        let mut previous = None;
+        for child in &self.children {
+            let symbol = child.symbol as usize;
+            if symbol >= N {
+                found.push(ViolationKind::SymbolOutOfRange { symbol });
+            } else if N <= 16 && self.bitmap & (1 << symbol) == 0 {
+                found.push(ViolationKind::UnmarkedChild { symbol });
+            }
+            if previous.is_some_and(|previous| symbol <= previous) {
+                found.push(ViolationKind::ChildOrder { symbol });
+            }
+            previous = Some(symbol);
+        }
+        found
+    }
+
+    // Bytes allocated for the child vector, whether or not they're in use.
+    pub(crate) fn children_capacity_bytes(&self) -> usize {
+        self.children.capacity() * size_of::<Box<Node<V, N>>>()
+    }
+
+    // Drops the child vector's spare capacity, returning how many bytes that gave back.
+    pub(crate) fn shrink_children(&mut self) -> usize {
+        let spare = self.children.capacity() - self.children.len();
+        self.children.shrink_to_fit();
+        spare * size_of::<Box<Node<V, N>>>()
+    }
+
+    // Cuts off every child at once, handing them to `pool`.
+    pub(crate) fn recycle_children(&mut self, pool: &mut Pool<V, N>) {
+        for child in self.take_children() {
+            pool.put(child);
+        }
+    }
+
+    // A node shaped like this one, holding `value` and no children yet.
+    fn mapped<U>(&self, value: Option<U>) -> Node<U, N> {
+        Node {
+            children: Vec::with_capacity(self.children.len()),
+            bitmap: self.bitmap,
+            symbol: self.symbol,
+            value,
+            hash: HashCache::new(),
+        }
+    }
+
+    // Rebuilds this subtree with every value replaced by `f` of its path and the value, in key
+    // order, consuming the nodes as it goes. Each frame is an old node's remaining children and
+    // the new node they go into, which is hooked onto its parent once they're all done.
+    pub(crate) fn map_values<U, F>(mut self, mut f: F) -> Node<U, N>
+    where
+        F: FnMut(&[u8], V) -> U,
+    {
+        let mut path = Vec::new();
+        let value = self.value.take().map(|value| f(&path, value));
+        let root = self.mapped(value);
+        let mut stack = vec![(core::mem::take(&mut self.children).into_iter(), root)];
+        loop {
+            let (children, _) = stack.last_mut().unwrap();
+            match children.next() {
+                Some(mut child) => {
+                    path.push(child.symbol);
+                    let value = child.value.take().map(|value| f(&path, value));
+                    let node = child.mapped(value);
+                    stack.push((core::mem::take(&mut child.children).into_iter(), node));
+                }
+                None => {
+                    let (_, node) = stack.pop().unwrap();
+                    let Some((_, parent)) = stack.last_mut() else {
+                        return node;
+                    };
+                    parent.children.push(Box::new(node));
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    // Like `map_values`, but leaves this subtree as it is and gets its values by reference.
+    pub(crate) fn map_values_ref<U, F>(&self, mut f: F) -> Node<U, N>
+    where
+        F: FnMut(&[u8], &V) -> U,
+    {
+        let mut path = Vec::new();
+        let root = self.mapped(self.value.as_ref().map(|value| f(&path, value)));
+        let mut stack = vec![(self.children.iter(), root)];
+        loop {
+            let (children, _) = stack.last_mut().unwrap();
+            match children.next() {
+                Some(child) => {
+                    path.push(child.symbol);
+                    let node = child.mapped(child.value.as_ref().map(|value| f(&path, value)));
+                    stack.push((child.children.iter(), node));
+                }
+                None => {
+                    let (_, node) = stack.pop().unwrap();
+                    let Some((_, parent)) = stack.last_mut() else {
+                        return node;
+                    };
+                    parent.children.push(Box::new(node));
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    // This function takes the reference to a node and a key and value
+    // then it sets the current node to the passed in node and loops over the hex_key which is a
+    // series of nibbles (for example 0x7abf would be 7, 10, 11, 15)
+    // for each nibble we grow the trie by either getting the child node at the index of the nibble or inserting a new node
+    // once we have the last nibble we set the value of the node to the value passed in
+    // and hand back whatever value was stored there before
+    pub(crate) fn insert(&mut self, hex_key: &str, value: V, pool: &mut Pool<V, N>) -> Option<V> {
+        self.insert_nibbles(key_symbols::<N>(hex_key), value, pool)
+    }
+
+    pub(crate) fn get(&self, hex_key: &str) -> Option<&V> {
+        let mut cur = self;
+        for nibble in key_symbols::<N>(hex_key) {
+            match cur.child(nibble) {
+                Some(child) => cur = child,
+                None => return None,
+            }
+        }
+        cur.value.as_ref()
+    }
+
+    // Returns the node sitting at the end of the nibble path, whether or not it holds a value.
+    pub(crate) fn find(&self, nibbles: impl IntoIterator<Item = usize>) -> Option<&Node<V, N>> {
+        let mut cur = self;
+        for nibble in nibbles {
+            cur = cur.child(nibble)?;
+        }
+        Some(cur)
+    }
+
+    // Membership only needs to know the final node has a value, so this never borrows it.
+    pub(crate) fn contains_key(&self, hex_key: &str) -> bool {
+        let mut cur = self;
+        for nibble in key_symbols::<N>(hex_key) {
+            match cur.child(nibble) {
+                Some(child) => cur = child,
+                None => return false,
+            }
+        }
+        cur.value.is_some()
+    }
+
+    // Same walk as get, just through mutable borrows so the value can be changed in place.
+    // The caller may change the value, so every node on the way down loses its cached hash.
+    pub(crate) fn get_mut(&mut self, hex_key: &str) -> Option<&mut V> {
+        let mut cur = self;
+        for nibble in key_symbols::<N>(hex_key) {
+            cur.invalidate();
+            match cur.child_mut(nibble) {
+                Some(child) => cur = child,
+                None => return None,
+            }
+        }
+        cur.invalidate();
+        cur.value.as_mut()
+    }
+
+    // Takes the value out of the node at the end of the key. Every node that would be left
+    // with no value and no children is cut off, so the trie never holds dead branches. Nothing
+    // is pruned (or allocated) when the key isn't there.
+    pub(crate) fn delete(&mut self, hex_key: &str, pool: &mut Pool<V, N>) -> Option<V> {
+        self.delete_nibbles(key_symbols::<N>(hex_key), pool)
+    }
+
+    // Two passes over the path, neither recursive. The first only reads: it checks the key holds
+    // a value and finds `cut`, the depth of the deepest node above it that stays because it has
+    // a value or another child. Below `cut` the path is a chain that exists only for this key.
+    // The second pass walks down to `cut` and unhooks that chain in one go, then walks the
+    // detached chain handing a node per step to the pool to reach the value.
+    pub(crate) fn delete_nibbles<I>(&mut self, nibbles: I, pool: &mut Pool<V, N>) -> Option<V>
+    where
+        I: IntoIterator<Item = usize>,
+        I::IntoIter: Clone,
+    {
+        let mut nibbles = nibbles.into_iter();
+        let (mut cut, mut depth) = (0, 0);
+        let mut cur = &*self;
+        for nibble in nibbles.clone() {
+            if cur.value.is_some() || cur.child_count() > 1 {
+                cut = depth;
+            }
+            cur = cur.child(nibble)?;
+            depth += 1;
+        }
+        cur.value.as_ref()?;
+        let keep_target = depth == 0 || cur.has_children();
+
+        let mut cur = self;
+        let stop = if keep_target { depth } else { cut };
+        for nibble in nibbles.by_ref().take(stop) {
+            cur.invalidate();
+            cur = cur.child_mut(nibble).unwrap();
+        }
+        cur.invalidate();
+        if keep_target {
+            return cur.value.take();
+        }
+        let mut chain = cur.take_child(nibbles.next().unwrap()).unwrap();
+        // Every node in the chain but the last has exactly one child. Popping it rather than
+        // using take_child keeps the vector's buffer for whoever gets the node from the pool.
+        while let Some(next) = chain.children.pop() {
+            pool.put(core::mem::replace(&mut chain, next));
+        }
+        let value = chain.value.take();
+        pool.put(chain);
+        value
+    }
+
+    // Counts the values in this subtree with an explicit stack instead of recursion.
+    pub(crate) fn count_values(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            count += node.value.is_some() as usize;
+            stack.extend(node.children().map(|(_, child)| child));
+        }
+        count
+    }
+
+    // Counts the nodes in this subtree, itself included.
+    pub(crate) fn count_nodes(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.children().map(|(_, child)| child));
+        }
+        count
+    }
+
+    // A node with no value and no children carries no information and can be pruned.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.value.is_none() && !self.has_children()
+    }
+
+    pub(crate) fn insert_nibbles<I: IntoIterator<Item = usize>>(
+        &mut self,
+        nibbles: I,
+        value: V,
+        pool: &mut Pool<V, N>,
+    ) -> Option<V> {
+        let mut cur = self;
+        for nib in nibbles {
+            cur.invalidate();
+            cur = cur.child_or_insert(nib, pool);
+        }
+        cur.invalidate();
+        cur.value.replace(value)
+    }
+
+    // Returns the value at the end of the path, first storing `f()` there if there isn't one,
+    // and whether it did. Walks down through existing nodes and only creates the rest of the
+    // path once it knows the value is missing, so a hit allocates nothing. The caller gets a
+    // mutable reference, so every node on the way loses its cached hash.
+    pub(crate) fn get_or_insert_nibbles<I, F>(
+        &mut self,
+        nibbles: I,
+        f: F,
+        pool: &mut Pool<V, N>,
+    ) -> (&mut V, bool)
+    where
+        I: IntoIterator<Item = usize>,
+        F: FnOnce() -> V,
+    {
+        let mut nibbles = nibbles.into_iter();
+        let mut cur = self;
+        cur.invalidate();
+        while let Some(nibble) = nibbles.next() {
+            if cur.child(nibble).is_none() {
+                let mut cur = cur.child_or_insert(nibble, pool);
+                for nibble in nibbles {
+                    cur = cur.child_or_insert(nibble, pool);
+                }
+                return (cur.value.insert(f()), true);
+            }
+            cur = cur.child_mut(nibble).unwrap();
+            cur.invalidate();
+        }
+        let inserted = cur.value.is_none();
+        (cur.value.get_or_insert_with(f), inserted)
+    }
+
+    // Replaces the value at the end of the path with whatever `f` makes of it, `None` meaning
+    // there's no value, and returns whether there was a value before and after. A path that
+    // isn't there is only created if `f` returns a value. While walking down it keeps `cut` like
+    // delete_nibbles does, so a value removed from a leaf prunes the chain above it with a
+    // second, shorter walk.
+    pub(crate) fn update_nibbles<I, F>(
+        &mut self,
+        nibbles: I,
+        f: F,
+        pool: &mut Pool<V, N>,
+    ) -> (bool, bool)
+    where
+        I: IntoIterator<Item = usize>,
+        I::IntoIter: Clone,
+        F: FnOnce(Option<V>) -> Option<V>,
+    {
+        let path = nibbles.into_iter();
+        let mut nibbles = path.clone();
+        let (mut cut, mut depth) = (0, 0);
+        let mut cur = &mut *self;
+        while let Some(nibble) = nibbles.next() {
+            if cur.value.is_some() || cur.child_count() > 1 {
+                cut = depth;
+            }
+            if cur.child(nibble).is_none() {
+                let Some(value) = f(None) else {
+                    return (false, false);
+                };
+                cur.invalidate();
+                let mut cur = cur.child_or_insert(nibble, pool);
+                for nibble in nibbles {
+                    cur = cur.child_or_insert(nibble, pool);
+                }
+                cur.value = Some(value);
+                return (false, true);
+            }
+            cur.invalidate();
+            cur = cur.child_mut(nibble).unwrap();
+            depth += 1;
+        }
+        cur.invalidate();
+        let had = cur.value.is_some();
+        cur.value = f(cur.value.take());
+        let has = cur.value.is_some();
+        if !had || has || depth == 0 || cur.has_children() {
+            return (had, has);
+        }
+
+        let mut nibbles = path;
+        let mut cur = self;
+        for nibble in nibbles.by_ref().take(cut) {
+            cur = cur.child_mut(nibble).unwrap();
+        }
+        let chain = cur.take_child(nibbles.next().unwrap()).unwrap();
+        pool.put(chain);
+        (had, has)
+    }
+
+    // Cuts off the subtree at the end of `path` (which must not be empty) and hands it back,
+    // pruning ancestors that are left empty just like delete does. The same two passes as
+    // delete_nibbles: the first finds `cut`, the deepest ancestor that stays, and the second
+    // unhooks the chain below it and hands the chain's nodes above the subtree to `pool`.
+    pub(crate) fn detach(
+        &mut self,
+        path: &[usize],
+        pool: &mut Pool<V, N>,
+    ) -> Option<Box<Node<V, N>>> {
+        if path.is_empty() {
+            return None;
+        }
+        let mut cut = 0;
+        let mut cur = &*self;
+        for (depth, &nibble) in path.iter().enumerate() {
+            if cur.value.is_some() || cur.child_count() > 1 {
+                cut = depth;
+            }
+            cur = cur.child(nibble)?;
+        }
+
+        let mut cur = self;
+        for &nibble in &path[..cut] {
+            cur.invalidate();
+            cur = cur.child_mut(nibble).unwrap();
+        }
+        cur.invalidate();
+        let mut chain = cur.take_child(path[cut]).unwrap();
+        for _ in cut + 1..path.len() {
+            let next = chain.children.pop().unwrap();
+            pool.put(core::mem::replace(&mut chain, next));
+        }
+        Some(chain)
+    }
+}
+
+impl<V> Node<V> {
+    // Visits every value in key order with its key, drops the ones `f` rejects and prunes
+    // children that end up empty, handing them to `pool`. Returns how many values were
+    // dropped. `f` can edit the values it keeps, so every cached hash goes.
+    //
+    // Each frame is a node taken out of its parent with the children it still has to visit.
+    // A node's children go back one at a time as they're finished, so by the time its frame
+    // is popped it knows whether it ended up empty.
+    pub(crate) fn retain<F>(&mut self, mut f: F, pool: &mut Pool<V, 16>) -> usize
+    where
+        F: FnMut(&str, &mut V) -> bool,
+    {
+        let mut key = String::new();
+        let mut removed = 0;
+        self.invalidate();
+        if let Some(value) = self.value.as_mut()
+            && !f(&key, value)
+        {
+            self.value = None;
+            removed += 1;
+        }
+        let mut stack = vec![(None, self.take_children().into_iter())];
+        loop {
+            let (_, children) = stack.last_mut().unwrap();
+            if let Some(mut child) = children.next() {
+                key.push(NIBBLE_TO_HEX[child.symbol as usize] as char);
+                child.invalidate();
+                if let Some(value) = child.value.as_mut()
+                    && !f(&key, value)
+                {
+                    child.value = None;
+                    removed += 1;
+                }
+                let grandchildren = child.take_children().into_iter();
+                stack.push((Some(child), grandchildren));
+                continue;
+            }
+            let Some(node) = stack.pop().unwrap().0 else {
+                return removed;
+            };
+            key.pop();
+            let parent = match stack.last_mut().unwrap() {
+                (Some(parent), _) => parent,
+                (None, _) => &mut *self,
+            };
+            if node.is_empty() {
+                pool.put(node);
+            } else {
+                parent.push_child(node);
+            }
+        }
+    }
+
+    // Moves everything at or after `at` (in key order) out of this subtree into a new node at
+    // the same position. Along the path, children after the path nibble move wholesale, the
+    // node at the end of the path moves whole and everything before the path stays.
+    //
+    // Walking down the path collects one new node per level holding what moved there, which
+    // are then hooked together from the bottom up. Nodes emptied on either side are pruned:
+    // on this side they can only be a chain at the end of the path, which is cut off like
+    // delete_nibbles cuts one, and handed to `pool`.
+    pub(crate) fn split_off(&mut self, at: &[usize], pool: &mut Pool<V, 16>) -> Node<V> {
+        let mut levels = Vec::new();
+        let mut reached = true;
+        let mut cur = &mut *self;
+        for &nibble in at {
+            cur.invalidate();
+            let mut out = Node::new();
+            for i in nibble + 1..16 {
+                if let Some(child) = cur.take_child(i) {
+                    pool.moved_out(&child);
+                    out.set_child(i, child);
+                }
+            }
+            levels.push(out);
+            if cur.child(nibble).is_none() {
+                reached = false;
+                break;
+            }
+            cur = cur.child_mut(nibble).unwrap();
+        }
+        let mut moved = Node::new();
+        if reached {
+            cur.invalidate();
+            let symbol = cur.symbol;
+            moved = core::mem::take(cur);
+            cur.symbol = symbol;
+            for (_, child) in moved.children() {
+                pool.moved_out(child);
+            }
+        }
+        for (out, &nibble) in levels.into_iter().zip(at).rev() {
+            let below = core::mem::replace(&mut moved, out);
+            if !below.is_empty() {
+                moved.set_child(nibble, Box::new(below));
+            }
+        }
+
+        let mut cut = 0;
+        let mut depth = 0;
+        let mut cur = &*self;
+        for &nibble in at {
+            if cur.value.is_some() || cur.child_count() > 1 {
+                cut = depth;
+            }
+            let Some(child) = cur.child(nibble) else {
+                break;
+            };
+            cur = child;
+            depth += 1;
+        }
+        if depth > 0 && cur.is_empty() {
+            let mut cur = &mut *self;
+            for &nibble in &at[..cut] {
+                cur = cur.child_mut(nibble).unwrap();
+            }
+            pool.put(cur.take_child(at[cut]).unwrap());
+        }
+        moved
+    }
+}
+
+// Nodes that delete and clear have cut out, kept for insert to reuse instead of allocating.
+// Only blank nodes are kept: `put` empties a node before storing it, and hands its children
+// on to the same treatment, so `take` never returns anything still holding a value, a child
+// or a cached hash. Nodes over `capacity` are freed as usual.
+//
+// Every node the trie gains goes through `take` and every node it prunes through `put`, except
+// for whole subtrees moving in from or out to another trie, which are counted with `moved_in`
+// and `moved_out`. That makes the pool where the node metrics are counted, and so where the
+// trie keeps them.
+pub(crate) struct Pool<V, const N: usize> {
+    free: Vec<Box<Node<V, N>>>,
+    capacity: usize,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Metrics,
+}
+
+// How many spare nodes a trie keeps unless told otherwise.
+const DEFAULT_POOL_CAPACITY: usize = 1024;
+
+impl<V, const N: usize> Pool<V, N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            free: Vec::new(),
+            capacity: DEFAULT_POOL_CAPACITY,
+            #[cfg(feature = "metrics")]
+            metrics: Metrics::default(),
+        }
+    }
+
+    pub(crate) fn take(&mut self) -> Box<Node<V, N>> {
+        #[cfg(feature = "metrics")]
+        self.metrics.record_nodes_created(1);
+        self.free.pop().unwrap_or_else(|| Box::new(Node::new()))
+    }
+
+    // Stores `node` and everything under it, without recursing.
+    pub(crate) fn put(&mut self, mut node: Box<Node<V, N>>) {
+        let mut work = Vec::new();
+        loop {
+            #[cfg(feature = "metrics")]
+            self.metrics.record_nodes_pruned(1);
+            work.append(&mut node.children);
+            node.bitmap = 0;
+            node.value = None;
+            node.invalidate();
+            if self.free.len() < self.capacity {
+                self.free.push(node);
+            }
+            match work.pop() {
+                Some(next) => node = next,
+                None => break,
+            }
+        }
+    }
+
+    // Counts `node` and everything under it as gained without coming from `take`.
+    pub(crate) fn moved_in(&self, node: &Node<V, N>) {
+        #[cfg(feature = "metrics")]
+        self.metrics.record_nodes_created(node.count_nodes());
+        #[cfg(not(feature = "metrics"))]
+        let _ = node;
+    }
+
+    // Counts `node` and everything under it as pruned without going through `put`.
+    pub(crate) fn moved_out(&self, node: &Node<V, N>) {
+        #[cfg(feature = "metrics")]
+        self.metrics.record_nodes_pruned(node.count_nodes());
+        #[cfg(not(feature = "metrics"))]
+        let _ = node;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.free.truncate(capacity);
+    }
+
+    // How many spare nodes still hold something, which put should make impossible.
+    pub(crate) fn dirty_nodes(&self) -> usize {
+        self.free
+            .iter()
+            .filter(|node| {
+                node.value.is_some()
+                    || !node.children.is_empty()
+                    || node.bitmap != 0
+                    || node.hash.get().is_some()
+            })
+            .count()
+    }
+
+    // Roughly how many bytes the spare nodes take up, counting the pool's own vector.
+    pub(crate) fn bytes(&self) -> usize {
+        let node_bytes: usize = self
+            .free
+            .iter()
+            .map(|node| size_of::<Node<V, N>>() + node.children_capacity_bytes())
+            .sum();
+        self.free.capacity() * size_of::<Box<Node<V, N>>>() + node_bytes
+    }
+
+    // Frees every spare node, returning roughly how many bytes went with them.
+    pub(crate) fn clear(&mut self) -> usize {
+        let bytes = self.bytes();
+        self.free = Vec::new();
+        bytes
+    }
+}
+
+// A cloned trie starts with an empty pool of the same capacity; spare nodes aren't worth
+// copying.
+impl<V, const N: usize> Clone for Pool<V, N> {
+    fn clone(&self) -> Self {
+        Self {
+            free: Vec::new(),
+            capacity: self.capacity,
+            #[cfg(feature = "metrics")]
+            metrics: Metrics::default(),
+        }
+    }
+}
+
+// A derived impl would recurse once per level, like the drop glue below, so the copy is built
+// with an explicit stack instead. Cached hashes come along, since they hold for the copy too.
+impl<V: Clone, const N: usize> Clone for Node<V, N> {
+    fn clone(&self) -> Self {
+        let copy = |node: &Node<V, N>| Node {
+            children: Vec::with_capacity(node.children.len()),
+            bitmap: node.bitmap,
+            symbol: node.symbol,
+            value: node.value.clone(),
+            hash: node.hash.clone(),
+        };
+        let mut stack = vec![(self.children.iter(), copy(self))];
+        loop {
+            let (children, _) = stack.last_mut().unwrap();
+            match children.next() {
+                Some(child) => stack.push((child.children.iter(), copy(child))),
+                None => {
+                    let (_, node) = stack.pop().unwrap();
+                    let Some((_, parent)) = stack.last_mut() else {
+                        return node;
+                    };
+                    parent.children.push(Box::new(node));
+                }
+            }
+        }
+    }
+}
+
+// Box's drop glue would free a subtree by recursing once per level, which a single long key
+// turns into a stack overflow. Instead each node moves its children onto a work list and frees
+// them from there; by the time a node from the list is dropped its children have been taken,
+// so this never nests more than one level and nothing is visited twice.
+impl<V, const N: usize> Drop for Node<V, N> {
+    fn drop(&mut self) {
+        if self.children.is_empty() {
+            return;
+        }
+        let mut work = core::mem::take(&mut self.children);
+        while let Some(mut node) = work.pop() {
+            work.append(&mut node.children);
+        }
+    }
+}
+
+// The children of a node with their symbols, in symbol order.
+pub(crate) struct Children<'a, V, const N: usize> {
+    children: core::slice::Iter<'a, Box<Node<V, N>>>,
+}
+
+impl<'a, V, const N: usize> Iterator for Children<'a, V, N> {
+    type Item = (usize, &'a Node<V, N>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let child = self.children.next()?;
+        Some((child.symbol as usize, child))
+    }
+}
+
+impl<V, const N: usize> DoubleEndedIterator for Children<'_, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let child = self.children.next_back()?;
+        Some((child.symbol as usize, child))
+    }
+}
+
+pub(crate) struct ChildrenMut<'a, V, const N: usize> {
+    children: core::slice::IterMut<'a, Box<Node<V, N>>>,
+}
+
+impl<'a, V, const N: usize> Iterator for ChildrenMut<'a, V, N> {
+    type Item = (usize, &'a mut Node<V, N>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let child = self.children.next()?;
+        Some((child.symbol as usize, child))
+    }
+}
+
+impl<V, const N: usize> DoubleEndedIterator for ChildrenMut<'_, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let child = self.children.next_back()?;
+        Some((child.symbol as usize, child))
+    }
+}
@@ -0,0 +1,166 @@
+// Callbacks for mirroring a trie's changes somewhere else. The trie holds at most one observer
+// and checks for it after each change, so a trie without one pays a branch per write and
+// builds no keys.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::iter::Walk;
+use crate::key::{key_symbols, nibbles_to_hex, symbols_to_hex};
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// Hears about the changes made to a [`Trie`], as set with
+/// [`Trie::set_observer`].
+///
+/// Keys are given the way the trie spells them: lowercase, with the
+/// characters it skips left out. Every method does nothing by default.
+///
+/// These are the methods that notify:
+///
+/// - [`Trie::insert_lossy`], [`Trie::insert_nibbles`], [`Trie::delete_lossy`],
+///   [`Trie::update`], [`Trie::compare_and_swap`] and
+///   [`Trie::get_or_insert_with`], and everything built on them, such as the
+///   `try_*` methods, [`Trie::apply_batch`], [`Trie::pop_first`] and the
+///   methods for integer, byte and [`Nibbles`](crate::Nibbles) keys;
+/// - [`VacantEntry::insert`] and [`OccupiedEntry::insert`], and so
+///   [`Entry::or_insert`] and the like when they insert;
+/// - [`CursorMut::insert`] and [`CursorMut::delete`];
+/// - [`Trie::merge`] and [`Trie::append`], with every key whose value they
+///   change. A key whose values a [`MergePolicy::WithFn`] combined is
+///   reported deleted and inserted again, since the function took the old
+///   value;
+/// - [`Trie::remove_prefix`], [`Trie::retain`] and [`Trie::split_off`], with
+///   every key they take out.
+///
+/// Changes made through a mutable reference, such as from
+/// [`Trie::get_mut`], [`Trie::values_mut`], [`Trie::walk_mut`] or
+/// [`OccupiedEntry::get_mut`], aren't seen, and neither are
+/// [`Trie::clear`], [`Trie::drain`] and [`Trie::rollback`].
+///
+/// [`VacantEntry::insert`]: crate::VacantEntry::insert
+/// [`OccupiedEntry::insert`]: crate::OccupiedEntry::insert
+/// [`OccupiedEntry::get_mut`]: crate::OccupiedEntry::get_mut
+/// [`Entry::or_insert`]: crate::Entry::or_insert
+/// [`CursorMut::insert`]: crate::CursorMut::insert
+/// [`CursorMut::delete`]: crate::CursorMut::delete
+/// [`MergePolicy::WithFn`]: crate::MergePolicy::WithFn
+///
+/// An observer is called while the trie is borrowed mutably, so it can't
+/// reach back into the trie it's watching; anything it needs from there has
+/// to come from the arguments.
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use trie::{Trie, TrieObserver};
+///
+/// struct Log(Arc<Mutex<Vec<String>>>);
+///
+/// impl TrieObserver<u32> for Log {
+///     fn on_insert(&mut self, key: &str, old: Option<&u32>, new: &u32) {
+///         self.0.lock().unwrap().push(format!("insert {key} {old:?} {new}"));
+///     }
+///
+///     fn on_delete(&mut self, key: &str, old: &u32) {
+///         self.0.lock().unwrap().push(format!("delete {key} {old}"));
+///     }
+/// }
+///
+/// let log = Arc::new(Mutex::new(Vec::new()));
+/// let mut trie = Trie::new();
+/// trie.set_observer(Box::new(Log(log.clone())));
+/// trie.insert_lossy("A1", 1);
+/// trie.insert_lossy("a1", 2);
+/// trie.delete_lossy("a1");
+/// assert_eq!(
+///     *log.lock().unwrap(),
+///     ["insert a1 None 1", "insert a1 Some(1) 2", "delete a1 2"],
+/// );
+/// ```
+pub trait TrieObserver<V>: Send {
+    /// `new` was stored under `key`, replacing `old` if there was a value
+    /// there.
+    fn on_insert(&mut self, key: &str, old: Option<&V>, new: &V) {
+        let _ = (key, old, new);
+    }
+
+    /// `old`, stored under `key`, was removed.
+    fn on_delete(&mut self, key: &str, old: &V) {
+        let _ = (key, old);
+    }
+
+    /// Every key starting with `prefix` was removed by
+    /// [`Trie::remove_prefix`], after [`TrieObserver::on_delete`] was called
+    /// for each of them. Only called if there was something to remove.
+    fn on_prune(&mut self, prefix: &str) {
+        let _ = prefix;
+    }
+}
+
+// The trie's observer, if it has one. Clones of a trie start without one: it was set up to
+// watch the original.
+pub(crate) struct Observer<V>(Option<Box<dyn TrieObserver<V>>>);
+
+impl<V> Default for Observer<V> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<V> Clone for Observer<V> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+// SAFETY: the observer is only ever reached through `&mut Observer`, so sharing `&Observer`
+// between threads can't touch it from two of them at once. This keeps a trie `Sync` without
+// asking every observer to be.
+unsafe impl<V> Sync for Observer<V> {}
+
+impl<V> Observer<V> {
+    pub(crate) fn get(&mut self) -> Option<&mut (dyn TrieObserver<V> + 'static)> {
+        self.0.as_deref_mut()
+    }
+}
+
+impl<V, const N: usize> Trie<V, N> {
+    /// Calls `observer` after every change made through the methods listed
+    /// on [`TrieObserver`], replacing the observer set before, if any.
+    pub fn set_observer(&mut self, observer: Box<dyn TrieObserver<V>>) {
+        self.observer.0 = Some(observer);
+    }
+
+    /// Stops notifying the current observer and returns it.
+    pub fn take_observer(&mut self) -> Option<Box<dyn TrieObserver<V>>> {
+        self.observer.0.take()
+    }
+}
+
+// `hex_key` the way the trie spells it.
+pub(crate) fn canonical_key<const N: usize>(hex_key: &str) -> String {
+    let symbols: Vec<u8> = key_symbols::<N>(hex_key).map(|s| s as u8).collect();
+    symbols_to_hex::<N>(&symbols)
+}
+
+// Tells `observer` about every value under `node`, whose key is `path`, as inserted where
+// there was nothing.
+pub(crate) fn notify_inserted<V>(
+    observer: &mut dyn TrieObserver<V>,
+    node: &Node<V>,
+    path: Vec<u8>,
+) {
+    let mut walk = Walk::new(node, path);
+    while let Some(value) = walk.next_value() {
+        observer.on_insert(&nibbles_to_hex(&walk.path), None, value);
+    }
+}
+
+// Tells `observer` about every value under `node`, whose key is `path`, as deleted.
+pub(crate) fn notify_deleted<V>(observer: &mut dyn TrieObserver<V>, node: &Node<V>, path: Vec<u8>) {
+    let mut walk = Walk::new(node, path);
+    while let Some(value) = walk.next_value() {
+        observer.on_delete(&nibbles_to_hex(&walk.path), value);
+    }
+}
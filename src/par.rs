@@ -0,0 +1,151 @@
+// Parallel walks over a trie with rayon. The trie is cut into pieces for rayon to hand out:
+// a piece is a node's value and some run of its children's subtrees, and splitting one halves
+// the run. A piece down to a single subtree moves into it first, so a trie with everything
+// under one nibble still splits as far as rayon asks.
+
+use std::ops::Range;
+
+use rayon::iter::ParallelIterator;
+use rayon::iter::plumbing::{Folder, UnindexedConsumer, UnindexedProducer, bridge_unindexed};
+
+use crate::iter::Walk;
+use crate::key::symbols_to_hex;
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// A parallel iterator over the entries of a [`Trie`], in no particular
+/// order. Returned by [`Trie::par_iter`].
+pub struct ParIter<'a, V, const N: usize = 16> {
+    root: &'a Node<V, N>,
+}
+
+impl<V: Sync, const N: usize> Trie<V, N> {
+    /// Returns a parallel iterator over all entries, yielding the same
+    /// entries as [`Trie::iter`] in no particular order.
+    ///
+    /// ```
+    /// use rayon::prelude::*;
+    ///
+    /// let mut trie = trie::Trie::new();
+    /// for i in 0..1000_u32 {
+    ///     trie.insert_int(i, i);
+    /// }
+    /// let sum: u32 = trie.par_iter().map(|(_, value)| value).sum();
+    /// assert_eq!(sum, 999 * 1000 / 2);
+    /// ```
+    pub fn par_iter(&self) -> ParIter<'_, V, N> {
+        ParIter { root: &self.root }
+    }
+
+    /// Calls `f` on every entry, in parallel.
+    pub fn par_for_each<F>(&self, f: F)
+    where
+        F: Fn(&str, &V) + Sync + Send,
+    {
+        self.par_iter().for_each(|(key, value)| f(&key, value));
+    }
+
+    /// Returns whether `f` holds for any entry. Every thread stops soon
+    /// after one finds one.
+    pub fn par_any<F>(&self, f: F) -> bool
+    where
+        F: Fn(&str, &V) -> bool + Sync + Send,
+    {
+        self.par_iter().any(|(key, value)| f(&key, value))
+    }
+
+    /// Returns some entry `f` holds for, not necessarily the first in key
+    /// order. Every thread stops soon after one finds one.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1", 1);
+    /// trie.insert_lossy("b0", 20);
+    /// assert_eq!(trie.par_find(|_, &value| value > 10), Some(("b0".to_string(), &20)));
+    /// ```
+    pub fn par_find<F>(&self, f: F) -> Option<(String, &V)>
+    where
+        F: Fn(&str, &V) -> bool + Sync + Send,
+    {
+        self.par_iter().find_any(|(key, value)| f(key, value))
+    }
+}
+
+impl<'a, V: Sync, const N: usize> ParallelIterator for ParIter<'a, V, N> {
+    type Item = (String, &'a V);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let piece = Piece {
+            node: self.root,
+            path: Vec::new(),
+            with_value: true,
+            children: 0..self.root.child_count(),
+        };
+        bridge_unindexed(piece, consumer)
+    }
+}
+
+// The value of `node` if `with_value`, and the subtrees of its children at `children`, which
+// are positions among the children it has.
+struct Piece<'a, V, const N: usize> {
+    node: &'a Node<V, N>,
+    path: Vec<u8>,
+    with_value: bool,
+    children: Range<usize>,
+}
+
+impl<'a, V: Sync, const N: usize> UnindexedProducer for Piece<'a, V, N> {
+    type Item = (String, &'a V);
+
+    fn split(mut self) -> (Self, Option<Self>) {
+        while self.children.len() == 1 && !(self.with_value && self.node.value.is_some()) {
+            let (symbol, child) = self.node.child_at(self.children.start);
+            self.node = child;
+            self.path.push(symbol as u8);
+            self.with_value = true;
+            self.children = 0..child.child_count();
+        }
+        // with one subtree left the node's value goes off on its own
+        let mid = match self.children.len() {
+            0 => return (self, None),
+            1 => self.children.start,
+            len => self.children.start + len / 2,
+        };
+        let rest = Piece {
+            node: self.node,
+            path: self.path.clone(),
+            with_value: false,
+            children: mid..self.children.end,
+        };
+        self.children.end = mid;
+        (self, Some(rest))
+    }
+
+    fn fold_with<F>(self, mut folder: F) -> F
+    where
+        F: Folder<Self::Item>,
+    {
+        if self.with_value
+            && let Some(value) = &self.node.value
+        {
+            folder = folder.consume((symbols_to_hex::<N>(&self.path), value));
+        }
+        for at in self.children {
+            if folder.full() {
+                break;
+            }
+            let (symbol, child) = self.node.child_at(at);
+            let mut path = self.path.clone();
+            path.push(symbol as u8);
+            let mut walk = Walk::new(child, path);
+            folder = folder.consume_iter(std::iter::from_fn(|| {
+                let value = walk.next_value()?;
+                Some((symbols_to_hex::<N>(&walk.path), value))
+            }));
+        }
+        folder
+    }
+}
@@ -0,0 +1,381 @@
+// Path-compressed (Patricia) storage.
+//
+// `Trie` spends one node per nibble of every key, which is what makes long sparse keys
+// expensive. Here an edge carries a whole run of nibbles: the child under symbol `i` of a node
+// sits at the end of the edge `i` followed by the child's `prefix`. Every node other than the
+// root either holds a value or has at least two children, so a run of value-less single-child
+// nodes never exists; inserting splits an edge where the new key leaves it, and deleting merges
+// a node left with one child back into the edge above it.
+//
+// Children are stored densely, the same way `Node` stores them: bit `i` of `bitmap` says
+// whether symbol `i` has a child, and the child's place in `children` is the number of bits set
+// below its own. Every walk over the nodes uses an explicit stack, so a key as deep as memory
+// allows can't overflow the call stack.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::key::{NIBBLE_TO_HEX, hex_to_nibbles, nibbles_to_hex};
+
+struct PatriciaNode<V> {
+    // the rest of the edge into this node, after the slot nibble; always empty on the root
+    prefix: Vec<u8>,
+    children: Vec<Box<PatriciaNode<V>>>,
+    bitmap: u16,
+    // this node's slot nibble in its parent
+    symbol: u8,
+    value: Option<V>,
+}
+
+impl<V> PatriciaNode<V> {
+    fn new(prefix: Vec<u8>, value: Option<V>) -> Self {
+        Self {
+            prefix,
+            children: Vec::new(),
+            bitmap: 0,
+            symbol: 0,
+            value,
+        }
+    }
+
+    // Where symbol `i` sits in `children`: `Ok` if it's there, else `Err` with where it would go.
+    fn position(&self, i: usize) -> Result<usize, usize> {
+        let below = (self.bitmap as u32 & ((1 << i) - 1)).count_ones() as usize;
+        if self.bitmap & (1 << i) != 0 {
+            Ok(below)
+        } else {
+            Err(below)
+        }
+    }
+
+    fn child(&self, i: usize) -> Option<&PatriciaNode<V>> {
+        let at = self.position(i).ok()?;
+        Some(&self.children[at])
+    }
+
+    fn child_mut(&mut self, i: usize) -> Option<&mut PatriciaNode<V>> {
+        let at = self.position(i).ok()?;
+        Some(&mut self.children[at])
+    }
+
+    // Puts `child` under symbol `i`, replacing whatever was there.
+    fn set_child(&mut self, i: usize, mut child: Box<PatriciaNode<V>>) {
+        child.symbol = i as u8;
+        match self.position(i) {
+            Ok(at) => self.children[at] = child,
+            Err(at) => {
+                self.children.insert(at, child);
+                self.bitmap |= 1 << i;
+            }
+        }
+    }
+
+    fn take_child(&mut self, i: usize) -> Option<Box<PatriciaNode<V>>> {
+        let at = self.position(i).ok()?;
+        self.bitmap &= !(1 << i);
+        Some(self.children.remove(at))
+    }
+
+    // The first child under symbol `from` or later.
+    fn next_child(&self, from: usize) -> Option<&PatriciaNode<V>> {
+        if from >= 16 {
+            return None;
+        }
+        let (Ok(at) | Err(at)) = self.position(from);
+        self.children.get(at).map(|child| &**child)
+    }
+
+    // Pulls a lone child up into this node, joining the two edges.
+    fn absorb_only_child(&mut self) {
+        if self.children.len() != 1 {
+            return;
+        }
+        let mut child = self.children.pop().unwrap();
+        self.prefix.push(child.symbol);
+        self.prefix.extend_from_slice(&child.prefix);
+        self.children = core::mem::take(&mut child.children);
+        self.bitmap = child.bitmap;
+        self.value = child.value.take();
+    }
+}
+
+// Moves the children onto a worklist so dropping a deep chain doesn't recurse once per node.
+impl<V> Drop for PatriciaNode<V> {
+    fn drop(&mut self) {
+        if self.children.is_empty() {
+            return;
+        }
+        let mut work = core::mem::take(&mut self.children);
+        while let Some(mut node) = work.pop() {
+            work.append(&mut node.children);
+        }
+    }
+}
+
+/// A path-compressed variant of [`Trie`](crate::Trie) keyed the same way.
+///
+/// Runs of nibbles with nothing branching off them are stored as a single
+/// edge, so a lone 64-nibble key costs one node instead of 64. It offers the
+/// basic map operations; the rest of the `Trie` API is not available here.
+pub struct PatriciaTrie<V> {
+    root: PatriciaNode<V>,
+    len: usize,
+}
+
+impl<V> Default for PatriciaTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> PatriciaTrie<V> {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self {
+            root: PatriciaNode::new(Vec::new(), None),
+            len: 0,
+        }
+    }
+
+    /// Inserts `value` under `hex_key`, returning the value previously stored
+    /// there.
+    pub fn insert(&mut self, hex_key: &str, value: V) -> Option<V> {
+        self.insert_nibbles(hex_to_nibbles(hex_key), value)
+    }
+
+    /// Inserts `value` under the key spelled by `nibbles` (each below 16).
+    pub fn insert_nibbles<I: IntoIterator<Item = usize>>(
+        &mut self,
+        nibbles: I,
+        value: V,
+    ) -> Option<V> {
+        let key: Vec<u8> = nibbles.into_iter().map(|n| n as u8).collect();
+        let mut cur = &mut self.root;
+        let mut rest = &key[..];
+        loop {
+            let Some((&nibble, tail)) = rest.split_first() else {
+                let old = cur.value.replace(value);
+                self.len += old.is_none() as usize;
+                return old;
+            };
+            let nibble = nibble as usize;
+            let at = match cur.position(nibble) {
+                Ok(at) => at,
+                Err(_) => {
+                    let leaf = PatriciaNode::new(tail.to_vec(), Some(value));
+                    cur.set_child(nibble, Box::new(leaf));
+                    self.len += 1;
+                    return None;
+                }
+            };
+            let child = &mut cur.children[at];
+            let common = common_prefix(&child.prefix, tail);
+            if common < child.prefix.len() {
+                // the key leaves this edge part way along: put a node at the fork and hang the
+                // old child below it
+                let mut fork = Box::new(PatriciaNode::new(child.prefix[..common].to_vec(), None));
+                fork.symbol = nibble as u8;
+                let mut old = core::mem::replace(child, fork);
+                let branch = old.prefix[common] as usize;
+                old.prefix.drain(..=common);
+                child.set_child(branch, old);
+            }
+            cur = child;
+            rest = &tail[common..];
+        }
+    }
+
+    /// Returns the value stored under `hex_key`, if any.
+    pub fn get(&self, hex_key: &str) -> Option<&V> {
+        let key: Vec<u8> = hex_to_nibbles(hex_key).map(|n| n as u8).collect();
+        let mut cur = &self.root;
+        let mut rest = &key[..];
+        while let Some((&nibble, tail)) = rest.split_first() {
+            cur = cur.child(nibble as usize)?;
+            rest = tail.strip_prefix(&cur.prefix[..])?;
+        }
+        cur.value.as_ref()
+    }
+
+    /// Returns `true` if a value is stored under `hex_key`.
+    pub fn contains_key(&self, hex_key: &str) -> bool {
+        self.get(hex_key).is_some()
+    }
+
+    /// Removes the value stored under `hex_key` and returns it.
+    ///
+    /// A node left empty is removed and one left with a single child is
+    /// merged with it, so the trie stays fully compressed.
+    pub fn delete(&mut self, hex_key: &str) -> Option<V> {
+        // Find the slot nibbles down to the entry first, so a miss changes nothing. Only the
+        // entry's node and its parent can need fixing up afterwards.
+        let key: Vec<u8> = hex_to_nibbles(hex_key).map(|n| n as u8).collect();
+        let mut path = Vec::new();
+        let mut cur = &self.root;
+        let mut rest = &key[..];
+        while let Some((&nibble, tail)) = rest.split_first() {
+            cur = cur.child(nibble as usize)?;
+            rest = tail.strip_prefix(&cur.prefix[..])?;
+            path.push(nibble as usize);
+        }
+        cur.value.as_ref()?;
+        self.len -= 1;
+
+        let Some((&last, above)) = path.split_last() else {
+            return self.root.value.take();
+        };
+        let mut parent = &mut self.root;
+        for &nibble in above {
+            parent = parent.child_mut(nibble).unwrap();
+        }
+        let target = parent.child_mut(last).unwrap();
+        let removed = target.value.take();
+        if target.children.is_empty() {
+            parent.take_child(last);
+            // the root keeps an empty prefix, so it never absorbs its child
+            if !above.is_empty() && parent.value.is_none() {
+                parent.absorb_only_child();
+            }
+        } else {
+            target.absorb_only_child();
+        }
+        removed
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the trie holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of nodes, including the root.
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.children.iter().map(|child| &**child));
+        }
+        count
+    }
+
+    /// Returns an iterator over all entries in key order.
+    pub fn iter(&self) -> PatriciaIter<'_, V> {
+        PatriciaIter {
+            stack: vec![(&self.root, 0, 0)],
+            path: Vec::new(),
+        }
+    }
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// An iterator over the entries of a [`PatriciaTrie`] in key order.
+///
+/// Returned by [`PatriciaTrie::iter`].
+pub struct PatriciaIter<'a, V> {
+    // (node, cursor, length of `path` above the node's edge); cursor 0 means the node's value
+    // is still pending and i + 1 means child i is next, like the Trie walk
+    stack: Vec<(&'a PatriciaNode<V>, usize, usize)>,
+    path: Vec<u8>,
+}
+
+impl<'a, V> Iterator for PatriciaIter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, cursor, _) = self.stack.last_mut()?;
+            let node = *node;
+            if *cursor == 0 {
+                *cursor = 1;
+                if let Some(value) = &node.value {
+                    return Some((nibbles_to_hex(&self.path), value));
+                }
+                continue;
+            }
+            match node.next_child(*cursor - 1) {
+                Some(child) => {
+                    *cursor = child.symbol as usize + 2;
+                    let above = self.path.len();
+                    self.path.push(child.symbol);
+                    self.path.extend_from_slice(&child.prefix);
+                    self.stack.push((child, 0, above));
+                }
+                None => {
+                    let (_, _, above) = self.stack.pop().unwrap();
+                    self.path.truncate(above);
+                }
+            }
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a PatriciaTrie<V> {
+    type Item = (String, &'a V);
+    type IntoIter = PatriciaIter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Same tree drawing as Trie's, with each line showing the whole edge into the node. The
+// indent is one shared string cut back to the depth of each line.
+impl<V: fmt::Display> fmt::Display for PatriciaTrie<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value_str = |node: &PatriciaNode<V>| {
+            node.value
+                .as_ref()
+                .map(|v| format!(" = {}", v))
+                .unwrap_or_default()
+        };
+        writeln!(f, "(root){}", value_str(&self.root))?;
+
+        // (node, depth, whether it is its parent's last child); children go on in reverse so
+        // they come off in symbol order
+        let mut stack: Vec<(&PatriciaNode<V>, usize, bool)> = Vec::new();
+        fn push_children<'a, V>(
+            stack: &mut Vec<(&'a PatriciaNode<V>, usize, bool)>,
+            node: &'a PatriciaNode<V>,
+            depth: usize,
+        ) {
+            let last = node.children.len().wrapping_sub(1);
+            for (i, child) in node.children.iter().enumerate().rev() {
+                stack.push((&**child, depth, i == last));
+            }
+        }
+        push_children(&mut stack, &self.root, 0);
+        let mut indent = String::new();
+        // widths[d] is the length of `indent` for a line at depth d
+        let mut widths = vec![0];
+        while let Some((node, depth, is_last)) = stack.pop() {
+            widths.truncate(depth + 1);
+            indent.truncate(widths[depth]);
+            let bullet = if is_last { "└── " } else { "├── " };
+            writeln!(
+                f,
+                "{}{}{}{}{}",
+                indent,
+                bullet,
+                NIBBLE_TO_HEX[node.symbol as usize] as char,
+                nibbles_to_hex(&node.prefix),
+                value_str(node)
+            )?;
+            indent.push_str(if is_last { "    " } else { "│   " });
+            widths.push(indent.len());
+            push_children(&mut stack, node, depth + 1);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+// A hex trie whose nodes are shared between copies.
+//
+// Children are `Arc`s, so cloning a trie copies the root pointer and nothing else. A write
+// goes down the key through `Arc::make_mut`, which copies a node only if another trie still
+// points at it; every node off the key's path stays shared. Once copied, a node is this trie's
+// alone and later writes through it copy nothing.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::key::{hex_to_nibbles, nibbles_to_hex};
+
+#[derive(Clone)]
+struct PersistentNode<V> {
+    children: [Option<Arc<PersistentNode<V>>>; 16],
+    value: Option<V>,
+}
+
+impl<V> PersistentNode<V> {
+    fn new() -> Self {
+        Self {
+            children: Default::default(),
+            value: None,
+        }
+    }
+
+    fn child_count(&self) -> usize {
+        self.children.iter().flatten().count()
+    }
+}
+
+// Dropping a long chain recursively could overflow the stack. The children a node was the last
+// owner of are unwrapped onto a list instead; shared ones only lose a count.
+impl<V> Drop for PersistentNode<V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.children.iter_mut().filter_map(Option::take).collect();
+        while let Some(child) = stack.pop() {
+            if let Some(mut node) = Arc::into_inner(child) {
+                stack.extend(node.children.iter_mut().filter_map(Option::take));
+            }
+        }
+    }
+}
+
+/// A hex-keyed trie that shares its nodes with its clones.
+///
+/// Cloning takes constant time, and the clone is a snapshot: writes to
+/// either copy afterwards copy the nodes along the written key and leave the
+/// other copy as it was. Everything off that path stays shared, so keeping
+/// many snapshots of a large trie costs only the paths that changed between
+/// them. Keys are read leniently, like [`Trie::insert_lossy`](crate::Trie::insert_lossy)
+/// reads them.
+///
+/// ```
+/// let mut trie = trie::PersistentTrie::new();
+/// trie.insert("a1", 1);
+/// trie.insert("b0", 2);
+/// let snapshot = trie.clone();
+/// trie.insert("a1", 10);
+/// trie.delete("b0");
+/// assert_eq!(snapshot.get("a1"), Some(&1));
+/// assert_eq!(snapshot.get("b0"), Some(&2));
+/// assert_eq!(trie.get("a1"), Some(&10));
+/// assert_eq!(trie.len(), 1);
+/// ```
+pub struct PersistentTrie<V> {
+    root: Arc<PersistentNode<V>>,
+    len: usize,
+}
+
+impl<V> Clone for PersistentTrie<V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: Arc::clone(&self.root),
+            len: self.len,
+        }
+    }
+}
+
+impl<V> Default for PersistentTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> PersistentTrie<V> {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self {
+            root: Arc::new(PersistentNode::new()),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of stored values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no values are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn find(&self, hex_key: &str) -> Option<&PersistentNode<V>> {
+        let mut node = &*self.root;
+        for nibble in hex_to_nibbles(hex_key) {
+            node = node.children[nibble].as_deref()?;
+        }
+        Some(node)
+    }
+
+    /// Returns the value stored under `hex_key`, if any.
+    pub fn get(&self, hex_key: &str) -> Option<&V> {
+        self.find(hex_key)?.value.as_ref()
+    }
+
+    /// Returns `true` if a value is stored under `hex_key`.
+    pub fn contains_key(&self, hex_key: &str) -> bool {
+        self.get(hex_key).is_some()
+    }
+
+    /// Returns `true` if this trie and `other` hold the very same nodes
+    /// below `prefix`, rather than equal copies of them, which is the case
+    /// for any part of a snapshot that neither side has written to since.
+    /// A prefix neither trie has nodes for counts as shared.
+    ///
+    /// ```
+    /// let mut trie = trie::PersistentTrie::new();
+    /// trie.insert("a1", 1);
+    /// trie.insert("b0", 2);
+    /// let snapshot = trie.clone();
+    /// trie.insert("b1", 3);
+    /// assert!(trie.shares_subtree(&snapshot, "a"));
+    /// assert!(!trie.shares_subtree(&snapshot, "b"));
+    /// ```
+    pub fn shares_subtree(&self, other: &Self, prefix: &str) -> bool {
+        match (self.find(prefix), other.find(prefix)) {
+            (Some(a), Some(b)) => core::ptr::eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+    }
+
+    /// Returns an iterator over all entries in key order.
+    pub fn iter(&self) -> PersistentIter<'_, V> {
+        PersistentIter {
+            stack: vec![(&*self.root, 0)],
+            path: Vec::new(),
+        }
+    }
+}
+
+impl<V: Clone> PersistentTrie<V> {
+    /// Stores `value` under `hex_key`, returning the value that was replaced.
+    ///
+    /// Nodes along the key that a clone still shares are copied first.
+    pub fn insert(&mut self, hex_key: &str, value: V) -> Option<V> {
+        let mut node = Arc::make_mut(&mut self.root);
+        for nibble in hex_to_nibbles(hex_key) {
+            let child =
+                node.children[nibble].get_or_insert_with(|| Arc::new(PersistentNode::new()));
+            node = Arc::make_mut(child);
+        }
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Removes the value stored under `hex_key` and returns it, pruning the
+    /// nodes it leaves empty.
+    ///
+    /// A missing key copies nothing. The value is cloned out if a clone of
+    /// the trie still holds it.
+    pub fn delete(&mut self, hex_key: &str) -> Option<V> {
+        let nibbles: Vec<usize> = hex_to_nibbles(hex_key).collect();
+        // The deepest node above the key that keeps a value or another child: the chain below
+        // it exists only for this key and goes if the key's node is left empty.
+        let mut cut = 0;
+        let mut node = &*self.root;
+        for (depth, &nibble) in nibbles.iter().enumerate() {
+            if node.value.is_some() || node.child_count() > 1 {
+                cut = depth;
+            }
+            node = node.children[nibble].as_deref()?;
+        }
+        node.value.as_ref()?;
+        self.len -= 1;
+        if nibbles.is_empty() || node.child_count() > 0 {
+            let mut node = Arc::make_mut(&mut self.root);
+            for &nibble in &nibbles {
+                node = Arc::make_mut(node.children[nibble].as_mut().unwrap());
+            }
+            return node.value.take();
+        }
+        let mut node = Arc::make_mut(&mut self.root);
+        for &nibble in &nibbles[..cut] {
+            node = Arc::make_mut(node.children[nibble].as_mut().unwrap());
+        }
+        let mut chain = node.children[nibbles[cut]].take().unwrap();
+        for &nibble in &nibbles[cut + 1..] {
+            chain = Arc::clone(chain.children[nibble].as_ref().unwrap());
+        }
+        match Arc::try_unwrap(chain) {
+            Ok(mut node) => node.value.take(),
+            Err(shared) => shared.value.clone(),
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a PersistentTrie<V> {
+    type Item = (String, &'a V);
+    type IntoIter = PersistentIter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`PersistentTrie`] in key order.
+///
+/// Returned by [`PersistentTrie::iter`].
+pub struct PersistentIter<'a, V> {
+    // A node being visited and the next child slot to look at; slot 0 means its own value
+    // hasn't been yielded yet.
+    stack: Vec<(&'a PersistentNode<V>, usize)>,
+    path: Vec<u8>,
+}
+
+impl<'a, V> Iterator for PersistentIter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, slot)) = self.stack.last_mut() {
+            let node: &'a PersistentNode<V> = node;
+            if *slot == 0 {
+                *slot = 1;
+                if let Some(value) = &node.value {
+                    return Some((nibbles_to_hex(&self.path), value));
+                }
+            }
+            match (*slot - 1..16).find(|&i| node.children[i].is_some()) {
+                Some(nibble) => {
+                    *slot = nibble + 2;
+                    self.path.push(nibble as u8);
+                    self.stack
+                        .push((node.children[nibble].as_deref().unwrap(), 0));
+                }
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,195 @@
+// Merkle inclusion and absence proofs.
+//
+// A proof walks the key's path from the root. For every node above the key's node it carries
+// that node's value and the hashes of its other filled child slots; for the key's node it
+// carries the child hashes only, since the verifier brings the value. Verifying hashes the
+// key's node and works back up, dropping each recomputed hash into the slot picked by the
+// key's nibble at that level.
+//
+// An absence proof stops at the deepest node on the key's path that exists. Either the key
+// ends there and the node has no value, or the key goes on and the node's slot for the next
+// nibble is empty. The node is sent whole (value and all child hashes), so the verifier can
+// check whichever of the two it is.
+
+use alloc::vec::Vec;
+
+use crate::codec::ValueCodec;
+use crate::key::hex_to_nibbles;
+use crate::merkle::{EMPTY_SLOT, TrieHasher, node_hash, subtree_hash};
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// The hashes of a node's filled child slots, in slot order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildHashes {
+    /// Bit `i` is set when slot `i` holds a child.
+    pub occupied: u16,
+    /// One hash per set bit of `occupied`.
+    pub hashes: Vec<[u8; 32]>,
+}
+
+/// One node on the path to a proven key, above the key's own node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofStep {
+    /// The node's encoded value, if it has one.
+    pub value: Option<Vec<u8>>,
+    /// The node's children other than the one the path continues into.
+    pub siblings: ChildHashes,
+}
+
+/// Evidence that a key holds a value under a given root, checked with
+/// [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    /// One step per nibble of the key, starting at the root.
+    pub steps: Vec<ProofStep>,
+    /// The children of the key's node.
+    pub children: ChildHashes,
+}
+
+/// Evidence that a key holds no value under a given root, checked with
+/// [`verify_absent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbsenceProof {
+    /// One step per nibble of the key up to the node where the path stops,
+    /// starting at the root.
+    pub steps: Vec<ProofStep>,
+    /// The encoded value of the node where the path stops.
+    pub value: Option<Vec<u8>>,
+    /// The children of the node where the path stops.
+    pub children: ChildHashes,
+}
+
+impl ChildHashes {
+    // Hashes every child of `node` except the one in slot `skip`.
+    pub(crate) fn of<H: TrieHasher, V: ValueCodec>(node: &Node<V>, skip: Option<usize>) -> Self {
+        let mut out = ChildHashes {
+            occupied: 0,
+            hashes: Vec::new(),
+        };
+        for (i, child) in node.children() {
+            if skip != Some(i) {
+                out.occupied |= 1 << i;
+                out.hashes.push(subtree_hash::<H, V>(child));
+            }
+        }
+        out
+    }
+
+    // Expands back to all 16 slots, or None if the hashes don't match the bitmap.
+    pub(crate) fn slots(&self) -> Option<[[u8; 32]; 16]> {
+        if self.hashes.len() != self.occupied.count_ones() as usize {
+            return None;
+        }
+        let mut slots = [EMPTY_SLOT; 16];
+        let mut hashes = self.hashes.iter();
+        for (i, slot) in slots.iter_mut().enumerate() {
+            if self.occupied & (1 << i) != 0 {
+                *slot = *hashes.next().unwrap();
+            }
+        }
+        Some(slots)
+    }
+}
+
+// Folds a node hash back up through `steps` to the root. `nibbles` is the path to the node
+// the hash belongs to, one nibble per step.
+pub(crate) fn fold_steps<H: TrieHasher>(
+    mut hash: [u8; 32],
+    steps: &[ProofStep],
+    nibbles: &[usize],
+) -> Option<[u8; 32]> {
+    for (step, &nibble) in steps.iter().zip(nibbles).rev() {
+        if step.siblings.occupied & (1 << nibble) != 0 {
+            return None;
+        }
+        let mut slots = step.siblings.slots()?;
+        slots[nibble] = hash;
+        hash = node_hash::<H>(step.value.as_deref(), &slots);
+    }
+    Some(hash)
+}
+
+// The steps down to (but not including) the node at the end of `nibbles`, and that node.
+pub(crate) fn path_steps<'a, H: TrieHasher, V: ValueCodec>(
+    mut node: &'a Node<V>,
+    nibbles: &[usize],
+) -> (Vec<ProofStep>, &'a Node<V>) {
+    let mut steps = Vec::with_capacity(nibbles.len());
+    for &nibble in nibbles {
+        steps.push(ProofStep {
+            value: node.value.as_ref().map(|v| v.encode().into_owned()),
+            siblings: ChildHashes::of::<H, V>(node, Some(nibble)),
+        });
+        node = node.child(nibble).unwrap();
+    }
+    (steps, node)
+}
+
+impl<V: ValueCodec> Trie<V> {
+    /// Builds an inclusion proof for `hex_key` under the root returned by
+    /// [`Trie::root_hash`] with the same hasher, or `None` if the key isn't
+    /// present.
+    pub fn prove<H: TrieHasher>(&self, hex_key: &str) -> Option<Proof> {
+        let nibbles: Vec<usize> = hex_to_nibbles(hex_key).collect();
+        self.root.find(nibbles.iter().copied())?.value.as_ref()?;
+        let (steps, node) = path_steps::<H, V>(&self.root, &nibbles);
+        Some(Proof {
+            steps,
+            children: ChildHashes::of::<H, V>(node, None),
+        })
+    }
+
+    /// Builds a proof that `hex_key` holds no value, or `None` if it does.
+    pub fn prove_absent<H: TrieHasher>(&self, hex_key: &str) -> Option<AbsenceProof> {
+        let nibbles: Vec<usize> = hex_to_nibbles(hex_key).collect();
+        let mut depth = 0;
+        let mut node = &self.root;
+        while let Some(child) = nibbles.get(depth).and_then(|&n| node.child(n)) {
+            node = child;
+            depth += 1;
+        }
+        if depth == nibbles.len() && node.value.is_some() {
+            return None;
+        }
+        let (steps, node) = path_steps::<H, V>(&self.root, &nibbles[..depth]);
+        Some(AbsenceProof {
+            steps,
+            value: node.value.as_ref().map(|v| v.encode().into_owned()),
+            children: ChildHashes::of::<H, V>(node, None),
+        })
+    }
+}
+
+/// Checks that `proof` shows `hex_key` holding the encoded `value` in a trie
+/// whose root hash under `H` is `root`.
+pub fn verify<H: TrieHasher>(root: [u8; 32], hex_key: &str, value: &[u8], proof: &Proof) -> bool {
+    let nibbles: Vec<usize> = hex_to_nibbles(hex_key).collect();
+    if proof.steps.len() != nibbles.len() {
+        return false;
+    }
+    let Some(slots) = proof.children.slots() else {
+        return false;
+    };
+    let leaf = node_hash::<H>(Some(value), &slots);
+    fold_steps::<H>(leaf, &proof.steps, &nibbles) == Some(root)
+}
+
+/// Checks that `proof` shows `hex_key` holding no value in a trie whose root
+/// hash under `H` is `root`.
+pub fn verify_absent<H: TrieHasher>(root: [u8; 32], hex_key: &str, proof: &AbsenceProof) -> bool {
+    let nibbles: Vec<usize> = hex_to_nibbles(hex_key).collect();
+    let depth = proof.steps.len();
+    let diverges = match nibbles.get(depth) {
+        // the key goes on, so the slot it would continue into has to be empty
+        Some(&next) => proof.children.occupied & (1 << next) == 0,
+        // the key ends at this node, so the node has to be valueless
+        None if depth == nibbles.len() => proof.value.is_none(),
+        None => false,
+    };
+    let Some(slots) = proof.children.slots() else {
+        return false;
+    };
+    let hash = node_hash::<H>(proof.value.as_deref(), &slots);
+    diverges && fold_steps::<H>(hash, &proof.steps, &nibbles[..depth]) == Some(root)
+}
@@ -0,0 +1,245 @@
+// Saved states a trie can go back to. Taking a snapshot only leaves a mark in an undo log;
+// from then on every method that changes the trie first notes in the log what it's about to
+// overwrite, either the value at one key or, for changes to a whole subtree, the subtree as
+// it was. Rolling back replays the log backwards from the mark. The trie's shape follows from
+// the keys it holds, so putting back each key's value also puts back exactly the nodes there
+// were, pruning included.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::iter::Walk;
+use crate::key::key_symbols;
+use crate::node::{Node, Pool};
+use crate::trie::Trie;
+
+/// Names a state saved by [`Trie::snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SnapshotId(u64);
+
+#[derive(Clone)]
+pub(crate) struct Snapshots<V, const N: usize> {
+    // The open snapshots, oldest first, each with where its part of `log` starts and the
+    // trie's length when it was taken.
+    open: Vec<(SnapshotId, usize, usize)>,
+    // What the changes since the oldest open snapshot overwrote, oldest first.
+    log: Vec<Undo<V, N>>,
+    // Copies a value into the log. It's set by `snapshot`, the one place `V: Clone` is known,
+    // so the methods that record changes don't need the bound themselves.
+    clone: Option<fn(&V) -> V>,
+    next: u64,
+}
+
+// One change to undo, by the symbol path it happened at.
+#[derive(Clone)]
+enum Undo<V, const N: usize> {
+    // The path held this value, or none.
+    Value(Vec<u8>, Option<V>),
+    // The path held this subtree, or none, with nothing under it that's there now.
+    Subtree(Vec<u8>, Option<Box<Node<V, N>>>),
+}
+
+impl<V, const N: usize> Default for Snapshots<V, N> {
+    fn default() -> Self {
+        Self {
+            open: Vec::new(),
+            log: Vec::new(),
+            clone: None,
+            next: 0,
+        }
+    }
+}
+
+impl<V, const N: usize> Snapshots<V, N> {
+    fn position(&self, id: SnapshotId) -> usize {
+        self.open
+            .iter()
+            .position(|(open, _, _)| *open == id)
+            .unwrap_or_else(|| panic!("{:?} was already rolled back or committed", id))
+    }
+
+    // Whether changes have to be logged, which only costs anything while a snapshot is open.
+    pub(crate) fn recording(&self) -> bool {
+        !self.open.is_empty()
+    }
+
+    // Notes that `path` held `old` before a change.
+    pub(crate) fn save_value(&mut self, path: &[u8], old: Option<&V>) {
+        if let (true, Some(clone)) = (self.recording(), self.clone) {
+            self.log.push(Undo::Value(path.to_vec(), old.map(clone)));
+        }
+    }
+
+    // Notes that `path` held `old` before a change, the caller having no more use for it.
+    pub(crate) fn save_subtree(&mut self, path: &[u8], old: Option<Box<Node<V, N>>>) {
+        if self.recording() {
+            self.log.push(Undo::Subtree(path.to_vec(), old));
+        }
+    }
+
+    // Notes the whole of `root` before a change that may touch any of it.
+    pub(crate) fn save_root(&mut self, root: &Node<V, N>) {
+        if let (true, Some(clone)) = (self.recording(), self.clone) {
+            let copy = root.map_values_ref(|_, value| clone(value));
+            self.log
+                .push(Undo::Subtree(Vec::new(), Some(Box::new(copy))));
+        }
+    }
+
+    // Notes every value under `node`, whose path is `path`, as there before a change.
+    pub(crate) fn save_values(&mut self, node: &Node<V, N>, path: Vec<u8>) {
+        if !self.recording() {
+            return;
+        }
+        let mut walk = Walk::new(node, path);
+        while let Some(value) = walk.next_value() {
+            self.save_value(&walk.path, Some(value));
+        }
+    }
+}
+
+impl<V, const N: usize> Trie<V, N> {
+    // Notes the value under `hex_key` before a change, if a snapshot is open.
+    pub(crate) fn save_key(&mut self, hex_key: &str) {
+        if self.snapshots.recording() {
+            let path: Vec<u8> = key_symbols::<N>(hex_key).map(|s| s as u8).collect();
+            self.save_path(&path);
+        }
+    }
+
+    // Notes the value at a symbol path before a change, if a snapshot is open.
+    pub(crate) fn save_path(&mut self, path: &[u8]) {
+        if self.snapshots.recording() {
+            let node = self.root.find(path.iter().map(|&s| s as usize));
+            let old = node.and_then(|node| node.value.as_ref());
+            self.snapshots.save_value(path, old);
+        }
+    }
+}
+
+impl<V: Clone, const N: usize> Trie<V, N> {
+    /// Saves the trie as it is now, for [`Trie::rollback`] to go back to.
+    ///
+    /// Taking a snapshot copies nothing. While one is open, each change
+    /// first saves whatever it overwrites, which is a clone of one value for
+    /// the methods that change one key, and of every value for the ones
+    /// that may change them all, such as [`Trie::retain`] or
+    /// [`Trie::values_mut`]. [`Trie::clear`] and [`Trie::remove_prefix`]
+    /// keep the nodes they take instead of copying them. Snapshots nest:
+    /// rolling back to one also drops every snapshot taken after it.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1", 1);
+    /// let before = trie.snapshot();
+    /// trie.insert_lossy("a1f", 2);
+    /// let inner = trie.snapshot();
+    /// trie.delete_lossy("a1");
+    /// trie.rollback(inner);
+    /// assert_eq!(trie.get_lossy("a1"), Some(&1));
+    /// trie.rollback(before);
+    /// assert_eq!(trie.get_lossy("a1f"), None);
+    /// assert_eq!(trie.len(), 1);
+    /// ```
+    pub fn snapshot(&mut self) -> SnapshotId {
+        let id = SnapshotId(self.snapshots.next);
+        self.snapshots.next += 1;
+        self.snapshots.clone = Some(V::clone);
+        let start = self.snapshots.log.len();
+        self.snapshots.open.push((id, start, self.len));
+        id
+    }
+
+    /// Puts the trie back the way it was when `id` was taken, node for
+    /// node, and drops `id` along with every snapshot taken after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was already rolled back to, committed or dropped by
+    /// rolling back to an earlier snapshot.
+    pub fn rollback(&mut self, id: SnapshotId) {
+        let at = self.snapshots.position(id);
+        let (_, start, len) = self.snapshots.open[at];
+        self.snapshots.open.truncate(at);
+        while self.snapshots.log.len() > start {
+            match self.snapshots.log.pop().unwrap() {
+                Undo::Value(path, old) => {
+                    let path = path.iter().map(|&s| s as usize);
+                    match old {
+                        Some(value) => {
+                            self.root.insert_nibbles(path, value, &mut self.pool);
+                        }
+                        None => {
+                            self.root.delete_nibbles(path, &mut self.pool);
+                        }
+                    }
+                }
+                Undo::Subtree(path, old) => self.root.replace_at(&path, old, &mut self.pool),
+            }
+        }
+        if self.snapshots.open.is_empty() {
+            self.snapshots.log = Vec::new();
+        }
+        self.len = len;
+    }
+
+    /// Keeps everything written since `id` was taken. Snapshots taken after
+    /// it stay, and once no snapshot is open the saved values are freed.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`Trie::rollback`].
+    pub fn commit(&mut self, id: SnapshotId) {
+        let at = self.snapshots.position(id);
+        let (_, start, _) = self.snapshots.open.remove(at);
+        // What `id` saved is still needed to roll back past it to an earlier snapshot, but
+        // the oldest one's share of the log has nothing left to go back to.
+        if at == 0 {
+            let end = self
+                .snapshots
+                .open
+                .first()
+                .map_or(start, |&(_, start, _)| start);
+            self.snapshots.log.drain(start..end);
+            for (_, start, _) in &mut self.snapshots.open {
+                *start -= end;
+            }
+        }
+        if self.snapshots.open.is_empty() {
+            self.snapshots.log = Vec::new();
+        }
+    }
+}
+
+impl<V, const N: usize> Node<V, N> {
+    // Puts `old` where the subtree at `path` is, dropping what's there now and pruning the
+    // ancestors that leaves empty. The root itself is emptied instead.
+    fn replace_at(&mut self, path: &[u8], old: Option<Box<Node<V, N>>>, pool: &mut Pool<V, N>) {
+        let path: Vec<usize> = path.iter().map(|&s| s as usize).collect();
+        let Some((&last, above)) = path.split_last() else {
+            self.recycle_children(pool);
+            self.value = None;
+            self.invalidate();
+            if let Some(old) = old {
+                for (_, child) in old.children() {
+                    pool.moved_in(child);
+                }
+                *self = *old;
+            }
+            return;
+        };
+        if let Some(current) = self.detach(&path, pool) {
+            pool.put(current);
+        }
+        if let Some(old) = old {
+            let mut cur = self;
+            for &symbol in above {
+                cur.invalidate();
+                cur = cur.child_or_insert(symbol, pool);
+            }
+            cur.invalidate();
+            pool.moved_in(&old);
+            cur.set_child(last, old);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::iter::Values;
+use crate::key::{hex_to_nibbles, nibbles_to_hex};
+use crate::merkle::TrieHasher;
+use crate::trie::Trie;
+
+/// A trie that stores every key under its hash, so crafted keys can't pile
+/// up along one long shared prefix.
+///
+/// Keys are normalised the same way [`Trie`] reads them (non-hex characters
+/// skipped, lowercase), hashed with `H`, and the 64 nibbles of the hash are
+/// used as the path. Lookups with the original key work as usual, but
+/// iteration follows hash order rather than key order. Original keys are only
+/// available while iterating if the trie was created with
+/// [`SecureTrie::with_preimages`].
+pub struct SecureTrie<V, H> {
+    inner: Trie<Stored<V>>,
+    keep_preimages: bool,
+    hasher: PhantomData<H>,
+}
+
+struct Stored<V> {
+    preimage: Option<String>,
+    value: V,
+}
+
+impl<V, H: TrieHasher> Default for SecureTrie<V, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, H: TrieHasher> SecureTrie<V, H> {
+    /// Creates an empty trie that doesn't keep original keys.
+    pub fn new() -> Self {
+        Self {
+            inner: Trie::new(),
+            keep_preimages: false,
+            hasher: PhantomData,
+        }
+    }
+
+    /// Creates an empty trie that stores each original key next to its
+    /// value, so [`SecureTrie::iter`] can report it.
+    pub fn with_preimages() -> Self {
+        Self {
+            keep_preimages: true,
+            ..Self::new()
+        }
+    }
+
+    /// Returns the lowercase hex path `hex_key` is stored under.
+    pub fn hashed_key(hex_key: &str) -> String {
+        let hash = H::hash(canonical(hex_key).as_bytes());
+        let nibbles: Vec<u8> = hash.iter().flat_map(|&b| [b >> 4, b & 0xf]).collect();
+        nibbles_to_hex(&nibbles)
+    }
+
+    /// Inserts `value` under `hex_key`, returning the previous value.
+    pub fn insert(&mut self, hex_key: &str, value: V) -> Option<V> {
+        let preimage = self.keep_preimages.then(|| canonical(hex_key));
+        self.inner
+            .insert_lossy(&Self::hashed_key(hex_key), Stored { preimage, value })
+            .map(|old| old.value)
+    }
+
+    /// Returns the value stored under `hex_key`, if any.
+    pub fn get(&self, hex_key: &str) -> Option<&V> {
+        self.inner
+            .get_lossy(&Self::hashed_key(hex_key))
+            .map(|s| &s.value)
+    }
+
+    /// Returns a mutable reference to the value stored under `hex_key`, if
+    /// any.
+    pub fn get_mut(&mut self, hex_key: &str) -> Option<&mut V> {
+        self.inner
+            .get_mut(&Self::hashed_key(hex_key))
+            .map(|s| &mut s.value)
+    }
+
+    /// Returns `true` if a value is stored under `hex_key`.
+    pub fn contains_key(&self, hex_key: &str) -> bool {
+        self.inner.contains_key(&Self::hashed_key(hex_key))
+    }
+
+    /// Removes the value stored under `hex_key` and returns it.
+    pub fn delete(&mut self, hex_key: &str) -> Option<V> {
+        self.inner
+            .delete_lossy(&Self::hashed_key(hex_key))
+            .map(|s| s.value)
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the trie holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns an iterator over the original keys (if kept) and values, in
+    /// the order of their hashed keys.
+    pub fn iter(&self) -> SecureIter<'_, V> {
+        SecureIter {
+            values: self.inner.values(),
+        }
+    }
+}
+
+/// An iterator over the entries of a [`SecureTrie`] in hashed-key order.
+///
+/// Yields the original key, or `None` if preimages aren't kept, with each
+/// value. Returned by [`SecureTrie::iter`].
+pub struct SecureIter<'a, V> {
+    values: Values<'a, Stored<V>>,
+}
+
+impl<'a, V> Iterator for SecureIter<'a, V> {
+    type Item = (Option<&'a str>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let stored = self.values.next()?;
+        Some((stored.preimage.as_deref(), &stored.value))
+    }
+}
+
+// The key as Trie reads it, which is also what gets hashed.
+fn canonical(hex_key: &str) -> String {
+    let nibbles: Vec<u8> = hex_to_nibbles(hex_key).map(|n| n as u8).collect();
+    nibbles_to_hex(&nibbles)
+}
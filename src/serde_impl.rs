@@ -0,0 +1,58 @@
+// Serde support, enabled by the `serde` feature.
+//
+// A trie is serialized as a map from canonical hex keys to values, in key order. That format
+// has no nested structure at all, so there is no child vector that could have the wrong
+// length and no nesting depth for adversarial input to blow the stack with: deserializing is
+// just one insert per entry. Interior and root values are ordinary entries (the root is the
+// empty key).
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::trie::Trie;
+
+impl<V: Serialize> Serialize for Trie<V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len))?;
+        for (key, value) in self {
+            map.serialize_entry(&key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for Trie<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(TrieVisitor(PhantomData))
+    }
+}
+
+struct TrieVisitor<V>(PhantomData<V>);
+
+impl<'de, V: Deserialize<'de>> Visitor<'de> for TrieVisitor<V> {
+    type Value = Trie<V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map from hex keys to values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut trie = Trie::new();
+        while let Some((key, value)) = map.next_entry::<String, V>()? {
+            if !key.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(de::Error::invalid_value(
+                    de::Unexpected::Str(&key),
+                    &"a key made only of hex digits",
+                ));
+            }
+            if trie.insert_lossy(&key, value).is_some() {
+                return Err(de::Error::custom(format_args!("duplicate key {key:?}")));
+            }
+        }
+        Ok(trie)
+    }
+}
@@ -0,0 +1,79 @@
+use alloc::boxed::Box;
+
+use crate::node::Node;
+use crate::trie::Trie;
+
+// Key-set operations. All of them walk both tries in lockstep and decide per child slot: where
+// only one side has a subtree it is either skipped outright or cloned whole without looking
+// inside, and only slots present on both sides are descended into.
+
+impl<V: Clone> Trie<V> {
+    /// Returns a trie with every key in `self` or `other`.
+    ///
+    /// Values come from `self` for keys present in both.
+    pub fn union(&self, other: &Trie<V>) -> Trie<V> {
+        Trie::from_root(union_rec(&self.root, &other.root))
+    }
+
+    /// Returns a trie with the keys present in both `self` and `other`,
+    /// carrying the values from `self`.
+    pub fn intersection<W>(&self, other: &Trie<W>) -> Trie<V> {
+        Trie::from_root(intersection_rec(&self.root, &other.root))
+    }
+
+    /// Returns a trie with the keys of `self` that are not in `other`,
+    /// carrying the values from `self`.
+    pub fn difference<W>(&self, other: &Trie<W>) -> Trie<V> {
+        Trie::from_root(difference_rec(&self.root, &other.root))
+    }
+}
+
+fn union_rec<V: Clone>(a: &Node<V>, b: &Node<V>) -> Node<V> {
+    let mut node = Node::new();
+    node.value = a.value.clone().or_else(|| b.value.clone());
+    for i in 0..16 {
+        let child = match (a.child(i), b.child(i)) {
+            (Some(ca), Some(cb)) => union_rec(ca, cb),
+            (Some(c), None) | (None, Some(c)) => c.clone(),
+            (None, None) => continue,
+        };
+        node.set_child(i, Box::new(child));
+    }
+    node
+}
+
+fn intersection_rec<V: Clone, W>(a: &Node<V>, b: &Node<W>) -> Node<V> {
+    let mut node = Node::new();
+    if b.value.is_some() {
+        node.value = a.value.clone();
+    }
+    for i in 0..16 {
+        if let (Some(ca), Some(cb)) = (a.child(i), b.child(i)) {
+            let child = intersection_rec(ca, cb);
+            if !child.is_empty() {
+                node.set_child(i, Box::new(child));
+            }
+        }
+    }
+    node
+}
+
+fn difference_rec<V: Clone, W>(a: &Node<V>, b: &Node<W>) -> Node<V> {
+    let mut node = Node::new();
+    if b.value.is_none() {
+        node.value = a.value.clone();
+    }
+    for i in 0..16 {
+        match (a.child(i), b.child(i)) {
+            (Some(ca), Some(cb)) => {
+                let child = difference_rec(ca, cb);
+                if !child.is_empty() {
+                    node.set_child(i, Box::new(child));
+                }
+            }
+            (Some(ca), None) => node.set_child(i, Box::new(ca.clone())),
+            _ => {}
+        }
+    }
+    node
+}
@@ -0,0 +1,186 @@
+// Compact binary snapshots.
+//
+// Layout: the magic bytes `RTRI`, a version byte, then every node in preorder (a node, then
+// its children in nibble order). A node is a little-endian u16 bitmap of the children that
+// follow, a flag byte saying whether it holds a value, and if so a little-endian u32 length
+// and the value bytes from `ValueCodec`.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use crate::codec::ValueCodec;
+use crate::node::{Node, Pool};
+use crate::observe::Observer;
+use crate::rollback::Snapshots;
+use crate::trie::Trie;
+
+const MAGIC: &[u8; 4] = b"RTRI";
+const VERSION: u8 = 1;
+
+/// Why a snapshot could not be loaded.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading from the source failed.
+    Io(io::Error),
+    /// The input doesn't start with the snapshot magic bytes.
+    BadMagic,
+    /// The snapshot was written by an unknown format version.
+    UnsupportedVersion(u8),
+    /// The input ended in the middle of the snapshot.
+    Truncated,
+    /// The input is a snapshot but its contents are inconsistent.
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "failed to read snapshot: {}", err),
+            SnapshotError::BadMagic => write!(f, "not a trie snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot version {}", v)
+            }
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::Corrupt(reason) => write!(f, "corrupt snapshot: {}", reason),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            SnapshotError::Truncated
+        } else {
+            SnapshotError::Io(err)
+        }
+    }
+}
+
+impl<V: ValueCodec> Trie<V> {
+    /// Writes a binary snapshot of the trie to `w`.
+    pub fn save_to<W: Write>(&self, w: W) -> io::Result<()> {
+        let mut w = BufWriter::new(w);
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION])?;
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            let mut bitmap = 0u16;
+            for (i, _) in node.children() {
+                bitmap |= 1 << i;
+            }
+            w.write_all(&bitmap.to_le_bytes())?;
+            match &node.value {
+                Some(value) => {
+                    let bytes = value.encode();
+                    let len = u32::try_from(bytes.len()).map_err(|_| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "value longer than 4 GiB")
+                    })?;
+                    w.write_all(&[1])?;
+                    w.write_all(&len.to_le_bytes())?;
+                    w.write_all(&bytes)?;
+                }
+                None => w.write_all(&[0])?,
+            }
+            // pushed in reverse so the lowest nibble is popped, and written, first
+            stack.extend(node.children().rev().map(|(_, child)| child));
+        }
+        w.flush()
+    }
+
+    /// Reads a trie back from a snapshot written by [`Trie::save_to`].
+    ///
+    /// The input is read incrementally and nodes are rebuilt with an
+    /// explicit stack, so neither file size nor nesting depth is limited by
+    /// memory for a buffer or by the call stack.
+    pub fn load_from<R: Read>(r: R) -> Result<Trie<V>, SnapshotError> {
+        let mut r = BufReader::new(r);
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = read_u8(&mut r)?;
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let mut len = 0;
+        // each frame is a node being filled in, the children still to read, and the nibble
+        // it hangs off in its parent
+        let (root, pending) = read_node(&mut r, &mut len)?;
+        let mut stack = vec![(root, pending, 0)];
+        let root = loop {
+            let (_, pending, _) = stack.last_mut().unwrap();
+            if *pending == 0 {
+                let (node, _, nibble) = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some((parent, _, _)) => parent.set_child(nibble, Box::new(node)),
+                    None => break node,
+                }
+                continue;
+            }
+            let nibble = pending.trailing_zeros() as usize;
+            *pending &= *pending - 1;
+            let (child, child_pending) = read_node(&mut r, &mut len)?;
+            if child.value.is_none() && child_pending == 0 {
+                return Err(SnapshotError::Corrupt("empty node"));
+            }
+            stack.push((child, child_pending, nibble));
+        };
+
+        if r.read(&mut [0])? != 0 {
+            return Err(SnapshotError::Corrupt("trailing data"));
+        }
+        Ok(Trie {
+            root,
+            len,
+            pool: Pool::new(),
+            snapshots: Snapshots::default(),
+            observer: Observer::default(),
+        })
+    }
+}
+
+fn read_u8(r: &mut impl Read) -> Result<u8, SnapshotError> {
+    let mut buf = [0; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+// Reads one node header and value, returning the node and its child bitmap.
+fn read_node<V: ValueCodec>(
+    r: &mut impl Read,
+    len: &mut usize,
+) -> Result<(Node<V>, u16), SnapshotError> {
+    let mut bitmap = [0; 2];
+    r.read_exact(&mut bitmap)?;
+    let mut node = Node::new();
+    match read_u8(r)? {
+        0 => {}
+        1 => {
+            let mut value_len = [0; 4];
+            r.read_exact(&mut value_len)?;
+            let value_len = u32::from_le_bytes(value_len) as u64;
+            // read through `take` so a bogus length can't make us allocate it up front
+            let mut bytes = Vec::new();
+            r.take(value_len).read_to_end(&mut bytes)?;
+            if bytes.len() as u64 != value_len {
+                return Err(SnapshotError::Truncated);
+            }
+            node.value = Some(V::decode(bytes).ok_or(SnapshotError::Corrupt("invalid value"))?);
+            *len += 1;
+        }
+        _ => return Err(SnapshotError::Corrupt("invalid value flag")),
+    }
+    Ok((node, u16::from_le_bytes(bitmap)))
+}
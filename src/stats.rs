@@ -0,0 +1,105 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::trie::Trie;
+use crate::visit::{WalkControl, walk_nodes};
+
+/// The shape of a [`Trie`], from [`Trie::stats`].
+///
+/// Depths are key lengths in symbols (hex digits at the default fanout), so
+/// the root is at depth 0.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TrieStats {
+    /// Nodes in the trie, including the root.
+    pub nodes: usize,
+    /// Nodes holding a value, which is the number of entries.
+    pub value_nodes: usize,
+    /// Nodes without children.
+    pub leaves: usize,
+    /// The depth of the deepest value, or 0 if there are none.
+    pub max_depth: usize,
+    /// The mean depth of the values, or 0.0 if there are none.
+    pub average_depth: f64,
+    /// `children_histogram[k]` is the number of nodes with exactly `k`
+    /// children, for `k` from 0 to the fanout.
+    pub children_histogram: Vec<usize>,
+    /// The most nodes in a row, parent to child, that have exactly one
+    /// child each. Path compression would fold each such run into one node.
+    pub longest_chain: usize,
+}
+
+impl fmt::Display for TrieStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} nodes, {} with values, {} leaves",
+            self.nodes, self.value_nodes, self.leaves
+        )?;
+        writeln!(
+            f,
+            "value depth: max {}, average {:.2}",
+            self.max_depth, self.average_depth
+        )?;
+        writeln!(f, "longest single-child chain: {}", self.longest_chain)?;
+        write!(f, "children per node:")?;
+        for (children, &count) in self.children_histogram.iter().enumerate() {
+            if count > 0 {
+                write!(f, "\n  {:>3}: {}", children, count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<V, const N: usize> Trie<V, N> {
+    /// Measures the shape of the trie: node and value counts, value depths,
+    /// how many children nodes have, and the longest run of single-child
+    /// nodes.
+    ///
+    /// The walk uses an explicit stack, so it handles a trie of any depth.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1f", 1);
+    /// trie.insert_lossy("a1e", 2);
+    /// let stats = trie.stats();
+    /// assert_eq!((stats.nodes, stats.value_nodes, stats.leaves), (5, 2, 2));
+    /// assert_eq!(stats.children_histogram[..3], [2, 2, 1]);
+    /// assert_eq!(stats.longest_chain, 2);
+    /// ```
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = TrieStats {
+            children_histogram: vec![0; N + 1],
+            ..TrieStats::default()
+        };
+        let mut depth_sum = 0;
+        // `runs[d]` is the length of the single-child run ending at the node at depth `d` of the
+        // current path.
+        let mut runs: Vec<usize> = Vec::new();
+        walk_nodes(&self.root, |path, node| {
+            let depth = path.len();
+            let children = node.child_count();
+            stats.nodes += 1;
+            stats.children_histogram[children] += 1;
+            if children == 0 {
+                stats.leaves += 1;
+            }
+            if node.value.is_some() {
+                stats.value_nodes += 1;
+                stats.max_depth = stats.max_depth.max(depth);
+                depth_sum += depth;
+            }
+            runs.truncate(depth);
+            let above = runs.last().copied().unwrap_or(0);
+            let run = if children == 1 { above + 1 } else { 0 };
+            stats.longest_chain = stats.longest_chain.max(run);
+            runs.push(run);
+            WalkControl::Continue
+        });
+        if stats.value_nodes > 0 {
+            stats.average_depth = depth_sum as f64 / stats.value_nodes as f64;
+        }
+        stats
+    }
+}
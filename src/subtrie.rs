@@ -0,0 +1,75 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::iter::{Range, Walk};
+use crate::key::{hex_to_nibbles, nibbles_to_hex};
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// A read-only view of the part of a [`Trie`] below some prefix.
+///
+/// Keys passed to and yielded by the view are relative to the prefix, so
+/// `trie.subtrie("a1")?.get("f")` looks up `"a1f"`. Returned by
+/// [`Trie::subtrie`].
+pub struct SubTrie<'a, V> {
+    node: &'a Node<V>,
+    prefix: Vec<u8>,
+}
+
+impl<V> Trie<V> {
+    /// Returns a view of everything stored below `prefix`, or `None` if no
+    /// key starts with it. `subtrie("")` views the whole trie.
+    pub fn subtrie(&self, prefix: &str) -> Option<SubTrie<'_, V>> {
+        let prefix: Vec<u8> = hex_to_nibbles(prefix).map(|n| n as u8).collect();
+        let node = self.root.find(prefix.iter().map(|&n| n as usize))?;
+        Some(SubTrie { node, prefix })
+    }
+}
+
+impl<'a, V> SubTrie<'a, V> {
+    /// Returns the prefix this view is rooted at, as canonical hex.
+    pub fn prefix(&self) -> String {
+        nibbles_to_hex(&self.prefix)
+    }
+
+    /// Returns the value stored under `prefix + hex_key`, if any.
+    pub fn get(&self, hex_key: &str) -> Option<&'a V> {
+        self.node.find(hex_to_nibbles(hex_key))?.value.as_ref()
+    }
+
+    /// Returns `true` if a value is stored under `prefix + hex_key`.
+    pub fn contains_key(&self, hex_key: &str) -> bool {
+        self.node
+            .find(hex_to_nibbles(hex_key))
+            .is_some_and(|node| node.value.is_some())
+    }
+
+    /// Returns an iterator over the entries in the view, with relative keys,
+    /// in key order.
+    pub fn iter(&self) -> Range<'a, V> {
+        Range {
+            walk: Walk::new(self.node, Vec::new()),
+            upper: None,
+        }
+    }
+
+    /// Counts the values in the view by walking it.
+    pub fn len(&self) -> usize {
+        self.node.count_values()
+    }
+
+    /// Returns `true` if the view holds no values.
+    ///
+    /// Since empty nodes are always pruned this is only possible for the
+    /// view of an empty trie.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<V: fmt::Display> fmt::Display for SubTrie<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
@@ -0,0 +1,263 @@
+//! A differential checker that runs the same operations on a [`Trie`] and
+//! on a `HashMap` standing in for it, and panics where they disagree.
+//!
+//! The map is keyed by the canonical form of each key: its hex digits,
+//! lowercased, with everything else left out, which is how the trie reads
+//! keys. The strict `try_*` operations are checked to reject exactly the
+//! keys holding something other than a hex digit, and to leave both sides
+//! alone when they do. After every operation the lengths must match and
+//! [`Trie::validate`] must pass.
+//!
+//! [`random_ops`] makes seeded sequences of operations with awkward keys,
+//! and [`ops_from_bytes`] turns arbitrary bytes into operations for a
+//! fuzzer; `fuzz/` holds a `cargo fuzz` target built on it.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use trie::Trie;
+//! use trie::testing::{apply_and_check, random_ops};
+//!
+//! let mut trie = Trie::new();
+//! let mut oracle = HashMap::new();
+//! for op in random_ops(7, 500) {
+//!     apply_and_check(&mut trie, &mut oracle, op);
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::key::KeyError;
+use crate::trie::Trie;
+
+/// One operation for [`apply_and_check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// [`Trie::insert_lossy`].
+    Insert {
+        /// The key, as given.
+        key: String,
+        /// The value to store.
+        value: String,
+    },
+    /// [`Trie::try_insert`].
+    TryInsert {
+        /// The key, as given.
+        key: String,
+        /// The value to store.
+        value: String,
+    },
+    /// [`Trie::delete_lossy`].
+    Delete {
+        /// The key, as given.
+        key: String,
+    },
+    /// [`Trie::try_delete`].
+    TryDelete {
+        /// The key, as given.
+        key: String,
+    },
+    /// [`Trie::get_lossy`].
+    Get {
+        /// The key, as given.
+        key: String,
+    },
+    /// [`Trie::try_get`].
+    TryGet {
+        /// The key, as given.
+        key: String,
+    },
+    /// [`Trie::remove_prefix`].
+    RemovePrefix {
+        /// The prefix, as given.
+        prefix: String,
+    },
+    /// [`Trie::count_prefix`] and [`Trie::iter_prefix`].
+    IterPrefix {
+        /// The prefix, as given.
+        prefix: String,
+    },
+    /// [`Trie::iter`], which must yield the map's entries in key order.
+    IterAll,
+    /// [`Trie::pop_first`].
+    PopFirst,
+}
+
+/// Applies `op` to `trie` and to `oracle`, and panics if the results or
+/// the two sides afterwards disagree.
+pub fn apply_and_check(trie: &mut Trie<String>, oracle: &mut HashMap<String, String>, op: Op) {
+    match &op {
+        Op::Insert { key, value } => {
+            let expected = oracle.insert(canonical(key), value.clone());
+            assert_eq!(trie.insert_lossy(key, value.clone()), expected, "{:?}", op);
+        }
+        Op::TryInsert { key, value } => {
+            let expected = strict(key).map(|()| oracle.insert(canonical(key), value.clone()));
+            assert_eq!(trie.try_insert(key, value.clone()), expected, "{:?}", op);
+        }
+        Op::Delete { key } => {
+            let expected = oracle.remove(&canonical(key));
+            assert_eq!(trie.delete_lossy(key), expected, "{:?}", op);
+        }
+        Op::TryDelete { key } => {
+            let expected = strict(key).map(|()| oracle.remove(&canonical(key)));
+            assert_eq!(trie.try_delete(key), expected, "{:?}", op);
+        }
+        Op::Get { key } => {
+            assert_eq!(trie.get_lossy(key), oracle.get(&canonical(key)), "{:?}", op);
+        }
+        Op::TryGet { key } => {
+            let expected = strict(key).map(|()| oracle.get(&canonical(key)));
+            assert_eq!(trie.try_get(key), expected, "{:?}", op);
+        }
+        Op::RemovePrefix { prefix } => {
+            let canonical = canonical(prefix);
+            let before = oracle.len();
+            // a prefix with no hex digits in it matches nothing rather than everything
+            if !canonical.is_empty() || prefix.is_empty() {
+                oracle.retain(|key, _| !key.starts_with(&canonical));
+            }
+            assert_eq!(
+                trie.remove_prefix(prefix),
+                before - oracle.len(),
+                "{:?}",
+                op
+            );
+        }
+        Op::IterPrefix { prefix } => {
+            let canonical = canonical(prefix);
+            let expected = sorted(oracle.iter().filter(|(key, _)| key.starts_with(&canonical)));
+            let found: Vec<_> = trie.iter_prefix(prefix).collect();
+            assert_eq!(found, expected, "{:?}", op);
+            assert_eq!(trie.count_prefix(prefix), expected.len(), "{:?}", op);
+        }
+        Op::IterAll => {
+            let found: Vec<_> = trie.iter().collect();
+            assert_eq!(found, sorted(oracle.iter()), "{:?}", op);
+        }
+        Op::PopFirst => {
+            let first = oracle.keys().min().cloned();
+            let expected = first.map(|key| {
+                let value = oracle.remove(&key).unwrap();
+                (key, value)
+            });
+            assert_eq!(trie.pop_first(), expected, "{:?}", op);
+        }
+    }
+    assert_eq!(trie.len(), oracle.len(), "length after {:?}", op);
+    if let Err(violations) = trie.validate() {
+        panic!("invariants broken after {:?}: {:?}", op, violations);
+    }
+}
+
+// The key the trie reads `key` as.
+fn canonical(key: &str) -> String {
+    key.chars()
+        .filter(char::is_ascii_hexdigit)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+// What the strict methods make of `key`: rejected at its first character that isn't a hex
+// digit.
+fn strict(key: &str) -> Result<(), KeyError> {
+    match key.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        Some((index, found)) => Err(KeyError::InvalidChar { index, found }),
+        None => Ok(()),
+    }
+}
+
+fn sorted<'a>(
+    entries: impl Iterator<Item = (&'a String, &'a String)>,
+) -> Vec<(String, &'a String)> {
+    let mut entries: Vec<_> = entries.map(|(key, value)| (key.clone(), value)).collect();
+    entries.sort();
+    entries
+}
+
+/// `n` operations from an xorshift generator started at `seed`, which must
+/// not be zero.
+///
+/// Keys are drawn to collide and to trip up key handling: the empty key,
+/// prefixes of each other, keys hundreds of digits long, uppercase digits,
+/// and characters that aren't hex digits at all, including ones outside
+/// ASCII.
+pub fn random_ops(seed: u64, n: usize) -> Vec<Op> {
+    let mut state = seed;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..n).map(|_| op_from(&mut next)).collect()
+}
+
+/// Reads operations out of `data`, using every byte, for a fuzzer to
+/// steer. Any input gives some sequence, possibly an empty one.
+pub fn ops_from_bytes(data: &[u8]) -> Vec<Op> {
+    let mut bytes = data.iter();
+    let mut ops = Vec::new();
+    // Each operation takes its bytes from the input until there are too few left.
+    while bytes.len() >= 8 {
+        let mut next = || {
+            let mut word = [0; 8];
+            for byte in &mut word {
+                *byte = *bytes.next().unwrap_or(&0);
+            }
+            u64::from_le_bytes(word)
+        };
+        ops.push(op_from(&mut next));
+    }
+    ops
+}
+
+// Characters keys are made of: digits in both cases, with the odd character the lenient
+// methods skip and the strict ones reject.
+const KEY_CHARS: [char; 28] = [
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'A', 'B', 'C',
+    'D', 'E', 'F', 'a', '1', '-', 'x', ' ', 'é',
+];
+
+// Keys most operations pick from, so they keep hitting each other's entries.
+const COMMON_KEYS: [&str; 8] = ["", "a", "a1", "a1f", "A1F", "a1-f", "b", "0"];
+
+fn op_from(next: &mut impl FnMut() -> u64) -> Op {
+    let word = next();
+    let key = key_from(word >> 8, next);
+    let value = format!("v{}", word >> 56);
+    match word % 16 {
+        0..=3 => Op::Insert { key, value },
+        4 | 5 => Op::TryInsert { key, value },
+        6 | 7 => Op::Delete { key },
+        8 => Op::TryDelete { key },
+        9 | 10 => Op::Get { key },
+        11 => Op::TryGet { key },
+        12 => Op::RemovePrefix { prefix: key },
+        13 => Op::IterPrefix { prefix: key },
+        14 => Op::IterAll,
+        _ => Op::PopFirst,
+    }
+}
+
+fn key_from(word: u64, next: &mut impl FnMut() -> u64) -> String {
+    match word % 8 {
+        0..=3 => COMMON_KEYS[(word >> 3) as usize % COMMON_KEYS.len()].to_string(),
+        4 => {
+            // A long key, sharing its start with every other long key.
+            let len = 200 + (word >> 3) as usize % 200;
+            let tail = format!("{:x}", next());
+            "a1".repeat(len / 2) + &tail
+        }
+        _ => {
+            let len = (word >> 3) as usize % 6;
+            let mut chars = next();
+            (0..len)
+                .map(|_| {
+                    let c = KEY_CHARS[(chars % KEY_CHARS.len() as u64) as usize];
+                    chars /= KEY_CHARS.len() as u64;
+                    c
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,879 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Bound;
+
+use crate::key::{
+    KeyError, check_key, hex_to_nibbles, key_symbols, nibbles_to_hex, symbols_to_hex,
+};
+use crate::node::{Node, Pool};
+use crate::observe::{Observer, canonical_key, notify_deleted};
+use crate::rollback::Snapshots;
+
+/// A trie mapping hex-string keys to values of type `V`.
+///
+/// `Trie<()>` works as a plain set of keys.
+///
+/// The empty key `""` is an ordinary key whose value lives on the root: it
+/// counts towards [`Trie::len`], sorts before every other key, matches every
+/// prefix query and is drawn as `(root) = value`. [`Trie::root_value`] reads
+/// it directly.
+///
+/// Most methods skip the characters of a key that aren't hex digits, so
+/// `"a1-f"` addresses the same entry as `"a1f"`, and `"xyz"` the empty key
+/// at the root. Each of them has a `try_*` twin, such as
+/// [`Trie::try_get_mut`] or [`Trie::try_iter_prefix`], that rejects such
+/// keys with a [`KeyError`] instead. For the basic map operations the
+/// lenient versions go by `insert_lossy`, `get_lossy` and `delete_lossy`;
+/// plain `insert`, `get` and `delete` do the same but are deprecated, so
+/// that skipping has to be asked for by name.
+///
+/// `N` is the number of children per node. The default of 16 takes one hex
+/// digit per level; 2 and 4 split each digit into 4 or 2 levels, and 256
+/// takes two digits (one byte) per level, ignoring a trailing odd digit.
+/// Other values fail to compile. Only the basic map operations, iteration
+/// and `Display` are available for fanouts other than 16.
+#[derive(Clone)]
+pub struct Trie<V, const N: usize = 16> {
+    pub(crate) root: Node<V, N>,
+    // Number of stored values, kept in step by every method that adds or removes one.
+    pub(crate) len: usize,
+    // Spare nodes left by delete and clear, which insert uses before allocating.
+    pub(crate) pool: Pool<V, N>,
+    // What changes since `snapshot` overwrote, for `rollback` to put back.
+    pub(crate) snapshots: Snapshots<V, N>,
+    // Told about changes by the methods that change one key or one prefix.
+    pub(crate) observer: Observer<V>,
+}
+
+impl<V, const N: usize> Default for Trie<V, N> {
+    fn default() -> Self {
+        Self::with_fanout()
+    }
+}
+
+impl<V, const N: usize> Trie<V, N> {
+    /// Creates an empty trie with `N` children per node, as in
+    /// `Trie::<u32, 256>::with_fanout()`.
+    pub fn with_fanout() -> Self {
+        Self {
+            root: Node::new(),
+            len: 0,
+            pool: Pool::new(),
+            snapshots: Snapshots::default(),
+            observer: Observer::default(),
+        }
+    }
+
+    /// Stores `value` under `hex_key`, skipping characters that aren't hex
+    /// digits.
+    #[deprecated(
+        note = "skips characters that aren't hex digits; use `try_insert`, or \
+                         `insert_lossy` where skipping them is intended"
+    )]
+    pub fn insert(&mut self, hex_key: &str, value: V) -> Option<V> {
+        self.insert_lossy(hex_key, value)
+    }
+
+    /// Returns a mutable reference to the value stored under `hex_key`,
+    /// storing the result of `f` there first if the key is missing.
+    ///
+    /// The key is walked once. `f` only runs on a miss, and the nodes for a
+    /// new key are only created then.
+    ///
+    /// ```
+    /// let mut cache = trie::Trie::new();
+    /// cache.get_or_insert_with("a1", || String::from("computed")).push('!');
+    /// let value = cache.get_or_insert_with("a1", || unreachable!());
+    /// assert_eq!(value, "computed!");
+    /// ```
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, hex_key: &str, f: F) -> &mut V {
+        self.save_key(hex_key);
+        let symbols = key_symbols::<N>(hex_key);
+        let (value, inserted) = self.root.get_or_insert_nibbles(symbols, f, &mut self.pool);
+        #[cfg(feature = "metrics")]
+        {
+            self.pool.metrics.record_lookup(!inserted);
+            if inserted {
+                let depth = key_symbols::<N>(hex_key).count();
+                self.pool.metrics.record_insert(false, depth);
+            }
+        }
+        if inserted {
+            self.len += 1;
+            if let Some(observer) = self.observer.get() {
+                observer.on_insert(&canonical_key::<N>(hex_key), None, value);
+            }
+        }
+        value
+    }
+
+    /// Like [`Trie::get_or_insert_with`], storing `value` if the key is
+    /// missing and dropping it otherwise.
+    pub fn get_or_insert(&mut self, hex_key: &str, value: V) -> &mut V {
+        self.get_or_insert_with(hex_key, || value)
+    }
+
+    /// Returns the value stored under `hex_key`, if any, skipping
+    /// characters that aren't hex digits.
+    #[deprecated(note = "skips characters that aren't hex digits; use `try_get`, or \
+                         `get_lossy` where skipping them is intended")]
+    pub fn get(&self, hex_key: &str) -> Option<&V> {
+        self.get_lossy(hex_key)
+    }
+
+    /// Returns the canonical form of `hex_key` along with its value, if any.
+    ///
+    /// The key comes back the way the trie spells it: lowercase, with the
+    /// characters [`Trie::get_lossy`] skips left out.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1f", 7);
+    /// assert_eq!(trie.get_key_value("A1-F"), Some(("a1f".to_string(), &7)));
+    /// ```
+    pub fn get_key_value(&self, hex_key: &str) -> Option<(String, &V)> {
+        let path: Vec<u8> = key_symbols::<N>(hex_key).map(|n| n as u8).collect();
+        self.entry_at(&path)
+    }
+
+    // The entry stored at a symbol path, with its key rebuilt from the path.
+    fn entry_at(&self, path: &[u8]) -> Option<(String, &V)> {
+        let node = self.root.find(path.iter().map(|&n| n as usize))?;
+        Some((symbols_to_hex::<N>(path), node.value.as_ref()?))
+    }
+
+    /// Returns `true` if a value is stored under `hex_key`.
+    ///
+    /// Interior nodes that only exist as part of a longer key don't count, so
+    /// after inserting `"a1f"` only, `contains_key("a1")` is `false`.
+    pub fn contains_key(&self, hex_key: &str) -> bool {
+        self.root.contains_key(hex_key)
+    }
+
+    /// Returns a mutable reference to the value stored under `hex_key`, if
+    /// any.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1f", String::from("leaf"));
+    /// *trie.get_mut("a1f").unwrap() += "-suffix";
+    /// assert_eq!(trie.get_lossy("a1f").unwrap(), "leaf-suffix");
+    /// ```
+    pub fn get_mut(&mut self, hex_key: &str) -> Option<&mut V> {
+        self.save_key(hex_key);
+        self.root.get_mut(hex_key)
+    }
+
+    /// Removes the value stored under `hex_key` and returns it, skipping
+    /// characters that aren't hex digits.
+    #[deprecated(
+        note = "skips characters that aren't hex digits; use `try_delete`, or \
+                         `delete_lossy` where skipping them is intended"
+    )]
+    pub fn delete(&mut self, hex_key: &str) -> Option<V> {
+        self.delete_lossy(hex_key)
+    }
+
+    /// Replaces the value under `hex_key` with what `f` makes of it, in one
+    /// walk down the key, and returns the value it had.
+    ///
+    /// `f` gets the current value, or `None` if there isn't one, and returns
+    /// the new value, or `None` to leave the key empty. Emptying a key prunes
+    /// the nodes it leaves behind just like [`Trie::delete_lossy`], and a missing
+    /// key only gets its nodes if `f` returns a value. `f` owns the value it
+    /// is given, so the one returned here is a clone of it.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// let bump = |count: Option<u32>| Some(count.unwrap_or(0) + 1);
+    /// assert_eq!(trie.update("a1", bump), None);
+    /// assert_eq!(trie.update("a1", bump), Some(1));
+    /// assert_eq!(trie.update("a1", |_| None), Some(2));
+    /// assert!(trie.is_empty());
+    /// assert_eq!(trie.node_count(), 1);
+    /// ```
+    pub fn update<F>(&mut self, hex_key: &str, f: F) -> Option<V>
+    where
+        V: Clone,
+        F: FnOnce(Option<V>) -> Option<V>,
+    {
+        self.save_key(hex_key);
+        let mut old = None;
+        let symbols = key_symbols::<N>(hex_key);
+        let counts = self.root.update_nibbles(
+            symbols,
+            |value| {
+                old = value.clone();
+                f(value)
+            },
+            &mut self.pool,
+        );
+        self.count_update(counts);
+        self.report_update(hex_key, counts, old.as_ref());
+        old
+    }
+
+    // Keeps `len` in step with what update_nibbles did: whether the key had a value before and
+    // whether it has one after.
+    pub(crate) fn count_update(&mut self, (had, has): (bool, bool)) {
+        match (had, has) {
+            (false, true) => self.len += 1,
+            (true, false) => self.len -= 1,
+            _ => {}
+        }
+    }
+
+    // Tells the metrics and the observer what update_nibbles did to `hex_key`, which held `old`
+    // before: it was written if it has a value now and deleted if it only had one before.
+    pub(crate) fn report_update(
+        &mut self,
+        hex_key: &str,
+        (had, has): (bool, bool),
+        old: Option<&V>,
+    ) {
+        #[cfg(feature = "metrics")]
+        match (had, has) {
+            (_, true) => self
+                .pool
+                .metrics
+                .record_insert(had, key_symbols::<N>(hex_key).count()),
+            (true, false) => self.pool.metrics.record_delete(),
+            _ => {}
+        }
+        let Some(observer) = self.observer.get() else {
+            return;
+        };
+        match (had, has, old) {
+            (_, true, _) => {
+                let new = self.root.get(hex_key).unwrap();
+                observer.on_insert(&canonical_key::<N>(hex_key), old, new);
+            }
+            (true, false, Some(old)) => observer.on_delete(&canonical_key::<N>(hex_key), old),
+            _ => {}
+        }
+    }
+
+    /// Like [`Trie::insert_lossy`], but fails instead of skipping characters that
+    /// aren't hex digits.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// let err = trie.try_insert("0xa1", ()).unwrap_err();
+    /// assert_eq!(err, trie::KeyError::InvalidChar { index: 1, found: 'x' });
+    /// assert!(trie.is_empty());
+    /// ```
+    pub fn try_insert(&mut self, hex_key: &str, value: V) -> Result<Option<V>, KeyError> {
+        check_key::<N>(hex_key)?;
+        Ok(self.insert_lossy(hex_key, value))
+    }
+
+    /// Like [`Trie::get_lossy`], but fails instead of skipping characters that
+    /// aren't hex digits.
+    pub fn try_get(&self, hex_key: &str) -> Result<Option<&V>, KeyError> {
+        check_key::<N>(hex_key)?;
+        Ok(self.get_lossy(hex_key))
+    }
+
+    /// Like [`Trie::delete_lossy`], but fails instead of skipping characters that
+    /// aren't hex digits.
+    pub fn try_delete(&mut self, hex_key: &str) -> Result<Option<V>, KeyError> {
+        check_key::<N>(hex_key)?;
+        Ok(self.delete_lossy(hex_key))
+    }
+
+    /// Like [`Trie::get_mut`], but fails instead of skipping characters that
+    /// aren't hex digits.
+    pub fn try_get_mut(&mut self, hex_key: &str) -> Result<Option<&mut V>, KeyError> {
+        check_key::<N>(hex_key)?;
+        Ok(self.get_mut(hex_key))
+    }
+
+    /// Like [`Trie::contains_key`], but fails instead of skipping characters
+    /// that aren't hex digits.
+    pub fn try_contains_key(&self, hex_key: &str) -> Result<bool, KeyError> {
+        check_key::<N>(hex_key)?;
+        Ok(self.contains_key(hex_key))
+    }
+
+    /// Like [`Trie::update`], but fails instead of skipping characters that
+    /// aren't hex digits, in which case `f` isn't called.
+    pub fn try_update<F>(&mut self, hex_key: &str, f: F) -> Result<Option<V>, KeyError>
+    where
+        V: Clone,
+        F: FnOnce(Option<V>) -> Option<V>,
+    {
+        check_key::<N>(hex_key)?;
+        Ok(self.update(hex_key, f))
+    }
+
+    /// Like [`Trie::get_or_insert_with`], but fails instead of skipping
+    /// characters that aren't hex digits, in which case `f` isn't called.
+    pub fn try_get_or_insert_with<F: FnOnce() -> V>(
+        &mut self,
+        hex_key: &str,
+        f: F,
+    ) -> Result<&mut V, KeyError> {
+        check_key::<N>(hex_key)?;
+        Ok(self.get_or_insert_with(hex_key, f))
+    }
+
+    /// Like [`Trie::get_or_insert`], but fails instead of skipping characters
+    /// that aren't hex digits.
+    pub fn try_get_or_insert(&mut self, hex_key: &str, value: V) -> Result<&mut V, KeyError> {
+        self.try_get_or_insert_with(hex_key, || value)
+    }
+
+    /// Stores `value` under `hex_key`, skipping characters that aren't hex
+    /// digits, so `"a1-f"` is stored as `"a1f"` and `"xyz"` under the
+    /// empty key.
+    ///
+    /// Like `HashMap::insert`, returns the value that was replaced, or `None`
+    /// if the key was not present.
+    pub fn insert_lossy(&mut self, hex_key: &str, value: V) -> Option<V> {
+        self.save_key(hex_key);
+        let old = self.root.insert(hex_key, value, &mut self.pool);
+        if old.is_none() {
+            self.len += 1;
+        }
+        #[cfg(feature = "metrics")]
+        self.pool
+            .metrics
+            .record_insert(old.is_some(), key_symbols::<N>(hex_key).count());
+        if let Some(observer) = self.observer.get() {
+            let new = self.root.get(hex_key).unwrap();
+            observer.on_insert(&canonical_key::<N>(hex_key), old.as_ref(), new);
+        }
+        old
+    }
+
+    /// Returns the value stored under `hex_key`, if any, skipping characters
+    /// that aren't hex digits like [`Trie::insert_lossy`].
+    pub fn get_lossy(&self, hex_key: &str) -> Option<&V> {
+        let value = self.root.get(hex_key);
+        #[cfg(feature = "metrics")]
+        self.pool.metrics.record_lookup(value.is_some());
+        value
+    }
+
+    /// Removes the value stored under `hex_key` and returns it, skipping
+    /// characters that aren't hex digits like [`Trie::insert_lossy`].
+    ///
+    /// Nodes left without values or children are pruned. Deleting a key that
+    /// is only a prefix of other keys leaves those keys untouched; that
+    /// includes `delete_lossy("")`, which only takes the root's value.
+    pub fn delete_lossy(&mut self, hex_key: &str) -> Option<V> {
+        self.save_key(hex_key);
+        let removed = self.root.delete(hex_key, &mut self.pool);
+        if removed.is_some() {
+            self.len -= 1;
+            #[cfg(feature = "metrics")]
+            self.pool.metrics.record_delete();
+        }
+        if let (Some(observer), Some(old)) = (self.observer.get(), &removed) {
+            observer.on_delete(&canonical_key::<N>(hex_key), old);
+        }
+        removed
+    }
+
+    /// Returns the value stored under the empty key, if any.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1", "leaf");
+    /// assert_eq!(trie.root_value(), None);
+    /// trie.insert_lossy("", "root");
+    /// assert_eq!(trie.root_value(), Some(&"root"));
+    /// assert_eq!(trie.len(), 2);
+    /// assert_eq!(trie.keys().next().unwrap(), "");
+    /// ```
+    pub fn root_value(&self) -> Option<&V> {
+        self.root.value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value stored under the empty key,
+    /// if any.
+    pub fn root_value_mut(&mut self) -> Option<&mut V> {
+        self.save_path(&[]);
+        self.root.invalidate();
+        self.root.value.as_mut()
+    }
+
+    /// Returns the number of nodes, including the root. There is one node per
+    /// distinct key prefix.
+    pub fn node_count(&self) -> usize {
+        self.root.count_nodes()
+    }
+
+    /// Returns the number of stored values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Sets how many spare nodes the trie keeps for reuse, freeing any above
+    /// the new limit.
+    ///
+    /// Nodes pruned by [`Trie::delete_lossy`] or emptied by [`Trie::clear`] go
+    /// into a pool that later inserts take from before allocating, which
+    /// saves most of the allocator traffic when keys come and go. The pool
+    /// holds up to 1024 nodes by default; 0 turns it off.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.set_pool_capacity(10_000);
+    /// trie.insert_lossy("a1f", 1);
+    /// trie.delete_lossy("a1f");
+    /// assert_eq!(trie.pool_len(), 3);
+    /// trie.insert_lossy("b2", 2); // built from two pooled nodes
+    /// assert_eq!(trie.pool_len(), 1);
+    /// ```
+    pub fn set_pool_capacity(&mut self, capacity: usize) {
+        self.pool.set_capacity(capacity);
+    }
+
+    /// Returns the number of spare nodes in the pool.
+    pub fn pool_len(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Frees every spare node in the pool. The capacity is unchanged.
+    pub fn shrink_pool(&mut self) {
+        self.pool.clear();
+    }
+
+    /// Releases memory the trie holds but doesn't need, returning roughly how
+    /// many bytes were given back.
+    ///
+    /// Child vectors keep the room they grew into after their children are
+    /// deleted; this trims every one to its length and empties the node pool.
+    /// Entries, key order and cached hashes are unaffected. It's worth
+    /// calling after deleting a large share of the keys.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for n in 0..16u8 {
+    ///     trie.insert_int(n, ());
+    /// }
+    /// for n in 1..16u8 {
+    ///     trie.delete_int(n);
+    /// }
+    /// assert!(trie.compact() > 0);
+    /// assert_eq!(trie.compact(), 0);
+    /// assert_eq!(trie.keys().collect::<Vec<_>>(), ["00"]);
+    /// ```
+    pub fn compact(&mut self) -> usize {
+        let mut reclaimed = self.pool.clear();
+        // Only capacity changes, not content, so the cached hashes stay valid.
+        let mut stack = vec![&mut self.root];
+        while let Some(node) = stack.pop() {
+            reclaimed += node.shrink_children();
+            stack.extend(node.children_mut().map(|(_, child)| child));
+        }
+        reclaimed
+    }
+
+    /// Returns `true` if the trie holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<V> Trie<V> {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self::with_fanout()
+    }
+
+    // Wraps an already built root, recounting its values.
+    pub(crate) fn from_root(root: Node<V>) -> Self {
+        let len = root.count_values();
+        Self {
+            root,
+            len,
+            pool: Pool::new(),
+            snapshots: Snapshots::default(),
+            observer: Observer::default(),
+        }
+    }
+
+    /// Stores `value` at the path given directly as nibbles.
+    ///
+    /// This skips hex parsing, which makes it the fast path for generated
+    /// keys. Returns the replaced value like [`Trie::insert_lossy`].
+    ///
+    /// Passing a `&Nibbles` uses a key that was checked when it was built.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any nibble is 16 or larger.
+    pub fn insert_nibbles<I: IntoIterator<Item = usize>>(
+        &mut self,
+        nibbles: I,
+        value: V,
+    ) -> Option<V> {
+        if cfg!(feature = "metrics") || self.observer.get().is_some() || self.snapshots.recording()
+        {
+            let nibbles: Vec<u8> = nibbles.into_iter().map(|n| n as u8).collect();
+            return self.insert_nibbles_observed(&nibbles, value);
+        }
+        let old = self.root.insert_nibbles(nibbles, value, &mut self.pool);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    // `insert_nibbles` with an observer to tell, a snapshot to save for or metrics to count,
+    // which need the key more than once or its length.
+    fn insert_nibbles_observed(&mut self, nibbles: &[u8], value: V) -> Option<V> {
+        self.save_path(nibbles);
+        let path = || nibbles.iter().map(|&n| n as usize);
+        let old = self.root.insert_nibbles(path(), value, &mut self.pool);
+        if old.is_none() {
+            self.len += 1;
+        }
+        #[cfg(feature = "metrics")]
+        self.pool
+            .metrics
+            .record_insert(old.is_some(), nibbles.len());
+        if let Some(observer) = self.observer.get() {
+            let new = self.root.find(path()).unwrap().value.as_ref().unwrap();
+            observer.on_insert(&nibbles_to_hex(nibbles), old.as_ref(), new);
+        }
+        old
+    }
+
+    /// Returns the entry with the longest key that is a prefix of `hex_key`.
+    ///
+    /// An exact match wins over shorter prefixes, and a value stored under the
+    /// empty key matches everything, acting as a default.
+    pub fn longest_prefix(&self, hex_key: &str) -> Option<(String, &V)> {
+        let mut path = Vec::new();
+        let mut cur = &self.root;
+        let mut best = cur.value.is_some().then_some(0);
+        for nibble in hex_to_nibbles(hex_key) {
+            match cur.child(nibble) {
+                Some(child) => cur = child,
+                None => break,
+            }
+            path.push(nibble as u8);
+            if cur.value.is_some() {
+                best = Some(path.len());
+            }
+        }
+        self.entry_at(&path[..best?])
+    }
+
+    /// Like [`Trie::longest_prefix`], but fails instead of skipping
+    /// characters that aren't hex digits.
+    pub fn try_longest_prefix(&self, hex_key: &str) -> Result<Option<(String, &V)>, KeyError> {
+        check_key::<16>(hex_key)?;
+        Ok(self.longest_prefix(hex_key))
+    }
+
+    /// Returns the entry with the smallest key.
+    ///
+    /// Since a key sorts before every key it is a prefix of, this is the
+    /// first value met while following the lowest child down from the root.
+    pub fn first_key_value(&self) -> Option<(String, &V)> {
+        let (path, value) = self.first_entry()?;
+        Some((nibbles_to_hex(&path), value))
+    }
+
+    /// Returns the entry with the largest key.
+    ///
+    /// This is the deepest value along the highest-child spine, because every
+    /// node without children holds a value.
+    pub fn last_key_value(&self) -> Option<(String, &V)> {
+        let (path, value) = self.last_entry()?;
+        Some((nibbles_to_hex(&path), value))
+    }
+
+    /// Returns the entry with the smallest key greater than `hex_key`, which
+    /// doesn't have to be in the trie itself.
+    ///
+    /// Only the path of `hex_key` and the path down to the answer are
+    /// walked, as in [`Trie::range`].
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for key in ["a1", "a1f", "b0"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// assert_eq!(trie.next_key("a1").unwrap().0, "a1f");
+    /// assert_eq!(trie.next_key("a2").unwrap().0, "b0");
+    /// assert_eq!(trie.next_key("b0"), None);
+    /// ```
+    pub fn next_key(&self, hex_key: &str) -> Option<(String, &V)> {
+        self.range((Bound::Excluded(hex_key), Bound::Unbounded))
+            .next()
+    }
+
+    /// Returns the entry with the largest key smaller than `hex_key`, which
+    /// doesn't have to be in the trie itself.
+    ///
+    /// Walking down the path of `hex_key`, every node passed holds smaller
+    /// keys in its own value and in the children before the one taken, and
+    /// the deeper the node the larger those keys are. So the answer is the
+    /// last key under the deepest such child, or the deepest such value if
+    /// there's no such child at that depth.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for key in ["a1", "a1f", "b0"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// assert_eq!(trie.prev_key("a1f").unwrap().0, "a1");
+    /// assert_eq!(trie.prev_key("b").unwrap().0, "a1f");
+    /// assert_eq!(trie.prev_key("a1"), None);
+    /// ```
+    pub fn prev_key(&self, hex_key: &str) -> Option<(String, &V)> {
+        // The deepest node with something smaller so far: how far down the key it is, the node,
+        // and the child before the key's path to take, if there is one.
+        let mut best = None;
+        let mut cur = &self.root;
+        for (depth, nibble) in hex_to_nibbles(hex_key).enumerate() {
+            let smaller = cur.children().rev().find(|&(i, _)| i < nibble);
+            if smaller.is_some() || cur.value.is_some() {
+                best = Some((depth, cur, smaller));
+            }
+            match cur.child(nibble) {
+                Some(child) => cur = child,
+                None => break,
+            }
+        }
+
+        let (depth, node, smaller) = best?;
+        let mut path: Vec<u8> = hex_to_nibbles(hex_key)
+            .take(depth)
+            .map(|n| n as u8)
+            .collect();
+        let (path, value) = match smaller {
+            Some((nibble, child)) => {
+                path.push(nibble as u8);
+                last_below(child, path)?
+            }
+            None => (path, node.value.as_ref()?),
+        };
+        Some((nibbles_to_hex(&path), value))
+    }
+
+    /// Removes and returns the entry with the smallest key.
+    pub fn pop_first(&mut self) -> Option<(String, V)> {
+        let (path, _) = self.first_entry()?;
+        self.pop_path(path)
+    }
+
+    /// Removes and returns the entry with the largest key.
+    pub fn pop_last(&mut self) -> Option<(String, V)> {
+        let (path, _) = self.last_entry()?;
+        self.pop_path(path)
+    }
+
+    /// Splits the trie in two at `at`, returning everything with a key
+    /// `>= at` in nibble order and keeping the rest.
+    ///
+    /// A value stored exactly at `at` moves; one stored at a strict prefix of
+    /// `at` stays, since it sorts first. `split_off("")` moves everything.
+    pub fn split_off(&mut self, at: &str) -> Trie<V> {
+        let at: Vec<usize> = hex_to_nibbles(at).collect();
+        let moved = Trie::from_root(self.root.split_off(&at, &mut self.pool));
+        self.snapshots.save_values(&moved.root, Vec::new());
+        if let Some(observer) = self.observer.get() {
+            notify_deleted(observer, &moved.root, Vec::new());
+        }
+        self.len -= moved.len;
+        moved
+    }
+
+    /// Like [`Trie::split_off`], but fails instead of skipping characters
+    /// that aren't hex digits, leaving the trie as it was.
+    pub fn try_split_off(&mut self, at: &str) -> Result<Trie<V>, KeyError> {
+        check_key::<16>(at)?;
+        Ok(self.split_off(at))
+    }
+
+    fn first_entry(&self) -> Option<(Vec<u8>, &V)> {
+        let mut path = Vec::new();
+        let mut cur = &self.root;
+        loop {
+            if let Some(value) = &cur.value {
+                return Some((path, value));
+            }
+            let (nibble, child) = cur.next_child(0)?;
+            path.push(nibble as u8);
+            cur = child;
+        }
+    }
+
+    fn last_entry(&self) -> Option<(Vec<u8>, &V)> {
+        last_below(&self.root, Vec::new())
+    }
+
+    // Returns the value at a path of nibbles, as `get_lossy` does for a hex key.
+    pub(crate) fn get_path(&self, nibbles: impl IntoIterator<Item = usize>) -> Option<&V> {
+        let value = self.root.find(nibbles).and_then(|node| node.value.as_ref());
+        #[cfg(feature = "metrics")]
+        self.pool.metrics.record_lookup(value.is_some());
+        value
+    }
+
+    fn pop_path(&mut self, path: Vec<u8>) -> Option<(String, V)> {
+        let value = self.delete_path(path.iter().map(|&n| n as usize))?;
+        Some((nibbles_to_hex(&path), value))
+    }
+
+    // Removes the value at a path of nibbles, as `delete` does for a hex key.
+    pub(crate) fn delete_path<I>(&mut self, nibbles: I) -> Option<V>
+    where
+        I: IntoIterator<Item = usize>,
+        I::IntoIter: Clone,
+    {
+        if self.observer.get().is_none() && !self.snapshots.recording() {
+            let removed = self.root.delete_nibbles(nibbles, &mut self.pool)?;
+            self.len -= 1;
+            #[cfg(feature = "metrics")]
+            self.pool.metrics.record_delete();
+            return Some(removed);
+        }
+        let nibbles: Vec<u8> = nibbles.into_iter().map(|n| n as u8).collect();
+        self.save_path(&nibbles);
+        let removed = self
+            .root
+            .delete_nibbles(nibbles.iter().map(|&n| n as usize), &mut self.pool)?;
+        self.len -= 1;
+        #[cfg(feature = "metrics")]
+        self.pool.metrics.record_delete();
+        if let Some(observer) = self.observer.get() {
+            observer.on_delete(&nibbles_to_hex(&nibbles), &removed);
+        }
+        Some(removed)
+    }
+
+    /// Returns how many keys start with `prefix`, counting a value stored
+    /// exactly at `prefix`.
+    ///
+    /// Only the subtree below the prefix is walked and no keys are built.
+    pub fn count_prefix(&self, prefix: &str) -> usize {
+        self.root
+            .find(hex_to_nibbles(prefix))
+            .map_or(0, Node::count_values)
+    }
+
+    /// Like [`Trie::count_prefix`], but fails instead of skipping characters
+    /// that aren't hex digits.
+    pub fn try_count_prefix(&self, prefix: &str) -> Result<usize, KeyError> {
+        check_key::<16>(prefix)?;
+        Ok(self.count_prefix(prefix))
+    }
+
+    /// Removes every entry whose key starts with `prefix` and returns how
+    /// many were removed.
+    ///
+    /// The subtree is detached in one step and ancestors left empty are
+    /// pruned. A value stored exactly at `prefix` is removed too, and the
+    /// empty prefix clears the whole trie.
+    ///
+    /// A prefix without a single hex digit in it, such as `"-"` or `"xyz"`,
+    /// is not taken as the empty prefix: it matches nothing and removes
+    /// nothing. Other characters that aren't hex digits are skipped;
+    /// [`Trie::try_remove_prefix`] rejects them instead.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// trie.insert_lossy("a1", ());
+    /// assert_eq!(trie.remove_prefix("xyz"), 0);
+    /// assert_eq!(trie.remove_prefix("a-"), 1);
+    /// assert!(trie.is_empty());
+    /// ```
+    pub fn remove_prefix(&mut self, prefix: &str) -> usize {
+        let path: Vec<usize> = hex_to_nibbles(prefix).collect();
+        if path.is_empty() && !prefix.is_empty() {
+            return 0;
+        }
+        if path.is_empty() {
+            let removed = self.len;
+            if let Some(observer) = self.observer.get().filter(|_| removed > 0) {
+                notify_deleted(observer, &self.root, Vec::new());
+                observer.on_prune("");
+            }
+            self.clear();
+            return removed;
+        }
+        let Some(subtree) = self.root.detach(&path, &mut self.pool) else {
+            return 0;
+        };
+        let removed = subtree.count_values();
+        self.len -= removed;
+        if let Some(observer) = self.observer.get() {
+            let path: Vec<u8> = path.iter().map(|&n| n as u8).collect();
+            notify_deleted(observer, &subtree, path.clone());
+            observer.on_prune(&nibbles_to_hex(&path));
+        }
+        if self.snapshots.recording() {
+            let path: Vec<u8> = path.iter().map(|&n| n as u8).collect();
+            self.pool.moved_out(&subtree);
+            self.snapshots.save_subtree(&path, Some(subtree));
+        } else {
+            self.pool.put(subtree);
+        }
+        removed
+    }
+
+    /// Like [`Trie::remove_prefix`], but fails instead of skipping characters
+    /// that aren't hex digits, removing nothing.
+    pub fn try_remove_prefix(&mut self, prefix: &str) -> Result<usize, KeyError> {
+        check_key::<16>(prefix)?;
+        Ok(self.remove_prefix(prefix))
+    }
+
+    /// Removes all entries, keeping the root node for reuse.
+    ///
+    /// The other nodes go to the trie's node pool, up to its capacity (see
+    /// [`Trie::set_pool_capacity`]).
+    pub fn clear(&mut self) {
+        if self.snapshots.recording() {
+            let root = core::mem::take(&mut self.root);
+            for (_, child) in root.children() {
+                self.pool.moved_out(child);
+            }
+            self.snapshots.save_subtree(&[], Some(Box::new(root)));
+        }
+        self.root.recycle_children(&mut self.pool);
+        self.root.value = None;
+        self.root.invalidate();
+        self.len = 0;
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    ///
+    /// `f` sees every entry in key order and may also edit the values it
+    /// keeps. Subtrees left empty are pruned like [`Trie::delete_lossy`] would.
+    pub fn retain<F: FnMut(&str, &mut V) -> bool>(&mut self, mut f: F) {
+        self.snapshots.save_root(&self.root);
+        let Some(observer) = self.observer.get() else {
+            self.len -= self.root.retain(f, &mut self.pool);
+            return;
+        };
+        let kept = |key: &str, value: &mut V| {
+            let keep = f(key, value);
+            if !keep {
+                observer.on_delete(key, value);
+            }
+            keep
+        };
+        self.len -= self.root.retain(kept, &mut self.pool);
+    }
+}
+
+// The last entry under `node`, whose key is `path`: the deepest value along the highest-child
+// spine, because every node without children holds a value.
+fn last_below<V>(node: &Node<V>, mut path: Vec<u8>) -> Option<(Vec<u8>, &V)> {
+    let mut cur = node;
+    while let Some((nibble, child)) = cur.children().next_back() {
+        path.push(nibble as u8);
+        cur = child;
+    }
+    cur.value.as_ref().map(|value| (path, value))
+}
@@ -0,0 +1,146 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::key::symbols_to_hex;
+use crate::trie::Trie;
+
+/// A broken structural invariant found by [`Trie::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantViolation {
+    /// The hex key of the node where the problem is. Problems with the trie
+    /// as a whole are reported at the root, `""`.
+    pub path: String,
+    pub kind: ViolationKind,
+}
+
+/// What [`Trie::validate`] found wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The node's occupancy bitmap marks a different number of children
+    /// than it stores.
+    ChildCount { marked: usize, stored: usize },
+    /// A child is stored under a symbol its parent's bitmap doesn't mark.
+    UnmarkedChild { symbol: usize },
+    /// A child's symbol isn't below the fanout.
+    SymbolOutOfRange { symbol: usize },
+    /// A child's symbol isn't greater than the one before it, so the
+    /// children aren't in strictly increasing order.
+    ChildOrder { symbol: usize },
+    /// A node other than the root holds neither a value nor children, which
+    /// pruning should have removed.
+    EmptyNode,
+    /// The node has a cached hash but a child of it doesn't, so a change
+    /// below it didn't clear the cache.
+    StaleHash,
+    /// The trie's length doesn't match the values it holds.
+    LenMismatch { len: usize, counted: usize },
+    /// Nodes waiting in the node pool still hold a value, children or a
+    /// cached hash.
+    DirtyPool { nodes: usize },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {:?}: ", self.path)?;
+        match self.kind {
+            ViolationKind::ChildCount { marked, stored } => write!(
+                f,
+                "bitmap marks {} children but {} are stored",
+                marked, stored
+            ),
+            ViolationKind::UnmarkedChild { symbol } => {
+                write!(f, "child {:x} isn't marked in the bitmap", symbol)
+            }
+            ViolationKind::SymbolOutOfRange { symbol } => {
+                write!(f, "child symbol {} is out of range", symbol)
+            }
+            ViolationKind::ChildOrder { symbol } => {
+                write!(f, "child {:x} is out of order", symbol)
+            }
+            ViolationKind::EmptyNode => write!(f, "node has no value and no children"),
+            ViolationKind::StaleHash => write!(f, "cached hash is stale"),
+            ViolationKind::LenMismatch { len, counted } => {
+                write!(f, "len is {} but {} values are stored", len, counted)
+            }
+            ViolationKind::DirtyPool { nodes } => {
+                write!(f, "{} pooled nodes aren't blank", nodes)
+            }
+        }
+    }
+}
+
+impl<V, const N: usize> Trie<V, N> {
+    /// Checks the trie's structural invariants, returning every violation
+    /// found.
+    ///
+    /// It checks that each node's child storage is consistent and in order,
+    /// that no node but the root is left empty, that [`Trie::len`] matches
+    /// a recount, that a node with a cached hash only has children with
+    /// cached hashes, and that the node pool only holds blank nodes. A trie
+    /// built and changed through this crate's methods always passes, so this
+    /// is for tests and for checking tries built in bulk.
+    ///
+    /// ```
+    /// let mut trie = trie::Trie::new();
+    /// for key in ["a1f", "a1e", "b"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// trie.delete_lossy("a1f");
+    /// assert_eq!(trie.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<InvariantViolation>> {
+        let mut violations = Vec::new();
+        let mut counted = 0;
+        let mut path = Vec::new();
+        let mut stack = vec![(&self.root, 0, 0)];
+        while let Some((node, depth, symbol)) = stack.pop() {
+            // The stack is depth first, so the entries above `depth` belong to nodes already
+            // done and the ones below it are this node's ancestors.
+            path.truncate(depth.max(1) - 1);
+            if depth > 0 {
+                path.push(symbol as u8);
+            }
+            let mut report = |kind| {
+                violations.push(InvariantViolation {
+                    path: symbols_to_hex::<N>(&path),
+                    kind,
+                })
+            };
+            for kind in node.layout_violations() {
+                report(kind);
+            }
+            if depth > 0 && node.is_empty() {
+                report(ViolationKind::EmptyNode);
+            }
+            if node.hash.get().is_some() && node.children().any(|(_, c)| c.hash.get().is_none()) {
+                report(ViolationKind::StaleHash);
+            }
+            counted += node.value.is_some() as usize;
+            stack.extend(node.children().map(|(i, child)| (child, depth + 1, i)));
+        }
+
+        let mut report = |kind| {
+            violations.push(InvariantViolation {
+                path: String::new(),
+                kind,
+            })
+        };
+        if counted != self.len {
+            report(ViolationKind::LenMismatch {
+                len: self.len,
+                counted,
+            });
+        }
+        let dirty = self.pool.dirty_nodes();
+        if dirty > 0 {
+            report(ViolationKind::DirtyPool { nodes: dirty });
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::trie::Trie;
+
+/// A trie that keeps every value a key has held, so it can answer what a
+/// key was at any earlier point.
+///
+/// Every insert and delete takes the next version number, starting at 1;
+/// version 0 is the empty trie. A key's history lists its changes oldest
+/// first, with deletes recorded as `None`, so [`VersionedTrie::get_at`]
+/// still finds the value a key had before it was deleted. Histories grow
+/// until [`VersionedTrie::truncate_history`] trims them.
+///
+/// ```
+/// let mut trie = trie::VersionedTrie::new();
+/// let v1 = trie.insert("a1", "first");
+/// let v2 = trie.insert("a1", "second");
+/// let v3 = trie.delete("a1").unwrap();
+/// assert_eq!(trie.get("a1"), None);
+/// assert_eq!(trie.get_at("a1", v1), Some(&"first"));
+/// assert_eq!(trie.get_at("a1", v2), Some(&"second"));
+/// assert_eq!(trie.get_at("a1", v3), None);
+/// assert_eq!(trie.history("a1"), [(1, Some("first")), (2, Some("second")), (3, None)]);
+/// ```
+pub struct VersionedTrie<V> {
+    inner: Trie<Vec<(u64, Option<V>)>>,
+    version: u64,
+    // keys whose latest change isn't a delete
+    live: usize,
+}
+
+impl<V> Default for VersionedTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> VersionedTrie<V> {
+    /// Creates an empty trie at version 0.
+    pub fn new() -> Self {
+        Self {
+            inner: Trie::new(),
+            version: 0,
+            live: 0,
+        }
+    }
+
+    /// Returns the version of the latest change, or 0 if there were none.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the number of keys holding a value now.
+    pub fn len(&self) -> usize {
+        self.live
+    }
+
+    /// Returns `true` if no key holds a value now.
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+
+    /// Stores `value` under `hex_key` as a new version, keeping the values
+    /// before it, and returns that version.
+    pub fn insert(&mut self, hex_key: &str, value: V) -> u64 {
+        self.version += 1;
+        let history = self.inner.get_or_insert_with(hex_key, Vec::new);
+        if !matches!(history.last(), Some((_, Some(_)))) {
+            self.live += 1;
+        }
+        history.push((self.version, Some(value)));
+        self.version
+    }
+
+    /// Records that `hex_key` was deleted as a new version and returns that
+    /// version, or `None` without taking a version if it holds no value.
+    pub fn delete(&mut self, hex_key: &str) -> Option<u64> {
+        let history = self.inner.get_mut(hex_key)?;
+        if !matches!(history.last(), Some((_, Some(_)))) {
+            return None;
+        }
+        self.version += 1;
+        self.live -= 1;
+        history.push((self.version, None));
+        Some(self.version)
+    }
+
+    /// Returns the value `hex_key` holds now.
+    pub fn get(&self, hex_key: &str) -> Option<&V> {
+        self.get_at(hex_key, self.version)
+    }
+
+    /// Returns the value `hex_key` held as of `version`: the latest change
+    /// to it at or before that version, unless that was a delete.
+    pub fn get_at(&self, hex_key: &str, version: u64) -> Option<&V> {
+        value_at(self.inner.get_lossy(hex_key)?, version)
+    }
+
+    /// Returns every change recorded for `hex_key`, oldest first, as
+    /// `(version, value)` pairs with `None` for a delete.
+    pub fn history(&self, hex_key: &str) -> &[(u64, Option<V>)] {
+        self.inner.get_lossy(hex_key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns an iterator over the keys that held a value as of `version`,
+    /// with those values, in key order.
+    pub fn iter_at(&self, version: u64) -> impl Iterator<Item = (String, &V)> {
+        self.inner
+            .iter()
+            .filter_map(move |(key, history)| Some((key, value_at(history, version)?)))
+    }
+
+    /// Forgets the changes that no version from `before` on needs, and so
+    /// gives up answering [`VersionedTrie::get_at`] for versions before it.
+    ///
+    /// Each key keeps the last change at or before `before`, which is its
+    /// value as of that version, and everything after. A key left with
+    /// nothing but a delete is dropped altogether.
+    ///
+    /// ```
+    /// let mut trie = trie::VersionedTrie::new();
+    /// trie.insert("a1", 1);
+    /// trie.insert("a1", 2);
+    /// trie.insert("a1", 3);
+    /// trie.truncate_history(2);
+    /// assert_eq!(trie.history("a1"), [(2, Some(2)), (3, Some(3))]);
+    /// assert_eq!(trie.get_at("a1", 2), Some(&2));
+    /// ```
+    pub fn truncate_history(&mut self, before: u64) {
+        self.inner.retain(|_, history| {
+            if let Some(at) = history.iter().rposition(|&(version, _)| version <= before) {
+                let kept = match history[at] {
+                    (_, None) => at + 1,
+                    _ => at,
+                };
+                history.drain(..kept);
+            }
+            !history.is_empty()
+        });
+    }
+}
+
+fn value_at<V>(history: &[(u64, Option<V>)], version: u64) -> Option<&V> {
+    let after = history.partition_point(|&(changed, _)| changed <= version);
+    history[..after].last()?.1.as_ref()
+}
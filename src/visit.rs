@@ -0,0 +1,127 @@
+use alloc::vec;
+
+use crate::nibbles::Nibbles;
+use crate::node::Node;
+use crate::trie::Trie;
+
+/// What [`Trie::walk`] and [`Trie::walk_mut`] do after visiting a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkControl {
+    /// Goes on to the node's children.
+    Continue,
+    /// Leaves out everything below the node and goes on with the rest.
+    SkipSubtree,
+    /// Ends the walk.
+    Stop,
+}
+
+// Visits `root` and the nodes below it pre-order, in key order, passing each node with its
+// path from `root`. Children are only pushed once `f` has decided about their parent, so a
+// skipped subtree is never touched.
+pub(crate) fn walk_nodes<'a, V, const N: usize, F>(root: &'a Node<V, N>, mut f: F)
+where
+    F: FnMut(&Nibbles, &'a Node<V, N>) -> WalkControl,
+{
+    let mut path = Nibbles::default();
+    // Each entry is a node, its depth and its symbol in the parent.
+    let mut stack = vec![(root, 0, 0)];
+    while let Some((node, depth, symbol)) = stack.pop() {
+        if depth > 0 {
+            path.0.truncate(depth - 1);
+            path.0.push(symbol);
+        }
+        match f(&path, node) {
+            WalkControl::Continue => stack.extend(
+                node.children()
+                    .rev()
+                    .map(|(symbol, child)| (child, depth + 1, symbol as u8)),
+            ),
+            WalkControl::SkipSubtree => {}
+            WalkControl::Stop => return,
+        }
+    }
+}
+
+impl<V> Trie<V> {
+    /// Visits every node pre-order, in key order, with its path and its
+    /// value if it holds one. What `f` returns decides whether the walk goes
+    /// below the node, skips everything under it or stops.
+    ///
+    /// Nodes without values are visited too, since they're where a whole
+    /// prefix can be skipped.
+    ///
+    /// ```
+    /// use trie::{Trie, WalkControl};
+    ///
+    /// let mut trie = Trie::new();
+    /// for key in ["a1", "ff", "ff0", "ff1"] {
+    ///     trie.insert_lossy(key, ());
+    /// }
+    /// let mut seen = Vec::new();
+    /// trie.walk(|path, value| {
+    ///     if path.to_hex() == "ff" {
+    ///         return WalkControl::SkipSubtree;
+    ///     }
+    ///     if value.is_some() {
+    ///         seen.push(path.to_hex());
+    ///     }
+    ///     WalkControl::Continue
+    /// });
+    /// assert_eq!(seen, ["a1"]);
+    /// ```
+    pub fn walk<F>(&self, mut f: F)
+    where
+        F: FnMut(&Nibbles, Option<&V>) -> WalkControl,
+    {
+        walk_nodes(&self.root, |path, node| f(path, node.value.as_ref()));
+    }
+
+    /// Like [`Trie::walk`], but `f` may change the values it's given.
+    ///
+    /// ```
+    /// use trie::{Trie, WalkControl};
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert_lossy("a1", 1);
+    /// trie.insert_lossy("b0", 2);
+    /// trie.walk_mut(|_, value| {
+    ///     if let Some(value) = value {
+    ///         *value *= 10;
+    ///         return WalkControl::Stop;
+    ///     }
+    ///     WalkControl::Continue
+    /// });
+    /// assert_eq!(trie.get_lossy("a1"), Some(&10));
+    /// assert_eq!(trie.get_lossy("b0"), Some(&2));
+    /// ```
+    pub fn walk_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Nibbles, Option<&mut V>) -> WalkControl,
+    {
+        let Trie {
+            root, snapshots, ..
+        } = self;
+        let mut path = Nibbles::default();
+        let mut stack = vec![(root, 0, 0)];
+        while let Some((node, depth, symbol)) = stack.pop() {
+            if depth > 0 {
+                path.0.truncate(depth - 1);
+                path.0.push(symbol);
+            }
+            // every node above one `f` changes has been visited, so dropping the hash of each
+            // visited node covers them all
+            node.invalidate();
+            let (value, children) = node.value_and_children_mut();
+            snapshots.save_value(&path.0, value.as_deref());
+            match f(&path, value) {
+                WalkControl::Continue => stack.extend(
+                    children
+                        .rev()
+                        .map(|(symbol, child)| (child, depth + 1, symbol as u8)),
+                ),
+                WalkControl::SkipSubtree => {}
+                WalkControl::Stop => return,
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+// Write-ahead logging.
+//
+// A `LoggedTrie` keeps two files: the log at the path it was opened with, and a snapshot
+// next to it with `.snapshot` appended to the file name. Each mutation is appended to the
+// log as one record before it touches the in-memory trie:
+//
+//   u32 LE payload length | u32 LE CRC-32 of the payload | payload
+//
+// where the payload is an op byte (1 = insert, 2 = delete), a u32 LE key length, the key
+// bytes and, for inserts, the value bytes from `ValueCodec`. Opening loads the snapshot (if
+// any) and replays the log over it. A crash can leave the last record half written; replay
+// stops at the first record whose length runs past the end of the file or whose CRC doesn't
+// match, and cuts the log back to the last complete record so new appends start clean.
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use crate::codec::ValueCodec;
+use crate::snapshot::SnapshotError;
+use crate::trie::Trie;
+
+const OP_INSERT: u8 = 1;
+const OP_DELETE: u8 = 2;
+
+/// A [`Trie`] whose mutations are appended to a log file before they are
+/// applied, so they survive a crash.
+///
+/// Reads go straight to the in-memory trie through `Deref`. Every write is
+/// synced to disk before it returns; [`LoggedTrie::checkpoint`] folds the log
+/// into a snapshot so it doesn't grow forever.
+pub struct LoggedTrie<V> {
+    trie: Trie<V>,
+    log: File,
+    log_path: PathBuf,
+    snapshot_path: PathBuf,
+}
+
+impl<V: ValueCodec> LoggedTrie<V> {
+    /// Opens the log at `path`, creating it if needed, and rebuilds the trie
+    /// from the last checkpoint plus every complete record in the log.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        let log_path = path.as_ref().to_path_buf();
+        let mut name = OsString::from(log_path.file_name().unwrap_or_default());
+        name.push(".snapshot");
+        let snapshot_path = log_path.with_file_name(name);
+
+        let mut trie = match File::open(&snapshot_path) {
+            Ok(file) => Trie::load_from(file)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Trie::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&log_path)?;
+        let good = replay(&mut trie, BufReader::new(&log))?;
+        if good < log.metadata()?.len() {
+            log.set_len(good)?;
+            log.sync_data()?;
+        }
+
+        Ok(Self {
+            trie,
+            log,
+            log_path,
+            snapshot_path,
+        })
+    }
+
+    /// Logs and applies an insert, returning the value previously stored
+    /// under `hex_key`.
+    pub fn insert(&mut self, hex_key: &str, value: V) -> io::Result<Option<V>> {
+        self.append(OP_INSERT, hex_key, &value.encode())?;
+        Ok(self.trie.insert_lossy(hex_key, value))
+    }
+
+    /// Logs and applies a delete, returning the removed value. Nothing is
+    /// logged when the key isn't present.
+    pub fn delete(&mut self, hex_key: &str) -> io::Result<Option<V>> {
+        if !self.trie.contains_key(hex_key) {
+            return Ok(None);
+        }
+        self.append(OP_DELETE, hex_key, &[])?;
+        Ok(self.trie.delete_lossy(hex_key))
+    }
+
+    /// Writes the current contents to the snapshot file and empties the log.
+    ///
+    /// The snapshot is written to a temporary file and renamed into place, so
+    /// a crash part way through leaves the previous snapshot intact. If the
+    /// crash lands after the rename but before the log is emptied, replaying
+    /// the old log over the new snapshot gives the same contents again.
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        let mut tmp_name = OsString::from(self.snapshot_path.file_name().unwrap_or_default());
+        tmp_name.push(".tmp");
+        let tmp_path = self.snapshot_path.with_file_name(tmp_name);
+
+        let tmp = File::create(&tmp_path)?;
+        self.trie.save_to(&tmp)?;
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, &self.snapshot_path)?;
+
+        self.log.set_len(0)?;
+        self.log.sync_data()
+    }
+
+    /// The path of the log file.
+    pub fn log_path(&self) -> &Path {
+        &self.log_path
+    }
+
+    /// The path of the snapshot file written by [`LoggedTrie::checkpoint`].
+    pub fn snapshot_path(&self) -> &Path {
+        &self.snapshot_path
+    }
+
+    /// Gives up logging and hands back the in-memory trie.
+    pub fn into_inner(self) -> Trie<V> {
+        self.trie
+    }
+
+    fn append(&mut self, op: u8, hex_key: &str, value: &[u8]) -> io::Result<()> {
+        let mut payload = Vec::with_capacity(5 + hex_key.len() + value.len());
+        payload.push(op);
+        payload.extend_from_slice(&(hex_key.len() as u32).to_le_bytes());
+        payload.extend_from_slice(hex_key.as_bytes());
+        payload.extend_from_slice(value);
+
+        // one write_all per record keeps a crash from interleaving half records
+        let mut record = Vec::with_capacity(8 + payload.len());
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(&crc32(&payload).to_le_bytes());
+        record.extend_from_slice(&payload);
+        (&self.log).write_all(&record)?;
+        self.log.sync_data()
+    }
+}
+
+impl<V> Deref for LoggedTrie<V> {
+    type Target = Trie<V>;
+
+    fn deref(&self) -> &Trie<V> {
+        &self.trie
+    }
+}
+
+// Applies every complete record from `r` and returns the byte offset just past the last one.
+// A short or checksum-failing record ends the replay; a record that checks out but doesn't
+// parse means the log was written by something else, so that is reported as corruption.
+fn replay<V: ValueCodec, R: Read>(trie: &mut Trie<V>, mut r: R) -> Result<u64, SnapshotError> {
+    let mut offset = 0u64;
+    loop {
+        let mut header = [0u8; 8];
+        if !read_full(&mut r, &mut header)? {
+            return Ok(offset);
+        }
+        let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+        let crc = u32::from_le_bytes(header[4..].try_into().unwrap());
+
+        let mut payload = Vec::new();
+        if (&mut r).take(len as u64).read_to_end(&mut payload)? < len || crc32(&payload) != crc {
+            return Ok(offset);
+        }
+        apply(trie, payload)?;
+        offset += 8 + len as u64;
+    }
+}
+
+fn apply<V: ValueCodec>(trie: &mut Trie<V>, mut payload: Vec<u8>) -> Result<(), SnapshotError> {
+    if payload.len() < 5 {
+        return Err(SnapshotError::Corrupt("log record too short"));
+    }
+    let key_len = u32::from_le_bytes(payload[1..5].try_into().unwrap()) as usize;
+    if payload.len() - 5 < key_len {
+        return Err(SnapshotError::Corrupt("log key runs past its record"));
+    }
+    let value = payload.split_off(5 + key_len);
+    let key = std::str::from_utf8(&payload[5..])
+        .map_err(|_| SnapshotError::Corrupt("log key is not UTF-8"))?;
+    match payload[0] {
+        OP_INSERT => {
+            let value =
+                V::decode(value).ok_or(SnapshotError::Corrupt("log value does not decode"))?;
+            trie.insert_lossy(key, value);
+        }
+        OP_DELETE if value.is_empty() => {
+            trie.delete_lossy(key);
+        }
+        _ => return Err(SnapshotError::Corrupt("unknown log record")),
+    }
+    Ok(())
+}
+
+// Like read_exact, except a clean end of input (or one that cuts the buffer short) is Ok(false).
+fn read_full<R: Read>(r: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => return Ok(false),
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+// CRC-32 (IEEE, reflected), bit at a time. Records are small and this only runs on writes
+// and on open, so a lookup table isn't worth it.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
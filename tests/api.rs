@@ -0,0 +1,43 @@
+// The library's public surface as a dependent crate sees it.
+
+use trie::{HexTrie, Trie};
+
+#[test]
+fn a_new_trie_is_empty() {
+    let trie: Trie<String> = Trie::new();
+    assert!(trie.is_empty());
+    assert_eq!(trie.len(), 0);
+    assert_eq!(trie.node_count(), 1);
+    assert_eq!(trie.to_string(), "(root)\n");
+    let default: HexTrie = HexTrie::default();
+    assert!(default.is_empty());
+}
+
+#[test]
+fn insert_get_delete() {
+    let mut trie = Trie::new();
+    assert_eq!(trie.try_insert("a1f", "leaf".to_string()), Ok(None));
+    assert_eq!(trie.try_get("a1f"), Ok(Some(&"leaf".to_string())));
+    assert_eq!(trie.try_get("a1"), Ok(None));
+    assert_eq!(trie.try_delete("a1f"), Ok(Some("leaf".to_string())));
+    assert_eq!(trie.try_get("a1f"), Ok(None));
+    assert!(trie.is_empty());
+}
+
+#[test]
+#[allow(deprecated)]
+fn the_original_names_still_work() {
+    let mut trie = Trie::new();
+    assert_eq!(trie.insert("A1-F", 1), None);
+    assert_eq!(trie.get("a1f"), Some(&1));
+    assert_eq!(trie.delete("a1f"), Some(1));
+    assert_eq!(trie.get("a1f"), None);
+}
+
+#[test]
+fn keys_are_read_case_insensitively() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("ABCDEF", 1);
+    assert_eq!(trie.get_lossy("abcdef"), Some(&1));
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["abcdef"]);
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+
+use trie::{ArenaTrie, Trie};
+
+mod common;
+use common::Rng;
+
+#[test]
+fn matches_a_btreemap_and_the_boxed_trie() {
+    let mut rng = Rng::new(62);
+    let mut arena = ArenaTrie::new();
+    let mut boxed = Trie::new();
+    let mut map = BTreeMap::new();
+    for step in 0..5_000 {
+        let (key, value) = (rng.key(5), rng.below(100));
+        match rng.below(4) {
+            0 => {
+                assert_eq!(arena.delete(&key), map.remove(&key));
+                boxed.delete_lossy(&key);
+            }
+            1 => assert_eq!(arena.get(&key), map.get(&key)),
+            _ => {
+                assert_eq!(arena.insert(&key, value), map.insert(key.clone(), value));
+                boxed.insert_lossy(&key, value);
+            }
+        }
+        assert_eq!(arena.len(), map.len(), "step {}", step);
+        // both prune the same way, so they hold the same nodes
+        assert_eq!(arena.node_count(), boxed.node_count(), "step {}", step);
+    }
+    let entries: Vec<(String, u64)> = arena.iter().map(|(key, value)| (key, *value)).collect();
+    assert_eq!(entries, map.into_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn freed_slots_are_reused() {
+    let mut arena = ArenaTrie::new();
+    for round in 0..100 {
+        for n in 0..50 {
+            arena.insert(&format!("{:04x}", n * 97 + round), n);
+        }
+        for n in 0..50 {
+            arena.delete(&format!("{:04x}", n * 97 + round));
+        }
+    }
+    assert!(arena.is_empty());
+    assert_eq!(arena.node_count(), 1);
+    // one round's worth of nodes at most, not a hundred
+    assert!(arena.arena_len() <= 1 + 50 * 4, "{}", arena.arena_len());
+}
+
+#[test]
+fn compact_keeps_the_entries_and_drops_free_slots() {
+    let mut rng = Rng::new(620);
+    let mut arena = ArenaTrie::new();
+    for _ in 0..500 {
+        arena.insert(&rng.key(6), rng.below(10));
+    }
+    let keys: Vec<String> = arena.iter().map(|(key, _)| key).collect();
+    for key in keys.iter().step_by(3) {
+        arena.delete(key);
+    }
+    let before: Vec<(String, u64)> = arena.iter().map(|(key, value)| (key, *value)).collect();
+    arena.compact();
+    assert_eq!(arena.arena_len(), arena.node_count());
+    let after: Vec<(String, u64)> = arena.iter().map(|(key, value)| (key, *value)).collect();
+    assert_eq!(after, before);
+    for (key, value) in &before {
+        assert_eq!(arena.get(key), Some(value));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+#[test]
+fn a_fresh_insert_returns_none() {
+    let mut trie = Trie::new();
+    assert_eq!(trie.insert_lossy("a1f", "leaf".to_string()), None);
+    assert_eq!(trie.insert_nibbles([10, 1, 14], "other".to_string()), None);
+    assert_eq!(trie.len(), 2);
+}
+
+#[test]
+fn an_overwrite_returns_the_old_value() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", "first".to_string());
+    assert_eq!(
+        trie.insert_lossy("a1f", "second".to_string()),
+        Some("first".to_string())
+    );
+    assert_eq!(
+        trie.insert_nibbles([10, 1, 15], "third".to_string()),
+        Some("second".to_string())
+    );
+    assert_eq!(trie.get_lossy("a1f").unwrap(), "third");
+    assert_eq!(trie.len(), 1);
+}
+
+#[test]
+fn an_overwrite_at_an_interior_node_keeps_its_children() {
+    let mut trie = Trie::new();
+    for key in ["a1", "a1e", "a1f", "a1f0"] {
+        trie.insert_lossy(key, key.to_uppercase());
+    }
+    let nodes = trie.node_count();
+    assert_eq!(
+        trie.insert_lossy("a1", "new".to_string()),
+        Some("A1".to_string())
+    );
+    assert_eq!(
+        trie.insert_nibbles([10, 1, 15], "newer".to_string()),
+        Some("A1F".to_string())
+    );
+    assert_eq!(trie.node_count(), nodes);
+    assert_eq!(trie.len(), 4);
+    assert_eq!(trie.get_lossy("a1e").unwrap(), "A1E");
+    assert_eq!(trie.get_lossy("a1f0").unwrap(), "A1F0");
+    assert_eq!(trie.get_lossy("a1").unwrap(), "new");
+}
+
+#[test]
+fn delete_returns_the_removed_value() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", "leaf".to_string());
+    assert_eq!(trie.try_delete("a1f"), Ok(Some("leaf".to_string())));
+    assert_eq!(trie.try_get("a1f"), Ok(None));
+}
+
+#[test]
+fn deleting_a_missing_key_leaves_the_trie_alone() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", 1);
+    let nodes = trie.node_count();
+    let pooled = trie.pool_len();
+    for key in ["a1", "a1f0", "b", "", "a1e"] {
+        assert_eq!(trie.try_delete(key), Ok(None), "{:?}", key);
+    }
+    assert_eq!(trie.node_count(), nodes);
+    assert_eq!(trie.pool_len(), pooled);
+    assert_eq!(trie.try_get("a1f"), Ok(Some(&1)));
+}
+
+#[test]
+fn deleting_a_prefix_key_keeps_the_shared_path() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", 1);
+    trie.insert_lossy("a1f", 2);
+    let nodes = trie.node_count();
+    assert_eq!(trie.try_delete("a1"), Ok(Some(1)));
+    assert_eq!(trie.node_count(), nodes);
+    assert_eq!(trie.try_get("a1f"), Ok(Some(&2)));
+}
+
+#[test]
+fn deleting_the_last_key_prunes_back_to_the_root() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", 1);
+    assert_eq!(trie.try_delete("a1f"), Ok(Some(1)));
+    assert_eq!(trie.node_count(), 1);
+    assert!(trie.is_empty());
+    assert!(trie.validate().is_ok());
+    assert_eq!(trie.to_string(), "(root)\n");
+}
+
+#[test]
+fn contains_key_ignores_interior_nodes() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", ());
+    assert!(trie.contains_key("a1f"));
+    assert!(!trie.contains_key("a1"));
+    assert!(!trie.contains_key("a"));
+    assert!(!trie.contains_key(""));
+    trie.insert_lossy("a1", ());
+    assert!(trie.contains_key("a1"));
+}
+
+#[test]
+fn contains_key_past_the_longest_key_is_false() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", ());
+    assert!(!trie.contains_key("a1f0"));
+    assert!(!trie.contains_key("a1f0123456789abcdef"));
+    assert!(!trie.contains_key("b"));
+}
+
+#[test]
+fn len_matches_a_hashmap_shadow() {
+    let mut rng = Rng::new(7);
+    for _ in 0..200 {
+        let mut trie = Trie::new();
+        let mut shadow = HashMap::new();
+        for _ in 0..100 {
+            let key = rng.key(4);
+            let value = rng.below(10);
+            match rng.below(3) {
+                0 => assert_eq!(trie.insert_lossy(&key, value), shadow.insert(key, value)),
+                1 => {
+                    let nibbles = key.chars().map(|c| c.to_digit(16).unwrap() as usize);
+                    assert_eq!(
+                        trie.insert_nibbles(nibbles, value),
+                        shadow.insert(key, value)
+                    );
+                }
+                _ => assert_eq!(trie.delete_lossy(&key), shadow.remove(&key)),
+            }
+            assert_eq!(trie.len(), shadow.len());
+            assert_eq!(trie.is_empty(), shadow.is_empty());
+        }
+    }
+}
+
+#[test]
+fn a_pruned_trie_is_empty() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", 1);
+    trie.insert_lossy("a1f", 2);
+    assert_eq!(trie.len(), 1);
+    trie.delete_lossy("a1");
+    assert_eq!(trie.len(), 1);
+    trie.delete_lossy("a1f");
+    assert_eq!(trie.len(), 0);
+    assert!(trie.is_empty());
+    trie.delete_lossy("a1f");
+    assert_eq!(trie.len(), 0);
+}
+
+#[test]
+fn the_empty_key_is_the_root_value() {
+    let mut trie = Trie::new();
+    assert_eq!(trie.try_insert("", 0), Ok(None));
+    trie.insert_lossy("a1", 1);
+    assert_eq!(trie.root_value(), Some(&0));
+    assert_eq!(trie.try_get(""), Ok(Some(&0)));
+    assert!(trie.contains_key(""));
+    assert_eq!(trie.len(), 2);
+    assert_eq!(trie.to_string(), "(root) = 0\n└── a1 = 1\n");
+
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["", "a1"]);
+    assert_eq!(trie.iter_prefix("").count(), 2);
+    assert_eq!(trie.count_prefix(""), 2);
+    assert_eq!(trie.first_key_value(), Some((String::new(), &0)));
+    assert_eq!(trie.longest_prefix("b"), Some((String::new(), &0)));
+
+    *trie.root_value_mut().unwrap() += 10;
+    assert_eq!(trie.try_delete(""), Ok(Some(10)));
+    assert_eq!(trie.try_delete(""), Ok(None));
+    assert_eq!(trie.root_value(), None);
+    assert_eq!(trie.len(), 1);
+    // the root stays when its value goes, and so does everything under it
+    assert_eq!(trie.try_get("a1"), Ok(Some(&1)));
+    assert_eq!(trie.to_string(), "(root)\n└── a1 = 1\n");
+}
@@ -0,0 +1,122 @@
+// apply_batch applies every op or none of them.
+
+use trie::{KeyError, Op, OpOutcome, Trie};
+
+mod common;
+use common::Rng;
+
+fn insert(key: &str, value: u32) -> Op<u32> {
+    Op::Insert {
+        key: key.to_string(),
+        value,
+    }
+}
+
+fn delete(key: &str) -> Op<u32> {
+    Op::Delete {
+        key: key.to_string(),
+    }
+}
+
+// Everything about a trie's shape and contents: its nodes one by one and the memory behind them.
+fn state(trie: &Trie<u32>) -> (String, usize) {
+    (format!("{:#?}", trie), trie.memory_usage().total_bytes)
+}
+
+#[test]
+fn a_late_bad_key_leaves_the_trie_untouched() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", 1);
+    trie.insert_lossy("b0", 2);
+    let before = state(&trie);
+    let mut ops: Vec<_> = (0..29).map(|n| insert(&format!("{:04x}", n), n)).collect();
+    ops.push(delete("a1"));
+    ops.push(insert("b0", 9));
+    ops.push(insert("c0ffee-", 10));
+    ops.push(insert("ff", 11));
+    let err = trie.apply_batch(&ops).unwrap_err();
+    assert_eq!(err.index, 31);
+    assert_eq!(err.key, "c0ffee-");
+    assert_eq!(
+        err.reason,
+        KeyError::InvalidChar {
+            index: 6,
+            found: '-'
+        }
+    );
+    assert_eq!(state(&trie), before);
+}
+
+#[test]
+fn the_receipt_reports_each_outcome() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", 1);
+    let receipt = trie
+        .apply_batch(&[
+            insert("b0", 2),
+            insert("a1", 3),
+            delete("a1"),
+            delete("a1"),
+            delete("b0f"),
+            insert("B0", 4),
+        ])
+        .unwrap();
+    assert_eq!(
+        receipt.outcomes,
+        [
+            OpOutcome::Inserted,
+            OpOutcome::Overwritten,
+            OpOutcome::Deleted,
+            OpOutcome::NotFound,
+            OpOutcome::NotFound,
+            OpOutcome::Overwritten,
+        ]
+    );
+    assert_eq!(trie.len(), 1);
+    assert_eq!(trie.get_lossy("b0"), Some(&4));
+    // deleting "a1" pruned its nodes
+    assert_eq!(trie.node_count(), 3);
+}
+
+#[test]
+fn an_empty_batch_does_nothing() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", 1);
+    let before = state(&trie);
+    assert!(trie.apply_batch(&[]).unwrap().outcomes.is_empty());
+    assert_eq!(state(&trie), before);
+}
+
+#[test]
+fn a_batch_matches_the_same_ops_one_by_one() {
+    let mut rng = Rng::new(81);
+    let mut batched = Trie::new();
+    let mut single = Trie::new();
+    for _ in 0..50 {
+        let ops: Vec<_> = (0..rng.below(40))
+            .map(|n| {
+                let key = rng.key(4);
+                if rng.below(3) == 0 {
+                    delete(&key)
+                } else {
+                    insert(&key, n as u32)
+                }
+            })
+            .collect();
+        let receipt = batched.apply_batch(&ops).unwrap();
+        for (op, outcome) in ops.iter().zip(&receipt.outcomes) {
+            let expected = match op {
+                Op::Insert { key, value } => match single.insert_lossy(key, *value) {
+                    Some(_) => OpOutcome::Overwritten,
+                    None => OpOutcome::Inserted,
+                },
+                Op::Delete { key } => match single.delete_lossy(key) {
+                    Some(_) => OpOutcome::Deleted,
+                    None => OpOutcome::NotFound,
+                },
+            };
+            assert_eq!(*outcome, expected);
+        }
+        assert_eq!(state(&batched), state(&single));
+    }
+}
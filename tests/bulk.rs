@@ -0,0 +1,72 @@
+// from_sorted_iter against building the same trie one insert at a time.
+
+use std::collections::BTreeMap;
+
+use trie::{Nibbles, Trie};
+
+mod common;
+use common::Rng;
+
+fn nibbles(key: &str) -> Nibbles {
+    key.parse().unwrap()
+}
+
+#[test]
+fn matches_inserting_each_key() {
+    let mut rng = Rng::new(83);
+    for _ in 0..100 {
+        let map: BTreeMap<String, u32> = (0..rng.below(300))
+            .map(|n| (rng.key(8), n as u32))
+            .collect();
+        let loaded =
+            Trie::from_sorted_iter(map.iter().map(|(key, &value)| (nibbles(key), value))).unwrap();
+        let mut inserted = Trie::new();
+        for (key, &value) in &map {
+            inserted.insert_lossy(key, value);
+        }
+        assert_eq!(loaded, inserted);
+        assert_eq!(loaded.len(), map.len());
+        assert_eq!(loaded.node_count(), inserted.node_count());
+        assert_eq!(format!("{:#?}", loaded), format!("{:#?}", inserted));
+        assert!(loaded.validate().is_ok());
+    }
+}
+
+#[test]
+fn empty_input_and_the_root_key() {
+    let empty = Trie::<u32>::from_sorted_iter([]).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.node_count(), 1);
+
+    let trie =
+        Trie::from_sorted_iter([(nibbles(""), 0), (nibbles("0"), 1), (nibbles("00"), 2)]).unwrap();
+    assert_eq!(trie.root_value(), Some(&0));
+    assert_eq!(trie.get_lossy("00"), Some(&2));
+    assert_eq!(trie.len(), 3);
+    assert_eq!(trie.node_count(), 3);
+}
+
+#[test]
+fn keys_out_of_order_are_refused() {
+    let err = Trie::from_sorted_iter([(nibbles("a1"), 1), (nibbles("b"), 2), (nibbles("a1f"), 3)])
+        .unwrap_err();
+    assert_eq!(err.index, 2);
+    assert_eq!(err.key, nibbles("a1f"));
+    assert_eq!(err.to_string(), "key a1f at index 2 is out of order");
+
+    // a repeated key isn't strictly increasing, and neither is a prefix after its extension
+    let err = Trie::from_sorted_iter([(nibbles("a1"), 1), (nibbles("a1"), 2)]).unwrap_err();
+    assert_eq!(err.index, 1);
+    let err = Trie::from_sorted_iter([(nibbles("a1"), 1), (nibbles("a"), 2)]).unwrap_err();
+    assert_eq!(err.index, 1);
+}
+
+#[test]
+fn a_dense_build_matches() {
+    let loaded =
+        Trie::from_sorted_iter((0..16u32.pow(4)).map(|n| (nibbles(&format!("{:04x}", n)), n)))
+            .unwrap();
+    assert_eq!(loaded.len(), 65_536);
+    assert_eq!(loaded.node_count(), 1 + 16 + 256 + 4096 + 65_536);
+    assert_eq!(loaded.get_lossy("beef"), Some(&0xbeef));
+}
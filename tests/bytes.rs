@@ -0,0 +1,162 @@
+// ByteTrie against a BTreeMap of byte strings.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use trie::ByteTrie;
+
+mod common;
+use common::Rng;
+
+// A key of up to `max_len` bytes drawn from a few values, low and high, so keys share prefixes
+// and byte order differs from signed order.
+fn random_key(rng: &mut Rng, max_len: u64) -> Vec<u8> {
+    let len = rng.below(max_len + 1);
+    (0..len)
+        .map(|_| [0x00, 0x01, 0x7f, 0xff][rng.below(4) as usize])
+        .collect()
+}
+
+// One node per distinct prefix of a stored key, the root included.
+fn prefix_nodes<V>(model: &BTreeMap<Vec<u8>, V>) -> usize {
+    let mut prefixes = BTreeSet::new();
+    prefixes.insert(&[][..]);
+    for key in model.keys() {
+        for end in 1..=key.len() {
+            prefixes.insert(&key[..end]);
+        }
+    }
+    prefixes.len()
+}
+
+fn assert_matches(trie: &ByteTrie<u32>, model: &BTreeMap<Vec<u8>, u32>) {
+    let entries: Vec<(Vec<u8>, u32)> = trie.iter().map(|(k, v)| (k, *v)).collect();
+    let expected: Vec<(Vec<u8>, u32)> = model.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    assert_eq!(entries, expected);
+    assert_eq!(trie.len(), model.len());
+    assert_eq!(trie.node_count(), prefix_nodes(model));
+}
+
+#[test]
+fn matches_a_btreemap() {
+    let mut rng = Rng::new(47);
+    let mut trie = ByteTrie::new();
+    let mut model = BTreeMap::new();
+    for n in 0..4_000u32 {
+        let key = random_key(&mut rng, 6);
+        match rng.below(4) {
+            0 | 1 => assert_eq!(trie.insert(&key, n), model.insert(key.clone(), n)),
+            2 => assert_eq!(trie.delete(&key), model.remove(&key)),
+            _ => {
+                if let Some(value) = trie.get_mut(&key) {
+                    *value += 1;
+                }
+                if let Some(value) = model.get_mut(&key) {
+                    *value += 1;
+                }
+            }
+        }
+        assert_eq!(trie.get(&key), model.get(&key));
+        assert_eq!(trie.contains_key(&key), model.contains_key(&key));
+        if n % 100 == 0 {
+            assert_matches(&trie, &model);
+        }
+    }
+    assert_matches(&trie, &model);
+
+    for key in model.keys() {
+        assert!(trie.delete(key).is_some());
+    }
+    assert!(trie.is_empty());
+    assert_eq!(trie.node_count(), 1);
+}
+
+#[test]
+fn iter_prefix_matches_a_btreemap() {
+    let mut rng = Rng::new(4_747);
+    let mut trie = ByteTrie::new();
+    let mut model = BTreeMap::new();
+    for n in 0..1_000u32 {
+        let key = random_key(&mut rng, 6);
+        trie.insert(&key, n);
+        model.insert(key, n);
+    }
+    for _ in 0..200 {
+        let prefix = random_key(&mut rng, 4);
+        let got: Vec<(Vec<u8>, u32)> = trie.iter_prefix(&prefix).map(|(k, v)| (k, *v)).collect();
+        let expected: Vec<(Vec<u8>, u32)> = model
+            .range(prefix.clone()..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        assert_eq!(got, expected, "prefix {:02x?}", prefix);
+    }
+    assert_eq!(trie.iter_prefix(&[0x02]).count(), 0);
+}
+
+#[test]
+fn deleting_prunes_back_to_the_nearest_kept_node() {
+    let mut trie = ByteTrie::new();
+    trie.insert(b"ab", 1);
+    trie.insert(b"abcde", 2);
+    trie.insert(b"abx", 3);
+    assert_eq!(trie.node_count(), 7);
+    // "abc".."abcde" goes, "ab" and its other child stay
+    assert_eq!(trie.delete(b"abcde"), Some(2));
+    assert_eq!(trie.node_count(), 4);
+    // a key with children below it only loses its value
+    assert_eq!(trie.delete(b"ab"), Some(1));
+    assert_eq!(trie.node_count(), 4);
+    assert_eq!(trie.get(b"abx"), Some(&3));
+
+    // misses leave the trie alone
+    assert_eq!(trie.delete(b"ab"), None);
+    assert_eq!(trie.delete(b"abxy"), None);
+    assert_eq!(trie.delete(b""), None);
+    assert_eq!(trie.node_count(), 4);
+
+    assert_eq!(trie.delete(b"abx"), Some(3));
+    assert_eq!(trie.node_count(), 1);
+    trie.insert(b"", 0);
+    assert_eq!(trie.delete(b""), Some(0));
+    assert!(trie.is_empty());
+}
+
+#[test]
+fn display_writes_two_hex_digits_per_byte() {
+    let mut trie = ByteTrie::new();
+    trie.insert(&[0x00, 0x0a], 1);
+    trie.insert(&[0x00, 0x0a, 0xff, 0x10], 2);
+    trie.insert(&[0x00, 0xb0], 3);
+    trie.insert(&[0xff], 4);
+    let expected = "\
+(root)
+├── 00
+│   ├── 0a = 1
+│   │   └── ff10 = 2
+│   └── b0 = 3
+└── ff = 4
+";
+    assert_eq!(trie.to_string(), expected);
+    trie.insert(b"", 0);
+    assert!(trie.to_string().starts_with("(root) = 0\n├── 00\n"));
+}
+
+// One node per byte, far deeper than anything recursing per node could go.
+#[test]
+fn a_deep_key_deletes_and_drops() {
+    const DEPTH: usize = 1_000_000;
+    let key = vec![0x5a; DEPTH];
+    let mut trie = ByteTrie::new();
+    trie.insert(&key, 1);
+    trie.insert(&key[..DEPTH / 2], 2);
+    assert_eq!(trie.node_count(), DEPTH + 1);
+    assert_eq!(trie.get(&key), Some(&1));
+    assert_eq!(trie.to_string().lines().count(), 3);
+
+    assert_eq!(trie.delete(&key), Some(1));
+    assert_eq!(trie.node_count(), DEPTH / 2 + 1);
+    assert_eq!(trie.iter().count(), 1);
+
+    trie.insert(&key, 1);
+    drop(trie);
+}
@@ -0,0 +1,78 @@
+// compare_and_swap for every pairing of what the caller expects with what the key holds.
+
+use trie::{CasError, Trie};
+
+// A trie holding `current` under "a1f", if anything, next to a longer key that keeps the
+// path's nodes shared.
+fn holding(current: Option<u32>) -> Trie<u32> {
+    let mut trie = Trie::new();
+    trie.insert_lossy("b0", 0);
+    if let Some(value) = current {
+        trie.insert_lossy("a1f", value);
+    }
+    trie
+}
+
+#[test]
+fn every_expected_and_actual_pairing() {
+    let states = [None, Some(1), Some(2)];
+    for current in states {
+        for expected in states {
+            for new in [None, Some(3)] {
+                let mut trie = holding(current);
+                let result = trie.compare_and_swap("a1f", expected.as_ref(), new);
+                if expected == current {
+                    assert_eq!(result, Ok(()));
+                    assert_eq!(trie.get_lossy("a1f").copied(), new);
+                    assert_eq!(trie.len(), 1 + usize::from(new.is_some()));
+                    // writing nothing leaves no nodes behind, like delete
+                    assert_eq!(trie, holding(new));
+                    assert_eq!(trie.node_count(), holding(new).node_count());
+                } else {
+                    assert_eq!(result, Err(CasError { actual: current }));
+                    assert_eq!(trie, holding(current));
+                    assert_eq!(trie.node_count(), holding(current).node_count());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn a_failed_swap_on_a_missing_key_builds_no_nodes() {
+    let mut trie = holding(None);
+    let nodes = trie.node_count();
+    let err = trie
+        .compare_and_swap("a1f00", Some(&1), Some(2))
+        .unwrap_err();
+    assert_eq!(err.actual, None);
+    assert_eq!(trie.node_count(), nodes);
+}
+
+#[test]
+fn retrying_with_the_actual_value_succeeds() {
+    let mut trie = holding(Some(5));
+    let mut expected = None;
+    let mut tries = 0;
+    while let Err(err) = trie.compare_and_swap("a1f", expected.as_ref(), Some(6)) {
+        expected = err.actual;
+        tries += 1;
+    }
+    assert_eq!(tries, 1);
+    assert_eq!(trie.get_lossy("a1f"), Some(&6));
+}
+
+#[test]
+fn the_error_says_what_the_key_holds() {
+    let err = holding(Some(1))
+        .compare_and_swap("a1f", None, Some(2))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "key holds 1 instead of the expected value");
+    let err = holding(None)
+        .compare_and_swap("a1f", Some(&1), None)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "key holds no value instead of the expected one"
+    );
+}
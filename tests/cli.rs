@@ -0,0 +1,199 @@
+// Runs the `trie` binary the way a shell would, checking what each command prints and the exit
+// status it ends with.
+#![cfg(feature = "std")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+mod common;
+use common::temp_dir;
+
+// Runs `trie --file <file> <args>`.
+fn trie(file: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_trie"))
+        .arg("--file")
+        .arg(file)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+fn data_file(test: &str) -> (PathBuf, PathBuf) {
+    let dir = temp_dir(test);
+    (dir.join("data.trie"), dir)
+}
+
+#[test]
+fn insert_then_get_prints_the_value() {
+    let (file, _) = data_file("cli-insert-get");
+    let insert = trie(&file, &["insert", "a1f", "leaf"]);
+    assert_eq!(insert.status.code(), Some(0), "{}", stderr(&insert));
+    assert!(file.exists());
+    let get = trie(&file, &["get", "a1f"]);
+    assert_eq!(get.status.code(), Some(0));
+    assert_eq!(stdout(&get), "leaf\n");
+}
+
+#[test]
+fn get_of_a_missing_key_exits_with_1() {
+    let (file, _) = data_file("cli-get-missing");
+    trie(&file, &["insert", "a1", "x"]);
+    let get = trie(&file, &["get", "a1f"]);
+    assert_eq!(get.status.code(), Some(1));
+    assert_eq!(stdout(&get), "");
+    assert!(stderr(&get).contains("\"a1f\" not found"));
+}
+
+#[test]
+fn delete_removes_the_key_and_exits_with_1_when_gone() {
+    let (file, _) = data_file("cli-delete");
+    trie(&file, &["insert", "a1", "x"]);
+    assert_eq!(trie(&file, &["delete", "a1"]).status.code(), Some(0));
+    assert_eq!(trie(&file, &["get", "a1"]).status.code(), Some(1));
+    assert_eq!(trie(&file, &["delete", "a1"]).status.code(), Some(1));
+}
+
+#[test]
+fn print_draws_the_trie() {
+    let (file, _) = data_file("cli-print");
+    for (key, value) in [("b", "0"), ("a1", "1"), ("a", "2")] {
+        trie(&file, &["insert", key, value]);
+    }
+    let print = trie(&file, &["print"]);
+    assert_eq!(print.status.code(), Some(0));
+    assert_eq!(
+        stdout(&print),
+        "(root)\n├── a = 2\n│   └── 1 = 1\n└── b = 0\n"
+    );
+}
+
+#[test]
+fn print_takes_a_prefix_and_a_max_depth() {
+    let (file, _) = data_file("cli-print-options");
+    for (key, value) in [("b", "0"), ("a1", "1"), ("a", "2"), ("a1f", "3")] {
+        trie(&file, &["insert", key, value]);
+    }
+    let under_a = stdout(&trie(&file, &["print", "--prefix", "a"]));
+    assert!(under_a.contains("1 = 1"), "{}", under_a);
+    assert!(!under_a.contains("b = 0"), "{}", under_a);
+    let shallow = stdout(&trie(&file, &["print", "--max-depth", "1"]));
+    assert!(shallow.contains("a = 2"), "{}", shallow);
+    assert!(!shallow.contains("f = 3"), "{}", shallow);
+}
+
+#[test]
+fn dump_and_load_move_entries_through_text() {
+    let (file, dir) = data_file("cli-dump-load");
+    trie(&file, &["insert", "a1", "one"]);
+    trie(&file, &["insert", "ff", "two"]);
+    let text = dir.join("entries.txt");
+    let dump = trie(&file, &["dump", text.to_str().unwrap()]);
+    assert_eq!(dump.status.code(), Some(0), "{}", stderr(&dump));
+    assert_eq!(fs::read_to_string(&text).unwrap(), "a1=one\nff=two\n");
+
+    let other = dir.join("other.trie");
+    let load = trie(&other, &["load", text.to_str().unwrap()]);
+    assert_eq!(load.status.code(), Some(0), "{}", stderr(&load));
+    assert_eq!(stdout(&load), "2 inserted, 0 overwritten, 0 skipped\n");
+    assert_eq!(stdout(&trie(&other, &["get", "ff"])), "two\n");
+}
+
+#[test]
+fn load_with_bad_lines_keeps_the_rest_and_exits_with_2() {
+    let (file, dir) = data_file("cli-load-bad");
+    let text = dir.join("entries.txt");
+    fs::write(&text, "a1=one\nnot hex=two\nff=three\n").unwrap();
+    let load = trie(&file, &["load", text.to_str().unwrap()]);
+    assert_eq!(load.status.code(), Some(2));
+    assert!(stdout(&load).contains("2 inserted, 0 overwritten, 1 skipped"));
+    assert_eq!(stdout(&trie(&file, &["get", "ff"])), "three\n");
+}
+
+#[test]
+fn a_bad_command_line_exits_with_2() {
+    let (file, _) = data_file("cli-usage");
+    for args in [
+        &[][..],
+        &["frobnicate"],
+        &["insert", "a1"],
+        &["get"],
+        &["print", "--max-depth", "deep"],
+        &["print", "--colour", "red"],
+    ] {
+        let output = trie(&file, args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(stderr(&output).contains("trie --help"), "{:?}", args);
+    }
+    assert!(!file.exists());
+}
+
+#[test]
+fn a_bad_key_exits_with_2() {
+    let (file, _) = data_file("cli-bad-key");
+    for args in [
+        &["insert", "xyz", "v"][..],
+        &["get", "a-1"],
+        &["delete", "g"],
+    ] {
+        let output = trie(&file, args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(stderr(&output).contains("invalid key"), "{:?}", args);
+    }
+}
+
+#[test]
+fn a_missing_file_option_exits_with_2() {
+    let output = Command::new(env!("CARGO_BIN_EXE_trie"))
+        .args(["get", "a1"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--file"));
+}
+
+#[test]
+fn an_unreadable_file_exits_with_3() {
+    let (file, dir) = data_file("cli-unreadable");
+    fs::write(&file, "not a snapshot").unwrap();
+    assert_eq!(trie(&file, &["get", "a1"]).status.code(), Some(3));
+
+    let file = dir.join("good.trie");
+    trie(&file, &["insert", "a1", "x"]);
+    let missing = dir.join("missing.txt");
+    assert_eq!(
+        trie(&file, &["load", missing.to_str().unwrap()])
+            .status
+            .code(),
+        Some(3)
+    );
+}
+
+#[test]
+fn an_unwritable_file_exits_with_3() {
+    let (_, dir) = data_file("cli-unwritable");
+    let file = dir.join("no-such-dir").join("data.trie");
+    assert_eq!(trie(&file, &["insert", "a1", "x"]).status.code(), Some(3));
+
+    let good = dir.join("data.trie");
+    let dump = trie(&good, &["dump", file.to_str().unwrap()]);
+    assert_eq!(dump.status.code(), Some(3));
+}
+
+#[test]
+fn help_exits_with_0() {
+    let output = Command::new(env!("CARGO_BIN_EXE_trie"))
+        .arg("--help")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("usage: trie"));
+}
@@ -0,0 +1,130 @@
+// The colored drawing has to be the plain one with escapes added, whatever the options.
+#![cfg(feature = "color")]
+
+use trie::{ColorChoice, DisplayOptions, Trie};
+
+mod common;
+use common::Rng;
+
+// Drops every `ESC [ ... m` sequence.
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            assert_eq!(chars.next(), Some('['));
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn demo() -> Trie<String> {
+    let mut trie = Trie::new();
+    for (key, value) in [
+        ("a1f", "leaf-A1F"),
+        ("a1e", "leaf-A1E"),
+        ("b0", "leaf-B0"),
+        ("00", "leaf-00"),
+        ("af", "leaf-AF"),
+    ] {
+        trie.insert_lossy(key, value.to_string());
+    }
+    trie
+}
+
+fn option_sets() -> Vec<DisplayOptions> {
+    vec![
+        DisplayOptions::default(),
+        DisplayOptions {
+            max_depth: Some(1),
+            ..DisplayOptions::default()
+        },
+        DisplayOptions {
+            max_entries: Some(2),
+            max_value_len: Some(3),
+            ..DisplayOptions::default()
+        },
+        DisplayOptions {
+            full_path_labels: true,
+            ..DisplayOptions::default()
+        },
+        DisplayOptions {
+            paths_only: true,
+            ..DisplayOptions::default()
+        },
+        DisplayOptions {
+            prefix: "a".to_string(),
+            ..DisplayOptions::default()
+        },
+        DisplayOptions {
+            prefix: "c".to_string(),
+            ..DisplayOptions::default()
+        },
+    ]
+}
+
+#[test]
+fn colored_output_strips_back_to_plain() {
+    let trie = demo();
+    for opts in option_sets() {
+        let plain = trie.display_with(opts.clone()).to_string();
+        let colored = trie
+            .display_colored(opts.clone(), ColorChoice::Always)
+            .to_string();
+        assert_eq!(strip_ansi(&colored), plain, "{:?}", opts);
+    }
+    let colored = trie.display_colored(DisplayOptions::default(), ColorChoice::Always);
+    assert_eq!(strip_ansi(&colored.to_string()), trie.to_string());
+}
+
+#[test]
+fn never_draws_exactly_the_plain_output() {
+    let trie = demo();
+    for opts in option_sets() {
+        let plain = trie.display_with(opts.clone()).to_string();
+        let never = trie.display_colored(opts, ColorChoice::Never).to_string();
+        assert_eq!(never, plain);
+    }
+}
+
+#[test]
+fn values_root_and_branches_get_their_own_colors() {
+    let colored = demo()
+        .display_colored(DisplayOptions::default(), ColorChoice::Always)
+        .to_string();
+    assert!(
+        colored.starts_with("\x1b[1;34m(root)\x1b[0m"),
+        "{:?}",
+        colored
+    );
+    assert!(
+        colored.contains("\x1b[1;32m00\x1b[0m = leaf-00"),
+        "{:?}",
+        colored
+    );
+    assert!(colored.contains("\x1b[2m├── \x1b[0m"), "{:?}", colored);
+    // a node without a value keeps the terminal's color
+    assert!(colored.contains("\x1b[2m├── \x1b[0ma\n"), "{:?}", colored);
+}
+
+#[test]
+fn random_tries_strip_back_to_plain() {
+    let mut rng = Rng::new(73);
+    for _ in 0..20 {
+        let mut trie = Trie::new();
+        for n in 0..rng.below(100) {
+            trie.insert_lossy(&rng.key(6), n);
+        }
+        let colored = trie
+            .display_colored(DisplayOptions::default(), ColorChoice::Always)
+            .to_string();
+        assert_eq!(strip_ansi(&colored), trie.to_string());
+    }
+}
@@ -0,0 +1,40 @@
+// What the integration tests share. Each test crate uses only some of it.
+#![allow(dead_code)]
+
+/// An xorshift generator, so the randomized tests are the same on every run.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed | 1)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number below `n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// A lowercase hex key of up to `max_len` digits, drawn from few enough
+    /// digits that keys keep sharing prefixes.
+    pub fn key(&mut self, max_len: u64) -> String {
+        let len = self.below(max_len + 1);
+        (0..len)
+            .map(|_| b"01af"[self.below(4) as usize] as char)
+            .collect()
+    }
+}
+
+/// A fresh, empty directory for one test to keep files in.
+pub fn temp_dir(test: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("radix-trie-{}-{}", std::process::id(), test));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,107 @@
+// Threads hammering ConcurrentTrie at once, on the same shards and on different ones.
+#![cfg(feature = "concurrent")]
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::thread;
+
+use trie::ConcurrentTrie;
+
+mod common;
+use common::Rng;
+
+const THREADS: u64 = 8;
+
+#[test]
+fn writers_on_their_own_keys_all_land() {
+    let trie = Arc::new(ConcurrentTrie::new());
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let trie = Arc::clone(&trie);
+            thread::spawn(move || {
+                let mut rng = Rng::new(92 + t);
+                // every thread writes all over the shards, each under its own last digit
+                for n in 0..2_000 {
+                    let key = format!("{:x}{:04x}{:x}", rng.below(16), n, t);
+                    assert_eq!(trie.insert(&key, n), None);
+                    assert_eq!(trie.get(&key), Some(n));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(trie.len(), THREADS as usize * 2_000);
+    assert_eq!(trie.iter().count(), trie.len());
+}
+
+#[test]
+fn mixed_ops_on_shared_keys_keep_the_count() {
+    let trie = Arc::new(ConcurrentTrie::new());
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let trie = Arc::clone(&trie);
+            thread::spawn(move || {
+                let mut rng = Rng::new(920 + t);
+                // each thread's net effect on the count: inserts that found nothing minus
+                // deletes that found something
+                let mut net = 0i64;
+                for _ in 0..5_000 {
+                    let key = rng.key(3);
+                    match rng.below(3) {
+                        0 => net += i64::from(trie.insert(&key, t).is_none()),
+                        1 => net -= i64::from(trie.delete(&key).is_some()),
+                        _ => {
+                            trie.get_with(&key, |&value| assert!(value < THREADS));
+                        }
+                    }
+                }
+                net
+            })
+        })
+        .collect();
+    let net: i64 = handles.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!(trie.len() as i64, net);
+    assert_eq!(trie.iter().count(), trie.len());
+}
+
+#[test]
+fn snapshots_taken_during_writes_are_consistent() {
+    let trie = Arc::new(ConcurrentTrie::new());
+    // a writer that always moves one value from one shard to another, so any consistent
+    // snapshot holds exactly one
+    trie.insert("0", 0u64);
+    let writer = {
+        let trie = Arc::clone(&trie);
+        thread::spawn(move || {
+            for n in 0..5_000u64 {
+                let from = format!("{:x}", n % 16);
+                let to = format!("{:x}", (n + 1) % 16);
+                trie.insert(&to, n + 1);
+                trie.delete(&from);
+            }
+        })
+    };
+    for _ in 0..500 {
+        let entries: BTreeMap<String, u64> = trie.iter().collect();
+        assert!((1..=2).contains(&entries.len()), "{:?}", entries);
+    }
+    writer.join().unwrap();
+    assert_eq!(trie.iter().collect::<Vec<_>>(), [("8".to_string(), 5_000)]);
+}
+
+#[test]
+fn the_empty_key_has_its_own_slot() {
+    let trie = ConcurrentTrie::new();
+    assert_eq!(trie.insert("", 1), None);
+    assert_eq!(trie.insert("0", 2), None);
+    assert_eq!(trie.insert("", 3), Some(1));
+    assert_eq!(trie.get(""), Some(3));
+    assert_eq!(trie.len(), 2);
+    let keys: Vec<_> = trie.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["", "0"]);
+    assert_eq!(trie.delete(""), Some(3));
+    assert_eq!(trie.get(""), None);
+    assert_eq!(trie.len(), 1);
+}
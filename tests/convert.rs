@@ -0,0 +1,95 @@
+// Round trips between the trie and the std maps, and the keys the conversions reject.
+#![cfg(feature = "std")]
+
+use std::collections::{BTreeMap, HashMap};
+
+use trie::{KeyError, Trie};
+
+mod common;
+use common::Rng;
+
+fn random_map(rng: &mut Rng) -> BTreeMap<String, u64> {
+    (0..rng.below(300)).map(|n| (rng.key(8), n)).collect()
+}
+
+#[test]
+fn btree_map_round_trips() {
+    let mut rng = Rng::new(76);
+    for _ in 0..50 {
+        let map = random_map(&mut rng);
+        let trie = Trie::try_from(map.clone()).unwrap();
+        assert_eq!(trie.len(), map.len());
+        for (key, value) in &map {
+            assert_eq!(trie.get_lossy(key), Some(value));
+        }
+        assert_eq!(BTreeMap::from(trie), map);
+    }
+}
+
+#[test]
+fn hash_map_round_trips() {
+    let mut rng = Rng::new(760);
+    for _ in 0..50 {
+        let map: HashMap<String, u64> = random_map(&mut rng).into_iter().collect();
+        let trie = Trie::try_from(map.clone()).unwrap();
+        assert_eq!(trie.len(), map.len());
+        let back: HashMap<String, u64> = trie.into();
+        assert_eq!(back, map);
+    }
+}
+
+#[test]
+fn uppercase_keys_come_back_lowercase() {
+    let map = BTreeMap::from([("A1".to_string(), 1), ("0f".to_string(), 2)]);
+    let trie = Trie::try_from(map).unwrap();
+    assert_eq!(trie.get_lossy("a1"), Some(&1));
+    let back = BTreeMap::from(trie);
+    assert_eq!(
+        back,
+        BTreeMap::from([("0f".to_string(), 2), ("a1".to_string(), 1)])
+    );
+}
+
+#[test]
+fn every_invalid_key_is_listed() {
+    let map: HashMap<String, u32> = [("a1", 1), ("xy", 2), ("0g", 3), ("ff", 4)]
+        .map(|(key, value)| (key.to_string(), value))
+        .into();
+    let err = Trie::try_from(map).unwrap_err();
+    assert_eq!(
+        err.invalid,
+        [
+            (
+                "0g".to_string(),
+                KeyError::InvalidChar {
+                    index: 1,
+                    found: 'g'
+                }
+            ),
+            (
+                "xy".to_string(),
+                KeyError::InvalidChar {
+                    index: 0,
+                    found: 'x'
+                }
+            ),
+        ]
+    );
+    assert!(err.colliding.is_empty());
+    assert_eq!(
+        err.to_string(),
+        "invalid key \"0g\": invalid hex character 'g' at index 1 (and 1 more)"
+    );
+}
+
+#[test]
+fn keys_differing_only_in_case_collide() {
+    let map: BTreeMap<String, u32> = [("A1", 1), ("a1", 2), ("b0", 3), ("B0", 4), ("ff", 5)]
+        .map(|(key, value)| (key.to_string(), value))
+        .into();
+    let err = Trie::try_from(map.clone()).unwrap_err();
+    assert!(err.invalid.is_empty());
+    assert_eq!(err.colliding, ["a1", "b0"]);
+    let map: HashMap<String, u32> = map.into_iter().collect();
+    assert_eq!(Trie::try_from(map).unwrap_err().colliding, ["a1", "b0"]);
+}
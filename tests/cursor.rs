@@ -0,0 +1,117 @@
+// get and in-order iteration rebuilt on the read cursor, checked against the trie's own.
+
+use trie::{Cursor, Trie};
+
+mod common;
+use common::Rng;
+
+fn random_trie(rng: &mut Rng) -> Trie<u32> {
+    let mut trie = Trie::new();
+    for n in 0..rng.below(300) {
+        trie.insert_lossy(&rng.key(8), n as u32);
+    }
+    trie
+}
+
+fn get_by_descending<'a>(trie: &'a Trie<u32>, key: &str) -> Option<&'a u32> {
+    let mut cursor = trie.cursor();
+    for c in key.chars() {
+        if !cursor.descend(c.to_digit(16).unwrap() as u8) {
+            return None;
+        }
+    }
+    cursor.value()
+}
+
+// Pre-order: a node's value comes before everything under it.
+fn entries_by_walking(mut cursor: Cursor<'_, u32>) -> Vec<(String, u32)> {
+    let mut entries = Vec::new();
+    loop {
+        if let Some(&value) = cursor.value() {
+            entries.push((cursor.path().to_hex(), value));
+        }
+        if cursor.first_child() {
+            continue;
+        }
+        while !cursor.next_sibling() {
+            if !cursor.ascend() {
+                return entries;
+            }
+        }
+    }
+}
+
+#[test]
+fn descending_finds_what_get_finds() {
+    let mut rng = Rng::new(85);
+    for _ in 0..30 {
+        let trie = random_trie(&mut rng);
+        for _ in 0..100 {
+            let key = rng.key(9);
+            assert_eq!(
+                get_by_descending(&trie, &key),
+                trie.get_lossy(&key),
+                "{}",
+                key
+            );
+        }
+    }
+}
+
+#[test]
+fn walking_yields_what_iter_yields() {
+    let mut rng = Rng::new(850);
+    for _ in 0..30 {
+        let trie = random_trie(&mut rng);
+        let expected: Vec<_> = trie.iter().map(|(key, &value)| (key, value)).collect();
+        assert_eq!(entries_by_walking(trie.cursor()), expected);
+    }
+}
+
+#[test]
+fn seek_lands_on_the_key_or_the_deepest_node_along_it() {
+    let mut trie = Trie::new();
+    for key in ["a1", "a1f0", "b0"] {
+        trie.insert_lossy(key, 0);
+    }
+    let mut cursor = trie.cursor();
+    assert!(cursor.seek("a1f"));
+    assert_eq!(cursor.path().to_hex(), "a1f");
+    assert_eq!(cursor.value(), None);
+    assert!(!cursor.seek("a1e9"));
+    assert_eq!(cursor.path().to_hex(), "a1");
+    assert!(cursor.seek("B0"));
+    assert_eq!(cursor.path().to_hex(), "b0");
+    assert!(cursor.seek(""));
+    assert!(cursor.path().is_empty());
+}
+
+#[test]
+fn the_root_has_no_parent_or_siblings() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a", 1);
+    let mut cursor = trie.cursor();
+    assert!(!cursor.ascend());
+    assert!(!cursor.next_sibling());
+    assert!(!cursor.descend(16));
+    assert!(!cursor.descend(0));
+    assert!(cursor.path().is_empty());
+    assert!(!Trie::<u32>::new().cursor().first_child());
+}
+
+#[test]
+fn cursors_walk_side_by_side() {
+    let mut trie = Trie::new();
+    for key in ["a1", "b0"] {
+        trie.insert_lossy(key, key.to_string());
+    }
+    let mut left = trie.cursor();
+    let mut right = trie.cursor();
+    assert!(left.seek("a1"));
+    assert!(right.seek("b0"));
+    let copy = left.clone();
+    assert!(left.ascend());
+    assert_eq!(copy.value().map(String::as_str), Some("a1"));
+    assert_eq!(right.value().map(String::as_str), Some("b0"));
+    assert_eq!(trie.get_lossy("a1"), copy.value());
+}
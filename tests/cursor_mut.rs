@@ -0,0 +1,119 @@
+// CursorMut against a BTreeMap, at every fanout, through one cursor that lives across the
+// whole run so its cached path is reused and cut back by the deletes along the way.
+
+use std::collections::BTreeMap;
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+// Keys near the last one most of the time, so the cursor keeps part of its path between calls.
+// At a fanout of 256 keys keep an even number of digits, which is all that fanout stores.
+fn next_key<const N: usize>(rng: &mut Rng, last: &str) -> String {
+    let mut key = if rng.below(3) > 0 {
+        let keep = rng.below(last.len() as u64 + 1) as usize;
+        last[..keep].to_string() + &rng.key(4)
+    } else {
+        rng.key(10)
+    };
+    if N == 256 && key.len() % 2 == 1 {
+        key.pop();
+    }
+    key
+}
+
+fn check_against_btreemap<const N: usize>(seed: u64, ops: usize) {
+    let mut rng = Rng::new(seed);
+    let mut trie = Trie::<u32, N>::with_fanout();
+    let mut model = BTreeMap::new();
+    {
+        let mut cursor = trie.cursor_mut();
+        let mut key = String::new();
+        for n in 0..ops as u32 {
+            key = next_key::<N>(&mut rng, &key);
+            match rng.below(5) {
+                0 | 1 => assert_eq!(
+                    cursor.insert(&key, n),
+                    model.insert(key.clone(), n),
+                    "insert {:?}",
+                    key
+                ),
+                2 | 3 => assert_eq!(cursor.delete(&key), model.remove(&key), "delete {:?}", key),
+                _ => {}
+            }
+            assert_eq!(cursor.get(&key), model.get(&key), "get {:?}", key);
+        }
+    }
+
+    let entries: Vec<(String, u32)> = trie.iter().map(|(k, v)| (k, *v)).collect();
+    let expected: Vec<(String, u32)> = model.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    assert_eq!(entries, expected);
+    assert_eq!(trie.len(), model.len());
+    assert_eq!(trie.validate(), Ok(()));
+    // every node the deletes left behind would also be there in a trie built from scratch
+    let mut fresh = Trie::<u32, N>::with_fanout();
+    for (key, &value) in &model {
+        fresh.insert_lossy(key, value);
+    }
+    assert_eq!(trie.node_count(), fresh.node_count());
+}
+
+#[test]
+fn matches_a_btreemap() {
+    check_against_btreemap::<16>(84, 3_000);
+}
+
+#[test]
+fn matches_a_btreemap_at_other_fanouts() {
+    check_against_btreemap::<2>(842, 1_000);
+    check_against_btreemap::<4>(844, 1_000);
+    check_against_btreemap::<256>(8_456, 1_000);
+}
+
+#[test]
+fn deletes_that_prune_the_cursors_own_path() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", 1);
+    let mut cursor = trie.cursor_mut();
+    assert_eq!(cursor.insert("a1b2c3", 2), None);
+    // the cursor sits at the end of "a1b2c3"; deleting it cuts the path back to "a1"
+    assert_eq!(cursor.delete("a1b2c3"), Some(2));
+    assert_eq!(cursor.get("a1b2c3"), None);
+    assert_eq!(cursor.get("a1b2"), None);
+    // and building back down from there reuses what's left
+    assert_eq!(cursor.insert("a1b2c4", 3), None);
+    assert_eq!(cursor.get("a1b2c4"), Some(&3));
+    assert_eq!(cursor.get("a1"), Some(&1));
+
+    // a delete that prunes all the way to the root, then more work on the same cursor
+    assert_eq!(cursor.delete("a1"), Some(1));
+    assert_eq!(cursor.delete("a1b2c4"), Some(3));
+    assert_eq!(cursor.get("a1b2c4"), None);
+    assert_eq!(cursor.delete("a1b2c4"), None);
+    assert_eq!(cursor.insert("a1b2", 4), None);
+    assert_eq!(cursor.insert("", 5), None);
+    assert_eq!(cursor.get("a1b2"), Some(&4));
+    drop(cursor);
+
+    assert_eq!(trie.len(), 2);
+    assert_eq!(trie.node_count(), 5);
+    assert_eq!(trie.validate(), Ok(()));
+}
+
+#[test]
+fn deleting_a_key_with_children_keeps_the_path() {
+    let mut trie = Trie::<u32, 4>::with_fanout();
+    let mut cursor = trie.cursor_mut();
+    cursor.insert("a1", 1);
+    cursor.insert("a1f", 2);
+    // "a1" only loses its value, so the cursor's nodes all stay in the trie
+    assert_eq!(cursor.delete("a1"), Some(1));
+    assert_eq!(cursor.get("a1"), None);
+    assert_eq!(cursor.get("a1f"), Some(&2));
+    assert_eq!(cursor.delete("a1f"), Some(2));
+    drop(cursor);
+    assert!(trie.is_empty());
+    assert_eq!(trie.node_count(), 1);
+    assert_eq!(trie.validate(), Ok(()));
+}
@@ -0,0 +1,141 @@
+// Keys long enough that anything recursing once per nibble would overflow the test thread's
+// stack.
+
+use trie::Trie;
+
+const DEPTH: usize = 50_000;
+
+fn deep_key(digit: char) -> String {
+    std::iter::repeat_n(digit, DEPTH).collect()
+}
+
+#[test]
+fn remove_prefix_under_a_deep_key() {
+    let mut trie = Trie::new();
+    let key = deep_key('a');
+    trie.insert_lossy(&key, 1);
+    trie.insert_lossy("a", 2);
+    trie.insert_lossy("b", 3);
+    assert_eq!(trie.remove_prefix("aa"), 1);
+    assert_eq!(trie.len(), 2);
+    assert_eq!(trie.node_count(), 3);
+    assert!(trie.validate().is_ok());
+
+    trie.insert_lossy(&key, 1);
+    assert_eq!(trie.remove_prefix(&key[..DEPTH - 1]), 1);
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["a", "b"]);
+}
+
+#[test]
+fn retain_over_a_deep_key() {
+    let mut trie = Trie::new();
+    let key = deep_key('b');
+    trie.insert_lossy(&key, 1);
+    trie.insert_lossy(&key[..DEPTH / 2], 2);
+    trie.insert_lossy("c", 3);
+    let mut seen = Vec::new();
+    trie.retain(|key, value| {
+        seen.push(key.len());
+        *value != 1
+    });
+    assert_eq!(seen, [DEPTH / 2, DEPTH, 1]);
+    assert_eq!(trie.len(), 2);
+    assert_eq!(trie.node_count(), DEPTH / 2 + 2);
+    assert!(trie.validate().is_ok());
+
+    trie.retain(|_, _| false);
+    assert!(trie.is_empty());
+    assert_eq!(trie.node_count(), 1);
+}
+
+#[test]
+fn merge_two_deep_keys() {
+    let key = deep_key('c');
+    let mut ours = Trie::new();
+    ours.insert_lossy(&key, 1);
+    let mut theirs = Trie::new();
+    theirs.insert_lossy(&key, 2);
+    theirs.insert_lossy(&(key.clone() + "d"), 3);
+    ours.merge(theirs, trie::MergePolicy::WithFn(Box::new(|_, a, b| a + b)));
+    assert_eq!(ours.len(), 2);
+    assert_eq!(ours.get_lossy(&key), Some(&3));
+    assert!(ours.validate().is_ok());
+}
+
+#[test]
+fn split_off_along_a_deep_key() {
+    let mut trie = Trie::new();
+    let key = deep_key('d');
+    trie.insert_lossy(&key, 1);
+    trie.insert_lossy(&(key.clone() + "e"), 2);
+    trie.insert_lossy("d", 3);
+    let moved = trie.split_off(&(key.clone() + "0"));
+    assert_eq!((trie.len(), moved.len()), (2, 1));
+    assert!(trie.validate().is_ok() && moved.validate().is_ok());
+
+    let all = trie.split_off("");
+    assert!(trie.is_empty());
+    assert_eq!(trie.node_count(), 1);
+    assert_eq!(all.len(), 2);
+}
+
+#[test]
+fn values_mut_down_a_deep_key() {
+    let mut trie = Trie::new();
+    trie.insert_lossy(&deep_key('a'), 1);
+    trie.insert_lossy(&deep_key('b'), 2);
+    trie.values_mut().for_each(|value| *value *= 10);
+    assert_eq!(trie.get_lossy(&deep_key('a')), Some(&10));
+    assert_eq!(trie.get_lossy(&deep_key('b')), Some(&20));
+}
+
+#[test]
+fn into_iter_dropped_partway_down_a_deep_key() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("0", 0);
+    trie.insert_lossy(&deep_key('a'), 1);
+    let mut iter = trie.into_iter();
+    assert_eq!(iter.next(), Some(("0".to_string(), 0)));
+    drop(iter);
+}
+
+#[test]
+fn clear_a_deep_chain() {
+    let mut trie = Trie::new();
+    trie.insert_lossy(&"0123456789".repeat(1_000), 1);
+    trie.insert_lossy("", 0);
+    trie.clear();
+    assert!(trie.is_empty());
+    assert_eq!(trie.node_count(), 1);
+    assert_eq!(trie.root_value(), None);
+    assert_eq!(trie, Trie::new());
+    assert_eq!(trie.to_string(), Trie::<i32>::new().to_string());
+    trie.insert_lossy("a1", 2);
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["a1"]);
+}
+
+#[test]
+fn delete_a_deep_key_prunes_the_whole_chain() {
+    let mut trie = Trie::new();
+    let key = deep_key('a');
+    trie.insert_lossy(&key, 1);
+    trie.insert_lossy(&key[..DEPTH / 2], 2);
+    assert_eq!(trie.delete_lossy(&key), Some(1));
+    // the chain is pruned back to the key that still holds a value
+    assert_eq!(trie.node_count(), DEPTH / 2 + 1);
+    assert_eq!(trie.delete_lossy(&key), None);
+    assert_eq!(trie.delete_lossy(&key[..DEPTH / 2]), Some(2));
+    assert_eq!(trie.node_count(), 1);
+    assert!(trie.is_empty());
+}
+
+#[test]
+fn drop_a_deep_trie() {
+    let mut trie = Trie::new();
+    trie.insert_lossy(&"f".repeat(100_000), 1);
+    trie.insert_lossy(&deep_key('0'), 2);
+    let copy = trie.clone();
+    drop(trie);
+    assert_eq!(copy.len(), 2);
+    drop(copy);
+}
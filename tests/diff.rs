@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use trie::{DiffEntry, Trie};
+
+mod common;
+use common::Rng;
+
+fn trie_of(entries: &[(&str, u32)]) -> Trie<u32> {
+    let mut trie = Trie::new();
+    for &(key, value) in entries {
+        trie.insert_lossy(key, value);
+    }
+    trie
+}
+
+#[test]
+fn identical_tries_have_no_diff() {
+    let a = trie_of(&[("a1", 1), ("a1f", 2), ("b", 3)]);
+    let b = a.clone();
+    assert_eq!(a.diff(&b).count(), 0);
+    assert_eq!(Trie::<u32>::new().diff(&Trie::new()).count(), 0);
+}
+
+#[test]
+fn a_change_at_an_interior_node() {
+    let a = trie_of(&[("a1", 1), ("a1f", 2)]);
+    let b = trie_of(&[("a1", 5), ("a1f", 2)]);
+    let diff: Vec<_> = a.diff(&b).collect();
+    assert_eq!(diff, [DiffEntry::Changed("a1".into(), &1, &5)]);
+}
+
+#[test]
+fn a_subtree_on_one_side_only() {
+    let a = trie_of(&[("a1", 1), ("b", 2), ("b0", 3), ("b01", 4)]);
+    let b = trie_of(&[("a1", 1), ("c2", 5)]);
+    let diff: Vec<_> = a.diff(&b).collect();
+    assert_eq!(
+        diff,
+        [
+            DiffEntry::Removed("b".into(), &2),
+            DiffEntry::Removed("b0".into(), &3),
+            DiffEntry::Removed("b01".into(), &4),
+            DiffEntry::Added("c2".into(), &5),
+        ]
+    );
+}
+
+#[test]
+fn diff_matches_comparing_two_btreemaps() {
+    let mut rng = Rng::new(27);
+    for _ in 0..300 {
+        let mut sides = [
+            (Trie::new(), BTreeMap::new()),
+            (Trie::new(), BTreeMap::new()),
+        ];
+        for (trie, map) in &mut sides {
+            for _ in 0..rng.below(30) {
+                let (key, value) = (rng.key(3), rng.below(3));
+                trie.insert_lossy(&key, value);
+                map.insert(key, value);
+            }
+        }
+        let [(a, old), (b, new)] = &sides;
+        let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        let expected: Vec<DiffEntry<u64>> = keys
+            .into_iter()
+            .filter_map(|key| match (old.get(key), new.get(key)) {
+                (Some(x), Some(y)) if x == y => None,
+                (Some(x), Some(y)) => Some(DiffEntry::Changed(key.clone(), x, y)),
+                (Some(x), None) => Some(DiffEntry::Removed(key.clone(), x)),
+                (None, Some(y)) => Some(DiffEntry::Added(key.clone(), y)),
+                (None, None) => unreachable!(),
+            })
+            .collect();
+        assert_eq!(a.diff(b).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "testing")]
+
+use std::collections::HashMap;
+
+use trie::Trie;
+use trie::testing::{apply_and_check, ops_from_bytes, random_ops};
+
+mod common;
+use common::Rng;
+
+#[test]
+fn random_ops_agree_with_a_hashmap() {
+    for seed in 0..3_000 {
+        let mut trie = Trie::new();
+        let mut oracle = HashMap::new();
+        for op in random_ops(seed, 50) {
+            apply_and_check(&mut trie, &mut oracle, op);
+        }
+    }
+}
+
+// What the fuzz target does, on inputs from a seeded generator instead of a fuzzer.
+#[test]
+fn ops_from_arbitrary_bytes_agree_with_a_hashmap() {
+    let mut rng = Rng::new(100);
+    for _ in 0..2_000 {
+        let data: Vec<u8> = (0..rng.below(256)).map(|_| rng.next() as u8).collect();
+        let mut trie = Trie::new();
+        let mut oracle = HashMap::new();
+        for op in ops_from_bytes(&data) {
+            apply_and_check(&mut trie, &mut oracle, op);
+        }
+    }
+}
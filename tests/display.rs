@@ -0,0 +1,388 @@
+// Golden tests of the tree drawing.
+
+use trie::{DisplayOptions, Trie};
+
+mod common;
+use common::Rng;
+
+// The trie the demo in main builds, plus one long key that shares nothing with the rest.
+fn demo() -> Trie<String> {
+    let mut trie = Trie::new();
+    for (key, value) in [
+        ("a1f", "leaf-A1F"),
+        ("a1e", "leaf-A1E"),
+        ("b0", "leaf-B0"),
+        ("00", "leaf-00"),
+        ("af", "leaf-AF"),
+        ("deadbeefcafe", "long"),
+    ] {
+        trie.insert_lossy(key, value.to_string());
+    }
+    trie
+}
+
+const DEMO: &str = "\
+(root)
+├── 00 = leaf-00
+├── a
+│   ├── 1
+│   │   ├── e = leaf-A1E
+│   │   └── f = leaf-A1F
+│   └── f = leaf-AF
+├── b0 = leaf-B0
+└── deadbeefcafe = long
+";
+
+#[test]
+fn single_child_runs_collapse() {
+    assert_eq!(demo().to_string(), DEMO);
+}
+
+#[test]
+fn runs_break_at_values_and_branches() {
+    let mut trie = demo();
+    trie.insert_lossy("dead", "mid".to_string());
+    trie.insert_lossy("deadbe01", "x".to_string());
+    assert!(
+        trie.to_string().ends_with(
+            "\
+└── dead = mid
+    └── be
+        ├── 01 = x
+        └── efcafe = long
+"
+        ),
+        "{}",
+        trie
+    );
+}
+
+// Counts what a drawing writes without keeping it.
+#[derive(Default)]
+struct Lines {
+    lines: usize,
+    longest: usize,
+    current: usize,
+}
+
+impl std::fmt::Write for Lines {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        for c in s.chars() {
+            if c == '\n' {
+                self.lines += 1;
+                self.current = 0;
+            } else {
+                self.current += 1;
+                self.longest = self.longest.max(self.current);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn a_long_key_draws_on_one_line() {
+    let mut trie = Trie::new();
+    let key = "abc".repeat(20_000);
+    trie.insert_lossy(&key, 1);
+    assert_eq!(trie.to_string(), format!("(root)\n└── {} = 1\n", key));
+}
+
+#[test]
+fn a_deep_nest_draws_without_recursing() {
+    // a value at every level stops the runs from collapsing
+    let depth = 3_000;
+    let mut trie = Trie::new();
+    for len in 1..=depth {
+        trie.insert_lossy(&"7".repeat(len), len);
+    }
+    let mut out = Lines::default();
+    std::fmt::write(&mut out, format_args!("{}", trie)).unwrap();
+    assert_eq!(out.lines, depth + 1);
+    // four columns of indent per level, then the branch, the digit and the value
+    assert_eq!(
+        out.longest,
+        4 * (depth - 1) + "└── 7 = 3000".chars().count()
+    );
+}
+
+#[test]
+fn a_wide_trie_draws_every_leaf() {
+    let mut trie = Trie::new();
+    for n in 0..4096 {
+        trie.insert_lossy(&format!("{:03x}", n), n);
+    }
+    let mut out = Lines::default();
+    std::fmt::write(&mut out, format_args!("{}", trie)).unwrap();
+    assert_eq!(out.lines, 1 + 16 + 256 + 4096);
+}
+
+#[test]
+fn max_depth_summarizes_what_it_cuts() {
+    let opts = DisplayOptions {
+        max_depth: Some(1),
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        demo().display_with(opts).to_string(),
+        "\
+(root)
+├── 0 … (+1 entry)
+├── a … (+3 entries)
+├── b … (+1 entry)
+└── d … (+1 entry)
+"
+    );
+}
+
+#[test]
+fn max_depth_and_max_value_len_combine() {
+    let opts = DisplayOptions {
+        max_depth: Some(2),
+        max_value_len: Some(4),
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        demo().display_with(opts).to_string(),
+        "\
+(root)
+├── 00 = leaf…
+├── a
+│   ├── 1 … (+2 entries)
+│   └── f = leaf…
+├── b0 = leaf…
+└── de … (+1 entry)
+"
+    );
+}
+
+#[test]
+fn max_entries_counts_the_values_it_stops_before() {
+    let opts = DisplayOptions {
+        max_entries: Some(3),
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        demo().display_with(opts).to_string(),
+        "\
+(root)
+├── 00 = leaf-00
+├── a
+│   ├── 1
+… (+5 more entries)
+"
+    );
+    // a limit the drawing fits in leaves no marker
+    let opts = DisplayOptions {
+        max_depth: Some(1),
+        max_entries: Some(4),
+        ..DisplayOptions::default()
+    };
+    assert!(!demo().display_with(opts).to_string().contains("more"));
+}
+
+#[test]
+fn default_options_draw_like_display() {
+    let trie = demo();
+    assert_eq!(
+        trie.display_with(DisplayOptions::default()).to_string(),
+        trie.to_string()
+    );
+    let opts = DisplayOptions {
+        max_value_len: Some(100),
+        ..DisplayOptions::default()
+    };
+    assert_eq!(trie.display_with(opts).to_string(), DEMO);
+}
+
+// What a drawing accounts for: the values it shows plus those its markers count.
+fn accounted(drawing: &str) -> usize {
+    drawing
+        .lines()
+        .map(|line| {
+            let hidden = line.split_once("(+").map_or(0, |(_, count)| {
+                count.split(' ').next().unwrap().parse().unwrap()
+            });
+            usize::from(line.contains(" = ")) + hidden
+        })
+        .sum()
+}
+
+#[test]
+fn markers_account_for_every_value() {
+    let mut rng = Rng::new(71);
+    for _ in 0..50 {
+        let mut trie = Trie::new();
+        for n in 0..rng.below(200) {
+            trie.insert_lossy(&rng.key(8), n);
+        }
+        let opts = DisplayOptions {
+            max_depth: Some(rng.below(6) as usize),
+            max_entries: Some(rng.below(40) as usize + 1),
+            ..DisplayOptions::default()
+        };
+        let drawing = trie.display_with(opts).to_string();
+        assert_eq!(accounted(&drawing), trie.len(), "{}", drawing);
+    }
+}
+
+#[test]
+fn full_path_labels_show_whole_keys() {
+    let opts = DisplayOptions {
+        full_path_labels: true,
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        demo().display_with(opts).to_string(),
+        "\
+(root)
+├── 00 = leaf-00
+├── a
+│   ├── a1
+│   │   ├── a1e = leaf-A1E
+│   │   └── a1f = leaf-A1F
+│   └── af = leaf-AF
+├── b0 = leaf-B0
+└── deadbeefcafe = long
+"
+    );
+}
+
+#[test]
+fn paths_only_lists_each_value_in_key_order() {
+    let opts = DisplayOptions {
+        paths_only: true,
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        demo().display_with(opts).to_string(),
+        "\
+00 = leaf-00
+a1e = leaf-A1E
+a1f = leaf-A1F
+af = leaf-AF
+b0 = leaf-B0
+deadbeefcafe = long
+"
+    );
+    let mut trie = demo();
+    trie.insert_lossy("", "top".to_string());
+    let opts = DisplayOptions {
+        paths_only: true,
+        max_depth: Some(2),
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        trie.display_with(opts).to_string(),
+        "\
+(root) = top
+00 = leaf-00
+a1 … (+2 entries)
+af = leaf-AF
+b0 = leaf-B0
+de … (+1 entry)
+"
+    );
+}
+
+#[test]
+fn paths_only_matches_iteration() {
+    let mut rng = Rng::new(72);
+    let mut trie = Trie::new();
+    for n in 0..500 {
+        let mut key = rng.key(10);
+        if key.is_empty() {
+            key.push('a');
+        }
+        trie.insert_lossy(&key, n);
+    }
+    let opts = DisplayOptions {
+        paths_only: true,
+        ..DisplayOptions::default()
+    };
+    let expected: String = trie
+        .iter()
+        .map(|(key, value)| format!("{} = {}\n", key, value))
+        .collect();
+    assert_eq!(trie.display_with(opts).to_string(), expected);
+}
+
+#[test]
+fn display_prefix_draws_one_subtree_with_relative_labels() {
+    assert_eq!(
+        demo().display_prefix("a").to_string(),
+        "\
+(a)
+├── 1
+│   ├── e = leaf-A1E
+│   └── f = leaf-A1F
+└── f = leaf-AF
+"
+    );
+    // a prefix inside a collapsed run starts the drawing partway along it
+    assert_eq!(
+        demo().display_prefix("de").to_string(),
+        "(de)\n└── adbeefcafe = long\n"
+    );
+    assert_eq!(
+        demo().display_prefix("A").to_string(),
+        demo().display_prefix("a").to_string()
+    );
+    assert_eq!(demo().display_prefix("").to_string(), DEMO);
+}
+
+#[test]
+fn display_prefix_of_a_missing_prefix_says_so() {
+    assert_eq!(
+        demo().display_prefix("c").to_string(),
+        "<prefix not present>\n"
+    );
+    assert_eq!(
+        demo().display_prefix("a1f0").to_string(),
+        "<prefix not present>\n"
+    );
+    let opts = DisplayOptions {
+        prefix: "c".to_string(),
+        paths_only: true,
+        ..DisplayOptions::default()
+    };
+    assert_eq!(demo().display_with(opts).to_string(), "");
+}
+
+#[test]
+fn a_prefix_composes_with_the_other_options() {
+    let opts = DisplayOptions {
+        prefix: "a".to_string(),
+        max_depth: Some(1),
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        demo().display_with(opts).to_string(),
+        "(a)\n├── 1 … (+2 entries)\n└── f = leaf-AF\n"
+    );
+    // full paths include the prefix
+    let opts = DisplayOptions {
+        prefix: "a".to_string(),
+        full_path_labels: true,
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        demo().display_with(opts).to_string(),
+        "\
+(a)
+├── a1
+│   ├── a1e = leaf-A1E
+│   └── a1f = leaf-A1F
+└── af = leaf-AF
+"
+    );
+    let opts = DisplayOptions {
+        prefix: "a".to_string(),
+        paths_only: true,
+        ..DisplayOptions::default()
+    };
+    assert_eq!(
+        demo().display_with(opts).to_string(),
+        "a1e = leaf-A1E\na1f = leaf-A1F\naf = leaf-AF\n"
+    );
+}
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+// Builds the same random contents along two different histories: `a` only gets the final
+// keys, `b` also gets keys that are deleted again and older values.
+fn same_contents(rng: &mut Rng) -> (Trie<u64>, Trie<u64>) {
+    let (mut a, mut b) = (Trie::new(), Trie::new());
+    for _ in 0..rng.below(30) {
+        let (key, value) = (rng.key(4), rng.below(5));
+        if rng.below(3) == 0 {
+            // no key from `rng` has a 5 in it
+            let extra = format!("{}5{}", key, rng.key(3));
+            b.insert_lossy(&extra, value);
+            b.delete_lossy(&extra);
+            b.insert_lossy(&key, value + 1);
+        }
+        a.insert_lossy(&key, value);
+        b.insert_lossy(&key, value);
+    }
+    (a, b)
+}
+
+#[test]
+fn equal_contents_compare_equal_whatever_the_history() {
+    let mut rng = Rng::new(28);
+    for _ in 0..300 {
+        let (a, b) = same_contents(&mut rng);
+        assert_eq!(a, b);
+        assert!(a.iter().eq(b.iter()));
+    }
+}
+
+#[test]
+fn equality_agrees_with_iter() {
+    let mut rng = Rng::new(280);
+    for _ in 0..500 {
+        let mut a = Trie::new();
+        let mut b = Trie::new();
+        for _ in 0..rng.below(6) {
+            a.insert_lossy(&rng.key(2), rng.below(2));
+        }
+        for _ in 0..rng.below(6) {
+            b.insert_lossy(&rng.key(2), rng.below(2));
+        }
+        assert_eq!(a == b, a.iter().eq(b.iter()));
+    }
+}
+
+#[test]
+fn a_value_at_an_interior_node_matters() {
+    let mut a = Trie::new();
+    a.insert_lossy("a1f", 1);
+    let mut b = a.clone();
+    b.insert_lossy("a1", 2);
+    assert_ne!(a, b);
+    b.delete_lossy("a1");
+    assert_eq!(a, b);
+}
+
+fn hash_of(trie: &Trie<u64>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    trie.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn equal_tries_hash_alike() {
+    let mut rng = Rng::new(29);
+    for _ in 0..300 {
+        let (a, b) = same_contents(&mut rng);
+        assert_eq!(hash_of(&a), hash_of(&b));
+    }
+}
+
+#[test]
+// The only interior mutability in a trie is its cache of hashes, which neither `Hash` nor `Eq`
+// look at.
+#[allow(clippy::mutable_key_type)]
+fn tries_dedupe_in_a_hash_set() {
+    let mut rng = Rng::new(290);
+    let mut set = HashSet::new();
+    for _ in 0..50 {
+        let (a, b) = same_contents(&mut rng);
+        set.insert(a);
+        assert!(!set.insert(b));
+    }
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "eth")]
+
+// Roots from the Ethereum tests repository's trie fixtures, with byte keys hex-encoded into
+// nibble paths.
+
+use sha3::{Digest, Keccak256};
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn eth_root(entries: &[(&str, &str)]) -> String {
+    let mut trie = Trie::new();
+    for (key, value) in entries {
+        trie.insert_lossy(&hex(key.as_bytes()), value.to_string());
+    }
+    hex(&trie.eth_root())
+}
+
+#[test]
+fn empty_trie() {
+    assert_eq!(
+        eth_root(&[]),
+        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+    );
+}
+
+#[test]
+fn single_leaf() {
+    // The root is the hash of the one leaf: an RLP list of the hex-prefixed path, 0x20
+    // flagging an even-length leaf, and the value, long enough to need a length byte.
+    let value = "a".repeat(95);
+    let mut leaf = vec![0xf8, 100, 0x82, 0x20, b'A', 0xb8, 95];
+    leaf.extend_from_slice(value.as_bytes());
+    let expected: [u8; 32] = Keccak256::digest(&leaf).into();
+    assert_eq!(eth_root(&[("A", &value)]), hex(&expected));
+}
+
+#[test]
+fn dogs() {
+    assert_eq!(
+        eth_root(&[
+            ("doe", "reindeer"),
+            ("dog", "puppy"),
+            ("dogglesworth", "cat"),
+        ]),
+        "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+    );
+}
+
+#[test]
+fn puppy() {
+    assert_eq!(
+        eth_root(&[
+            ("do", "verb"),
+            ("horse", "stallion"),
+            ("doge", "coin"),
+            ("dog", "puppy"),
+        ]),
+        "5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+    );
+}
+
+#[test]
+fn foo() {
+    assert_eq!(
+        eth_root(&[("foo", "bar"), ("food", "bass")]),
+        "17beaa1648bafa633cda809c90c04af50fc8aed3cb40d16efbddee6fdf63c4c3"
+    );
+}
+
+#[test]
+fn insertion_order_does_not_matter() {
+    let mut rng = Rng::new(42);
+    for _ in 0..100 {
+        let mut entries: Vec<(String, String)> = (0..rng.below(20))
+            .map(|_| (rng.key(6), rng.key(40)))
+            .collect();
+        entries.sort();
+        entries.dedup_by(|a, b| a.0 == b.0);
+        let mut forward = Trie::new();
+        let mut backward = Trie::new();
+        for (key, value) in &entries {
+            forward.insert_lossy(key, value.clone());
+        }
+        for (key, value) in entries.iter().rev() {
+            backward.insert_lossy(key, value.clone());
+        }
+        assert_eq!(forward.eth_root(), backward.eth_root());
+    }
+}
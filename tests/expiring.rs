@@ -0,0 +1,140 @@
+// ExpiringTrie on a clock moved by hand, so nothing sleeps.
+#![cfg(feature = "std")]
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use trie::{Clock, ExpiringTrie};
+
+mod common;
+use common::Rng;
+
+struct Manual {
+    start: Instant,
+    now: Cell<Instant>,
+}
+
+impl Manual {
+    fn new() -> Manual {
+        let start = Instant::now();
+        Manual {
+            start,
+            now: Cell::new(start),
+        }
+    }
+
+    fn set_secs(&self, secs: u64) {
+        self.now.set(self.start + Duration::from_secs(secs));
+    }
+}
+
+impl Clock for Manual {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+fn secs(n: u64) -> Duration {
+    Duration::from_secs(n)
+}
+
+#[test]
+fn an_entry_expires_at_its_deadline() {
+    let mut cache = ExpiringTrie::with_clock(Manual::new());
+    cache.insert_with_ttl("a1", 1, secs(10));
+    cache.clock().set_secs(9);
+    assert_eq!(cache.get("a1"), Some(&1));
+    assert_eq!(cache.expires_at("a1"), Some(cache.clock().start + secs(10)));
+    cache.clock().set_secs(10);
+    assert_eq!(cache.get("a1"), None);
+    assert_eq!(cache.expires_at("a1"), None);
+    assert_eq!(cache.iter().count(), 0);
+}
+
+#[test]
+fn len_counts_expired_entries_until_they_are_removed() {
+    let mut cache = ExpiringTrie::with_clock(Manual::new());
+    cache.insert_with_ttl("a1", 1, secs(1));
+    cache.insert_with_ttl("a2", 2, secs(1));
+    cache.insert_with_ttl("a3", 3, secs(1));
+    cache.insert("b0", 4);
+    cache.clock().set_secs(5);
+    assert_eq!(cache.len(), 4);
+    assert_eq!(cache.get("a1"), None);
+    assert_eq!(cache.len(), 4);
+    // evicting on access takes one out
+    assert_eq!(cache.get_or_evict("a1"), None);
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.get_or_evict("b0"), Some(&4));
+    // deleting an expired entry removes it but has no value to give back
+    assert_eq!(cache.delete("a2"), None);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.purge_expired(), 1);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.purge_expired(), 0);
+    assert_eq!(cache.iter().count(), cache.len());
+}
+
+#[test]
+fn writing_over_an_expired_entry_replaces_nothing_live() {
+    let mut cache = ExpiringTrie::with_clock(Manual::new());
+    cache.insert_with_ttl("a1", 1, secs(1));
+    assert_eq!(cache.insert_with_ttl("a1", 2, secs(1)), Some(1));
+    cache.clock().set_secs(2);
+    assert_eq!(cache.insert("a1", 3), None);
+    assert_eq!(cache.get("a1"), Some(&3));
+    assert_eq!(cache.expires_at("a1"), None);
+    cache.clock().set_secs(1_000_000);
+    assert_eq!(cache.get("a1"), Some(&3));
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn a_ttl_past_what_instant_holds_never_expires() {
+    let mut cache = ExpiringTrie::with_clock(Manual::new());
+    cache.insert_with_ttl("a1", 1, Duration::MAX);
+    cache.clock().set_secs(u32::MAX as u64);
+    assert_eq!(cache.get("a1"), Some(&1));
+    assert_eq!(cache.purge_expired(), 0);
+}
+
+#[test]
+fn purge_agrees_with_a_map_of_deadlines() {
+    let mut rng = Rng::new(98);
+    for _ in 0..30 {
+        let mut cache = ExpiringTrie::with_clock(Manual::new());
+        // each key's deadline in seconds from the start, or None for never
+        let mut model: BTreeMap<String, (u64, Option<u64>)> = BTreeMap::new();
+        let mut now = 0;
+        for n in 0..rng.below(300) {
+            let key = rng.key(4);
+            match rng.below(4) {
+                0 => {
+                    cache.insert(&key, n);
+                    model.insert(key, (n, None));
+                }
+                1 | 2 => {
+                    let ttl = rng.below(20);
+                    cache.insert_with_ttl(&key, n, secs(ttl));
+                    model.insert(key, (n, Some(now + ttl)));
+                }
+                _ => {
+                    now += rng.below(5);
+                    cache.clock().set_secs(now);
+                }
+            }
+        }
+        let live = |(_, deadline): &(u64, Option<u64>)| deadline.is_none_or(|at| at > now);
+        let expected: Vec<(String, u64)> = model
+            .iter()
+            .filter(|(_, entry)| live(entry))
+            .map(|(key, &(value, _))| (key.clone(), value))
+            .collect();
+        let entries: Vec<(String, u64)> = cache.iter().map(|(key, &value)| (key, value)).collect();
+        assert_eq!(entries, expected);
+        assert_eq!(cache.len(), model.len());
+        assert_eq!(cache.purge_expired(), model.len() - expected.len());
+        assert_eq!(cache.len(), expected.len());
+    }
+}
@@ -0,0 +1,159 @@
+// Golden tests of the diagram exports, on the trie the demo in main builds.
+
+use trie::{DotOptions, Trie};
+
+fn demo() -> Trie<String> {
+    let mut trie = Trie::new();
+    for (key, value) in [
+        ("a1f", "leaf-A1F"),
+        ("a1e", "leaf-A1E"),
+        ("b0", "leaf-B0"),
+        ("00", "leaf-00"),
+        ("af", "leaf-AF"),
+    ] {
+        trie.insert_lossy(key, value.to_string());
+    }
+    trie
+}
+
+const DEMO_DOT: &str = r##"digraph trie {
+    node [shape=circle];
+    n [label="(root)"];
+    n0 [label="0"];
+    n -> n0 [label="0"];
+    n00 [label="0\nleaf-00", shape=doublecircle, style=filled, fillcolor="#cde8ff"];
+    n0 -> n00 [label="0"];
+    na [label="a"];
+    n -> na [label="a"];
+    na1 [label="1"];
+    na -> na1 [label="1"];
+    na1e [label="e\nleaf-A1E", shape=doublecircle, style=filled, fillcolor="#cde8ff"];
+    na1 -> na1e [label="e"];
+    na1f [label="f\nleaf-A1F", shape=doublecircle, style=filled, fillcolor="#cde8ff"];
+    na1 -> na1f [label="f"];
+    naf [label="f\nleaf-AF", shape=doublecircle, style=filled, fillcolor="#cde8ff"];
+    na -> naf [label="f"];
+    nb [label="b"];
+    n -> nb [label="b"];
+    nb0 [label="0\nleaf-B0", shape=doublecircle, style=filled, fillcolor="#cde8ff"];
+    nb -> nb0 [label="0"];
+}
+"##;
+
+#[test]
+fn dot_of_the_demo_trie() {
+    assert_eq!(demo().to_dot(DotOptions::default()), DEMO_DOT);
+}
+
+#[test]
+fn dot_with_full_paths_to_a_depth() {
+    let opts = DotOptions {
+        full_path_labels: true,
+        max_depth: Some(1),
+        show_empty_interior: true,
+    };
+    assert_eq!(
+        demo().to_dot(opts),
+        r#"digraph trie {
+    node [shape=circle];
+    n [label="(root)"];
+    n0 [label="0"];
+    n -> n0 [label="0"];
+    na [label="a"];
+    n -> na [label="a"];
+    nb [label="b"];
+    n -> nb [label="b"];
+}
+"#
+    );
+}
+
+#[test]
+fn dot_without_empty_interior_nodes() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1e", "x".to_string());
+    trie.insert_lossy("b0", "y".to_string());
+    let opts = DotOptions {
+        show_empty_interior: false,
+        ..DotOptions::default()
+    };
+    assert_eq!(
+        trie.to_dot(opts),
+        r##"digraph trie {
+    node [shape=circle];
+    n [label="(root)"];
+    na1e [label="e\nx", shape=doublecircle, style=filled, fillcolor="#cde8ff"];
+    n -> na1e [label="a1e"];
+    nb0 [label="0\ny", shape=doublecircle, style=filled, fillcolor="#cde8ff"];
+    n -> nb0 [label="b0"];
+}
+"##
+    );
+}
+
+#[test]
+fn dot_escapes_values() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a", "say \"hi\"\\\nbye".to_string());
+    let dot = trie.to_dot(DotOptions::default());
+    assert!(
+        dot.contains(r#"na [label="a\nsay \"hi\"\\\nbye", "#),
+        "{}",
+        dot
+    );
+}
+
+const DEMO_MERMAID: &str = r#"graph TD
+    n["(root)"]
+    n0["0"]
+    n -->|0| n0
+    n00["0 = leaf-00"]
+    n0 -->|0| n00
+    na["a"]
+    n -->|a| na
+    na1["1"]
+    na -->|1| na1
+    na1e["e = leaf-A1E"]
+    na1 -->|e| na1e
+    na1f["f = leaf-A1F"]
+    na1 -->|f| na1f
+    naf["f = leaf-AF"]
+    na -->|f| naf
+    nb["b"]
+    n -->|b| nb
+    nb0["0 = leaf-B0"]
+    nb -->|0| nb0
+"#;
+
+#[test]
+fn mermaid_of_the_demo_trie() {
+    assert_eq!(demo().to_mermaid(), DEMO_MERMAID);
+}
+
+#[test]
+fn mermaid_stops_at_the_node_budget() {
+    assert_eq!(
+        demo().to_mermaid_with_budget(3),
+        r#"graph TD
+    n["(root)"]
+    n0["0"]
+    n -->|0| n0
+    n00["0 = leaf-00"]
+    n0 -->|0| n00
+    n --> truncated["..."]
+"#
+    );
+    assert_eq!(demo().to_mermaid_with_budget(100), DEMO_MERMAID);
+}
+
+#[test]
+fn mermaid_sanitizes_values() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a", "say \"hi\" [x] <b>".to_string());
+    let mermaid = trie.to_mermaid();
+    assert!(
+        mermaid.contains(r#"na["a = say #quot;hi#quot; #91;x#93; #lt;b#gt;"]"#),
+        "{}",
+        mermaid
+    );
+}
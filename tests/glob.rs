@@ -0,0 +1,85 @@
+// iter_glob against a regex filter over iter(), the brute-force way to match a pattern.
+
+use regex::Regex;
+use trie::{GlobError, GlobPattern, Trie};
+
+mod common;
+use common::Rng;
+
+// The same pattern as a regex over whole lowercase keys.
+fn regex_of(pattern: &str) -> Regex {
+    let body: String = pattern
+        .chars()
+        .map(|c| match c {
+            '?' => "[0-9a-f]".to_string(),
+            '*' => "[0-9a-f]*".to_string(),
+            _ => c.to_ascii_lowercase().to_string(),
+        })
+        .collect();
+    Regex::new(&format!("^{}$", body)).unwrap()
+}
+
+fn random_pattern(rng: &mut Rng) -> String {
+    (0..rng.below(6))
+        .map(|_| b"01af?*"[rng.below(6) as usize] as char)
+        .collect()
+}
+
+#[test]
+fn matches_a_regex_filter_over_iter() {
+    let mut rng = Rng::new(101);
+    for _ in 0..50 {
+        let mut trie = Trie::new();
+        for n in 0..rng.below(300) {
+            trie.insert_lossy(&rng.key(6), n);
+        }
+        for _ in 0..30 {
+            let pattern = random_pattern(&mut rng);
+            let regex = regex_of(&pattern);
+            let expected: Vec<(String, &u64)> =
+                trie.iter().filter(|(key, _)| regex.is_match(key)).collect();
+            let glob = GlobPattern::parse(&pattern).unwrap();
+            let found: Vec<(String, &u64)> = trie.iter_glob(&glob).collect();
+            assert_eq!(found, expected, "{:?}", pattern);
+        }
+    }
+}
+
+#[test]
+fn patterns_match_whole_keys() {
+    let mut trie = Trie::new();
+    for key in ["", "a", "a1", "a1f", "b"] {
+        trie.insert_lossy(key, ());
+    }
+    let keys = |pattern: &str| -> Vec<String> {
+        let glob = GlobPattern::parse(pattern).unwrap();
+        trie.iter_glob(&glob).map(|(key, _)| key).collect()
+    };
+    assert_eq!(keys(""), [""]);
+    assert_eq!(keys("a"), ["a"]);
+    assert_eq!(keys("A*"), ["a", "a1", "a1f"]);
+    assert_eq!(keys("?"), ["a", "b"]);
+    assert_eq!(keys("*"), ["", "a", "a1", "a1f", "b"]);
+    assert_eq!(keys("**f"), ["a1f"]);
+    assert_eq!(keys("*1*"), ["a1", "a1f"]);
+    assert_eq!(keys("??"), ["a1"]);
+    assert!(keys("c*").is_empty());
+}
+
+#[test]
+fn bad_characters_are_rejected_where_they_are() {
+    assert_eq!(
+        GlobPattern::parse("a?g*"),
+        Err(GlobError {
+            index: 2,
+            found: 'g'
+        })
+    );
+    let err = GlobPattern::parse("ab[0]").unwrap_err();
+    assert_eq!((err.index, err.found), (2, '['));
+    assert_eq!(
+        err.to_string(),
+        "invalid character '[' at index 2 in glob pattern"
+    );
+    assert!(GlobPattern::parse("").is_ok());
+}
@@ -0,0 +1,122 @@
+// find_within_hamming against measuring the distance to every stored key.
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+// The nibbles two same-length keys differ in, or None if their lengths differ.
+fn distance(a: &str, b: &str) -> Option<usize> {
+    (a.len() == b.len()).then(|| a.chars().zip(b.chars()).filter(|(x, y)| x != y).count())
+}
+
+fn random_key(rng: &mut Rng, len: usize) -> String {
+    (0..len)
+        .map(|_| b"0123456789abcdef"[rng.below(16) as usize] as char)
+        .collect()
+}
+
+#[test]
+fn matches_measuring_every_key() {
+    let mut rng = Rng::new(102);
+    let mut trie = Trie::new();
+    // mostly one width, near copies of each other, with some shorter and longer keys mixed in
+    let mut base = random_key(&mut rng, 8);
+    for n in 0..3_000u32 {
+        let mut key = base.clone();
+        for _ in 0..rng.below(4) {
+            let at = rng.below(8) as usize;
+            key.replace_range(at..at + 1, &random_key(&mut rng, 1));
+        }
+        if rng.below(50) == 0 {
+            base = key.clone();
+        }
+        match rng.below(10) {
+            0 => {
+                key.pop();
+            }
+            1 => key.push('0'),
+            _ => {}
+        }
+        trie.insert_lossy(&key, n);
+    }
+    for _ in 0..100 {
+        let query = match rng.below(2) {
+            0 => {
+                trie.iter()
+                    .nth(rng.below(trie.len() as u64) as usize)
+                    .unwrap()
+                    .0
+            }
+            _ => random_key(&mut rng, 8),
+        };
+        for k in 0..4 {
+            let mut expected: Vec<(usize, String, &u32)> = trie
+                .iter()
+                .filter_map(|(key, value)| Some((distance(&key, &query)?, key, value)))
+                .filter(|&(d, _, _)| d <= k)
+                .collect();
+            expected.sort();
+            let expected: Vec<(String, &u32)> = expected
+                .into_iter()
+                .map(|(_, key, value)| (key, value))
+                .collect();
+            assert_eq!(
+                trie.find_within_hamming(&query, k),
+                expected,
+                "{} {}",
+                query,
+                k
+            );
+        }
+    }
+}
+
+#[test]
+fn k_zero_is_an_exact_get() {
+    let mut trie = Trie::new();
+    for key in ["a1f0", "a1ff", "a1f"] {
+        trie.insert_lossy(key, key);
+    }
+    assert_eq!(
+        trie.find_within_hamming("a1f0", 0),
+        [("a1f0".to_string(), &"a1f0")]
+    );
+    assert_eq!(trie.find_within_hamming("A1-F0", 0).len(), 1);
+    assert!(trie.find_within_hamming("a1f1", 0).is_empty());
+    assert!(trie.find_within_hamming("a1", 0).is_empty());
+}
+
+#[test]
+fn only_keys_of_the_same_length_match() {
+    let mut trie = Trie::new();
+    for key in ["a1", "a1f", "a1f0", "b2f01"] {
+        trie.insert_lossy(key, ());
+    }
+    let keys: Vec<String> = trie
+        .find_within_hamming("a1f0", 10)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, ["a1f0"]);
+    let keys: Vec<String> = trie
+        .find_within_hamming("", 3)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert!(keys.is_empty());
+}
+
+#[test]
+fn closer_keys_come_first() {
+    let mut trie = Trie::new();
+    for key in ["ffff", "0fff", "00ff", "f0f0", "fff0"] {
+        trie.insert_lossy(key, ());
+    }
+    let keys: Vec<String> = trie
+        .find_within_hamming("ffff", 2)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, ["ffff", "0fff", "fff0", "00ff", "f0f0"]);
+}
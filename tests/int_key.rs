@@ -0,0 +1,69 @@
+use trie::{IntKey, Trie};
+
+mod common;
+use common::Rng;
+
+#[test]
+fn shuffled_u32s_iterate_in_numeric_order() {
+    let mut rng = Rng::new(49);
+    let mut numbers: Vec<u32> = (0..2_000).map(|_| rng.next() as u32).collect();
+    numbers.extend([0, 1, 15, 16, 255, 256, u32::MAX]);
+    let mut trie = Trie::new();
+    for &n in &numbers {
+        trie.insert_int(n, n);
+    }
+    numbers.sort();
+    numbers.dedup();
+    let seen: Vec<u32> = trie.iter_int::<u32>().map(|(key, _)| key).collect();
+    assert_eq!(seen, numbers);
+    assert!(trie.iter_int::<u32>().all(|(key, &value)| key == value));
+}
+
+#[test]
+fn keys_have_a_fixed_width() {
+    let mut trie = Trie::new();
+    trie.insert_u64(3, "three");
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["0000000000000003"]);
+    assert_eq!(trie.get_lossy("0000000000000003"), Some(&"three"));
+    assert_eq!(trie.get_lossy("3"), None);
+    assert_eq!(u8::NIBBLES, 2);
+    assert_eq!(u16::NIBBLES, 4);
+    assert_eq!(u128::NIBBLES, 32);
+}
+
+#[test]
+fn every_width_round_trips() {
+    fn check<K: IntKey + PartialEq + std::fmt::Debug>(keys: &[K]) {
+        let mut trie = Trie::new();
+        for (n, &key) in keys.iter().enumerate() {
+            assert_eq!(trie.insert_int(key, n), None);
+        }
+        for (n, &key) in keys.iter().enumerate() {
+            assert_eq!(trie.get_int(key), Some(&n));
+        }
+        let decoded: Vec<K> = trie.iter_int::<K>().map(|(key, _)| key).collect();
+        assert_eq!(decoded, keys);
+        for (n, &key) in keys.iter().enumerate() {
+            assert_eq!(trie.delete_int(key), Some(n));
+        }
+        assert!(trie.is_empty());
+        assert_eq!(trie.node_count(), 1);
+    }
+    check(&[0u8, 7, 128, 255]);
+    check(&[0u16, 300, u16::MAX]);
+    check(&[1u32, 1 << 20, u32::MAX]);
+    check(&[0u64, 3, u64::MAX / 2, u64::MAX]);
+    check(&[5u128, 1 << 100, u128::MAX]);
+}
+
+#[test]
+fn u64_helpers_match_the_generic_ones() {
+    let mut trie = Trie::new();
+    assert_eq!(trie.insert_u64(42, 'a'), None);
+    assert_eq!(trie.get_int(42u64), Some(&'a'));
+    assert_eq!(trie.insert_int(42u64, 'b'), Some('a'));
+    assert_eq!(trie.get_u64(42), Some(&'b'));
+    assert_eq!(trie.get_u64(43), None);
+    assert_eq!(trie.delete_u64(42), Some('b'));
+    assert_eq!(trie.delete_u64(42), None);
+}
@@ -0,0 +1,94 @@
+use std::net::IpAddr;
+
+use trie::IpTrie;
+
+fn table() -> IpTrie<&'static str> {
+    let mut table = IpTrie::new();
+    for (net, len, hop) in [
+        ("0.0.0.0", 0, "default"),
+        ("10.0.0.0", 8, "corp"),
+        ("10.1.0.0", 16, "lab"),
+        ("10.1.2.0", 24, "rack"),
+        ("10.1.2.3", 32, "host"),
+        ("192.168.0.0", 16, "home"),
+        // host bits past the length don't matter
+        ("172.16.99.99", 12, "private"),
+    ] {
+        table.insert_v4(net.parse().unwrap(), len, hop);
+    }
+    for (net, len, hop) in [
+        ("2001:db8::", 32, "doc"),
+        ("2001:db8:1::", 48, "site"),
+        ("::1", 128, "loopback"),
+    ] {
+        table.insert_v6(net.parse().unwrap(), len, hop);
+    }
+    table
+}
+
+fn lookup(table: &IpTrie<&'static str>, addr: &str) -> Option<&'static str> {
+    table.lookup(addr.parse::<IpAddr>().unwrap()).copied()
+}
+
+#[test]
+fn the_most_specific_route_wins() {
+    let table = table();
+    for (addr, hop) in [
+        ("10.1.2.3", "host"),
+        ("10.1.2.4", "rack"),
+        ("10.1.3.1", "lab"),
+        ("10.200.0.1", "corp"),
+        ("11.0.0.1", "default"),
+        ("192.168.44.1", "home"),
+        ("172.31.255.255", "private"),
+        ("172.32.0.0", "default"),
+        ("255.255.255.255", "default"),
+    ] {
+        assert_eq!(lookup(&table, addr), Some(hop), "{}", addr);
+    }
+}
+
+#[test]
+fn ipv6_routes_are_separate() {
+    let table = table();
+    assert_eq!(lookup(&table, "2001:db8:1::42"), Some("site"));
+    assert_eq!(lookup(&table, "2001:db8:2::42"), Some("doc"));
+    assert_eq!(lookup(&table, "::1"), Some("loopback"));
+    // the IPv4 default route doesn't cover IPv6
+    assert_eq!(lookup(&table, "::2"), None);
+    assert_eq!(lookup(&table, "fe80::1"), None);
+}
+
+#[test]
+fn longest_match_reports_the_prefix_length() {
+    let table = table();
+    let addr: IpAddr = "10.1.9.9".parse().unwrap();
+    assert_eq!(table.longest_match(addr), Some((16, &"lab")));
+    let addr: IpAddr = "8.8.8.8".parse().unwrap();
+    assert_eq!(table.longest_match(addr), Some((0, &"default")));
+}
+
+#[test]
+fn removing_a_route_falls_back_to_the_next_one() {
+    let mut table = table();
+    assert_eq!(table.len(), 10);
+    assert_eq!(
+        table.remove_v4("10.1.2.0".parse().unwrap(), 24),
+        Some("rack")
+    );
+    assert_eq!(table.remove_v4("10.1.2.0".parse().unwrap(), 24), None);
+    assert_eq!(lookup(&table, "10.1.2.4"), Some("lab"));
+    assert_eq!(lookup(&table, "10.1.2.3"), Some("host"));
+    assert_eq!(
+        table.remove_v4("0.0.0.0".parse().unwrap(), 0),
+        Some("default")
+    );
+    assert_eq!(lookup(&table, "11.0.0.1"), None);
+    assert_eq!(table.len(), 8);
+}
+
+#[test]
+#[should_panic(expected = "over 32")]
+fn an_ipv4_prefix_over_32_bits_panics() {
+    IpTrie::new().insert_v4("10.0.0.0".parse().unwrap(), 33, ());
+}
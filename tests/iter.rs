@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+fn trie_of(keys: &[&str]) -> Trie<String> {
+    let mut trie = Trie::new();
+    for key in keys {
+        trie.insert_lossy(key, key.to_uppercase());
+    }
+    trie
+}
+
+#[test]
+fn iter_visits_keys_in_order() {
+    let trie = trie_of(&["b0", "a1f", "00", "a1e"]);
+    let keys: Vec<String> = trie.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["00", "a1e", "a1f", "b0"]);
+}
+
+#[test]
+fn iter_includes_interior_and_root_values() {
+    let trie = trie_of(&["a1f", "a1", ""]);
+    let entries: Vec<(String, &String)> = trie.iter().collect();
+    assert_eq!(
+        entries,
+        [
+            (String::new(), &String::new()),
+            ("a1".to_string(), &"A1".to_string()),
+            ("a1f".to_string(), &"A1F".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn iter_agrees_with_a_btreemap() {
+    let mut rng = Rng::new(9);
+    for _ in 0..100 {
+        let mut trie = Trie::new();
+        let mut map = BTreeMap::new();
+        for _ in 0..rng.below(50) {
+            let key = rng.key(5);
+            let value = rng.below(100);
+            trie.insert_lossy(&key, value);
+            map.insert(key, value);
+        }
+        let entries: Vec<(String, u64)> = trie.iter().map(|(key, value)| (key, *value)).collect();
+        let expected: Vec<(String, u64)> = map.into_iter().collect();
+        assert_eq!(entries, expected);
+    }
+}
+
+#[test]
+fn iter_can_stop_partway() {
+    let trie = trie_of(&["0", "1", "2"]);
+    let mut iter = trie.iter();
+    assert_eq!(iter.next().map(|(key, _)| key), Some("0".to_string()));
+    assert_eq!(iter.next().map(|(key, _)| key), Some("1".to_string()));
+}
+
+#[test]
+fn keys_and_values_agree_with_iter_and_len() {
+    let trie = trie_of(&["b0", "a1f", "00", "a1", "a1e"]);
+    let keys: Vec<String> = trie.keys().collect();
+    let values: Vec<&String> = trie.values().collect();
+    let (iter_keys, iter_values): (Vec<String>, Vec<&String>) = trie.iter().unzip();
+    assert_eq!(keys, iter_keys);
+    assert_eq!(values, iter_values);
+    assert_eq!(keys.len(), trie.len());
+    assert_eq!(values.len(), trie.len());
+}
+
+#[test]
+fn an_empty_trie_yields_nothing() {
+    let trie = Trie::<String>::new();
+    assert_eq!(trie.keys().next(), None);
+    assert_eq!(trie.values().next(), None);
+    assert_eq!(trie.iter().next(), None);
+}
+
+#[test]
+fn values_mut_reaches_every_value() {
+    let mut trie = trie_of(&["b0", "a1f", "00", "a1", ""]);
+    trie.values_mut().for_each(|value| value.push_str("-done"));
+    for key in ["b0", "a1f", "00", "a1", ""] {
+        assert_eq!(
+            trie.get_lossy(key),
+            Some(&format!("{}-done", key.to_uppercase()))
+        );
+    }
+}
+
+#[test]
+fn values_mut_goes_below_nodes_without_values() {
+    // "a" and "a1" are only on the way to "a1f"
+    let mut trie = trie_of(&["a1f", "a1f0"]);
+    assert_eq!(trie.values_mut().count(), 2);
+}
+
+#[test]
+fn into_iter_moves_every_entry_out() {
+    let keys = ["b0", "a1f", "00", "a1", "a1e", ""];
+    let map: BTreeMap<String, String> = trie_of(&keys).into_iter().collect();
+    let expected: BTreeMap<String, String> = keys
+        .iter()
+        .map(|key| (key.to_string(), key.to_uppercase()))
+        .collect();
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn into_iter_drops_what_it_did_not_yield() {
+    use std::rc::Rc;
+
+    let value = Rc::new(());
+    let mut trie = Trie::new();
+    for key in ["0", "1", "10", "2", "3f"] {
+        trie.insert_lossy(key, value.clone());
+    }
+    let mut iter = trie.into_iter();
+    assert_eq!(iter.next().map(|(key, _)| key), Some("0".to_string()));
+    assert_eq!(Rc::strong_count(&value), 5);
+    drop(iter);
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn drain_yields_everything_in_order_and_empties_the_trie() {
+    let keys = ["b0", "a1f", "00", "a1", ""];
+    let mut trie = trie_of(&keys);
+    let drained: Vec<(String, String)> = trie.drain().collect();
+    let mut expected: Vec<(String, String)> = keys
+        .iter()
+        .map(|key| (key.to_string(), key.to_uppercase()))
+        .collect();
+    expected.sort();
+    assert_eq!(drained, expected);
+    assert!(trie.is_empty());
+    assert_eq!(trie.node_count(), 1);
+}
+
+#[test]
+fn a_drain_dropped_partway_still_empties_the_trie() {
+    let mut trie = trie_of(&["b0", "a1f", "00", "a1"]);
+    let mut drain = trie.drain();
+    assert_eq!(drain.next(), Some(("00".to_string(), "00".to_uppercase())));
+    drop(drain);
+    assert!(trie.is_empty());
+    assert_eq!(trie.iter().next(), None);
+    assert!(trie.validate().is_ok());
+    trie.insert_lossy("a1", "again".to_string());
+    assert_eq!(trie.len(), 1);
+}
+
+#[test]
+fn pages_from_iter_from_stitch_into_iter() {
+    let mut rng = Rng::new(55);
+    for page_size in [1, 3, 10] {
+        let mut trie = Trie::new();
+        for _ in 0..200 {
+            trie.insert_lossy(&rng.key(5), ());
+        }
+        let mut pages: Vec<String> = Vec::new();
+        loop {
+            // each page starts after the last key of the one before
+            let (start, skip) = match pages.last() {
+                Some(last) => (last.as_str(), 1),
+                None => ("", 0),
+            };
+            let page: Vec<String> = trie
+                .iter_from(start)
+                .skip(skip)
+                .take(page_size)
+                .map(|(key, _)| key)
+                .collect();
+            let full = page.len() == page_size;
+            pages.extend(page);
+            if !full {
+                break;
+            }
+        }
+        assert_eq!(pages, trie.keys().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn iter_from_starts_at_the_first_key_not_below_start() {
+    let trie = trie_of(&["0", "a1", "a1f", "a2", "b"]);
+    let from = |start| {
+        trie.iter_from(start)
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(from("a1"), ["a1", "a1f", "a2", "b"]);
+    assert_eq!(from("a10"), ["a1f", "a2", "b"]);
+    assert_eq!(from("a"), ["a1", "a1f", "a2", "b"]);
+    assert_eq!(from("a1ff"), ["a2", "b"]);
+    assert_eq!(from("c"), Vec::<String>::new());
+    assert_eq!(from(""), ["0", "a1", "a1f", "a2", "b"]);
+}
+
+fn random_pair(rng: &mut Rng) -> (Trie<u64>, BTreeMap<String, u64>) {
+    let mut trie = Trie::new();
+    let mut map = BTreeMap::new();
+    for n in 0..rng.below(200) {
+        let key = rng.key(6);
+        trie.insert_lossy(&key, n);
+        map.insert(key, n);
+    }
+    (trie, map)
+}
+
+#[test]
+fn rev_agrees_with_a_reversed_btreemap() {
+    let mut rng = Rng::new(87);
+    for _ in 0..100 {
+        let (trie, map) = random_pair(&mut rng);
+        let entries: Vec<(String, u64)> = trie
+            .iter()
+            .rev()
+            .map(|(key, &value)| (key, value))
+            .collect();
+        let expected: Vec<(String, u64)> = map.clone().into_iter().rev().collect();
+        assert_eq!(entries, expected);
+        assert!(trie.keys().rev().eq(map.keys().rev().cloned()));
+        assert!(trie.values().rev().eq(map.values().rev()));
+    }
+}
+
+#[test]
+fn rev_yields_values_after_their_descendants() {
+    let trie = trie_of(&["", "a", "a1", "a1f", "a2", "b"]);
+    let keys: Vec<String> = trie.keys().rev().collect();
+    assert_eq!(keys, ["b", "a2", "a1f", "a1", "a", ""]);
+}
+
+#[test]
+fn both_ends_never_meet_on_one_entry() {
+    let mut rng = Rng::new(870);
+    for _ in 0..100 {
+        let (trie, map) = random_pair(&mut rng);
+        let mut iter = trie.iter();
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        loop {
+            let entry = if rng.below(2) == 0 {
+                iter.next().map(|entry| front.push(entry))
+            } else {
+                iter.next_back().map(|entry| back.push(entry))
+            };
+            if entry.is_none() {
+                break;
+            }
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+        back.reverse();
+        front.extend(back);
+        let keys: Vec<String> = front.into_iter().map(|(key, _)| key).collect();
+        assert!(keys.iter().eq(map.keys()));
+    }
+}
+
+#[test]
+fn range_runs_backwards_too() {
+    let mut rng = Rng::new(871);
+    for _ in 0..50 {
+        let (trie, map) = random_pair(&mut rng);
+        let prefix = rng.key(2);
+        let keys: Vec<String> = trie
+            .iter_prefix(&prefix)
+            .rev()
+            .map(|(key, _)| key)
+            .collect();
+        let expected: Vec<String> = map
+            .keys()
+            .filter(|key| key.starts_with(&prefix))
+            .rev()
+            .cloned()
+            .collect();
+        assert_eq!(keys, expected);
+    }
+}
+
+#[test]
+fn iterators_know_how_many_entries_are_left() {
+    let mut rng = Rng::new(88);
+    for _ in 0..50 {
+        let (trie, _) = random_pair(&mut rng);
+        assert_eq!(trie.iter().len(), trie.len());
+        assert_eq!(trie.keys().len(), trie.len());
+        assert_eq!(trie.values().len(), trie.len());
+        let mut iter = trie.iter();
+        let mut left = trie.len();
+        while left > 0 {
+            if rng.below(2) == 0 {
+                iter.next().unwrap();
+            } else {
+                iter.next_back().unwrap();
+            }
+            left -= 1;
+            assert_eq!(iter.len(), left);
+            assert_eq!(iter.size_hint(), (left, Some(left)));
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.len(), 0);
+
+        let mut owned = trie.clone().into_iter();
+        for left in (0..trie.len()).rev() {
+            owned.next().unwrap();
+            assert_eq!(owned.len(), left);
+        }
+        let mut copy = trie.clone();
+        let mut drain = copy.drain();
+        assert_eq!(drain.len(), trie.len());
+        if drain.next().is_some() {
+            assert_eq!(drain.len(), trie.len() - 1);
+        }
+    }
+}
+
+#[test]
+fn range_bounds_hold_while_consumed() {
+    let mut rng = Rng::new(880);
+    for _ in 0..50 {
+        let (trie, _) = random_pair(&mut rng);
+        let prefix = rng.key(2);
+        let mut range = trie.iter_prefix(&prefix);
+        let mut left = trie.iter_prefix(&prefix).count();
+        loop {
+            let (lower, upper) = range.size_hint();
+            assert!(lower <= left);
+            assert!(upper.is_none_or(|upper| upper >= left));
+            if range.next().is_none() {
+                break;
+            }
+            left -= 1;
+        }
+        assert_eq!(left, 0);
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "json")]
+
+use serde_json::json;
+use trie::{JsonError, Trie};
+
+#[test]
+fn exports_one_object_per_node() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", "x".to_string());
+    trie.insert_lossy("a1f", "y".to_string());
+    trie.insert_lossy("", "root".to_string());
+    assert_eq!(
+        trie.to_json(),
+        json!({
+            "value": "root",
+            "children": {
+                "a": {"children": {"1": {"value": "x", "children": {"f": {"value": "y"}}}}}
+            }
+        })
+    );
+}
+
+#[test]
+fn round_trips() {
+    let mut trie = Trie::new();
+    for (n, key) in ["", "0", "a1", "a1f", "a1e", "ff00"]
+        .into_iter()
+        .enumerate()
+    {
+        trie.insert_lossy(key, n as u64);
+    }
+    let back = Trie::<u64>::from_json(&trie.to_json()).unwrap();
+    assert_eq!(back, trie);
+    assert_eq!(
+        Trie::<u64>::from_json(&Trie::<u64>::new().to_json()).unwrap(),
+        Trie::new()
+    );
+}
+
+#[test]
+fn null_or_missing_values_mean_no_value() {
+    let trie = Trie::<u64>::from_json(&json!({
+        "value": null,
+        "children": {"a": {"children": {"1": {"value": 7}}}}
+    }))
+    .unwrap();
+    assert_eq!(trie.iter().collect::<Vec<_>>(), [("a1".to_string(), &7)]);
+}
+
+#[test]
+fn malformed_json_is_rejected() {
+    let reject = |json| Trie::<u64>::from_json(&json).unwrap_err();
+    assert!(matches!(reject(json!([])), JsonError::NotAnObject(_)));
+    assert!(matches!(
+        reject(json!({"children": {"a": 3}})),
+        JsonError::NotAnObject(_)
+    ));
+    assert!(matches!(
+        reject(json!({"kids": {}})),
+        JsonError::UnknownField(_, _)
+    ));
+    for key in ["g", "ab", "", "A "] {
+        let mut children = serde_json::Map::new();
+        children.insert(key.to_string(), json!({"value": 1}));
+        assert!(
+            matches!(
+                reject(json!({ "children": children })),
+                JsonError::InvalidChildKey(_, _)
+            ),
+            "{:?}",
+            key
+        );
+    }
+    assert!(matches!(
+        reject(json!({"value": "not a number"})),
+        JsonError::InvalidValue(_, _)
+    ));
+}
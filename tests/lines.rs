@@ -0,0 +1,74 @@
+// load_lines and dump_lines, the plain-text import and export.
+#![cfg(feature = "std")]
+
+use trie::{LineError, SkippedLine, Trie};
+
+fn skipped(line: usize, reason: LineError) -> SkippedLine {
+    SkippedLine { line, reason }
+}
+
+#[test]
+fn counts_inserts_overwrites_and_skips() {
+    let mut trie = Trie::<u32>::new();
+    trie.insert_lossy("ff", 0);
+    let input = "a1=1\nb0=2\na1=3\nff=4\nnope=5\n";
+    let report = trie.load_lines(input.as_bytes(), '=').unwrap();
+    assert_eq!(report.inserted, 2);
+    assert_eq!(report.overwritten, 2);
+    assert_eq!(report.skipped, [skipped(5, LineError::InvalidKey)]);
+    assert_eq!(trie.get_lossy("a1"), Some(&3));
+    assert_eq!(trie.get_lossy("ff"), Some(&4));
+    assert_eq!(trie.len(), 3);
+}
+
+#[test]
+fn bad_lines_are_reported_by_line_number() {
+    let mut trie = Trie::<u32>::new();
+    let mut input = b"a1=1\nno separator\nxyz=2\nb0=not a number\n".to_vec();
+    input.extend_from_slice(b"c0=\xff\xfe\n");
+    input.extend_from_slice(b"d0=5");
+    let report = trie.load_lines(&input[..], '=').unwrap();
+    assert_eq!(
+        report.skipped,
+        [
+            skipped(2, LineError::MissingSeparator),
+            skipped(3, LineError::InvalidKey),
+            skipped(4, LineError::InvalidValue),
+            skipped(5, LineError::InvalidUtf8),
+        ]
+    );
+    assert_eq!(report.inserted, 2);
+    // the last line needs no line break
+    assert_eq!(trie.get_lossy("d0"), Some(&5));
+}
+
+#[test]
+fn blank_and_comment_lines_are_ignored_but_counted() {
+    let mut trie = Trie::<String>::new();
+    let input = "\n   \n# a comment=with a separator\n  # indented\r\n a1 =x=y\r\nbad\n";
+    let report = trie.load_lines(input.as_bytes(), '=').unwrap();
+    assert_eq!(report.inserted, 1);
+    assert_eq!(report.skipped, [skipped(6, LineError::MissingSeparator)]);
+    // the key is trimmed, the value is everything after the first separator
+    assert_eq!(trie.get_lossy("a1").unwrap(), "x=y");
+}
+
+#[test]
+fn dump_then_load_round_trips_the_root_key() {
+    let mut trie = Trie::new();
+    for (key, value) in [("", "root"), ("a1", "one"), ("a1f", "two"), ("ff", "three")] {
+        trie.insert_lossy(key, value.to_string());
+    }
+    let mut text = Vec::new();
+    trie.dump_lines(&mut text, '\t').unwrap();
+    assert_eq!(
+        String::from_utf8(text.clone()).unwrap(),
+        "\troot\na1\tone\na1f\ttwo\nff\tthree\n"
+    );
+    let mut loaded = Trie::new();
+    let report = loaded.load_lines(&text[..], '\t').unwrap();
+    assert_eq!(report.inserted, 4);
+    assert!(report.skipped.is_empty());
+    assert_eq!(loaded, trie);
+    assert_eq!(loaded.root_value().map(String::as_str), Some("root"));
+}
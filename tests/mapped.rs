@@ -0,0 +1,225 @@
+// MappedTrie reading what write_mapped wrote, and refusing files that don't hold up.
+#![cfg(feature = "std")]
+
+use trie::{MappedTrie, SnapshotError, Trie};
+
+mod common;
+use common::Rng;
+
+const HEADER: usize = 16;
+const RECORD: usize = 76;
+
+// Nodes are numbered breadth-first: the root is 0, "a" 1, "b" 2, "a1" 3, "b0" 4 and "a1f" 5,
+// and the values follow in the same order.
+fn demo() -> Trie<String> {
+    let mut trie = Trie::new();
+    for (key, value) in [("a1", "one"), ("a1f", "two"), ("b0", "three"), ("", "root")] {
+        trie.insert_lossy(key, value.to_string());
+    }
+    trie
+}
+
+fn bytes_of(trie: &Trie<String>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    trie.write_mapped(&mut bytes).unwrap();
+    bytes
+}
+
+fn entries<B: AsRef<[u8]>>(mapped: &MappedTrie<B>, prefix: &str) -> Vec<(String, Vec<u8>)> {
+    mapped
+        .iter_prefix(prefix)
+        .map(|entry| entry.map(|(key, value)| (key, value.to_vec())))
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn write_u32(bytes: &mut [u8], at: usize, value: u32) {
+    bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+// Where node `index`'s child under `nibble` is stored, and its value length.
+fn child_slot(index: usize, nibble: usize) -> usize {
+    HEADER + index * RECORD + nibble * 4
+}
+
+fn value_len_slot(index: usize) -> usize {
+    HEADER + index * RECORD + 16 * 4 + 8
+}
+
+#[test]
+fn round_trips_get_and_iter_prefix() {
+    let mut rng = Rng::new(32);
+    for _ in 0..30 {
+        let mut trie = Trie::new();
+        for n in 0..rng.below(300) {
+            trie.insert_lossy(&rng.key(6), format!("value-{}", n));
+        }
+        let mapped = MappedTrie::from_bytes(bytes_of(&trie)).unwrap();
+        for _ in 0..50 {
+            let key = rng.key(7);
+            let expected = trie.get_lossy(&key).map(|value| value.as_bytes());
+            assert_eq!(mapped.get(&key).unwrap(), expected, "{:?}", key);
+        }
+        for prefix in ["", "a", "0f", "a1a"] {
+            let expected: Vec<(String, Vec<u8>)> = trie
+                .iter_prefix(prefix)
+                .map(|(key, value)| (key, value.as_bytes().to_vec()))
+                .collect();
+            assert_eq!(entries(&mapped, prefix), expected, "{:?}", prefix);
+        }
+    }
+}
+
+#[test]
+fn the_root_value_and_missing_keys() {
+    let mapped = MappedTrie::from_bytes(bytes_of(&demo())).unwrap();
+    assert_eq!(mapped.get("").unwrap(), Some(&b"root"[..]));
+    assert_eq!(mapped.get("A1").unwrap(), Some(&b"one"[..]));
+    assert_eq!(mapped.get("a").unwrap(), None);
+    assert_eq!(mapped.get("a1f0").unwrap(), None);
+    assert!(entries(&mapped, "c").is_empty());
+    let empty = MappedTrie::from_bytes(bytes_of(&Trie::new())).unwrap();
+    assert_eq!(empty.get("").unwrap(), None);
+    assert!(entries(&empty, "").is_empty());
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn open_maps_a_written_file() {
+    let path = common::temp_dir("mapped-open").join("demo.trm");
+    demo()
+        .write_mapped(std::fs::File::create(&path).unwrap())
+        .unwrap();
+    let mapped = MappedTrie::open(&path).unwrap();
+    assert_eq!(mapped.get("a1f").unwrap(), Some(&b"two"[..]));
+    let keys: Vec<String> = entries(&mapped, "a")
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, ["a1", "a1f"]);
+    assert!(matches!(
+        MappedTrie::open(path.with_extension("missing")),
+        Err(SnapshotError::Io(_))
+    ));
+}
+
+#[test]
+fn a_bad_header_is_refused() {
+    let good = bytes_of(&demo());
+
+    let mut bytes = good.clone();
+    bytes[0] = b'X';
+    assert!(matches!(
+        MappedTrie::from_bytes(bytes),
+        Err(SnapshotError::BadMagic)
+    ));
+    assert!(matches!(
+        MappedTrie::from_bytes(&b"nope"[..]),
+        Err(SnapshotError::BadMagic)
+    ));
+
+    let mut bytes = good.clone();
+    bytes[4] = 9;
+    assert!(matches!(
+        MappedTrie::from_bytes(bytes),
+        Err(SnapshotError::UnsupportedVersion(9))
+    ));
+
+    let mut bytes = good.clone();
+    write_u32(&mut bytes, 8, 0);
+    assert!(matches!(
+        MappedTrie::from_bytes(bytes),
+        Err(SnapshotError::Corrupt(_))
+    ));
+
+    let mut bytes = good.clone();
+    write_u32(&mut bytes, 8, u32::MAX);
+    assert!(matches!(
+        MappedTrie::from_bytes(bytes),
+        Err(SnapshotError::Truncated)
+    ));
+}
+
+#[test]
+fn truncated_records_are_refused() {
+    let good = bytes_of(&demo());
+    let records_end = HEADER + demo().node_count() * RECORD;
+    for len in [0, 3, HEADER - 1, HEADER, records_end - 1] {
+        assert!(
+            matches!(
+                MappedTrie::from_bytes(&good[..len]),
+                Err(SnapshotError::Truncated)
+            ),
+            "{}",
+            len
+        );
+    }
+    // cutting into the blob only fails the values that are cut
+    let mapped = MappedTrie::from_bytes(&good[..good.len() - 1]).unwrap();
+    assert_eq!(mapped.get("").unwrap(), Some(&b"root"[..]));
+    assert_eq!(mapped.get("b0").unwrap(), Some(&b"three"[..]));
+    assert!(matches!(mapped.get("a1f"), Err(SnapshotError::Corrupt(_))));
+}
+
+#[test]
+fn a_child_at_or_below_its_parent_is_refused() {
+    let good = bytes_of(&demo());
+    // node 1 is "a", whose child under 1 is "a1"
+    for bad in [1, 0x7fff_ffff] {
+        let mut bytes = good.clone();
+        write_u32(&mut bytes, child_slot(1, 1), bad);
+        let mapped = MappedTrie::from_bytes(bytes).unwrap();
+        assert!(matches!(mapped.get("a1"), Err(SnapshotError::Corrupt(_))));
+        let mut iter = mapped.iter_prefix("");
+        assert!(iter.by_ref().any(|entry| entry.is_err()));
+        assert!(iter.next().is_none());
+    }
+    // a pointer back up from "a1" to "a", which would loop
+    let mut bytes = good;
+    write_u32(&mut bytes, child_slot(3, 15), 1);
+    let mapped = MappedTrie::from_bytes(bytes).unwrap();
+    assert!(matches!(mapped.get("a1f"), Err(SnapshotError::Corrupt(_))));
+    assert!(mapped.iter_prefix("a1").next().unwrap().is_ok());
+}
+
+#[test]
+fn a_value_past_the_blob_is_refused() {
+    let good = bytes_of(&demo());
+    let mut bytes = good.clone();
+    write_u32(&mut bytes, value_len_slot(0), 1 << 20);
+    let mapped = MappedTrie::from_bytes(bytes).unwrap();
+    assert!(matches!(mapped.get(""), Err(SnapshotError::Corrupt(_))));
+
+    let mut bytes = good;
+    let offset = value_len_slot(0) - 8;
+    bytes[offset..offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    let mapped = MappedTrie::from_bytes(bytes).unwrap();
+    assert!(matches!(mapped.get(""), Err(SnapshotError::Corrupt(_))));
+    assert!(mapped.iter_prefix("").next().unwrap().is_err());
+}
+
+// Runs every lookup a file allows, which must end in answers or errors, never a panic.
+fn read_everything(bytes: &[u8]) {
+    let Ok(mapped) = MappedTrie::from_bytes(bytes) else {
+        return;
+    };
+    for key in ["", "a", "a1", "a1f", "b0", "ff"] {
+        let _ = mapped.get(key);
+    }
+    assert!(mapped.iter_prefix("").count() < 100);
+}
+
+#[test]
+fn no_damage_makes_a_read_panic() {
+    let good = bytes_of(&demo());
+    for len in 0..good.len() {
+        read_everything(&good[..len]);
+    }
+    for at in 0..good.len() {
+        for flip in [0x01, 0x80, 0xff] {
+            let mut bytes = good.clone();
+            bytes[at] ^= flip;
+            read_everything(&bytes);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+fn random_trie(rng: &mut Rng, n: usize) -> Trie<String> {
+    let mut trie = Trie::new();
+    for _ in 0..n {
+        trie.insert_lossy(&rng.key(6), rng.key(4));
+    }
+    trie
+}
+
+#[test]
+fn compact_changes_nothing_visible() {
+    let mut rng = Rng::new(64);
+    for _ in 0..100 {
+        let n = rng.below(200) as usize;
+        let mut trie = random_trie(&mut rng, n);
+        let keys: Vec<String> = trie.keys().collect();
+        for key in &keys {
+            if rng.below(4) != 0 {
+                trie.delete_lossy(key);
+            }
+        }
+        let before: Vec<(String, String)> = trie.iter().map(|(k, v)| (k, v.clone())).collect();
+        let nodes = trie.node_count();
+        trie.compact();
+        let after: Vec<(String, String)> = trie.iter().map(|(k, v)| (k, v.clone())).collect();
+        assert_eq!(after, before);
+        for key in &keys {
+            assert_eq!(
+                trie.get_lossy(key),
+                before.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+            );
+        }
+        assert_eq!(trie.node_count(), nodes);
+        assert_eq!(trie.pool_len(), 0);
+        assert!(trie.validate().is_ok());
+    }
+}
+
+#[test]
+fn compact_reports_what_memory_usage_loses() {
+    let mut rng = Rng::new(640);
+    let mut trie = random_trie(&mut rng, 2_000);
+    trie.remove_prefix("a");
+    trie.remove_prefix("0");
+    let before = trie.memory_usage();
+    assert!(before.spare_children_bytes > 0);
+    let reclaimed = trie.compact();
+    let after = trie.memory_usage();
+    assert!(reclaimed > 0);
+    assert_eq!(after.spare_children_bytes, 0);
+    assert_eq!(before.total_bytes - after.total_bytes, reclaimed);
+    assert_eq!(trie.compact(), 0);
+}
+
+#[test]
+fn memory_usage_grows_linearly_with_the_keys() {
+    let mut rng = Rng::new(65);
+    let mut trie = Trie::new();
+    let mut totals = Vec::new();
+    for size in [2_000, 4_000, 8_000, 16_000] {
+        while trie.len() < size {
+            let key: String = (0..8).map(|_| format!("{:x}", rng.below(16))).collect();
+            trie.insert_lossy(&key, String::with_capacity(10));
+        }
+        totals.push(trie.memory_usage().total_bytes as f64);
+    }
+    for pair in totals.windows(2) {
+        let ratio = pair[1] / pair[0];
+        assert!((1.6..2.4).contains(&ratio), "{:?}", totals);
+    }
+}
+
+#[test]
+fn memory_usage_counts_capacity_and_shrinks_again() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", String::with_capacity(100));
+    let usage = trie.memory_usage();
+    assert_eq!(usage.value_bytes, 100);
+    assert_eq!(usage.node_count, 3);
+
+    let mut rng = Rng::new(650);
+    for _ in 0..2_000 {
+        trie.insert_lossy(&rng.key(6), "x".repeat(8));
+    }
+    let full = trie.memory_usage();
+    trie.remove_prefix("a");
+    trie.remove_prefix("f");
+    trie.compact();
+    let trimmed = trie.memory_usage();
+    assert!(
+        trimmed.total_bytes < full.total_bytes * 2 / 3,
+        "{} vs {}",
+        trimmed,
+        full
+    );
+    assert!(
+        trimmed
+            .to_string()
+            .contains(&format!("in {} nodes", trie.node_count()))
+    );
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use trie::{MergePolicy, Trie};
+
+mod common;
+use common::Rng;
+
+fn trie_of(entries: &[(&str, u32)]) -> Trie<u32> {
+    let mut trie = Trie::new();
+    for &(key, value) in entries {
+        trie.insert_lossy(key, value);
+    }
+    trie
+}
+
+fn entries(trie: &Trie<u32>) -> Vec<(String, u32)> {
+    trie.iter().map(|(key, &value)| (key, value)).collect()
+}
+
+#[test]
+fn policies_pick_the_winner() {
+    let ours = [("", 1), ("a1", 2), ("a1f", 3)];
+    let theirs = [("a1", 20), ("a2", 30), ("", 10)];
+
+    let mut trie = trie_of(&ours);
+    trie.merge(trie_of(&theirs), MergePolicy::KeepSelf);
+    let expected = [("", 1), ("a1", 2), ("a1f", 3), ("a2", 30)];
+    assert_eq!(entries(&trie), entries(&trie_of(&expected)));
+    assert_eq!(trie.len(), 4);
+
+    let mut trie = trie_of(&ours);
+    trie.merge(trie_of(&theirs), MergePolicy::KeepOther);
+    let expected = [("", 10), ("a1", 20), ("a1f", 3), ("a2", 30)];
+    assert_eq!(entries(&trie), entries(&trie_of(&expected)));
+}
+
+#[test]
+fn resolver_sees_conflicts_in_key_order() {
+    let mut ours = trie_of(&[("b", 1), ("a1f", 2), ("a", 3), ("", 4)]);
+    let theirs = trie_of(&[("a", 10), ("b", 20), ("", 30), ("a1f", 40), ("c", 50)]);
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let sink = log.clone();
+    ours.merge(
+        theirs,
+        MergePolicy::WithFn(Box::new(move |key, a, b| {
+            sink.borrow_mut().push(key.to_string());
+            a * 100 + b
+        })),
+    );
+    assert_eq!(*log.borrow(), ["", "a", "a1f", "b"]);
+    assert_eq!(ours.get_lossy("a1f"), Some(&240));
+    assert_eq!(ours.get_lossy("c"), Some(&50));
+    assert_eq!(ours.len(), 5);
+}
+
+#[test]
+fn append_empties_the_other_trie() {
+    let mut ours = trie_of(&[("a", 1)]);
+    let mut theirs = trie_of(&[("a", 2), ("b", 3)]);
+    ours.append(&mut theirs);
+    assert!(theirs.is_empty());
+    assert_eq!(entries(&ours), [("a".to_string(), 2), ("b".to_string(), 3)]);
+}
+
+#[test]
+fn merge_matches_a_btreemap() {
+    let mut rng = Rng::new(24);
+    for _ in 0..200 {
+        let mut sides = [
+            (Trie::new(), BTreeMap::new()),
+            (Trie::new(), BTreeMap::new()),
+        ];
+        for (trie, map) in &mut sides {
+            for _ in 0..rng.below(40) {
+                let key = rng.key(4);
+                let value = rng.below(100) as u32;
+                trie.insert_lossy(&key, value);
+                map.insert(key, value);
+            }
+        }
+        let [(mut ours, mut expected), (theirs, other)] = sides;
+        for (key, value) in other {
+            *expected.entry(key).or_insert(0) += value;
+        }
+        ours.merge(theirs, MergePolicy::WithFn(Box::new(|_, a, b| a + b)));
+        assert_eq!(
+            ours.iter()
+                .map(|(k, v)| (k, *v))
+                .collect::<BTreeMap<_, _>>(),
+            expected
+        );
+        assert_eq!(ours.len(), expected.len());
+        assert!(ours.validate().is_ok());
+    }
+}
@@ -0,0 +1,261 @@
+#![cfg(feature = "sha2")]
+
+use sha2::{Digest, Sha256};
+use trie::{AbsenceProof, EMPTY_SLOT, Proof, Sha256Hasher, Trie, verify, verify_absent};
+
+mod common;
+use common::Rng;
+
+fn root(trie: &Trie<String>) -> [u8; 32] {
+    trie.root_hash::<Sha256Hasher>()
+}
+
+fn random_entries(rng: &mut Rng) -> Vec<(String, String)> {
+    let mut entries: Vec<(String, String)> = (0..rng.below(40))
+        .map(|_| (rng.key(5), rng.key(2)))
+        .collect();
+    entries.sort();
+    entries.dedup_by(|a, b| a.0 == b.0);
+    entries
+}
+
+#[test]
+fn the_root_is_independent_of_insertion_order() {
+    let mut rng = Rng::new(38);
+    for _ in 0..200 {
+        let entries = random_entries(&mut rng);
+        let mut forward = Trie::new();
+        for (key, value) in &entries {
+            forward.insert_lossy(key, value.clone());
+        }
+        let mut shuffled = entries.clone();
+        for i in (1..shuffled.len()).rev() {
+            shuffled.swap(i, rng.below(i as u64 + 1) as usize);
+        }
+        let mut other = Trie::new();
+        for (key, value) in &shuffled {
+            // a detour through a key that's deleted again leaves no trace
+            other.insert_lossy(&format!("{}5", key), String::new());
+            other.insert_lossy(key, value.clone());
+            other.delete_lossy(&format!("{}5", key));
+        }
+        assert_eq!(root(&forward), root(&other));
+    }
+}
+
+#[test]
+fn the_root_commits_to_every_value() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", "x".to_string());
+    trie.insert_lossy("a1f", "y".to_string());
+    let before = root(&trie);
+    *trie.get_mut("a1").unwrap() = "z".to_string();
+    assert_ne!(root(&trie), before);
+    *trie.get_mut("a1").unwrap() = "x".to_string();
+    assert_eq!(root(&trie), before);
+    trie.insert_lossy("", String::new());
+    assert_ne!(root(&trie), before);
+}
+
+#[test]
+fn the_empty_root_hashes_one_empty_node() {
+    let mut node = vec![0u8];
+    for _ in 0..16 {
+        node.extend_from_slice(&EMPTY_SLOT);
+    }
+    let expected: [u8; 32] = Sha256::digest(&node).into();
+    assert_eq!(root(&Trie::new()), expected);
+}
+
+fn proven(trie: &Trie<String>, key: &str) -> Proof {
+    trie.prove::<Sha256Hasher>(key).unwrap()
+}
+
+fn checks(root: [u8; 32], key: &str, value: &str, proof: &Proof) -> bool {
+    verify::<Sha256Hasher>(root, key, value.as_bytes(), proof)
+}
+
+#[test]
+fn every_stored_key_proves() {
+    let mut rng = Rng::new(39);
+    for _ in 0..100 {
+        let mut trie = Trie::new();
+        for (key, value) in random_entries(&mut rng) {
+            trie.insert_lossy(&key, value);
+        }
+        let root = root(&trie);
+        for (key, value) in trie.iter() {
+            assert!(checks(root, &key, value, &proven(&trie, &key)), "{:?}", key);
+        }
+    }
+}
+
+#[test]
+fn a_missing_key_has_no_proof() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", "x".to_string());
+    for key in ["a1", "a1f0", "b", ""] {
+        assert!(trie.prove::<Sha256Hasher>(key).is_none(), "{:?}", key);
+    }
+}
+
+#[test]
+fn tampered_proofs_fail() {
+    let mut trie = Trie::new();
+    for (key, value) in [("a1", "x"), ("a1f", "y"), ("a2", "z"), ("b", "w")] {
+        trie.insert_lossy(key, value.to_string());
+    }
+    let root = root(&trie);
+    let proof = proven(&trie, "a1f");
+    assert!(checks(root, "a1f", "y", &proof));
+
+    assert!(!checks(root, "a1f", "Y", &proof));
+    assert!(!checks(root, "a1e", "y", &proof));
+    assert!(!checks(root, "a1", "y", &proof));
+    assert!(!checks([1; 32], "a1f", "y", &proof));
+
+    // a sibling hash at each level
+    for level in 0..proof.steps.len() {
+        for hash in 0..proof.steps[level].siblings.hashes.len() {
+            let mut bad = proof.clone();
+            bad.steps[level].siblings.hashes[hash][0] ^= 1;
+            assert!(
+                !checks(root, "a1f", "y", &bad),
+                "level {} sibling {}",
+                level,
+                hash
+            );
+        }
+        let mut bad = proof.clone();
+        bad.steps[level].siblings.occupied ^= 1 << 7;
+        assert!(!checks(root, "a1f", "y", &bad), "level {} bitmap", level);
+        let mut bad = proof.clone();
+        bad.steps[level].value = Some(b"forged".to_vec());
+        assert!(!checks(root, "a1f", "y", &bad), "level {} value", level);
+    }
+    let mut short = proof.clone();
+    short.steps.pop();
+    assert!(!checks(root, "a1f", "y", &short));
+}
+
+fn hex(hash: [u8; 32]) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// "a1" and "a1f" hold values and "a" is only on the way to them.
+fn absence_trie() -> Trie<String> {
+    let mut trie = Trie::new();
+    for (key, value) in [("a1", "x"), ("a1f", "y"), ("b0", "z")] {
+        trie.insert_lossy(key, value.to_string());
+    }
+    trie
+}
+
+// computed outside the crate from the node encoding described in `merkle.rs`
+const ABSENCE_ROOT: &str = "fd0727140246924fda7d1bcbf799ba95a16cae6103c60862e91d785fa8d48c67";
+
+fn absent(trie: &Trie<String>, key: &str) -> AbsenceProof {
+    trie.prove_absent::<Sha256Hasher>(key).unwrap()
+}
+
+#[test]
+fn absence_proofs_against_a_fixed_root() {
+    let trie = absence_trie();
+    let root = root(&trie);
+    assert_eq!(hex(root), ABSENCE_ROOT);
+    // a prefix of stored keys whose node has no value, diverging at the first nibble, a
+    // missing slot below a stored key, a missing slot midway and the empty key
+    for key in ["a", "c", "a1f0", "a2", "b", ""] {
+        let proof = absent(&trie, key);
+        assert!(
+            verify_absent::<Sha256Hasher>(root, key, &proof),
+            "{:?}",
+            key
+        );
+        assert!(
+            !verify_absent::<Sha256Hasher>([0; 32], key, &proof),
+            "{:?}",
+            key
+        );
+    }
+    for key in ["a1", "a1f", "b0"] {
+        assert!(
+            trie.prove_absent::<Sha256Hasher>(key).is_none(),
+            "{:?}",
+            key
+        );
+    }
+}
+
+#[test]
+fn an_absence_proof_only_covers_its_key() {
+    let trie = absence_trie();
+    let root = root(&trie);
+    let proof = absent(&trie, "a");
+    assert!(!verify_absent::<Sha256Hasher>(root, "a1", &proof));
+    assert!(!verify_absent::<Sha256Hasher>(root, "b", &proof));
+
+    // claiming the node holds no value when it does
+    let proof = absent(&trie, "a1f0");
+    let mut forged = proof.clone();
+    forged.steps[2].value = None;
+    assert!(!verify_absent::<Sha256Hasher>(root, "a1f0", &forged));
+    let mut forged = proof;
+    forged.children.occupied |= 1;
+    assert!(!verify_absent::<Sha256Hasher>(root, "a1f0", &forged));
+}
+
+// The root of the same entries in a fresh trie, which has nothing cached.
+fn from_scratch(trie: &Trie<String>) -> [u8; 32] {
+    let mut fresh = Trie::new();
+    for (key, value) in trie.iter() {
+        fresh.insert_lossy(&key, value.clone());
+    }
+    root(&fresh)
+}
+
+#[test]
+fn cached_roots_match_a_recomputation_after_every_change() {
+    let mut rng = Rng::new(41);
+    let mut trie = Trie::new();
+    for step in 0..1_000 {
+        let key = rng.key(5);
+        match rng.below(6) {
+            0..=2 => {
+                trie.insert_lossy(&key, rng.key(2));
+            }
+            3 => {
+                let nibbles = key.chars().map(|c| c.to_digit(16).unwrap() as usize);
+                trie.insert_nibbles(nibbles, rng.key(2));
+            }
+            4 => {
+                trie.delete_lossy(&key);
+            }
+            _ => {
+                if let Some(value) = trie.get_mut(&key) {
+                    value.push('!');
+                }
+            }
+        }
+        if rng.below(3) == 0 {
+            assert_eq!(root(&trie), from_scratch(&trie), "step {}", step);
+        }
+    }
+}
+
+#[test]
+fn clones_keep_their_own_caches() {
+    let mut a = Trie::new();
+    for key in ["a1", "a1f", "b0"] {
+        a.insert_lossy(key, key.to_string());
+    }
+    let before = root(&a);
+    let mut b = a.clone();
+    b.insert_lossy("a1e", "new".to_string());
+    b.delete_lossy("b0");
+    assert_eq!(root(&a), before);
+    assert_eq!(root(&b), from_scratch(&b));
+    a.insert_lossy("c", "other".to_string());
+    assert_eq!(root(&a), from_scratch(&a));
+    assert_eq!(root(&b), from_scratch(&b));
+}
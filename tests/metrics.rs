@@ -0,0 +1,117 @@
+#![cfg(feature = "metrics")]
+
+use trie::{MergePolicy, Nibbles, Trie};
+
+mod common;
+use common::Rng;
+
+// The counters as one tuple: lookups, hits, inserts, overwrites, deletes, nodes created,
+// nodes pruned and max depth.
+fn counters<V>(trie: &Trie<V>) -> (u64, u64, u64, u64, u64, u64, u64, u64) {
+    let m = trie.metrics();
+    (
+        m.lookups(),
+        m.hits(),
+        m.inserts(),
+        m.overwrites(),
+        m.deletes(),
+        m.nodes_created(),
+        m.nodes_pruned(),
+        m.max_depth(),
+    )
+}
+
+#[test]
+fn scripted_workload_counts_exactly() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", 1);
+    trie.insert_lossy("a1", 2);
+    trie.insert_lossy("a1f", 3);
+    assert_eq!(counters(&trie), (0, 0, 3, 1, 0, 3, 0, 3));
+
+    trie.get_lossy("a1f");
+    trie.get_lossy("a2");
+    trie.get_int(0xa1u8);
+    trie.get_bytes(&[0xb0]);
+    trie.get_nibbles(&"a1".parse::<Nibbles>().unwrap());
+    assert_eq!(counters(&trie), (5, 3, 3, 1, 0, 3, 0, 3));
+
+    trie.insert_int(0xb0u8, 4);
+    trie.insert_bytes(&[0xb0, 0x01], 5);
+    trie.insert_nibbles([0xc], 6);
+    assert_eq!(counters(&trie), (5, 3, 6, 1, 0, 8, 0, 4));
+
+    trie.delete_int(0xb0u8);
+    trie.delete_bytes(&[0xb0, 0x01]);
+    trie.delete_nibbles(&"c".parse::<Nibbles>().unwrap());
+    trie.pop_first();
+    assert_eq!(counters(&trie), (5, 3, 6, 1, 4, 8, 5, 4));
+
+    trie.update("a1f", |old| old.map(|old| old + 1));
+    trie.update("d", |_| Some(7));
+    trie.update("d", |_| None);
+    trie.compare_and_swap("a1f", Some(&4), Some(5)).unwrap();
+    trie.compare_and_swap("a1f", Some(&0), None).unwrap_err();
+    assert_eq!(counters(&trie), (5, 3, 9, 3, 5, 9, 6, 4));
+
+    trie.get_or_insert_with("a1f", || 0);
+    trie.get_or_insert_with("e", || 8);
+    *trie.entry("e").or_insert(0) += 1;
+    trie.entry("f").or_insert(9);
+    assert_eq!(counters(&trie), (9, 5, 11, 3, 5, 11, 6, 4));
+
+    let mut cursor = trie.cursor_mut();
+    cursor.get("e");
+    cursor.insert("e", 10);
+    cursor.delete("f");
+    assert_eq!(counters(&trie), (10, 6, 12, 4, 6, 11, 7, 4));
+}
+
+#[test]
+fn node_counts_track_the_node_count() {
+    let mut rng = Rng::new(97);
+    let mut trie = Trie::new();
+    for round in 0..2_000 {
+        let key = rng.key(5);
+        match rng.below(12) {
+            0..=3 => {
+                trie.insert_lossy(&key, round);
+            }
+            4..=5 => {
+                trie.delete_lossy(&key);
+            }
+            6 => {
+                trie.remove_prefix(&rng.key(2));
+            }
+            7 => trie.retain(|_, value| *value % 3 != 0),
+            8 => {
+                let moved = trie.split_off(&key);
+                if rng.below(2) == 0 {
+                    trie.merge(moved, MergePolicy::KeepSelf);
+                }
+            }
+            9 => {
+                trie.drain().take(rng.below(4) as usize).for_each(drop);
+            }
+            10 => {
+                let id = trie.snapshot();
+                for _ in 0..4 {
+                    trie.insert_lossy(&rng.key(5), round);
+                    trie.remove_prefix(&rng.key(1));
+                }
+                if rng.below(2) == 0 {
+                    trie.rollback(id);
+                } else {
+                    trie.commit(id);
+                }
+            }
+            _ => trie.clear(),
+        }
+        let m = trie.metrics();
+        assert_eq!(
+            m.nodes_created() - m.nodes_pruned(),
+            trie.node_count() as u64 - 1,
+            "after round {round}"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+#[cfg(feature = "std")]
+use std::cell::Cell;
+use std::sync::{Arc, Mutex};
+
+use trie::{MergePolicy, Trie, TrieObserver};
+
+// Writes every call down, so a test can compare against the calls it expects.
+struct Log(Arc<Mutex<Vec<String>>>);
+
+impl TrieObserver<u32> for Log {
+    fn on_insert(&mut self, key: &str, old: Option<&u32>, new: &u32) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("insert {key} {old:?} {new}"));
+    }
+
+    fn on_delete(&mut self, key: &str, old: &u32) {
+        self.0.lock().unwrap().push(format!("delete {key} {old}"));
+    }
+
+    fn on_prune(&mut self, prefix: &str) {
+        self.0.lock().unwrap().push(format!("prune {prefix}"));
+    }
+}
+
+// A trie holding `entries`, watched from then on, and the log of what it's told.
+fn watched(entries: &[(&str, u32)]) -> (Trie<u32>, Arc<Mutex<Vec<String>>>) {
+    let mut trie = Trie::new();
+    for &(key, value) in entries {
+        trie.insert_lossy(key, value);
+    }
+    let log = Arc::new(Mutex::new(Vec::new()));
+    trie.set_observer(Box::new(Log(log.clone())));
+    (trie, log)
+}
+
+fn calls(log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+    core::mem::take(&mut *log.lock().unwrap())
+}
+
+// The calls sorted, for methods that don't make them in key order.
+fn sorted_calls(log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+    let mut calls = calls(log);
+    calls.sort();
+    calls
+}
+
+#[test]
+fn update_and_compare_and_swap_notify() {
+    let (mut trie, log) = watched(&[("a1", 1)]);
+    trie.update("A1", |old| old.map(|old| old + 1));
+    trie.update("b", |_| Some(7));
+    trie.update("b", |_| None);
+    trie.update("c", |_| None);
+    assert_eq!(
+        calls(&log),
+        ["insert a1 Some(1) 2", "insert b None 7", "delete b 7"]
+    );
+
+    trie.compare_and_swap("a1", Some(&2), Some(3)).unwrap();
+    trie.compare_and_swap("a1", Some(&9), None).unwrap_err();
+    trie.compare_and_swap("a1", Some(&3), None).unwrap();
+    assert_eq!(calls(&log), ["insert a1 Some(2) 3", "delete a1 3"]);
+}
+
+#[test]
+fn get_or_insert_with_notifies_only_when_it_inserts() {
+    let (mut trie, log) = watched(&[("a1", 1)]);
+    trie.get_or_insert_with("a1", || 5);
+    trie.get_or_insert_with("b", || 5);
+    assert_eq!(calls(&log), ["insert b None 5"]);
+}
+
+#[test]
+fn entries_notify_when_they_store() {
+    let (mut trie, log) = watched(&[("a1", 1)]);
+    trie.entry("a1").or_insert(5);
+    trie.entry("a1f").or_insert(2);
+    if let trie::Entry::Occupied(mut entry) = trie.entry("a1") {
+        entry.insert(3);
+    }
+    assert_eq!(calls(&log), ["insert a1f None 2", "insert a1 Some(1) 3"]);
+}
+
+#[test]
+fn cursor_mut_notifies() {
+    let (mut trie, log) = watched(&[("a1", 1)]);
+    let mut cursor = trie.cursor_mut();
+    cursor.insert("a1f", 2);
+    cursor.insert("a1", 3);
+    cursor.delete("a1f");
+    cursor.delete("b");
+    assert_eq!(
+        calls(&log),
+        ["insert a1f None 2", "insert a1 Some(1) 3", "delete a1f 2"]
+    );
+}
+
+#[test]
+fn merge_notifies_every_changed_key() {
+    let other = || {
+        let mut other = Trie::new();
+        for (key, value) in [("a1", 10), ("a2", 20), ("b", 30)] {
+            other.insert_lossy(key, value);
+        }
+        other
+    };
+    let (mut trie, log) = watched(&[("a", 1), ("a1", 2)]);
+    trie.merge(other(), MergePolicy::KeepSelf);
+    assert_eq!(
+        sorted_calls(&log),
+        ["insert a2 None 20", "insert b None 30"]
+    );
+
+    let (mut trie, log) = watched(&[("a", 1), ("a1", 2)]);
+    trie.merge(other(), MergePolicy::KeepOther);
+    assert_eq!(
+        sorted_calls(&log),
+        [
+            "insert a1 Some(2) 10",
+            "insert a2 None 20",
+            "insert b None 30"
+        ]
+    );
+
+    let (mut trie, log) = watched(&[("a1", 2)]);
+    trie.merge(other(), MergePolicy::WithFn(Box::new(|_, a, b| a + b)));
+    assert_eq!(
+        sorted_calls(&log),
+        [
+            "delete a1 2",
+            "insert a1 None 12",
+            "insert a2 None 20",
+            "insert b None 30"
+        ]
+    );
+
+    let (mut trie, log) = watched(&[]);
+    trie.append(&mut other());
+    assert_eq!(sorted_calls(&log).len(), 3);
+}
+
+#[test]
+fn retain_and_split_off_notify_what_they_take_out() {
+    let (mut trie, log) = watched(&[("a1", 1), ("a2", 2), ("b", 3)]);
+    trie.retain(|_, value| *value != 2);
+    assert_eq!(calls(&log), ["delete a2 2"]);
+    let moved = trie.split_off("b");
+    assert_eq!(moved.len(), 1);
+    assert_eq!(calls(&log), ["delete b 3"]);
+    trie.remove_prefix("a");
+    assert_eq!(calls(&log), ["delete a1 1", "prune a"]);
+}
+
+#[test]
+fn drain_keeps_the_observer_and_the_pool() {
+    let (mut trie, log) = watched(&[("a1", 1), ("b", 2)]);
+    trie.set_pool_capacity(0);
+    assert_eq!(trie.drain().count(), 2);
+    assert!(trie.is_empty());
+    trie.insert_lossy("c", 3);
+    trie.delete_lossy("c");
+    assert_eq!(calls(&log), ["insert c None 3", "delete c 3"]);
+    assert_eq!(trie.pool_len(), 0);
+}
+
+// An observer with interior mutability is `Send` but not `Sync`, and still goes on a trie
+// that is. Without std the hash cache keeps a trie from being `Sync` anyway.
+#[cfg(feature = "std")]
+struct Counter(Cell<u32>);
+
+#[cfg(feature = "std")]
+impl TrieObserver<u32> for Counter {
+    fn on_insert(&mut self, _: &str, _: Option<&u32>, _: &u32) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn observers_need_not_be_sync() {
+    fn is_sync<T: Sync>(_: &T) {}
+    let mut trie = Trie::new();
+    trie.set_observer(Box::new(Counter(Cell::new(0))));
+    trie.insert_lossy("a1", 1);
+    is_sync(&trie);
+}
@@ -0,0 +1,89 @@
+// The parallel walks yield exactly what the sequential ones do, however the trie is shaped.
+#![cfg(feature = "rayon")]
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+fn par_keys(trie: &Trie<u64>) -> Vec<String> {
+    let mut keys: Vec<String> = trie.par_iter().map(|(key, _)| key).collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn par_iter_yields_what_iter_yields() {
+    let mut rng = Rng::new(91);
+    for _ in 0..50 {
+        let mut trie = Trie::new();
+        for n in 0..rng.below(2_000) {
+            trie.insert_lossy(&rng.key(8), n);
+        }
+        let keys: Vec<String> = trie.keys().collect();
+        assert_eq!(par_keys(&trie), keys);
+        let sum: u64 = trie.par_iter().map(|(_, value)| value).sum();
+        assert_eq!(sum, trie.values().sum::<u64>());
+    }
+}
+
+#[test]
+fn a_skewed_trie_yields_everything() {
+    // everything under one long prefix, with values along the way and one at the root
+    let mut trie = Trie::new();
+    let prefix = "f".repeat(40);
+    trie.insert_lossy("", 0);
+    for n in 0..4_096u64 {
+        trie.insert_lossy(&format!("{}{:03x}", prefix, n), n);
+    }
+    for len in 1..prefix.len() {
+        trie.insert_lossy(&prefix[..len], len as u64);
+    }
+    let keys: Vec<String> = trie.keys().collect();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    pool.install(|| assert_eq!(par_keys(&trie), keys));
+}
+
+#[test]
+fn par_for_each_visits_every_entry_once() {
+    let mut trie = Trie::new();
+    for n in 0..10_000u64 {
+        trie.insert_lossy(&format!("{:04x}", n), n);
+    }
+    let seen = Mutex::new(BTreeSet::new());
+    let calls = AtomicUsize::new(0);
+    trie.par_for_each(|key, &value| {
+        assert_eq!(u64::from_str_radix(key, 16).unwrap(), value);
+        calls.fetch_add(1, Ordering::Relaxed);
+        seen.lock().unwrap().insert(value);
+    });
+    assert_eq!(calls.into_inner(), 10_000);
+    assert_eq!(seen.into_inner().unwrap(), (0..10_000).collect());
+}
+
+#[test]
+fn par_any_and_par_find() {
+    let mut trie = Trie::new();
+    for n in 0..10_000u64 {
+        trie.insert_lossy(&format!("{:04x}", n), n);
+    }
+    assert!(trie.par_any(|_, &value| value == 9_999));
+    assert!(!trie.par_any(|_, &value| value > 10_000));
+    assert_eq!(
+        trie.par_find(|key, _| key == "0abc"),
+        Some(("0abc".to_string(), &0xabc))
+    );
+    let (key, value) = trie.par_find(|_, &value| value % 1_000 == 7).unwrap();
+    assert_eq!(value % 1_000, 7);
+    assert_eq!(trie.get_lossy(&key), Some(value));
+    assert_eq!(trie.par_find(|_, &value| value > 10_000), None);
+    assert!(!Trie::<u64>::new().par_any(|_, _| true));
+}
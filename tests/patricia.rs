@@ -0,0 +1,208 @@
+// PatriciaTrie against a BTreeMap, and the shape of its compressed edges.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use trie::PatriciaTrie;
+
+mod common;
+use common::Rng;
+
+// How many nodes a fully compressed trie over `keys` has: the root, every key, and every
+// place two keys part ways. Forks show up as the common prefix of neighbours in key order.
+fn compressed_nodes<V>(keys: &BTreeMap<String, V>) -> usize {
+    let mut nodes: BTreeSet<&str> = keys.keys().map(String::as_str).collect();
+    let sorted: Vec<&str> = keys.keys().map(String::as_str).collect();
+    for pair in sorted.windows(2) {
+        let common = pair[0]
+            .bytes()
+            .zip(pair[1].bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if common < pair[0].len() {
+            nodes.insert(&pair[0][..common]);
+        }
+    }
+    nodes.remove("");
+    nodes.len() + 1
+}
+
+fn assert_matches(trie: &PatriciaTrie<u32>, model: &BTreeMap<String, u32>) {
+    let entries: Vec<(String, u32)> = trie.iter().map(|(k, v)| (k, *v)).collect();
+    let expected: Vec<(String, u32)> = model.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    assert_eq!(entries, expected);
+    assert_eq!(trie.len(), model.len());
+    assert_eq!(trie.node_count(), compressed_nodes(model));
+}
+
+#[test]
+fn matches_a_btreemap() {
+    let mut rng = Rng::new(44);
+    let mut trie = PatriciaTrie::new();
+    let mut model = BTreeMap::new();
+    for n in 0..4_000u32 {
+        let key = rng.key(8);
+        match rng.below(3) {
+            0 | 1 => assert_eq!(trie.insert(&key, n), model.insert(key.clone(), n)),
+            _ => assert_eq!(trie.delete(&key), model.remove(&key)),
+        }
+        assert_eq!(trie.get(&key), model.get(&key));
+        assert_eq!(trie.contains_key(&key), model.contains_key(&key));
+        if n % 100 == 0 {
+            assert_matches(&trie, &model);
+        }
+    }
+    assert_matches(&trie, &model);
+
+    for key in model.keys() {
+        assert!(trie.delete(key).is_some());
+    }
+    assert!(trie.is_empty());
+    assert_eq!(trie.node_count(), 1);
+}
+
+#[test]
+fn inserting_splits_an_edge() {
+    let mut trie = PatriciaTrie::new();
+    trie.insert("abcdef", 1);
+    assert_eq!(trie.node_count(), 2);
+    // ends part way along the edge
+    trie.insert("abc", 2);
+    assert_eq!(trie.node_count(), 3);
+    // leaves the edge part way along, so a value-less fork goes in at "ab"
+    trie.insert("abd", 3);
+    assert_eq!(trie.node_count(), 5);
+    assert_eq!(trie.get("ab"), None);
+    assert_eq!(trie.get("abcdef"), Some(&1));
+    assert_eq!(trie.get("abc"), Some(&2));
+    assert_eq!(trie.get("abd"), Some(&3));
+    assert_eq!(trie.get("abcd"), None);
+    assert_eq!(trie.get("abcdef0"), None);
+}
+
+#[test]
+fn deleting_merges_a_lone_child_back() {
+    let mut trie = PatriciaTrie::new();
+    for (key, value) in [("abcdef", 1), ("abc", 2), ("abd", 3)] {
+        trie.insert(key, value);
+    }
+    // the fork at "ab" is left with one child and takes it over
+    assert_eq!(trie.delete("abd"), Some(3));
+    assert_eq!(trie.node_count(), 3);
+    // "abc" is left with no value and one child, and takes it over
+    assert_eq!(trie.delete("abc"), Some(2));
+    assert_eq!(trie.node_count(), 2);
+    assert_eq!(trie.to_string(), "(root)\n└── abcdef = 1\n");
+
+    // misses leave the trie alone
+    assert_eq!(trie.delete("abc"), None);
+    assert_eq!(trie.delete("abcdef0"), None);
+    assert_eq!(trie.delete("b"), None);
+    assert_eq!(trie.node_count(), 2);
+
+    assert_eq!(trie.delete("abcdef"), Some(1));
+    assert_eq!(trie.node_count(), 1);
+}
+
+#[test]
+fn the_root_keeps_an_empty_edge() {
+    let mut trie = PatriciaTrie::new();
+    trie.insert("", 0);
+    trie.insert("abc", 1);
+    assert_eq!(trie.delete(""), Some(0));
+    assert_eq!(trie.node_count(), 2);
+    assert_eq!(trie.get("abc"), Some(&1));
+    assert_eq!(trie.to_string(), "(root)\n└── abc = 1\n");
+}
+
+#[test]
+fn iterates_in_key_order_across_edges() {
+    let mut trie = PatriciaTrie::new();
+    let keys = ["ff", "a1", "0fff", "a", "a0ff", "0ffe", ""];
+    for (i, key) in keys.iter().enumerate() {
+        trie.insert(key, i);
+    }
+    let mut sorted = keys.to_vec();
+    sorted.sort();
+    let seen: Vec<String> = trie.iter().map(|(k, _)| k).collect();
+    assert_eq!(seen, sorted);
+    let via_into_iter: Vec<String> = (&trie).into_iter().map(|(k, _)| k).collect();
+    assert_eq!(via_into_iter, sorted);
+}
+
+#[test]
+fn display_draws_whole_edges() {
+    let mut trie = PatriciaTrie::new();
+    for (key, value) in [("abc", 1), ("abcdef", 2), ("abd", 3), ("f", 4)] {
+        trie.insert(key, value);
+    }
+    let expected = "\
+(root)
+├── ab
+│   ├── c = 1
+│   │   └── def = 2
+│   └── d = 3
+└── f = 4
+";
+    assert_eq!(trie.to_string(), expected);
+    trie.insert("", 0);
+    assert!(trie.to_string().starts_with("(root) = 0\n├── ab\n"));
+}
+
+// Deep enough that a walk recursing once per node would overflow this stack.
+const DEPTH: usize = 2_000;
+const SMALL_STACK: usize = 64 * 1024;
+
+fn on_a_small_stack(f: impl FnOnce() + Send + 'static) {
+    std::thread::Builder::new()
+        .stack_size(SMALL_STACK)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+// Every key is a prefix of the next, so each one is a node of its own.
+fn chain() -> PatriciaTrie<usize> {
+    let mut trie = PatriciaTrie::new();
+    for len in 1..=DEPTH {
+        trie.insert_nibbles(std::iter::repeat_n(0, len), len);
+    }
+    trie
+}
+
+#[test]
+fn display_of_a_deep_chain() {
+    on_a_small_stack(|| {
+        let text = chain().to_string();
+        assert_eq!(text.lines().count(), DEPTH + 1);
+        let last = text.lines().last().unwrap();
+        assert_eq!(
+            last,
+            format!("{}└── 0 = {}", "    ".repeat(DEPTH - 1), DEPTH)
+        );
+    });
+}
+
+#[test]
+fn deep_chains_delete_and_drop() {
+    on_a_small_stack(|| {
+        let mut trie = chain();
+        assert_eq!(trie.node_count(), DEPTH + 1);
+        let deepest = "0".repeat(DEPTH);
+        assert_eq!(trie.delete(&deepest), Some(DEPTH));
+        assert_eq!(trie.delete(&deepest[..1]), Some(1));
+        assert_eq!(trie.node_count(), DEPTH - 1);
+        assert_eq!(trie.get(&deepest[..2]), Some(&2));
+        assert_eq!(trie.get(&deepest[..DEPTH - 1]), Some(&(DEPTH - 1)));
+        drop(trie);
+
+        // and one long edge with nodes hanging off it all the way down
+        let mut trie = PatriciaTrie::new();
+        trie.insert(&deepest, 0);
+        for len in (1..DEPTH).step_by(2) {
+            trie.insert_nibbles(std::iter::repeat_n(0, len).chain([1]), len);
+        }
+        assert_eq!(trie.delete(&deepest), Some(0));
+        assert_eq!(trie.len(), DEPTH / 2);
+    });
+}
@@ -0,0 +1,112 @@
+// PersistentTrie against a BTreeMap, and what its clones keep sharing.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use trie::PersistentTrie;
+
+mod common;
+use common::Rng;
+
+fn entries<V: Clone>(trie: &PersistentTrie<V>) -> Vec<(String, V)> {
+    trie.iter()
+        .map(|(key, value)| (key, value.clone()))
+        .collect()
+}
+
+#[test]
+fn agrees_with_a_btreemap() {
+    let mut rng = Rng::new(93);
+    for _ in 0..50 {
+        let mut trie = PersistentTrie::new();
+        let mut map = BTreeMap::new();
+        for n in 0..rng.below(300) {
+            let key = rng.key(5);
+            if rng.below(3) == 0 {
+                assert_eq!(trie.delete(&key), map.remove(&key));
+            } else {
+                assert_eq!(trie.insert(&key, n), map.insert(key, n));
+            }
+        }
+        assert_eq!(trie.len(), map.len());
+        assert_eq!(entries(&trie), map.into_iter().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn snapshots_stay_as_they_were() {
+    let mut rng = Rng::new(930);
+    let mut trie = PersistentTrie::new();
+    let mut snapshots = Vec::new();
+    for n in 0..500 {
+        let key = rng.key(5);
+        if rng.below(3) == 0 {
+            trie.delete(&key);
+        } else {
+            trie.insert(&key, n);
+        }
+        if n % 50 == 0 {
+            snapshots.push((trie.clone(), entries(&trie)));
+        }
+    }
+    for (snapshot, expected) in &snapshots {
+        assert_eq!(&entries(snapshot), expected);
+        assert_eq!(snapshot.len(), expected.len());
+    }
+}
+
+#[test]
+fn writes_leave_untouched_subtrees_shared() {
+    // the values count their owners, so a node copied by a write shows as one more count
+    let mut trie = PersistentTrie::new();
+    let a = Arc::new("a");
+    let b = Arc::new("b");
+    trie.insert("a1f", Arc::clone(&a));
+    trie.insert("b0", Arc::clone(&b));
+    let snapshot = trie.clone();
+    trie.insert("b1", Arc::new("b1"));
+    trie.insert("b0", Arc::new("new"));
+    assert!(trie.shares_subtree(&snapshot, "a"));
+    assert!(!trie.shares_subtree(&snapshot, "b"));
+    // the "a" subtree was never copied, and "b0" was replaced rather than cloned
+    assert_eq!(Arc::strong_count(&a), 2);
+    assert_eq!(Arc::strong_count(&b), 2);
+    assert_eq!(**snapshot.get("b0").unwrap(), "b");
+    assert_eq!(snapshot.get("b1"), None);
+
+    // a write through a node that's no longer shared copies nothing more
+    trie.insert("b2", Arc::new("b2"));
+    assert_eq!(Arc::strong_count(&b), 2);
+    drop(snapshot);
+    assert_eq!(Arc::strong_count(&a), 2);
+    assert_eq!(Arc::strong_count(&b), 1);
+}
+
+#[test]
+fn delete_prunes_without_touching_the_snapshot() {
+    let mut trie = PersistentTrie::new();
+    trie.insert("a1", 1);
+    trie.insert("a1f00", 2);
+    let snapshot = trie.clone();
+    assert_eq!(trie.delete("a1f00"), Some(2));
+    assert_eq!(trie.delete("a1f00"), None);
+    assert_eq!(snapshot.get("a1f00"), Some(&2));
+
+    let mut fresh = PersistentTrie::new();
+    fresh.insert("a1", 1);
+    // neither has any node left under "a1f"
+    assert!(trie.shares_subtree(&fresh, "a1f"));
+    assert!(!trie.shares_subtree(&snapshot, "a1f"));
+    assert_eq!(entries(&trie), entries(&fresh));
+}
+
+#[test]
+fn a_deep_chain_drops_without_recursing() {
+    let mut trie = PersistentTrie::new();
+    trie.insert(&"7".repeat(100_000), ());
+    let snapshot = trie.clone();
+    trie.insert(&"7".repeat(99_999), ());
+    drop(trie);
+    assert_eq!(snapshot.len(), 1);
+    drop(snapshot);
+}
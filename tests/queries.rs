@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+fn trie_of(keys: &[&str]) -> Trie<String> {
+    let mut trie = Trie::new();
+    for key in keys {
+        trie.insert_lossy(key, key.to_string());
+    }
+    trie
+}
+
+fn found(entry: Option<(String, &String)>) -> Option<String> {
+    entry.map(|(key, value)| {
+        assert_eq!(&key, value);
+        key
+    })
+}
+
+#[test]
+fn longest_prefix_picks_the_most_specific() {
+    let trie = trie_of(&["a", "a1", "a1f0", "a1f0e"]);
+    assert_eq!(found(trie.longest_prefix("a1f3")), Some("a1".into()));
+    assert_eq!(found(trie.longest_prefix("a1f0")), Some("a1f0".into()));
+    assert_eq!(found(trie.longest_prefix("a1f0e9")), Some("a1f0e".into()));
+    assert_eq!(found(trie.longest_prefix("a2")), Some("a".into()));
+    assert_eq!(found(trie.longest_prefix("b")), None);
+}
+
+#[test]
+fn longest_prefix_of_a_key_shorter_than_all_stored() {
+    let trie = trie_of(&["a1f", "a1e"]);
+    assert_eq!(found(trie.longest_prefix("a1")), None);
+    assert_eq!(found(trie.longest_prefix("")), None);
+}
+
+#[test]
+fn a_root_value_is_the_default_route() {
+    let trie = trie_of(&["", "a1"]);
+    assert_eq!(found(trie.longest_prefix("b")), Some("".into()));
+    assert_eq!(found(trie.longest_prefix("a1f")), Some("a1".into()));
+}
+
+fn bound(rng: &mut Rng, key: &str) -> Bound<String> {
+    match rng.below(3) {
+        0 => Bound::Included(key.to_string()),
+        1 => Bound::Excluded(key.to_string()),
+        _ => Bound::Unbounded,
+    }
+}
+
+#[test]
+fn range_matches_btreemap_range() {
+    let mut rng = Rng::new(16);
+    for _ in 0..300 {
+        let mut trie = Trie::new();
+        let mut map = BTreeMap::new();
+        for _ in 0..rng.below(40) {
+            let key = rng.key(4);
+            trie.insert_lossy(&key, ());
+            map.insert(key, ());
+        }
+        let (mut low, mut high) = (rng.key(4), rng.key(4));
+        if low > high {
+            std::mem::swap(&mut low, &mut high);
+        }
+        let (start, end) = (bound(&mut rng, &low), bound(&mut rng, &high));
+        // BTreeMap::range panics on an empty range with both ends excluded
+        if low == high && matches!((&start, &end), (Bound::Excluded(_), Bound::Excluded(_))) {
+            continue;
+        }
+        let range = (
+            start.as_ref().map(String::as_str),
+            end.as_ref().map(String::as_str),
+        );
+        let keys: Vec<String> = trie.range(range).map(|(key, _)| key).collect();
+        let expected: Vec<String> = map
+            .range::<str, _>(range)
+            .map(|(key, _)| key.clone())
+            .collect();
+        assert_eq!(keys, expected, "{:?}", range);
+    }
+}
+
+#[test]
+fn range_sorts_a_prefix_before_its_extensions() {
+    let trie = trie_of(&["a0", "a1", "a1f", "a2"]);
+    let keys: Vec<String> = trie.range("a1".."a2").map(|(key, _)| key).collect();
+    assert_eq!(keys, ["a1", "a1f"]);
+    let keys: Vec<String> = trie.range("a10"..="a1f").map(|(key, _)| key).collect();
+    assert_eq!(keys, ["a1f"]);
+    let keys: Vec<String> = trie.range(.."a1").map(|(key, _)| key).collect();
+    assert_eq!(keys, ["a0"]);
+}
+
+#[test]
+fn first_and_last_with_prefix_keys() {
+    let mut trie = trie_of(&["a", "a1", "aff"]);
+    assert_eq!(found(trie.first_key_value()), Some("a".into()));
+    assert_eq!(found(trie.last_key_value()), Some("aff".into()));
+
+    assert_eq!(trie.pop_first(), Some(("a".into(), "a".into())));
+    assert_eq!(trie.pop_last(), Some(("aff".into(), "aff".into())));
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["a1"]);
+    // popping "aff" pruned the "f" branch
+    assert_eq!(trie.node_count(), 3);
+    assert_eq!(trie.pop_last(), Some(("a1".into(), "a1".into())));
+    assert_eq!(trie.pop_first(), None);
+    assert_eq!(trie.node_count(), 1);
+}
+
+#[test]
+fn popping_matches_a_btreemap() {
+    let mut rng = Rng::new(17);
+    for _ in 0..100 {
+        let mut trie = Trie::new();
+        let mut map = BTreeMap::new();
+        for _ in 0..rng.below(30) {
+            let key = rng.key(4);
+            trie.insert_lossy(&key, ());
+            map.insert(key, ());
+        }
+        while !map.is_empty() {
+            if rng.below(2) == 0 {
+                assert_eq!(trie.pop_first(), map.pop_first());
+            } else {
+                assert_eq!(trie.pop_last(), map.pop_last());
+            }
+            assert!(trie.validate().is_ok());
+        }
+        assert!(trie.is_empty());
+    }
+}
+
+#[test]
+fn subtrie_lookups_are_relative_to_the_prefix() {
+    let trie = trie_of(&["a1", "a1f", "a1f0", "a2", "b"]);
+    let view = trie.subtrie("a1").unwrap();
+    assert_eq!(view.prefix(), "a1");
+    for key in ["", "f", "f0", "0", "ff"] {
+        assert_eq!(
+            view.get(key),
+            trie.get_lossy(&format!("a1{}", key)),
+            "{:?}",
+            key
+        );
+        assert_eq!(
+            view.contains_key(key),
+            trie.contains_key(&format!("a1{}", key))
+        );
+    }
+    let keys: Vec<String> = view.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["", "f", "f0"]);
+    assert_eq!(view.len(), 3);
+    assert_eq!(view.len(), trie.count_prefix("a1"));
+}
+
+#[test]
+fn subtrie_of_nothing_is_the_whole_trie() {
+    let trie = trie_of(&["a1", "b"]);
+    let view = trie.subtrie("").unwrap();
+    assert_eq!(view.len(), trie.len());
+    assert_eq!(
+        view.iter().collect::<Vec<_>>(),
+        trie.iter().collect::<Vec<_>>()
+    );
+    assert!(trie.subtrie("c").is_none());
+}
+
+#[test]
+fn subtrie_displays_only_its_part() {
+    let trie = trie_of(&["a1", "a1f", "b"]);
+    let drawing = trie.subtrie("a1").unwrap().to_string();
+    assert_eq!(drawing, "(root) = a1\n└── f = a1f\n");
+}
+
+#[test]
+fn count_prefix_over_a_dense_trie() {
+    // every key of one to four digits
+    let mut trie = Trie::new();
+    for len in 1..=4 {
+        for n in 0..16usize.pow(len) {
+            trie.insert_lossy(&format!("{:01$x}", n, len as usize), ());
+        }
+    }
+    let total = trie.len();
+    for digit in "0123456789abcdef".chars() {
+        assert_eq!(trie.count_prefix(&digit.to_string()), total / 16);
+    }
+    assert_eq!(trie.count_prefix(""), total);
+    assert_eq!(trie.count_prefix("a1"), 1 + 16 + 256);
+    assert_eq!(trie.count_prefix("a1f0"), 1);
+    assert_eq!(trie.count_prefix("a1f00"), 0);
+}
+
+#[test]
+fn count_prefix_counts_the_prefix_itself() {
+    let trie = trie_of(&["a1", "a1f", "b"]);
+    assert_eq!(trie.count_prefix("a1"), 2);
+    assert_eq!(trie.count_prefix("a1f"), 1);
+    assert_eq!(trie.count_prefix("a"), 2);
+    assert_eq!(trie.count_prefix("c"), 0);
+}
+
+#[test]
+fn get_key_value_returns_the_canonical_key() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", 1);
+    trie.insert_lossy("", 0);
+    assert_eq!(trie.get_key_value("A1F"), Some(("a1f".to_string(), &1)));
+    assert_eq!(trie.get_key_value("a1F"), Some(("a1f".to_string(), &1)));
+    assert_eq!(trie.get_key_value("A1-f"), Some(("a1f".to_string(), &1)));
+    assert_eq!(trie.get_key_value(""), Some((String::new(), &0)));
+    assert_eq!(trie.get_key_value("a1"), None);
+    assert_eq!(trie.get_key_value("A1F0"), None);
+    // `longest_prefix` reports its match the same way
+    assert_eq!(trie.longest_prefix("A1FE"), Some(("a1f".to_string(), &1)));
+}
+
+#[test]
+fn next_and_prev_key_match_btreemap_range() {
+    let mut rng = Rng::new(86);
+    for _ in 0..50 {
+        let mut trie = Trie::new();
+        let mut map = BTreeMap::new();
+        for _ in 0..rng.below(100) {
+            let key = rng.key(6);
+            trie.insert_lossy(&key, key.clone());
+            map.insert(key.clone(), key);
+        }
+        for _ in 0..50 {
+            let query = rng.key(7);
+            let above = map
+                .range::<str, _>((Bound::Excluded(query.as_str()), Bound::Unbounded))
+                .next()
+                .map(|(key, _)| key.clone());
+            let below = map
+                .range::<str, _>((Bound::Unbounded, Bound::Excluded(query.as_str())))
+                .next_back()
+                .map(|(key, _)| key.clone());
+            assert_eq!(found(trie.next_key(&query)), above, "next of {:?}", query);
+            assert_eq!(found(trie.prev_key(&query)), below, "prev of {:?}", query);
+        }
+    }
+}
+
+#[test]
+fn next_and_prev_key_edge_cases() {
+    let trie = trie_of(&["10", "a1", "a1f", "a1f00", "e"]);
+    // smaller than every key, and larger
+    assert_eq!(found(trie.next_key("0")), Some("10".into()));
+    assert_eq!(found(trie.prev_key("0")), None);
+    assert_eq!(found(trie.next_key("f")), None);
+    assert_eq!(found(trie.prev_key("f")), Some("e".into()));
+    // a stored key is skipped over, never returned for itself
+    assert_eq!(found(trie.next_key("a1f")), Some("a1f00".into()));
+    assert_eq!(found(trie.prev_key("a1f")), Some("a1".into()));
+    // a prefix of stored keys comes before all of them
+    assert_eq!(found(trie.next_key("a")), Some("a1".into()));
+    assert_eq!(found(trie.prev_key("a")), Some("10".into()));
+    assert_eq!(found(trie.next_key("a1f0")), Some("a1f00".into()));
+    assert_eq!(found(trie.prev_key("a1f0")), Some("a1f".into()));
+    // the empty key is below everything
+    assert_eq!(found(trie.next_key("")), Some("10".into()));
+    assert_eq!(found(trie.prev_key("")), None);
+    assert_eq!(found(trie_of(&[]).next_key("a")), None);
+}
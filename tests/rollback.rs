@@ -0,0 +1,169 @@
+use std::rc::Rc;
+
+use trie::{MergePolicy, Trie, WalkControl};
+
+mod common;
+use common::Rng;
+
+fn entries(trie: &Trie<u64>) -> Vec<(String, u64)> {
+    trie.iter().map(|(key, value)| (key, *value)).collect()
+}
+
+// Changes `trie` through one of the methods that write to it, picked by `rng`.
+fn mutate(trie: &mut Trie<u64>, rng: &mut Rng) {
+    let key = rng.key(4);
+    let value = rng.below(100);
+    match rng.below(20) {
+        0..=3 => {
+            trie.insert_lossy(&key, value);
+        }
+        4..=5 => {
+            trie.delete_lossy(&key);
+        }
+        6 => {
+            trie.update(&key, |old| old.map(|old| old + 1));
+        }
+        7 => {
+            let current = trie.get_lossy(&key).copied();
+            let _ = trie.compare_and_swap(&key, current.as_ref(), Some(value));
+        }
+        8 => *trie.get_or_insert_with(&key, || value) += 1,
+        9 => {
+            if let Some(old) = trie.get_mut(&key) {
+                *old = value;
+            }
+        }
+        10 => {
+            trie.entry(&key).or_insert(value);
+        }
+        11 => {
+            let mut cursor = trie.cursor_mut();
+            cursor.insert(&key, value);
+            cursor.delete(&rng.key(4));
+        }
+        12 => {
+            trie.remove_prefix(&rng.key(2));
+        }
+        13 => trie.retain(|_, value| *value % 3 != 0),
+        14 => {
+            trie.split_off(&key);
+        }
+        15 => {
+            let mut other = Trie::new();
+            for _ in 0..rng.below(8) {
+                other.insert_lossy(&rng.key(4), rng.below(100));
+            }
+            trie.merge(other, MergePolicy::KeepOther);
+        }
+        16 => trie.values_mut().for_each(|value| *value += 1),
+        17 => trie.walk_mut(|_, value| {
+            if let Some(value) = value {
+                *value *= 2;
+            }
+            WalkControl::Continue
+        }),
+        18 => {
+            trie.pop_first();
+        }
+        _ => {
+            if rng.below(4) == 0 {
+                trie.clear();
+            } else {
+                trie.drain().take(2).for_each(drop);
+            }
+        }
+    }
+}
+
+#[test]
+fn rollback_restores_every_entry_and_node() {
+    let mut rng = Rng::new(94);
+    for _ in 0..300 {
+        let mut trie = Trie::new();
+        for _ in 0..rng.below(30) {
+            trie.insert_lossy(&rng.key(4), rng.below(100));
+        }
+        let before = entries(&trie);
+        let nodes = trie.node_count();
+        let id = trie.snapshot();
+        for _ in 0..rng.below(20) {
+            mutate(&mut trie, &mut rng);
+        }
+        trie.rollback(id);
+        assert_eq!(entries(&trie), before);
+        assert_eq!(trie.len(), before.len());
+        assert_eq!(trie.node_count(), nodes);
+        assert!(trie.validate().is_ok());
+    }
+}
+
+#[test]
+fn nested_snapshots_roll_back_last_in_first_out() {
+    let mut rng = Rng::new(95);
+    for _ in 0..100 {
+        let mut trie = Trie::new();
+        let mut saved = Vec::new();
+        for _ in 0..5 {
+            saved.push((entries(&trie), trie.snapshot()));
+            for _ in 0..rng.below(10) {
+                mutate(&mut trie, &mut rng);
+            }
+        }
+        while let Some((before, id)) = saved.pop() {
+            trie.rollback(id);
+            assert_eq!(entries(&trie), before);
+            assert!(trie.validate().is_ok());
+        }
+    }
+}
+
+#[test]
+fn rolling_back_past_a_committed_snapshot_undoes_it_too() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", 1);
+    let outer = trie.snapshot();
+    trie.insert_lossy("a1f", 2);
+    let inner = trie.snapshot();
+    trie.delete_lossy("a1");
+    trie.commit(inner);
+    assert_eq!(entries(&trie), [("a1f".to_string(), 2)]);
+    trie.rollback(outer);
+    assert_eq!(entries(&trie), [("a1".to_string(), 1)]);
+}
+
+#[test]
+#[should_panic(expected = "already rolled back or committed")]
+fn rolling_back_to_a_dropped_snapshot_panics() {
+    let mut trie = Trie::<u64>::new();
+    let outer = trie.snapshot();
+    let inner = trie.snapshot();
+    trie.rollback(outer);
+    trie.rollback(inner);
+}
+
+#[test]
+fn commit_releases_the_saved_values() {
+    let value = Rc::new(1);
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", value.clone());
+    let outer = trie.snapshot();
+    let inner = trie.snapshot();
+    trie.insert_lossy("a1", Rc::new(2));
+    trie.retain(|_, _| true);
+    assert!(Rc::strong_count(&value) > 1);
+    trie.commit(inner);
+    // the outer snapshot can still go back to `value`
+    assert!(Rc::strong_count(&value) > 1);
+    trie.commit(outer);
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn no_snapshot_keeps_nothing() {
+    let value = Rc::new(1);
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", value.clone());
+    trie.values_mut().for_each(drop);
+    trie.delete_lossy("a1");
+    assert_eq!(Rc::strong_count(&value), 1);
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "sha2")]
+
+use std::collections::HashMap;
+
+use sha2::{Digest, Sha256};
+use trie::{SecureTrie, Sha256Hasher};
+
+mod common;
+use common::Rng;
+
+type Secure<V> = SecureTrie<V, Sha256Hasher>;
+
+#[test]
+fn keys_are_stored_under_their_hash() {
+    let hash: String = Sha256::digest(b"a1f")
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    assert_eq!(Secure::<()>::hashed_key("a1f"), hash);
+    // the key is read the way `Trie` reads it before hashing
+    assert_eq!(Secure::<()>::hashed_key("A1-F"), hash);
+}
+
+#[test]
+fn original_keys_keep_working() {
+    let mut rng = Rng::new(43);
+    let mut trie = Secure::new();
+    let mut map = HashMap::new();
+    for _ in 0..2_000 {
+        let (key, value) = (rng.key(6), rng.below(100));
+        match rng.below(4) {
+            0 => assert_eq!(trie.delete(&key), map.remove(&key)),
+            1 => assert_eq!(trie.get(&key), map.get(&key)),
+            _ => assert_eq!(trie.insert(&key, value), map.insert(key, value)),
+        }
+        assert_eq!(trie.len(), map.len());
+    }
+    for (key, value) in &map {
+        assert!(trie.contains_key(key));
+        assert_eq!(trie.get(&key.to_uppercase()), Some(value));
+    }
+}
+
+#[test]
+fn iteration_follows_hashed_order() {
+    let keys = ["00", "01", "a1f", "ff", "ffff", ""];
+    let mut trie = Secure::with_preimages();
+    for key in keys {
+        trie.insert(key, key.len());
+    }
+    let mut by_hash = keys.to_vec();
+    by_hash.sort_by_key(|key| Secure::<usize>::hashed_key(key));
+    let seen: Vec<&str> = trie.iter().map(|(key, _)| key.unwrap()).collect();
+    assert_eq!(seen, by_hash);
+    assert!(trie.iter().all(|(key, &len)| key.unwrap().len() == len));
+}
+
+#[test]
+fn preimages_are_only_kept_when_asked_for() {
+    let mut trie = Secure::new();
+    trie.insert("a1", 1);
+    assert_eq!(trie.iter().collect::<Vec<_>>(), [(None, &1)]);
+    let mut trie = Secure::with_preimages();
+    trie.insert("A1", 1);
+    assert_eq!(trie.iter().collect::<Vec<_>>(), [(Some("a1"), &1)]);
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "serde")]
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+fn sample() -> Trie<String> {
+    let mut trie = Trie::new();
+    for key in ["", "a1", "a1f", "a1f0", "ff"] {
+        trie.insert_lossy(key, format!("v{}", key));
+    }
+    trie
+}
+
+#[test]
+fn round_trips_through_json() {
+    let trie = sample();
+    let json = serde_json::to_string(&trie).unwrap();
+    assert_eq!(
+        json,
+        r#"{"":"v","a1":"va1","a1f":"va1f","a1f0":"va1f0","ff":"vff"}"#
+    );
+    let back: Trie<String> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, trie);
+    assert_eq!(back.root_value(), Some(&"v".to_string()));
+}
+
+#[test]
+fn round_trips_through_bincode() {
+    let mut rng = Rng::new(30);
+    for _ in 0..100 {
+        let mut trie = Trie::new();
+        for _ in 0..rng.below(40) {
+            trie.insert_lossy(&rng.key(5), rng.below(1000));
+        }
+        let bytes = bincode::serialize(&trie).unwrap();
+        let back: Trie<u64> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back, trie);
+        assert!(back.validate().is_ok());
+    }
+}
+
+#[test]
+fn rejects_bad_keys_and_duplicates() {
+    assert!(serde_json::from_str::<Trie<u32>>(r#"{"a1":1,"xy":2}"#).is_err());
+    assert!(serde_json::from_str::<Trie<u32>>(r#"{"a1":1,"A1":2}"#).is_err());
+    assert!(serde_json::from_str::<Trie<u32>>(r#"[1, 2]"#).is_err());
+}
+
+#[test]
+fn a_deep_key_deserializes_without_recursing() {
+    let key = "a".repeat(50_000);
+    let json = format!(r#"{{"{}":1}}"#, key);
+    let trie: Trie<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(trie.get_lossy(&key), Some(&1));
+    assert_eq!(serde_json::to_string(&trie).unwrap(), json);
+}
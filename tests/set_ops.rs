@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+fn random_trie(rng: &mut Rng, side: u64) -> Trie<u64> {
+    let mut trie = Trie::new();
+    for _ in 0..rng.below(40) {
+        trie.insert_lossy(&rng.key(4), side);
+    }
+    trie
+}
+
+fn key_set<V>(trie: &Trie<V>) -> HashSet<String> {
+    trie.keys().collect()
+}
+
+#[test]
+fn set_operations_match_hashset() {
+    let mut rng = Rng::new(25);
+    for _ in 0..300 {
+        let (a, b) = (random_trie(&mut rng, 1), random_trie(&mut rng, 2));
+        let (ka, kb) = (key_set(&a), key_set(&b));
+
+        let union = a.union(&b);
+        assert_eq!(key_set(&union), &ka | &kb);
+        let intersection = a.intersection(&b);
+        assert_eq!(key_set(&intersection), &ka & &kb);
+        let difference = a.difference(&b);
+        assert_eq!(key_set(&difference), &ka - &kb);
+
+        for result in [&union, &intersection, &difference] {
+            assert!(result.validate().is_ok());
+        }
+        // values come from `self` wherever it has the key
+        for (key, value) in union.iter() {
+            assert_eq!(*value, if ka.contains(&key) { 1 } else { 2 });
+        }
+        assert!(
+            intersection
+                .values()
+                .chain(difference.values())
+                .all(|&v| v == 1)
+        );
+    }
+}
+
+#[test]
+fn set_operations_keep_interior_values() {
+    let mut a = Trie::new();
+    let mut b = Trie::new();
+    for key in ["a1", "a1f", ""] {
+        a.insert_lossy(key, "a");
+    }
+    for key in ["a1", "a1e"] {
+        b.insert_lossy(key, "b");
+    }
+    assert_eq!(
+        a.intersection(&b).iter().collect::<Vec<_>>(),
+        [("a1".to_string(), &"a")]
+    );
+    assert_eq!(a.difference(&b).keys().collect::<Vec<_>>(), ["", "a1f"]);
+    assert_eq!(
+        a.union(&b).keys().collect::<Vec<_>>(),
+        ["", "a1", "a1e", "a1f"]
+    );
+}
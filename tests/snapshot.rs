@@ -0,0 +1,89 @@
+#![cfg(feature = "std")]
+
+use trie::{SnapshotError, Trie};
+
+mod common;
+use common::Rng;
+
+fn save(trie: &Trie<String>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    trie.save_to(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn a_dense_trie_round_trips() {
+    // every key of one to four digits, with a root value on top
+    let mut trie = Trie::new();
+    trie.insert_lossy("", "root".to_string());
+    for len in 1..=4 {
+        for n in 0..16usize.pow(len) {
+            let key = format!("{:01$x}", n, len as usize);
+            trie.insert_lossy(&key, key.repeat(2));
+        }
+    }
+    let bytes = save(&trie);
+    assert!(bytes.starts_with(b"RTRI"));
+    let back = Trie::<String>::load_from(bytes.as_slice()).unwrap();
+    assert_eq!(back, trie);
+    assert_eq!(back.len(), trie.len());
+    assert_eq!(back.node_count(), trie.node_count());
+}
+
+#[test]
+fn every_truncation_is_an_error() {
+    let mut trie = Trie::new();
+    for key in ["", "a1", "a1f", "ff0"] {
+        trie.insert_lossy(key, format!("value of {}", key));
+    }
+    let bytes = save(&trie);
+    for len in 0..bytes.len() {
+        let result = Trie::<String>::load_from(&bytes[..len]);
+        assert!(
+            matches!(
+                result,
+                Err(SnapshotError::Truncated | SnapshotError::BadMagic)
+            ),
+            "cut at {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn corrupt_bytes_never_panic() {
+    let mut rng = Rng::new(31);
+    let mut trie = Trie::new();
+    for _ in 0..50 {
+        trie.insert_lossy(&rng.key(5), rng.key(3));
+    }
+    let bytes = save(&trie);
+    for _ in 0..2000 {
+        let mut corrupt = bytes.clone();
+        for _ in 0..=rng.below(3) {
+            let at = rng.below(corrupt.len() as u64) as usize;
+            corrupt[at] ^= 1 << rng.below(8);
+        }
+        // anything that still loads has to be a well-formed trie
+        if let Ok(loaded) = Trie::<String>::load_from(corrupt.as_slice()) {
+            assert!(loaded.validate().is_ok());
+        }
+    }
+}
+
+#[test]
+fn the_header_is_checked() {
+    let bytes = save(&Trie::new());
+    let mut wrong_magic = bytes.clone();
+    wrong_magic[0] = b'X';
+    assert!(matches!(
+        Trie::<String>::load_from(wrong_magic.as_slice()),
+        Err(SnapshotError::BadMagic)
+    ));
+    let mut wrong_version = bytes;
+    wrong_version[4] = 99;
+    assert!(matches!(
+        Trie::<String>::load_from(wrong_version.as_slice()),
+        Err(SnapshotError::UnsupportedVersion(99))
+    ));
+}
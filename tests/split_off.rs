@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+
+use trie::Trie;
+
+mod common;
+use common::Rng;
+
+#[test]
+fn split_at_a_stored_key_moves_it() {
+    let mut trie = Trie::new();
+    for key in ["", "a", "a1", "a1f", "a2", "b"] {
+        trie.insert_lossy(key, ());
+    }
+    let moved = trie.split_off("a1");
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["", "a"]);
+    assert_eq!(moved.keys().collect::<Vec<_>>(), ["a1", "a1f", "a2", "b"]);
+    assert_eq!(trie.node_count(), 2);
+}
+
+#[test]
+fn split_matches_btreemap_split_off() {
+    let mut rng = Rng::new(26);
+    for _ in 0..500 {
+        let mut trie = Trie::new();
+        let mut map = BTreeMap::new();
+        for _ in 0..rng.below(30) {
+            let key = rng.key(4);
+            let value = rng.below(100);
+            trie.insert_lossy(&key, value);
+            map.insert(key, value);
+        }
+        let at = rng.key(4);
+        let moved = trie.split_off(&at);
+        let moved_map = map.split_off(&at);
+        for (trie, map) in [(&trie, &map), (&moved, &moved_map)] {
+            let entries: BTreeMap<_, _> = trie.iter().map(|(k, v)| (k, *v)).collect();
+            assert_eq!(&entries, map, "split at {:?}", at);
+            assert_eq!(trie.len(), map.len());
+            assert!(trie.validate().is_ok(), "split at {:?}", at);
+        }
+    }
+}
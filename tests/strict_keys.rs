@@ -0,0 +1,153 @@
+use trie::{KeyError, Trie};
+
+fn invalid(index: usize, found: char) -> KeyError {
+    KeyError::InvalidChar { index, found }
+}
+
+#[test]
+fn rejects_whitespace_prefixes_and_unicode() {
+    let mut trie = Trie::new();
+    for (key, err) in [
+        ("a1 f", invalid(2, ' ')),
+        (" a1", invalid(0, ' ')),
+        ("a1\n", invalid(2, '\n')),
+        ("0xa1", invalid(1, 'x')),
+        ("0Xa1", invalid(1, 'X')),
+        ("a1é", invalid(2, 'é')),
+        ("日本", invalid(0, '日')),
+        ("١٢", invalid(0, '١')),
+        ("xyz", invalid(0, 'x')),
+        ("-", invalid(0, '-')),
+        ("ab-", invalid(2, '-')),
+        ("ab\u{0}", invalid(2, '\u{0}')),
+    ] {
+        assert_eq!(trie.try_insert(key, 1), Err(err), "{:?}", key);
+        assert_eq!(trie.try_get(key), Err(err), "{:?}", key);
+        assert_eq!(trie.try_delete(key), Err(err), "{:?}", key);
+    }
+    assert!(trie.is_empty());
+    assert_eq!(trie.node_count(), 1);
+}
+
+#[test]
+fn fully_invalid_keys_never_reach_the_root() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("", "root");
+    assert!(trie.try_insert("xyz", "oops").is_err());
+    assert!(trie.try_delete("zz").is_err());
+    assert_eq!(trie.root_value(), Some(&"root"));
+    assert_eq!(trie.try_get("?"), Err(invalid(0, '?')));
+    assert_eq!(trie.try_get(""), Ok(Some(&"root")));
+}
+
+#[test]
+fn accepts_either_case() {
+    let mut trie = Trie::new();
+    assert_eq!(trie.try_insert("A1f", 1), Ok(None));
+    assert_eq!(trie.try_get("a1F"), Ok(Some(&1)));
+    assert_eq!(trie.try_insert("a1f", 2), Ok(Some(1)));
+    assert_eq!(trie.try_delete("A1F"), Ok(Some(2)));
+    assert!(trie.is_empty());
+}
+
+#[test]
+fn multibyte_characters_are_reported_whole() {
+    let trie = Trie::<()>::new();
+    assert_eq!(trie.try_get("éa1x"), Err(invalid(0, 'é')));
+    assert_eq!(trie.try_get("a1éx"), Err(invalid(2, 'é')));
+    assert_eq!(trie.try_get("é✓"), Err(invalid(0, 'é')));
+    assert_eq!(trie.try_get("ab✓"), Err(invalid(2, '✓')));
+}
+
+#[test]
+fn lossy_methods_skip_what_the_strict_ones_reject() {
+    let mut trie = Trie::new();
+    assert_eq!(trie.insert_lossy("0x a1-f", 1), None);
+    assert_eq!(trie.get_lossy("0 A1 F"), Some(&1));
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["0a1f"]);
+    assert_eq!(trie.insert_lossy("xyz", 2), None);
+    assert_eq!(trie.root_value(), Some(&2));
+    assert_eq!(trie.delete_lossy("0-A-1-F"), Some(1));
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_defaults_stay_lenient() {
+    let mut trie = Trie::new();
+    assert_eq!(trie.insert("a1-f", 1), None);
+    assert_eq!(trie.get("A1F"), Some(&1));
+    assert_eq!(trie.delete("a 1 f"), Some(1));
+    assert!(trie.is_empty());
+}
+
+#[test]
+fn the_error_says_where() {
+    let err = Trie::<()>::new().try_get("a1-f").unwrap_err();
+    assert_eq!(err.to_string(), "invalid hex character '-' at index 2");
+}
+
+#[test]
+fn every_key_taking_method_has_a_strict_twin() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", 1);
+    trie.insert_lossy("a1f", 2);
+    let err = invalid(2, '-');
+    assert_eq!(trie.try_get_mut("a1-f"), Err(err));
+    assert_eq!(trie.try_contains_key("a1-f"), Err(err));
+    assert!(matches!(trie.try_entry("a1-f"), Err(e) if e == err));
+    assert_eq!(trie.try_update("a1-f", |_| unreachable!()), Err(err));
+    assert_eq!(
+        trie.try_get_or_insert_with("a1-f", || unreachable!()),
+        Err(err)
+    );
+    assert_eq!(trie.try_get_or_insert("a1-f", 3), Err(err));
+    assert!(matches!(trie.try_iter_prefix("a1-f"), Err(e) if e == err));
+    assert_eq!(trie.try_count_prefix("a1-f"), Err(err));
+    assert_eq!(trie.try_longest_prefix("a1-f"), Err(err));
+    assert!(matches!(trie.try_split_off("a1-f"), Err(e) if e == err));
+    assert_eq!(trie.try_remove_prefix("a1-f"), Err(err));
+    assert_eq!(trie.len(), 2);
+    assert_eq!(trie.node_count(), 4);
+
+    *trie.try_get_mut("A1F").unwrap().unwrap() += 10;
+    assert_eq!(trie.try_contains_key("a1f"), Ok(true));
+    *trie.try_entry("b").unwrap().or_insert(0) += 1;
+    assert_eq!(trie.try_update("b", |v| v.map(|v| v + 1)), Ok(Some(1)));
+    assert_eq!(trie.try_get_or_insert("c", 4).map(|v| *v), Ok(4));
+    let keys: Vec<String> = trie.try_iter_prefix("a").unwrap().map(|(k, _)| k).collect();
+    assert_eq!(keys, ["a1", "a1f"]);
+    assert_eq!(trie.try_count_prefix("a1"), Ok(2));
+    assert_eq!(
+        trie.try_longest_prefix("a1f0"),
+        Ok(Some(("a1f".to_string(), &12)))
+    );
+    assert_eq!(trie.try_split_off("c").unwrap().len(), 1);
+    assert_eq!(trie.try_remove_prefix("b"), Ok(1));
+    assert_eq!(trie.keys().collect::<Vec<_>>(), ["a1", "a1f"]);
+}
+
+#[test]
+fn try_iter_prefix_takes_odd_prefixes_at_fanout_256() {
+    let mut trie = Trie::<u8, 256>::with_fanout();
+    trie.try_insert("a0", 0).unwrap();
+    trie.try_insert("b0", 1).unwrap();
+    let keys: Vec<String> = trie.try_iter_prefix("a").unwrap().map(|(k, _)| k).collect();
+    assert_eq!(keys, ["a0"]);
+    assert!(trie.try_iter_prefix("a-").is_err());
+}
+
+#[test]
+fn a_prefix_without_hex_digits_removes_nothing() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("", 0);
+    trie.insert_lossy("a1", 1);
+    for prefix in ["-", "xyz", " ", "\n"] {
+        assert_eq!(trie.remove_prefix(prefix), 0, "{:?}", prefix);
+    }
+    assert_eq!(trie.len(), 2);
+    assert_eq!(trie.try_remove_prefix("xyz"), Err(invalid(0, 'x')));
+    assert_eq!(trie.len(), 2);
+    // the empty prefix itself still clears everything
+    assert_eq!(trie.remove_prefix(""), 2);
+    assert!(trie.is_empty());
+}
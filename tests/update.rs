@@ -0,0 +1,119 @@
+// The single-walk read-modify-write methods.
+
+use std::cell::Cell;
+
+use trie::Trie;
+
+#[test]
+fn get_or_insert_with_only_calls_f_on_a_miss() {
+    let calls = Cell::new(0);
+    let make = || {
+        calls.set(calls.get() + 1);
+        format!("made-{}", calls.get())
+    };
+    let mut trie = Trie::new();
+    assert_eq!(trie.get_or_insert_with("a1f", make), "made-1");
+    assert_eq!(calls.get(), 1);
+    assert_eq!(trie.get_or_insert_with("a1f", make), "made-1");
+    assert_eq!(trie.get_or_insert_with("A1F", make), "made-1");
+    assert_eq!(calls.get(), 1);
+    // a prefix of a stored key has nodes but no value, so it's still a miss
+    assert_eq!(trie.get_or_insert_with("a1", make), "made-2");
+    assert_eq!(calls.get(), 2);
+    assert_eq!(trie.len(), 2);
+}
+
+#[test]
+fn get_or_insert_with_hands_back_a_usable_reference() {
+    let mut trie = Trie::new();
+    trie.get_or_insert_with("b0", String::new).push_str("first");
+    trie.get_or_insert_with("b0", String::new)
+        .push_str("-second");
+    assert_eq!(trie.get_lossy("b0").unwrap(), "first-second");
+
+    let mut counts = Trie::new();
+    for key in ["a1", "b0", "a1", "a1"] {
+        *counts.get_or_insert(key, 0) += 1;
+    }
+    assert_eq!(counts.get_lossy("a1"), Some(&3));
+    assert_eq!(counts.get_lossy("b0"), Some(&1));
+}
+
+#[test]
+fn get_or_insert_with_builds_nodes_only_when_inserting() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1f", 1);
+    let nodes = trie.node_count();
+    assert_eq!(*trie.get_or_insert_with("a1f", || unreachable!()), 1);
+    assert_eq!(trie.node_count(), nodes);
+    trie.get_or_insert_with("a1e0", || 2);
+    assert_eq!(trie.node_count(), nodes + 2);
+    assert_eq!(trie.len(), 2);
+}
+
+#[test]
+fn update_from_none_to_some_creates_the_path() {
+    let mut trie = Trie::new();
+    let mut seen = Some(0);
+    assert_eq!(
+        trie.update("a1f", |value| {
+            seen = value;
+            Some(7)
+        }),
+        None
+    );
+    assert_eq!(seen, None);
+    assert_eq!(trie.get_lossy("a1f"), Some(&7));
+    assert_eq!(trie.len(), 1);
+    assert_eq!(trie.node_count(), 4);
+}
+
+#[test]
+fn update_from_some_to_some_replaces_the_value() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", "old".to_string());
+    let mut seen = None;
+    let previous = trie.update("a1", |value| {
+        seen = value.clone();
+        Some(value.unwrap() + "-new")
+    });
+    assert_eq!(previous.as_deref(), Some("old"));
+    assert_eq!(seen.as_deref(), Some("old"));
+    assert_eq!(trie.get_lossy("a1").unwrap(), "old-new");
+    assert_eq!(trie.len(), 1);
+}
+
+#[test]
+fn update_from_some_to_none_prunes_like_delete() {
+    let mut updated = Trie::new();
+    let mut deleted = Trie::new();
+    for trie in [&mut updated, &mut deleted] {
+        trie.insert_lossy("a1", 1);
+        trie.insert_lossy("a1f00", 2);
+    }
+    assert_eq!(updated.update("a1f00", |_| None), Some(2));
+    assert_eq!(deleted.delete_lossy("a1f00"), Some(2));
+    assert_eq!(updated.node_count(), deleted.node_count());
+    assert_eq!(updated.node_count(), 3);
+    assert_eq!(updated.len(), 1);
+    assert_eq!(updated.to_string(), deleted.to_string());
+}
+
+#[test]
+fn update_from_none_to_none_leaves_no_path() {
+    let mut trie = Trie::new();
+    trie.insert_lossy("a1", 1);
+    let mut called = false;
+    assert_eq!(
+        trie.update("a1f00", |value| {
+            called = true;
+            assert_eq!(value, None);
+            None
+        }),
+        None
+    );
+    assert!(called);
+    assert_eq!(trie.node_count(), 3);
+    assert_eq!(trie.len(), 1);
+    assert!(!trie.contains_key("a1f00"));
+}
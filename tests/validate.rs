@@ -0,0 +1,122 @@
+use trie::{Nibbles, Trie, TrieHasher, WalkControl};
+
+mod common;
+use common::Rng;
+
+// A cheap stand-in for a real hash, enough to fill the hash caches.
+struct Fold;
+
+impl TrieHasher for Fold {
+    fn hash(data: &[u8]) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, byte) in data.iter().enumerate() {
+            out[i % 32] = out[i % 32].wrapping_mul(31).wrapping_add(*byte);
+        }
+        out
+    }
+}
+
+fn assert_valid<V, const N: usize>(trie: &Trie<V, N>, after: &str) {
+    if let Err(violations) = trie.validate() {
+        panic!("after {after}: {violations:?}");
+    }
+}
+
+#[test]
+fn stays_valid_under_random_inserts_and_deletes() {
+    let mut rng = Rng::new(67);
+    for _ in 0..200 {
+        let mut trie = Trie::new();
+        for _ in 0..100 {
+            let key = rng.key(5);
+            let op = match rng.below(6) {
+                0..=2 => {
+                    trie.insert_lossy(&key, rng.below(10));
+                    "insert"
+                }
+                3 => {
+                    let nibbles: Nibbles = key.parse().unwrap();
+                    trie.insert_nibbles(&nibbles, rng.below(10));
+                    "insert_nibbles"
+                }
+                4 => {
+                    trie.delete_lossy(&key);
+                    "delete"
+                }
+                _ => {
+                    trie.remove_prefix(&rng.key(2));
+                    "remove_prefix"
+                }
+            };
+            assert_valid(&trie, &format!("{op} {key:?}"));
+        }
+    }
+}
+
+#[test]
+fn hash_caches_stay_valid_across_changes() {
+    let mut rng = Rng::new(68);
+    let mut trie = Trie::new();
+    for _ in 0..1_000 {
+        let key = rng.key(4);
+        match rng.below(5) {
+            0..=1 => {
+                trie.insert_lossy(&key, rng.below(10));
+            }
+            2 => {
+                trie.delete_lossy(&key);
+            }
+            3 => {
+                if let Some(value) = trie.get_mut(&key) {
+                    *value += 1;
+                }
+            }
+            _ => trie.walk_mut(|_, value| {
+                if let Some(value) = value {
+                    *value += 1;
+                    return WalkControl::Stop;
+                }
+                WalkControl::Continue
+            }),
+        }
+        assert_valid(&trie, &key);
+        trie.root_hash::<Fold>();
+        assert_valid(&trie, "hashing");
+    }
+}
+
+#[test]
+fn other_fanouts_stay_valid() {
+    fn run<const N: usize>(seed: u64) {
+        let mut rng = Rng::new(seed);
+        let mut trie = Trie::<u64, N>::with_fanout();
+        for _ in 0..2_000 {
+            let key = rng.key(6);
+            if rng.below(3) == 0 {
+                trie.delete_lossy(&key);
+            } else {
+                trie.insert_lossy(&key, 0);
+            }
+            assert_valid(&trie, &key);
+        }
+    }
+    run::<2>(1);
+    run::<4>(2);
+    run::<16>(3);
+    run::<256>(4);
+}
+
+#[test]
+fn a_trie_emptied_again_is_a_bare_root() {
+    let mut rng = Rng::new(69);
+    let mut trie = Trie::new();
+    let keys: Vec<String> = (0..500).map(|_| rng.key(6)).collect();
+    for key in &keys {
+        trie.insert_lossy(key, ());
+    }
+    for key in &keys {
+        trie.delete_lossy(key);
+    }
+    assert_valid(&trie, "deleting everything");
+    assert_eq!(trie.node_count(), 1);
+}
@@ -0,0 +1,110 @@
+// VersionedTrie answers for every past version what a map copied at that version holds.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use trie::VersionedTrie;
+
+mod common;
+use common::Rng;
+
+// Runs random inserts and deletes, returning the trie and the map as of every version, the
+// empty map for version 0 first.
+fn random_history(rng: &mut Rng) -> (VersionedTrie<u64>, Vec<BTreeMap<String, u64>>) {
+    let mut trie = VersionedTrie::new();
+    let mut maps = vec![BTreeMap::new()];
+    for n in 0..rng.below(200) {
+        let mut map = maps.last().unwrap().clone();
+        let key = rng.key(3);
+        if rng.below(3) == 0 {
+            match trie.delete(&key) {
+                Some(version) => {
+                    assert!(map.remove(&key).is_some());
+                    assert_eq!(version, maps.len() as u64);
+                }
+                None => {
+                    assert!(!map.contains_key(&key));
+                    continue;
+                }
+            }
+        } else {
+            assert_eq!(trie.insert(&key, n), maps.len() as u64);
+            map.insert(key, n);
+        }
+        maps.push(map);
+    }
+    (trie, maps)
+}
+
+#[test]
+fn get_at_answers_for_every_version() {
+    let mut rng = Rng::new(95);
+    for _ in 0..30 {
+        let (trie, maps) = random_history(&mut rng);
+        assert_eq!(trie.version() as usize, maps.len() - 1);
+        assert_eq!(trie.len(), maps.last().unwrap().len());
+        let keys: BTreeSet<String> = maps.iter().flat_map(|map| map.keys().cloned()).collect();
+        for (version, map) in maps.iter().enumerate() {
+            for key in &keys {
+                assert_eq!(trie.get_at(key, version as u64), map.get(key));
+            }
+            let entries: Vec<_> = trie
+                .iter_at(version as u64)
+                .map(|(key, &value)| (key, value))
+                .collect();
+            assert_eq!(entries, map.clone().into_iter().collect::<Vec<_>>());
+        }
+    }
+}
+
+#[test]
+fn insert_overwrite_delete_sequence() {
+    let mut trie = VersionedTrie::new();
+    let v1 = trie.insert("a1", "one");
+    let v2 = trie.insert("b0", "other");
+    let v3 = trie.insert("a1", "two");
+    let v4 = trie.delete("a1").unwrap();
+    assert_eq!(trie.delete("a1"), None);
+    assert_eq!(trie.delete("ff"), None);
+    let v5 = trie.insert("a1", "three");
+    assert_eq!([v1, v2, v3, v4, v5], [1, 2, 3, 4, 5]);
+    assert_eq!(trie.get_at("a1", 0), None);
+    assert_eq!(trie.get_at("a1", v2), Some(&"one"));
+    assert_eq!(trie.get_at("a1", v3), Some(&"two"));
+    assert_eq!(trie.get_at("a1", v4), None);
+    assert_eq!(trie.get("a1"), Some(&"three"));
+    assert_eq!(trie.get_at("a1", 100), Some(&"three"));
+    assert_eq!(
+        trie.history("a1"),
+        [
+            (1, Some("one")),
+            (3, Some("two")),
+            (4, None),
+            (5, Some("three"))
+        ]
+    );
+    assert!(trie.history("ff").is_empty());
+    assert_eq!(trie.len(), 2);
+}
+
+#[test]
+fn truncate_history_keeps_later_versions_answering() {
+    let mut rng = Rng::new(950);
+    for _ in 0..30 {
+        let (mut trie, maps) = random_history(&mut rng);
+        let before = rng.below(maps.len() as u64);
+        trie.truncate_history(before);
+        let keys: BTreeSet<String> = maps.iter().flat_map(|map| map.keys().cloned()).collect();
+        for (version, map) in maps.iter().enumerate().skip(before as usize) {
+            for key in &keys {
+                assert_eq!(trie.get_at(key, version as u64), map.get(key));
+            }
+        }
+        for key in &keys {
+            let history = trie.history(key);
+            // at most one change from before the cut survives, and never a lone delete
+            assert!(history.iter().filter(|(v, _)| *v < before).count() <= 1);
+            assert!(!matches!(history, [(_, None)]) || history[0].0 > before);
+        }
+        assert_eq!(trie.len(), maps.last().unwrap().len());
+    }
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "std")]
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use trie::LoggedTrie;
+
+mod common;
+use common::temp_dir;
+
+#[test]
+fn writes_survive_reopening() {
+    let path = temp_dir("wal-reopen").join("log");
+    let mut logged = LoggedTrie::<String>::open(&path).unwrap();
+    logged.insert("a1", "one".to_string()).unwrap();
+    logged.insert("a1f", "two".to_string()).unwrap();
+    assert_eq!(logged.delete("a1").unwrap(), Some("one".to_string()));
+    assert_eq!(logged.delete("b").unwrap(), None);
+    drop(logged);
+
+    let logged = LoggedTrie::<String>::open(&path).unwrap();
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged.get_lossy("a1f"), Some(&"two".to_string()));
+}
+
+#[test]
+fn a_torn_final_record_is_dropped_and_cut_off() {
+    let path = temp_dir("wal-torn").join("log");
+    let mut logged = LoggedTrie::<u64>::open(&path).unwrap();
+    logged.insert("a1", 1).unwrap();
+    logged.insert("b2", 2).unwrap();
+    drop(logged);
+    let complete = fs::metadata(&path).unwrap().len();
+
+    // Every way of cutting the last record short loses only that record.
+    let whole = fs::read(&path).unwrap();
+    // Both records have two-digit keys and eight-byte values, so they're the same size.
+    let record = complete as usize / 2;
+    for cut in 1..record {
+        fs::write(&path, &whole[..whole.len() - cut]).unwrap();
+        let logged = LoggedTrie::<u64>::open(&path).unwrap();
+        assert_eq!(logged.len(), 1, "cut {} bytes", cut);
+        assert_eq!(logged.get_lossy("a1"), Some(&1));
+        assert_eq!(logged.get_lossy("b2"), None);
+        assert_eq!(fs::metadata(&path).unwrap().len(), complete - record as u64);
+    }
+
+    let mut logged = LoggedTrie::<u64>::open(&path).unwrap();
+    logged.insert("c3", 3).unwrap();
+    drop(logged);
+    let logged = LoggedTrie::<u64>::open(&path).unwrap();
+    assert_eq!(logged.keys().collect::<Vec<_>>(), ["a1", "c3"]);
+}
+
+#[test]
+fn a_record_with_a_bad_checksum_ends_the_replay() {
+    let path = temp_dir("wal-crc").join("log");
+    let mut logged = LoggedTrie::<u64>::open(&path).unwrap();
+    logged.insert("a1", 1).unwrap();
+    logged.insert("b2", 2).unwrap();
+    drop(logged);
+
+    let mut bytes = fs::read(&path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    fs::write(&path, &bytes).unwrap();
+    let logged = LoggedTrie::<u64>::open(&path).unwrap();
+    assert_eq!(logged.keys().collect::<Vec<_>>(), ["a1"]);
+}
+
+#[test]
+fn garbage_after_the_log_is_ignored() {
+    let path = temp_dir("wal-garbage").join("log");
+    let mut logged = LoggedTrie::<u64>::open(&path).unwrap();
+    logged.insert("a1", 1).unwrap();
+    drop(logged);
+    OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(&[0xff; 3])
+        .unwrap();
+    let logged = LoggedTrie::<u64>::open(&path).unwrap();
+    assert_eq!(logged.len(), 1);
+}
+
+#[test]
+fn checkpoint_folds_the_log_into_the_snapshot() {
+    let path = temp_dir("wal-checkpoint").join("log");
+    let mut logged = LoggedTrie::<u64>::open(&path).unwrap();
+    logged.insert("a1", 1).unwrap();
+    logged.checkpoint().unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+    assert!(logged.snapshot_path().exists());
+    logged.insert("b2", 2).unwrap();
+    drop(logged);
+
+    let logged = LoggedTrie::<u64>::open(&path).unwrap();
+    assert_eq!(logged.keys().collect::<Vec<_>>(), ["a1", "b2"]);
+}